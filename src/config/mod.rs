@@ -1,42 +1,193 @@
 //! Configuration module for OFI engine
 
+use crate::signals::{ReasonCode, SignalType};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::Path;
 
 /// TOML configuration structure
 #[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 struct TomlConfig {
     #[serde(rename = "ofi")]
     ofi_config: Option<OFITomlConfig>,
     #[serde(rename = "strategy")]
     strategy_config: Option<StrategyTomlConfig>,
+    #[serde(rename = "tier")]
+    tier_config: Option<Vec<TierTomlConfig>>,
+    #[serde(rename = "maintenance")]
+    maintenance_config: Option<Vec<MaintenanceTomlConfig>>,
+    #[serde(rename = "intent")]
+    intent_config: Option<Vec<IntentTomlConfig>>,
+    #[serde(rename = "symbols")]
+    symbols_config: Option<HashMap<String, SymbolTomlConfig>>,
+    #[serde(rename = "synthetic")]
+    synthetic_config: Option<Vec<SyntheticTomlConfig>>,
+    #[serde(rename = "group")]
+    group_config: Option<Vec<GroupTomlConfig>>,
+    #[serde(rename = "bounds")]
+    bounds_config: Option<HashMap<String, FieldBounds>>,
 }
 
 #[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 struct OFITomlConfig {
     #[serde(rename = "websocket_url")]
     websocket_url: Option<String>,
+    #[serde(rename = "exchange")]
+    exchange: Option<Exchange>,
     #[serde(rename = "analysis_duration_limit_ms")]
     analysis_duration_limit_ms: Option<u64>,
     #[serde(rename = "analysis_duration_per_cycle_ms")]
     analysis_duration_per_cycle_ms: Option<u64>,
     #[serde(rename = "trade_storage_limit")]
     trade_storage_limit: Option<usize>,
+    #[serde(rename = "analysis_trade_limit")]
+    analysis_trade_limit: Option<usize>,
+    #[serde(rename = "global_entry_limit")]
+    global_entry_limit: Option<usize>,
+    #[serde(rename = "expected_trade_rate")]
+    expected_trade_rate: Option<f64>,
+    #[serde(rename = "strict_lookback_coverage")]
+    strict_lookback_coverage: Option<bool>,
     #[serde(rename = "strong_signal_confidence")]
     strong_signal_confidence: Option<f64>,
     #[serde(rename = "reversal_signal_confidence")]
     reversal_signal_confidence: Option<f64>,
     #[serde(rename = "exhaustion_signal_confidence")]
     exhaustion_signal_confidence: Option<f64>,
+    #[serde(rename = "absorption_confirmation_window_ms")]
+    absorption_confirmation_window_ms: Option<u64>,
+    #[serde(rename = "absorption_confirmed_confidence")]
+    absorption_confirmed_confidence: Option<f64>,
+    #[serde(rename = "absorption_tentative_confidence")]
+    absorption_tentative_confidence: Option<f64>,
     #[serde(rename = "market_condition_adaptation")]
     market_condition_adaptation: Option<bool>,
     #[serde(rename = "max_concurrent_websocket_connections")]
     max_concurrent_websocket_connections: Option<usize>,
+    #[serde(rename = "status_http_addr")]
+    status_http_addr: Option<String>,
+    #[serde(rename = "signal_server_addr")]
+    signal_server_addr: Option<String>,
+    #[serde(rename = "mqtt_host")]
+    mqtt_host: Option<String>,
+    #[serde(rename = "mqtt_port")]
+    mqtt_port: Option<u16>,
+    #[serde(rename = "mqtt_client_id")]
+    mqtt_client_id: Option<String>,
+    #[serde(rename = "mqtt_signal_topic")]
+    mqtt_signal_topic: Option<String>,
+    #[serde(rename = "mqtt_heartbeat_topic")]
+    mqtt_heartbeat_topic: Option<String>,
+    #[serde(rename = "mqtt_qos")]
+    mqtt_qos: Option<u8>,
+    #[serde(rename = "mqtt_reconnect_secs")]
+    mqtt_reconnect_secs: Option<u64>,
+    #[serde(rename = "signal_db_path")]
+    signal_db_path: Option<String>,
+    #[serde(rename = "task_start_stagger_ms")]
+    task_start_stagger_ms: Option<u64>,
+    #[serde(rename = "task_startup_grace_secs")]
+    task_startup_grace_secs: Option<u64>,
+    #[serde(rename = "rank_watchlist")]
+    rank_watchlist: Option<bool>,
+    #[serde(rename = "min_execution_confidence")]
+    min_execution_confidence: Option<f64>,
+    #[serde(rename = "reconnect_clean_ms")]
+    reconnect_clean_ms: Option<u64>,
+    #[serde(rename = "reconnect_error_ms")]
+    reconnect_error_ms: Option<u64>,
+    #[serde(rename = "reconnect_spacing_ms")]
+    reconnect_spacing_ms: Option<u64>,
+    #[serde(rename = "reconnect_jitter_ms")]
+    reconnect_jitter_ms: Option<u64>,
+    #[serde(rename = "stop_on_invalid_symbol")]
+    stop_on_invalid_symbol: Option<bool>,
+    #[serde(rename = "require_all_channels")]
+    require_all_channels: Option<bool>,
+    #[serde(rename = "channel_startup_timeout_ms")]
+    channel_startup_timeout_ms: Option<u64>,
+    #[serde(rename = "deadman_timeout_secs")]
+    deadman_timeout_secs: Option<u64>,
+    #[serde(rename = "position_monitor_interval_secs")]
+    position_monitor_interval_secs: Option<u64>,
+    #[serde(rename = "max_inflight_position_monitors")]
+    max_inflight_position_monitors: Option<usize>,
+    #[serde(rename = "timestamp_unit")]
+    timestamp_unit: Option<TimestampUnit>,
+    #[serde(rename = "timestamp_source")]
+    timestamp_source: Option<TimestampSource>,
+    #[serde(rename = "emit_no_signal_events")]
+    emit_no_signal_events: Option<bool>,
+    #[serde(rename = "market_data_feed_capacity")]
+    market_data_feed_capacity: Option<usize>,
+    #[serde(rename = "flight_recorder_messages")]
+    flight_recorder_messages: Option<usize>,
+    #[serde(rename = "flight_recorder_dir")]
+    flight_recorder_dir: Option<String>,
+    #[serde(rename = "executor_max_retries")]
+    executor_max_retries: Option<u32>,
+    #[serde(rename = "max_daily_signals_per_symbol")]
+    max_daily_signals_per_symbol: Option<u32>,
+    #[serde(rename = "executor_retry_backoff_ms")]
+    executor_retry_backoff_ms: Option<u64>,
+    #[serde(rename = "cvd_state_dir")]
+    cvd_state_dir: Option<String>,
+    #[serde(rename = "cvd_resume_window_secs")]
+    cvd_resume_window_secs: Option<u64>,
+    #[serde(rename = "preflight_checks")]
+    preflight_checks: Option<bool>,
+    #[serde(rename = "config_audit")]
+    config_audit: Option<bool>,
+    #[serde(rename = "python_unavailable_policy")]
+    python_unavailable_policy: Option<PythonUnavailablePolicy>,
+    #[serde(rename = "fallback_watchlist")]
+    fallback_watchlist: Option<Vec<String>>,
+    #[serde(rename = "analysis_version_gate")]
+    analysis_version_gate: Option<bool>,
+    #[serde(rename = "coalesce_analyses")]
+    coalesce_analyses: Option<bool>,
+    #[serde(rename = "channels")]
+    channels: Option<Vec<String>>,
+    #[serde(rename = "verify_inst_id")]
+    verify_inst_id: Option<bool>,
+    #[serde(rename = "unsubscribe_before_subscribe")]
+    unsubscribe_before_subscribe: Option<bool>,
+    #[serde(rename = "warmup_min_trades")]
+    warmup_min_trades: Option<usize>,
+    #[serde(rename = "warmup_min_book_levels")]
+    warmup_min_book_levels: Option<usize>,
+    #[serde(rename = "credentials_path")]
+    credentials_path: Option<String>,
+    #[serde(rename = "exchanges")]
+    exchanges: Option<Vec<String>>,
+    #[serde(rename = "analysis_batch_ms")]
+    analysis_batch_ms: Option<u64>,
+    #[serde(rename = "analysis_batch_max_updates")]
+    analysis_batch_max_updates: Option<usize>,
+    #[serde(rename = "analysis_cadence_ms")]
+    analysis_cadence_ms: Option<u64>,
+    #[serde(rename = "book_dedupe")]
+    book_dedupe: Option<BookDedupe>,
+    #[serde(rename = "error_log_coalesce_ms")]
+    error_log_coalesce_ms: Option<u64>,
+    #[serde(rename = "resync_suppress_ms")]
+    resync_suppress_ms: Option<u64>,
+    #[serde(rename = "dashboard_refresh_ms")]
+    dashboard_refresh_ms: Option<u64>,
+    #[serde(rename = "adaptive_skip_max_rate")]
+    adaptive_skip_max_rate: Option<f64>,
+    #[serde(rename = "adaptive_skip_max_factor")]
+    adaptive_skip_max_factor: Option<u64>,
+    #[serde(rename = "heartbeat_interval_secs")]
+    heartbeat_interval_secs: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 struct StrategyTomlConfig {
     #[serde(rename = "imbalance_threshold")]
     imbalance_threshold: Option<f64>,
@@ -46,48 +197,903 @@ struct StrategyTomlConfig {
     delta_threshold: Option<f64>,
     #[serde(rename = "lookback_period_ms")]
     lookback_period_ms: Option<u64>,
+    #[serde(rename = "min_book_quality")]
+    min_book_quality: Option<f64>,
+    #[serde(rename = "min_book_update_rate")]
+    min_book_update_rate: Option<f64>,
+    #[serde(rename = "reject_crossed_books")]
+    reject_crossed_books: Option<bool>,
+    #[serde(rename = "book_quality_confidence_scaling")]
+    book_quality_confidence_scaling: Option<bool>,
+    #[serde(rename = "book_quality_max_spread_bps")]
+    book_quality_max_spread_bps: Option<f64>,
+    #[serde(rename = "book_quality_max_age_ms")]
+    book_quality_max_age_ms: Option<u64>,
+    #[serde(rename = "book_quality_use_twa_spread")]
+    book_quality_use_twa_spread: Option<bool>,
+    #[serde(rename = "fast_mode")]
+    fast_mode: Option<bool>,
+    #[serde(rename = "fast_mode_levels")]
+    fast_mode_levels: Option<usize>,
+    #[serde(rename = "fast_mode_signal_confidence")]
+    fast_mode_signal_confidence: Option<f64>,
+    #[serde(rename = "touch_imbalance_threshold")]
+    touch_imbalance_threshold: Option<f64>,
+    #[serde(rename = "touch_imbalance_updates")]
+    touch_imbalance_updates: Option<usize>,
+    #[serde(rename = "touch_imbalance_confidence")]
+    touch_imbalance_confidence: Option<f64>,
+    #[serde(rename = "replenishment_confidence_weight")]
+    replenishment_confidence_weight: Option<f64>,
+    #[serde(rename = "multi_lookback")]
+    multi_lookback: Option<Vec<u64>>,
+    #[serde(rename = "funding_rate_extreme")]
+    funding_rate_extreme: Option<f64>,
+    #[serde(rename = "funding_suppress_continuation")]
+    funding_suppress_continuation: Option<bool>,
+    #[serde(rename = "funding_reversal_boost")]
+    funding_reversal_boost: Option<f64>,
+    #[serde(rename = "funding_rate_ttl_secs")]
+    funding_rate_ttl_secs: Option<u64>,
+    #[serde(rename = "signal_price_source")]
+    signal_price_source: Option<SignalPriceSource>,
+    #[serde(rename = "weighted_mid_levels")]
+    weighted_mid_levels: Option<usize>,
+    #[serde(rename = "volatility_window_ms")]
+    volatility_window_ms: Option<u64>,
+    #[serde(rename = "reference_volatility")]
+    reference_volatility: Option<f64>,
+    #[serde(rename = "max_volatility_multiplier")]
+    max_volatility_multiplier: Option<f64>,
+    #[serde(rename = "imbalance_level_cap_multiple")]
+    imbalance_level_cap_multiple: Option<f64>,
+    #[serde(rename = "flow_consistency_buckets")]
+    flow_consistency_buckets: Option<usize>,
+    #[serde(rename = "flow_consistency_confidence_scaling")]
+    flow_consistency_confidence_scaling: Option<bool>,
+    #[serde(rename = "imbalance_momentum_snapshots")]
+    imbalance_momentum_snapshots: Option<usize>,
+    #[serde(rename = "delta_decay_half_life_ms")]
+    delta_decay_half_life_ms: Option<u64>,
+    #[serde(rename = "suppress_decaying_imbalance")]
+    suppress_decaying_imbalance: Option<bool>,
+    #[serde(rename = "jump_suppress_bps")]
+    jump_suppress_bps: Option<f64>,
+    #[serde(rename = "jump_cooldown_ms")]
+    jump_cooldown_ms: Option<u64>,
+    #[serde(rename = "max_trade_deviation_bps")]
+    max_trade_deviation_bps: Option<f64>,
+    #[serde(rename = "reorder_buffer_ms")]
+    reorder_buffer_ms: Option<u64>,
+    #[serde(rename = "imbalance_basis")]
+    imbalance_basis: Option<ImbalanceBasis>,
+    #[serde(rename = "imbalance_notional_depth")]
+    imbalance_notional_depth: Option<f64>,
+    #[serde(rename = "signal_confirm_ms")]
+    signal_confirm_ms: Option<u64>,
+    #[serde(rename = "adapt_thin_books")]
+    adapt_thin_books: Option<bool>,
+    #[serde(rename = "stacked_min_depth")]
+    stacked_min_depth: Option<usize>,
+    #[serde(rename = "explain_signals")]
+    explain_signals: Option<bool>,
+    #[serde(rename = "record_effective_params")]
+    record_effective_params: Option<bool>,
+    #[serde(rename = "sizing_slippage_bps")]
+    sizing_slippage_bps: Option<f64>,
+    #[serde(rename = "unified_event_clock")]
+    unified_event_clock: Option<bool>,
+    #[serde(rename = "large_print_notional")]
+    large_print_notional: Option<f64>,
+    #[serde(rename = "large_print_min_count")]
+    large_print_min_count: Option<usize>,
+    #[serde(rename = "min_buy_trades")]
+    min_buy_trades: Option<usize>,
+    #[serde(rename = "min_sell_trades")]
+    min_sell_trades: Option<usize>,
+    #[serde(rename = "large_print_confidence_boost")]
+    large_print_confidence_boost: Option<f64>,
+    #[serde(rename = "consensus_confidence_boost")]
+    consensus_confidence_boost: Option<f64>,
+    #[serde(rename = "absorption_interpretation")]
+    absorption_interpretation: Option<AbsorptionInterpretation>,
+    #[serde(rename = "direction_stickiness_ms")]
+    direction_stickiness_ms: Option<u64>,
+    #[serde(rename = "direction_flip_confidence")]
+    direction_flip_confidence: Option<f64>,
+    #[serde(rename = "calibration_horizon_ms")]
+    calibration_horizon_ms: Option<u64>,
+    #[serde(rename = "calibration_min_samples")]
+    calibration_min_samples: Option<u64>,
 }
 
-/// Configuration for the OFI engine
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OFIConfig {
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+struct SymbolTomlConfig {
+    #[serde(rename = "min_execution_confidence")]
+    min_execution_confidence: Option<f64>,
+    #[serde(rename = "websocket_url")]
+    websocket_url: Option<String>,
+    #[serde(rename = "exchange")]
+    exchange: Option<Exchange>,
+    #[serde(rename = "strategies")]
+    strategies: Option<Vec<ReasonCode>>,
+}
+
+/// Per-symbol overrides from the [symbols.<SYMBOL>] sections; unset fields fall back to the global value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SymbolOverride {
+    pub min_execution_confidence: Option<f64>,
+    pub websocket_url: Option<String>,  // Endpoint serving this symbol, e.g. another venue's public stream
+    pub exchange: Option<Exchange>,  // Venue whose subscribe and message formats this symbol's stream uses
+    pub strategies: Option<Vec<ReasonCode>>,  // Rules allowed to emit for this symbol, None = all, empty = data collection only
+}
+
+/// Venue protocol of a WebSocket stream: how channels are subscribed and payloads parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Exchange {
+    /// Bitget v2 public stream (USDT-FUTURES `books` and `trade` channels)
+    Bitget,
+}
+
+impl Exchange {
+    /// Lower-case name, as used for the exchange's credentials (`credentials.<name>`)
+    pub fn name(self) -> &'static str {
+        match self {
+            Exchange::Bitget => "bitget",
+        }
+    }
+}
+
+/// Unit of the exchange's raw timestamps; all internal timestamps are milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampUnit {
+    /// Infer the unit from the magnitude of each timestamp
+    Auto,
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl TimestampUnit {
+    /// Convert a raw exchange timestamp in this unit to milliseconds since epoch
+    pub fn to_millis(self, raw: u64) -> u64 {
+        let unit = match self {
+            // Epoch seconds stay below 1e11 until the year 5138; each finer unit adds three digits
+            TimestampUnit::Auto => match raw {
+                0..=99_999_999_999 => TimestampUnit::Seconds,
+                100_000_000_000..=99_999_999_999_999 => TimestampUnit::Millis,
+                100_000_000_000_000..=99_999_999_999_999_999 => TimestampUnit::Micros,
+                _ => TimestampUnit::Nanos,
+            },
+            unit => unit,
+        };
+        match unit {
+            TimestampUnit::Seconds => raw.saturating_mul(1000),
+            TimestampUnit::Micros => raw / 1000,
+            TimestampUnit::Nanos => raw / 1_000_000,
+            _ => raw,
+        }
+    }
+}
+
+/// Clock stamped on signals and used for their duplicate window.
+///
+/// Exchange event time is comparable across the pipeline and in replays but can jump with
+/// exchange clock corrections or stalled feeds; local receipt time is monotonic on this host but
+/// includes network latency and skews against exchange timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum TimestampSource {
+    /// Timestamp of the market data the signal was computed from
+    Exchange,
+    /// Wall clock of this host when the signal is forwarded
+    Local,
+}
+
+/// Which price a signal carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum SignalPriceSource {
+    /// (best bid + best ask) / 2
+    Mid,
+    /// Top-of-book size-weighted mid, leaning towards the side with less resting size
+    Microprice,
+    /// Price of the most recent trade
+    LastTrade,
+    /// Size-weighted average price of the top `weighted_mid_levels` levels of both sides
+    WeightedMid,
+}
+
+/// What the imbalance ratios and the continuation rule are computed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ImbalanceBasis {
+    /// Ratio of resting bid to ask notional in the current book (stacked imbalance detection)
+    Resting,
+    /// Order flow imbalance: top-of-book size changes between consecutive books over the lookback
+    Flow,
+}
+
+/// Which incoming order books count as redundant resends of the stored book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum BookDedupe {
+    /// Every book is applied and analyzed
+    Off,
+    /// Skip books with the same levels and the same timestamp as the stored book
+    Exact,
+    /// Skip books with the same levels whatever their timestamp; a newer timestamp still
+    /// advances the stored book's timestamp, so staleness checks see the feed as alive
+    Levels,
+}
+
+/// What the binary does when the Python screener or executor modules cannot be imported at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum PythonUnavailablePolicy {
+    /// Log the failed import and keep running; screener refreshes yield an empty watchlist and
+    /// executor calls fail per signal
+    Ignore,
+    /// Exit at startup with the import error
+    FailFast,
+    /// Analyze `fallback_watchlist` instead of screener candidates and log signals without
+    /// executing them (dry-run); position monitoring is skipped
+    Fallback,
+}
+
+/// Which way an absorption event is traded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum AbsorptionInterpretation {
+    /// The absorbing side wins: heavy selling absorbed at the bid is a Buy, heavy buying absorbed
+    /// at the ask is a Sell (the aggressors are exhausting into passive liquidity)
+    Reversal,
+    /// The aggressors win: heavy selling into the bid is a Sell, heavy buying into the ask is a Buy
+    /// (the passive liquidity is expected to give way)
+    Continuation,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+struct TierTomlConfig {
+    #[serde(rename = "min_confidence")]
+    min_confidence: f64,
+    #[serde(rename = "label")]
+    label: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+struct MaintenanceTomlConfig {
+    #[serde(rename = "start")]
+    start: String,
+    #[serde(rename = "end")]
+    end: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+struct IntentTomlConfig {
+    #[serde(rename = "signal_type")]
+    signal_type: SignalType,
+    #[serde(rename = "reason_code")]
+    reason_code: Option<ReasonCode>,
+    #[serde(rename = "action")]
+    action: IntentAction,
+}
+
+/// What a downstream executor should do with a signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum IntentAction {
+    /// Open or add to a long position
+    Long,
+    /// Open or add to a short position
+    Short,
+    /// Reduce an existing position without opening a new one
+    Reduce,
+    /// Produce no intent
+    Ignore,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+struct SyntheticTomlConfig {
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "long_leg")]
+    long_leg: String,
+    #[serde(rename = "short_leg")]
+    short_leg: String,
+    #[serde(rename = "ratio")]
+    ratio: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+struct GroupTomlConfig {
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "members")]
+    members: Vec<String>,
+    #[serde(rename = "delta_notional_threshold")]
+    delta_notional_threshold: f64,
+    #[serde(rename = "imbalance_threshold")]
+    imbalance_threshold: Option<f64>,
+    #[serde(rename = "min_members")]
+    min_members: Option<usize>,
+    #[serde(rename = "confidence")]
+    confidence: Option<f64>,
+}
+
+/// API credentials for one exchange
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ExchangeCredentials {
     pub api_key: String,
     pub secret_key: String,
     pub passphrase: String,
+}
+
+/// Credential names per exchange, prefixed with the upper-cased exchange name (`OKX_API_KEY`)
+const CREDENTIAL_SUFFIXES: [&str; 3] = ["API_KEY", "SECRET_KEY", "PASSPHRASE"];
+
+/// Which configuration layer a resolved field's value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigSource {
+    /// Built-in default, not set anywhere
+    Default,
+    /// A key of the [ofi] table
+    TomlOfi,
+    /// A key of the [strategy] table
+    TomlStrategy,
+    /// An array-of-tables section such as [[tier]] or [[group]]
+    TomlSection,
+    /// Environment variables (credentials)
+    Env,
+    /// The files under `credentials_path`
+    CredentialsFile,
+    /// [symbols.<SYMBOL>] per-symbol overrides
+    Override,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ConfigSource::Default => "default",
+            ConfigSource::TomlOfi => "toml-ofi",
+            ConfigSource::TomlStrategy => "toml-strategy",
+            ConfigSource::TomlSection => "toml-section",
+            ConfigSource::Env => "env",
+            ConfigSource::CredentialsFile => "credentials-file",
+            ConfigSource::Override => "override",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One resolved config field in a `source_audit`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigAuditEntry {
+    pub field: String,
+    pub source: ConfigSource,
+    pub value: serde_json::Value,
+}
+
+/// Config fields filled from array-of-tables sections, by section name
+const SECTION_FIELDS: [(&str, &str); 6] = [
+    ("tier", "confidence_tiers"),
+    ("maintenance", "maintenance_windows"),
+    ("intent", "intent_rules"),
+    ("synthetic", "synthetics"),
+    ("group", "groups"),
+    ("bounds", "field_bounds"),
+];
+
+/// Keys a TOML section struct reads, taken from its `Deserialize` impl so they are exactly the
+/// keys the loader applies (a key a section does not read is ignored, not applied)
+fn toml_section_keys<T: serde::de::DeserializeOwned>() -> &'static [&'static str] {
+    struct FieldRecorder(Option<&'static [&'static str]>);
+
+    impl<'de> serde::Deserializer<'de> for &mut FieldRecorder {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            Err(serde::de::Error::custom("only the field names are recorded"))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+            self.0 = Some(fields);
+            self.deserialize_any(visitor)
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+            unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut recorder = FieldRecorder(None);
+    let _ = T::deserialize(&mut recorder);
+    recorder.0.unwrap_or_default()
+}
+
+/// Allowed range of a numeric config field; an unset side is unbounded
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FieldBounds {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// Sane ranges checked at load, as (field, min, max); a [bounds.<field>] section overrides one.
+/// Values outside them are almost certainly unit or typo errors (a lookback in seconds given in
+/// ms, a notional threshold with extra zeros) rather than a deliberate setting.
+const DEFAULT_FIELD_BOUNDS: [(&str, f64, f64); 18] = [
+    ("imbalance_threshold", 1.0, 100.0),
+    ("absorption_threshold", 0.0, 1e10),
+    ("delta_threshold", 0.0, 1e10),
+    ("lookback_period_ms", 1.0, 300_000.0),  // Up to 5 minutes
+    ("analysis_duration_limit_ms", 1.0, 86_400_000.0),  // Up to 1 day
+    ("analysis_duration_per_cycle_ms", 1.0, 3_600_000.0),  // Up to 1 hour
+    ("trade_storage_limit", 1.0, 1_000_000.0),
+    ("analysis_trade_limit", 0.0, 1_000_000.0),
+    ("global_entry_limit", 0.0, 100_000_000.0),
+    ("book_quality_max_spread_bps", 0.0, 10_000.0),
+    ("book_quality_max_age_ms", 0.0, 3_600_000.0),
+    ("imbalance_level_cap_multiple", 0.0, 1_000.0),
+    ("delta_decay_half_life_ms", 0.0, 3_600_000.0),
+    ("position_monitor_interval_secs", 1.0, 86_400.0),
+    ("deadman_timeout_secs", 0.0, 86_400.0),
+    ("market_data_feed_capacity", 0.0, 1_000_000.0),
+    ("flight_recorder_messages", 0.0, 1_000_000.0),
+    ("sizing_slippage_bps", 0.0, 1_000.0),
+];
+
+/// A synthetic spread instrument analyzed like a symbol: `long_leg - ratio * short_leg`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SyntheticInstrument {
+    pub name: String,
+    pub long_leg: String,
+    pub short_leg: String,
+    pub ratio: f64,  // Units of the short leg per unit of the long leg
+}
+
+/// A basket of correlated symbols whose flow is aggregated into a group-level signal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SymbolGroup {
+    pub name: String,
+    pub members: Vec<String>,
+    pub delta_notional_threshold: f64,  // Net notional delta (USDT) across the members needed for a group signal
+    pub imbalance_threshold: f64,  // Book-notional-weighted imbalance needed in the signal's direction
+    pub min_members: usize,  // Members with data needed before the group is evaluated
+    pub confidence: f64,  // Confidence of group signals
+}
+
+/// Maps a signal type, optionally narrowed to one reason code, to an intent action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct IntentRule {
+    pub signal_type: SignalType,
+    pub reason_code: Option<ReasonCode>,  // None = any reason code
+    pub action: IntentAction,
+}
+
+/// An exchange maintenance window (UTC) during which signals are not forwarded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum MaintenanceWindow {
+    /// Recurs every day between two times of day, given as seconds since UTC midnight.
+    /// A window whose end is before its start wraps past midnight.
+    Daily { start_secs: u32, end_secs: u32 },
+    /// A one-off window between two absolute UTC timestamps in milliseconds
+    Once { start_ms: i64, end_ms: i64 },
+}
+
+impl MaintenanceWindow {
+    /// Parse a window from `start`/`end` strings: either both "HH:MM[:SS]" (daily) or both RFC 3339 (one-off)
+    pub fn parse(start: &str, end: &str) -> Result<Self, String> {
+        match (parse_time_of_day(start), parse_time_of_day(end)) {
+            (Some(start_secs), Some(end_secs)) => {
+                if start_secs == end_secs {
+                    return Err(format!("Maintenance window {}-{} has zero length", start, end));
+                }
+                Ok(MaintenanceWindow::Daily { start_secs, end_secs })
+            }
+            _ => {
+                let start_ms = chrono::DateTime::parse_from_rfc3339(start)
+                    .map_err(|e| format!("Invalid maintenance window start '{}': {}", start, e))?
+                    .timestamp_millis();
+                let end_ms = chrono::DateTime::parse_from_rfc3339(end)
+                    .map_err(|e| format!("Invalid maintenance window end '{}': {}", end, e))?
+                    .timestamp_millis();
+                if end_ms <= start_ms {
+                    return Err(format!("Maintenance window end '{}' must be after start '{}'", end, start));
+                }
+                Ok(MaintenanceWindow::Once { start_ms, end_ms })
+            }
+        }
+    }
+
+    /// Check whether the window covers the given UTC timestamp (milliseconds)
+    pub fn contains(&self, now_ms: i64) -> bool {
+        match *self {
+            MaintenanceWindow::Daily { start_secs, end_secs } => {
+                let secs_of_day = (now_ms.div_euclid(1000).rem_euclid(86_400)) as u32;
+                if start_secs < end_secs {
+                    secs_of_day >= start_secs && secs_of_day < end_secs
+                } else {
+                    secs_of_day >= start_secs || secs_of_day < end_secs
+                }
+            }
+            MaintenanceWindow::Once { start_ms, end_ms } => now_ms >= start_ms && now_ms < end_ms,
+        }
+    }
+}
+
+impl std::fmt::Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            MaintenanceWindow::Daily { start_secs, end_secs } => write!(
+                f,
+                "daily {:02}:{:02}:{:02}-{:02}:{:02}:{:02} UTC",
+                start_secs / 3600, (start_secs % 3600) / 60, start_secs % 60,
+                end_secs / 3600, (end_secs % 3600) / 60, end_secs % 60
+            ),
+            MaintenanceWindow::Once { start_ms, end_ms } => write!(f, "once {}ms-{}ms UTC", start_ms, end_ms),
+        }
+    }
+}
+
+/// Parse "HH:MM" or "HH:MM:SS" into seconds since midnight
+fn parse_time_of_day(value: &str) -> Option<u32> {
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    if parts.len() < 2 || parts.len() > 3 {
+        return None;
+    }
+    let hours: u32 = parts[0].parse().ok()?;
+    let minutes: u32 = parts[1].parse().ok()?;
+    let seconds: u32 = match parts.get(2) {
+        Some(s) => s.parse().ok()?,
+        None => 0,
+    };
+    if hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+/// A confidence tier: signals with confidence >= `min_confidence` get `label`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ConfidenceTier {
+    pub min_confidence: f64,
+    pub label: String,
+}
+
+/// Configuration for the OFI engine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OFIConfig {
     pub websocket_url: String,
+    pub exchange: Exchange,  // Venue protocol of websocket_url, and of overrides that set no exchange
     pub imbalance_threshold: f64,
     pub absorption_threshold: f64,
     pub delta_threshold: f64,
     pub lookback_period_ms: u64,
+    pub min_book_quality: f64,  // Suppress signals when book quality is below this, 0 = no gate
+    pub min_book_update_rate: f64,  // Book updates per second over the lookback required for StrongBuy/StrongSell, 0 = no gate
+    pub reject_crossed_books: bool,  // No signal while the best bid is at or above the best ask
+    pub book_quality_confidence_scaling: bool,  // Multiply signal confidence by book quality
+    pub book_quality_max_spread_bps: f64,  // Spread at which the book quality spread component reaches 0
+    pub book_quality_max_age_ms: u64,  // Book lag behind the latest trade at which the freshness component reaches 0
+    pub book_quality_use_twa_spread: bool,  // Score the spread component on the lookback's time-weighted average spread instead of the current one
+    pub fast_mode: bool,  // Top-of-book imbalance + delta only; skips stacked, absorption, exhaustion and book quality
+    pub fast_mode_levels: usize,  // Levels per side considered in fast mode
+    pub fast_mode_signal_confidence: f64,  // Confidence of fast mode Buy/Sell signals
+    pub touch_imbalance_threshold: f64,  // Best bid/ask size ratio (or its inverse for sells) held for a touch imbalance signal, 0 = no signal
+    pub touch_imbalance_updates: usize,  // Consecutive book updates the touch imbalance must hold
+    pub touch_imbalance_confidence: f64,  // Confidence of touch imbalance Buy/Sell signals
+    pub replenishment_confidence_weight: f64,  // How much a slow refill of the supporting touch lowers reversal confidence, 0 = not at all, 1 = fully
+    pub multi_lookback: Vec<u64>,  // Candidate lookbacks (ms) evaluated per analysis, the best window's signal is emitted; empty = lookback_period_ms only
+    pub funding_rate_extreme: f64,  // Absolute funding rate (0.0005 = 0.05%) from which the funding gate applies, 0 = no gate
+    pub funding_suppress_continuation: bool,  // Under extreme funding, drop continuation signals joining the crowded side
+    pub funding_reversal_boost: f64,  // Under extreme funding, confidence multiplier of reversal signals against the crowded side
+    pub funding_rate_ttl_secs: u64,  // Funding rates older than this are ignored
+    pub signal_price_source: SignalPriceSource,  // Price carried by signals; falls back to mid when unavailable
+    pub weighted_mid_levels: usize,  // Levels per side in the weighted mid (OFIMetrics and the WeightedMid price source)
+    pub volatility_window_ms: u64,  // Window of trades for realized volatility in OFIMetrics and the market-condition multiplier
+    pub reference_volatility: f64,  // Realized volatility at which the market-condition multiplier is 1.0, 0 = no adaptation
+    pub max_volatility_multiplier: f64,  // Market-condition multiplier bounds: 1/max..max
+    pub imbalance_level_cap_multiple: f64,  // Cap each level's notional at this multiple of the median level, 0 = no cap
+    pub flow_consistency_buckets: usize,  // Sub-intervals of the lookback used for flow consistency, 0 = not computed
+    pub flow_consistency_confidence_scaling: bool,  // Multiply signal confidence by flow consistency
+    pub imbalance_momentum_snapshots: usize,  // Recent books the imbalance momentum is fitted over, 0 = not computed
+    pub delta_decay_half_life_ms: u64,  // Age at which a trade counts half in the lookback delta, 0 = every trade counts fully
+    pub suppress_decaying_imbalance: bool,  // Withhold directional signals whose imbalance momentum opposes them
+    pub jump_suppress_bps: f64,  // Mid or trade price jump that triggers signal suppression, 0 = disabled
+    pub jump_cooldown_ms: u64,  // How long signals stay suppressed after a jump
+    pub max_trade_deviation_bps: f64,  // Drop trades printing further than this from the current mid, 0 = keep all
+    pub reorder_buffer_ms: u64,  // Hold trades this long and release them in timestamp order before they reach CVD, 0 = arrival order
+    pub imbalance_basis: ImbalanceBasis,  // Resting book ratio or order flow imbalance between consecutive books
+    pub imbalance_notional_depth: f64,  // Resting imbalance over this much notional per side closest to the mid instead of by level count, 0 = by levels
+    pub signal_confirm_ms: u64,  // A candidate signal must keep re-qualifying this long before it is emitted, 0 = emit immediately
+    pub adapt_thin_books: bool,  // Scale stacked imbalance levels to the available depth instead of skipping thin books
+    pub stacked_min_depth: usize,  // Levels required on both bid and ask sides before stacked imbalance is computed
+    pub explain_signals: bool,  // Attach the evaluated rule inputs to every signal as a SignalExplanation
+    pub record_effective_params: bool,  // Attach the resolved thresholds and detection settings to emitted signals as EffectiveParams
+    pub sizing_slippage_bps: f64,  // Slippage budget from the touch for the signals' max_advisable_size, 0 = no sizing hint
+    pub unified_event_clock: bool,  // Anchor the lookback at the latest book or trade timestamp instead of the book's
+    pub large_print_notional: f64,  // Trades at least this notional count as large prints, 0 = no large print confirmation
+    pub large_print_min_count: usize,  // Large prints in the signal's direction required to confirm StrongBuy/StrongSell
+    pub min_buy_trades: usize,  // Buy trades in the lookback required for continuation signals, 0 = no gate
+    pub min_sell_trades: usize,  // Sell trades in the lookback required for continuation signals, 0 = no gate
+    pub large_print_confidence_boost: f64,  // Added to the strong signal confidence when large prints confirm it
+    pub consensus_confidence_boost: f64,  // Added to a signal's confidence per other rule agreeing on its direction; conflicting rules emit the strongest unboosted
+    pub absorption_interpretation: AbsorptionInterpretation,  // Trade absorption as a reversal or a continuation
+    pub direction_stickiness_ms: u64,  // After a directional signal, opposite signals need the flip confidence for this long, 0 = disabled
+    pub direction_flip_confidence: f64,  // Confidence an opposite signal needs inside the stickiness window, 0 = always suppressed
+    pub calibration_horizon_ms: u64,  // Resolve signal outcomes after this long and blend confidence with the hit-rate, 0 = disabled
+    pub calibration_min_samples: u64,  // Resolved outcomes needed before the hit-rate is blended in; also its weighting prior
     pub analysis_duration_limit_ms: u64,
     pub analysis_duration_per_cycle_ms: u64,  // Duration for each analysis cycle
     pub trade_storage_limit: usize,
+    pub analysis_trade_limit: usize,  // Most recent trades fed into one analysis; lookback_period_ms filters within these
+    pub global_entry_limit: usize,  // Cap on stored trades + book levels across all symbols, LRU symbols evicted first, 0 = no cap
+    pub expected_trade_rate: f64,  // Trades per second of a busy symbol, used to check the lookback is covered, 0 = no check
+    pub strict_lookback_coverage: bool,  // Fail config load instead of warning when the retained trades can't cover the lookback
     pub strong_signal_confidence: f64,
     pub reversal_signal_confidence: f64,
     pub exhaustion_signal_confidence: f64,
-    pub market_condition_adaptation: bool,
+    pub absorption_confirmation_window_ms: u64,  // Wait this long for a delta flip after absorption, 0 = emit absorption immediately
+    pub absorption_confirmed_confidence: f64,  // Confidence of an absorption confirmed by a delta flip
+    pub absorption_tentative_confidence: Option<f64>,  // Emit unconfirmed absorption at this confidence, None = emit nothing
+    pub market_condition_adaptation: bool,  // Scale thresholds by realized volatility (needs reference_volatility)
     pub max_concurrent_websocket_connections: Option<usize>,  // Maximum concurrent WebSocket connections
+    pub confidence_tiers: Vec<ConfidenceTier>,  // Ordered by ascending min_confidence, empty = no tier labels
+    pub maintenance_windows: Vec<MaintenanceWindow>,  // Signals are not forwarded inside these windows
+    pub intent_rules: Vec<IntentRule>,  // Signal to order intent mapping, unmatched signals use the default mapping
+    pub synthetics: Vec<SyntheticInstrument>,  // Spreads analyzed alongside the watchlist, fed by both legs' streams
+    pub groups: Vec<SymbolGroup>,  // Baskets whose members' flow is aggregated into group signals
+    pub field_bounds: HashMap<String, FieldBounds>,  // [bounds.<field>] overrides of the default numeric ranges
+    pub status_http_addr: Option<String>,  // e.g. "127.0.0.1:8089", status endpoint disabled if not provided
+    pub signal_server_addr: Option<String>,  // e.g. "127.0.0.1:8090", WebSocket signal stream (signal-server feature)
+    pub mqtt_host: Option<String>,  // MQTT broker host signals are published to (mqtt feature)
+    pub mqtt_port: u16,  // MQTT broker port
+    pub mqtt_client_id: String,  // Client id presented to the broker
+    pub mqtt_signal_topic: String,  // Topic for signals, `{symbol}` is replaced by the symbol
+    pub mqtt_heartbeat_topic: Option<String>,  // Topic for symbol heartbeats (with heartbeat_interval_secs), not published if not provided
+    pub mqtt_qos: u8,  // MQTT QoS level of published messages: 0, 1 or 2
+    pub mqtt_reconnect_secs: u64,  // Delay before reconnecting after a broker disconnect
+    pub signal_db_path: Option<String>,  // SQLite database recording emitted signals (sqlite feature)
+    pub task_start_stagger_ms: u64,  // Delay between consecutive new task startups on watchlist refresh, 0 = no stagger
+    pub task_startup_grace_secs: u64,  // Tasks younger than this are not stopped on watchlist refresh, 0 = no grace
+    pub rank_watchlist: bool,  // Connect only the top-ranked screener candidates that fit the connection limit
+    pub min_execution_confidence: f64,  // Signals below this confidence are not forwarded for execution
+    pub symbol_overrides: HashMap<String, SymbolOverride>,  // Per-symbol overrides keyed by symbol
+    pub reconnect_clean_ms: u64,  // Reconnect delay after a server close or clean stream end
+    pub reconnect_error_ms: u64,  // Reconnect delay after a connection error
+    pub reconnect_spacing_ms: u64,  // Minimum gap between reconnect attempts across all symbols, 0 = no spacing
+    pub reconnect_jitter_ms: u64,  // Random extra reconnect delay of up to this much, 0 = no jitter
+    pub stop_on_invalid_symbol: bool,  // Stop a symbol's task instead of reconnecting when the exchange rejects the symbol (e.g. delisted)
+    pub require_all_channels: bool,  // Suppress signals until both books and trade channels deliver data
+    pub channel_startup_timeout_ms: u64,  // Reconnect if a required channel delivers nothing within this time, 0 = suppress only
+    pub deadman_timeout_secs: u64,  // Flatten all positions if no analysis cycle runs for this long, 0 = disabled
+    pub position_monitor_interval_secs: u64,  // Interval between Python position monitor calls
+    pub max_inflight_position_monitors: usize,  // Monitor calls allowed to run at once; a tick beyond this is skipped, not queued
+    pub timestamp_unit: TimestampUnit,  // Unit of exchange timestamps, normalized to milliseconds at ingestion
+    pub timestamp_source: TimestampSource,  // Clock for signal timestamps and the duplicate signal window
+    pub emit_no_signal_events: bool,  // Forward NoSignal results to the observability sink (never to execution)
+    pub market_data_feed_capacity: usize,  // Buffered events per subscriber of the engine's raw book/trade feed, 0 = no feed
+    pub flight_recorder_messages: usize,  // Raw WebSocket messages kept per symbol for crash post-mortems, 0 = not recorded
+    pub flight_recorder_dir: String,  // Directory the recorded messages are dumped to on panic or on demand
+    pub executor_max_retries: u32,  // Retries of the Python executor call on transient errors only
+    pub max_daily_signals_per_symbol: u32,  // Signals forwarded for execution per symbol per UTC day, 0 = unlimited
+    pub executor_retry_backoff_ms: u64,  // Initial retry delay, doubled on each subsequent retry
+    pub cvd_state_dir: Option<String>,  // Directory for persisted per-symbol session CVD, not persisted if not provided
+    pub cvd_resume_window_secs: u64,  // Resume persisted CVD only if it is at most this old, otherwise start from zero
+    pub preflight_checks: bool,  // Validate config, WebSocket reachability and Python imports at startup, exit on failure
+    pub config_audit: bool,  // Log every resolved config field with the layer it came from at startup
+    pub python_unavailable_policy: PythonUnavailablePolicy,  // Startup behaviour when the Python screener/executor modules fail to import
+    pub fallback_watchlist: Vec<String>,  // Symbols analyzed in place of screener candidates under the Fallback policy
+    pub analysis_version_gate: bool,  // Run book strategies once per book version and skip analysis when nothing changed
+    pub coalesce_analyses: bool,  // At most one analysis per symbol at a time; triggers meanwhile coalesce into one rerun
+    pub channels: Vec<String>,  // Bitget channels subscribed per symbol; must include "books" and "trade"
+    pub verify_inst_id: bool,  // Drop messages whose instId differs from the connection's symbol
+    pub unsubscribe_before_subscribe: bool,  // Unsubscribe the symbol's channels before subscribing on every connect
+    pub warmup_min_trades: usize,  // Trades in the window before a symbol counts as ready
+    pub warmup_min_book_levels: usize,  // Book levels per side before a symbol counts as ready
+    pub credentials_path: Option<String>,  // Secrets directory or KEY=VALUE file for credentials, env vars take precedence
+    pub exchanges: Vec<String>,  // Exchanges whose <EXCHANGE>_API_KEY etc. credentials are loaded; Bitget is always loaded
+    pub credentials: HashMap<String, ExchangeCredentials>,  // Per-exchange credentials keyed by lower-case name, Bitget's included
+    pub analysis_batch_ms: u64,  // Coalesce book/trade updates for up to this long into one analysis, 0 = analyze every message
+    pub analysis_batch_max_updates: usize,  // Analyze early once this many updates are batched, 0 = window only
+    pub analysis_cadence_ms: u64,  // Analyze on a fixed wall-clock tick instead of on messages (overrides batching), 0 = message-driven
+    pub book_dedupe: BookDedupe,  // Skip storing and analyzing books identical to the stored one
+    pub error_log_coalesce_ms: u64,  // Log a repeated connection/parse/analysis error once per window with a repeat count, 0 = log every error
+    pub resync_suppress_ms: u64,  // Withhold signals for this long after a book snapshot (subscribe or resync), 0 = disabled
+    pub dashboard_refresh_ms: u64,  // Redraw interval of the terminal dashboard (`--tui`, `tui` feature)
+    pub adaptive_skip_max_rate: f64,  // Data messages per second above which analyses are downsampled, 0 = analyze every message
+    pub adaptive_skip_max_factor: u64,  // Upper bound of the adaptive skip factor
+    pub heartbeat_interval_secs: u64,  // Emit a per-symbol state heartbeat this often, 0 = no heartbeats
 }
 
 impl Default for OFIConfig {
     fn default() -> Self {
         Self {
-            api_key: String::new(),
-            secret_key: String::new(),
-            passphrase: String::new(),
             websocket_url: String::new(),  // Harus disediakan di config.toml
+            exchange: Exchange::Bitget,  // Bitget if not provided
             imbalance_threshold: 0.0,  // Harus disediakan di config.toml
             absorption_threshold: 0.0,  // Harus disediakan di config.toml
             delta_threshold: 0.0,  // Harus disediakan di config.toml
             lookback_period_ms: 0,  // Harus disediakan di config.toml
+            min_book_quality: 0.0,  // No book quality gate if not provided
+            min_book_update_rate: 0.0,  // Strong signals not gated on update rate if not provided
+            reject_crossed_books: true,  // Crossed and locked books guarded if not provided
+            book_quality_confidence_scaling: false,  // Confidence not scaled if not provided
+            book_quality_max_spread_bps: 20.0,
+            book_quality_max_age_ms: 5000,
+            book_quality_use_twa_spread: false,  // Current spread scored if not provided
+            fast_mode: false,  // Full-depth analysis if not provided
+            fast_mode_levels: 1,
+            fast_mode_signal_confidence: 0.0,  // Harus disediakan jika fast_mode diaktifkan
+            touch_imbalance_threshold: 0.0,  // No touch imbalance signal if not provided
+            touch_imbalance_updates: 3,
+            touch_imbalance_confidence: 0.5,
+            replenishment_confidence_weight: 0.0,  // Reversal confidence not weighted by replenishment if not provided
+            multi_lookback: Vec::new(),  // Single lookback_period_ms window if not provided
+            funding_rate_extreme: 0.0,  // No funding gate if not provided
+            funding_suppress_continuation: true,
+            funding_reversal_boost: 1.0,  // Reversal confidence unchanged if not provided
+            funding_rate_ttl_secs: 300,
+            signal_price_source: SignalPriceSource::Mid,
+            weighted_mid_levels: 5,
+            volatility_window_ms: 60000,
+            reference_volatility: 0.0,  // Thresholds not scaled by volatility if not provided
+            max_volatility_multiplier: 2.0,
+            imbalance_level_cap_multiple: 0.0,  // Levels uncapped if not provided
+            flow_consistency_buckets: 5,
+            flow_consistency_confidence_scaling: false,  // Confidence not scaled if not provided
+            imbalance_momentum_snapshots: 0,  // Imbalance momentum not computed if not provided
+            delta_decay_half_life_ms: 0,  // Unweighted delta if not provided
+            suppress_decaying_imbalance: false,
+            jump_suppress_bps: 0.0,  // Jump guard disabled if not provided
+            jump_cooldown_ms: 3000,
+            max_trade_deviation_bps: 0.0,  // All trades kept if not provided
+            reorder_buffer_ms: 0,  // Trades applied in arrival order if not provided
+            imbalance_basis: ImbalanceBasis::Resting,
+            imbalance_notional_depth: 0.0,  // Imbalance measured over levels if not provided
+            signal_confirm_ms: 0,  // Signals emitted on the first qualifying tick if not provided
+            adapt_thin_books: false,  // Books thinner than the checked depth never stack if not provided
+            stacked_min_depth: 1,  // Only a non-empty opposite side required if not provided
+            explain_signals: false,  // No explanation recorded if not provided
+            record_effective_params: false,  // No parameter snapshot recorded if not provided
+            sizing_slippage_bps: 0.0,  // No sizing hint if not provided
+            unified_event_clock: false,  // Lookback anchored at the book timestamp if not provided
+            large_print_notional: 0.0,  // Continuation signals need no large prints if not provided
+            large_print_min_count: 1,
+            min_buy_trades: 0,  // Continuation not gated on trade counts if not provided
+            min_sell_trades: 0,
+            large_print_confidence_boost: 0.0,
+            consensus_confidence_boost: 0.0,  // Agreeing rules recorded but not boosted if not provided
+            absorption_interpretation: AbsorptionInterpretation::Reversal,
+            direction_stickiness_ms: 0,  // Opposite signals never held back if not provided
+            direction_flip_confidence: 0.0,
+            calibration_horizon_ms: 0,  // Raw confidence reported if not provided
+            calibration_min_samples: 20,
             analysis_duration_limit_ms: 0,  // Harus disediakan di config.toml
             analysis_duration_per_cycle_ms: 0,  // Harus disediakan di config.toml
             trade_storage_limit: 0,  // Harus disediakan di config.toml
+            analysis_trade_limit: 100,
+            global_entry_limit: 0,  // No global cap if not provided
+            expected_trade_rate: 0.0,  // Lookback coverage not checked if not provided
+            strict_lookback_coverage: false,
             strong_signal_confidence: 0.0,  // Harus disediakan di config.toml
             reversal_signal_confidence: 0.0,  // Harus disediakan di config.toml
             exhaustion_signal_confidence: 0.0,  // Harus disediakan di config.toml
+            absorption_confirmation_window_ms: 0,  // Two-stage absorption disabled if not provided
+            absorption_confirmed_confidence: 0.0,  // Harus disediakan jika absorption_confirmation_window_ms diset
+            absorption_tentative_confidence: None,  // Unconfirmed absorption emits nothing if not provided
             market_condition_adaptation: false,  // Harus disediakan di config.toml
-            max_concurrent_websocket_connections: None,  // Defaults to 20 in main.rs if not provided
+            max_concurrent_websocket_connections: None,  // Defaults to 20 (max_concurrent_connections) if not provided
+            confidence_tiers: Vec::new(),  // Optional, [[tier]] entries in config.toml
+            maintenance_windows: Vec::new(),  // Optional, [[maintenance]] entries in config.toml
+            intent_rules: Vec::new(),  // Optional, [[intent]] entries in config.toml
+            synthetics: Vec::new(),  // Optional, [[synthetic]] entries in config.toml
+            groups: Vec::new(),  // Optional, [[group]] entries in config.toml
+            field_bounds: HashMap::new(),  // Default ranges only if not provided
+            status_http_addr: None,  // Status endpoint disabled if not provided
+            signal_server_addr: None,  // Signal server disabled if not provided
+            mqtt_host: None,  // MQTT publisher disabled if not provided
+            mqtt_port: 1883,
+            mqtt_client_id: "ofi-sentinel".to_string(),
+            mqtt_signal_topic: "ofi/signals/{symbol}".to_string(),
+            mqtt_heartbeat_topic: None,  // Heartbeats not published over MQTT if not provided
+            mqtt_qos: 1,
+            mqtt_reconnect_secs: 5,
+            signal_db_path: None,  // Signals not stored if not provided
+            task_start_stagger_ms: 0,  // Start all new tasks at once if not provided
+            task_startup_grace_secs: 0,  // No startup grace if not provided
+            rank_watchlist: false,  // Every candidate gets a task, queued on the connection limit, if not provided
+            min_execution_confidence: 0.0,  // Forward every actionable signal if not provided
+            symbol_overrides: HashMap::new(),  // Optional, [symbols.<SYMBOL>] sections in config.toml
+            reconnect_clean_ms: 5000,
+            reconnect_error_ms: 5000,
+            reconnect_spacing_ms: 0,  // Symbols reconnect independently if not provided
+            reconnect_jitter_ms: 0,
+            stop_on_invalid_symbol: true,  // Invalid symbols are not retried if not provided
+            require_all_channels: false,  // Signals allowed on partial subscriptions if not provided
+            channel_startup_timeout_ms: 30000,
+            deadman_timeout_secs: 0,  // Dead-man's switch disabled if not provided
+            position_monitor_interval_secs: 60,
+            max_inflight_position_monitors: 1,  // No overlapping monitor calls if not provided
+            timestamp_unit: TimestampUnit::Auto,  // Detected from magnitude if not provided
+            timestamp_source: TimestampSource::Local,  // Local receipt time, as before the option existed, if not provided
+            emit_no_signal_events: false,  // NoSignal results are discarded if not provided
+            market_data_feed_capacity: 0,  // No raw market data feed if not provided
+            flight_recorder_messages: 0,  // Raw messages not recorded if not provided
+            flight_recorder_dir: "flight-recorder".to_string(),  // Default dump directory if not provided
+            executor_max_retries: 0,  // Single attempt if not provided
+            max_daily_signals_per_symbol: 0,  // No daily cap if not provided
+            executor_retry_backoff_ms: 500,
+            cvd_state_dir: None,  // Session CVD not persisted if not provided
+            cvd_resume_window_secs: 900,
+            preflight_checks: false,  // Startup pre-flight skipped if not provided
+            config_audit: false,  // No config audit logged if not provided
+            python_unavailable_policy: PythonUnavailablePolicy::Ignore,  // Keeps running without Python if not provided
+            fallback_watchlist: Vec::new(),
+            analysis_version_gate: false,  // Every message triggers a full analysis if not provided
+            coalesce_analyses: false,  // Analyses not guarded if not provided
+            channels: vec!["books".to_string(), "trade".to_string()],
+            verify_inst_id: false,  // Messages routed by their own instId if not provided
+            unsubscribe_before_subscribe: false,  // Fresh connections subscribe directly if not provided
+            warmup_min_trades: 0,  // Ready as soon as a two-sided book arrives if not provided
+            warmup_min_book_levels: 1,
+            credentials_path: None,  // Credentials from env vars only if not provided
+            exchanges: vec!["bitget".to_string()],  // Bitget only if not provided
+            credentials: HashMap::new(),  // Loaded from env vars and credentials_path
+            analysis_batch_ms: 0,  // No batching if not provided
+            analysis_batch_max_updates: 0,
+            analysis_cadence_ms: 0,  // Message-driven analysis if not provided
+            book_dedupe: BookDedupe::Off,  // Every book applied if not provided
+            error_log_coalesce_ms: 0,  // Every error logged if not provided
+            resync_suppress_ms: 0,  // Signals allowed right after a snapshot if not provided
+            dashboard_refresh_ms: 500,
+            adaptive_skip_max_rate: 0.0,  // No downsampling if not provided
+            adaptive_skip_max_factor: 64,
+            heartbeat_interval_secs: 0,  // No symbol heartbeats if not provided
+        }
+    }
+}
+
+#[cfg(test)]
+impl OFIConfig {
+    /// Defaults with the settings config.toml must provide taken from the shipped config.toml
+    pub(crate) fn for_tests() -> Self {
+        Self {
+            websocket_url: "wss://ws.bitget.com/v2/ws/public".to_string(),
+            imbalance_threshold: 3.0,
+            absorption_threshold: 1000.0,
+            delta_threshold: 50000.0,
+            lookback_period_ms: 5000,
+            analysis_duration_limit_ms: 3_600_000,
+            analysis_duration_per_cycle_ms: 5000,
+            trade_storage_limit: 200,
+            strong_signal_confidence: 0.9,
+            reversal_signal_confidence: 0.8,
+            exhaustion_signal_confidence: 0.7,
+            ..Self::default()
         }
     }
 }
@@ -109,6 +1115,9 @@ impl OFIConfig {
             if let Some(url) = ofi_toml.websocket_url {
                 config.websocket_url = url;
             }
+            if let Some(exchange) = ofi_toml.exchange {
+                config.exchange = exchange;
+            }
             if let Some(limit) = ofi_toml.analysis_duration_limit_ms {
                 config.analysis_duration_limit_ms = limit;
             }
@@ -118,6 +1127,18 @@ impl OFIConfig {
             if let Some(limit) = ofi_toml.trade_storage_limit {
                 config.trade_storage_limit = limit;
             }
+            if let Some(limit) = ofi_toml.analysis_trade_limit {
+                config.analysis_trade_limit = limit;
+            }
+            if let Some(limit) = ofi_toml.global_entry_limit {
+                config.global_entry_limit = limit;
+            }
+            if let Some(rate) = ofi_toml.expected_trade_rate {
+                config.expected_trade_rate = rate;
+            }
+            if let Some(strict) = ofi_toml.strict_lookback_coverage {
+                config.strict_lookback_coverage = strict;
+            }
             if let Some(confidence) = ofi_toml.strong_signal_confidence {
                 config.strong_signal_confidence = confidence;
             }
@@ -127,12 +1148,195 @@ impl OFIConfig {
             if let Some(confidence) = ofi_toml.exhaustion_signal_confidence {
                 config.exhaustion_signal_confidence = confidence;
             }
+            if let Some(window) = ofi_toml.absorption_confirmation_window_ms {
+                config.absorption_confirmation_window_ms = window;
+            }
+            if let Some(confidence) = ofi_toml.absorption_confirmed_confidence {
+                config.absorption_confirmed_confidence = confidence;
+            }
+            if let Some(confidence) = ofi_toml.absorption_tentative_confidence {
+                config.absorption_tentative_confidence = Some(confidence);
+            }
             if let Some(adaptation) = ofi_toml.market_condition_adaptation {
                 config.market_condition_adaptation = adaptation;
             }
             if let Some(max_connections) = ofi_toml.max_concurrent_websocket_connections {
                 config.max_concurrent_websocket_connections = Some(max_connections);
             }
+            if let Some(addr) = ofi_toml.status_http_addr {
+                config.status_http_addr = Some(addr);
+            }
+            if let Some(addr) = ofi_toml.signal_server_addr {
+                config.signal_server_addr = Some(addr);
+            }
+            if let Some(host) = ofi_toml.mqtt_host {
+                config.mqtt_host = Some(host);
+            }
+            if let Some(port) = ofi_toml.mqtt_port {
+                config.mqtt_port = port;
+            }
+            if let Some(client_id) = ofi_toml.mqtt_client_id {
+                config.mqtt_client_id = client_id;
+            }
+            if let Some(topic) = ofi_toml.mqtt_signal_topic {
+                config.mqtt_signal_topic = topic;
+            }
+            if let Some(topic) = ofi_toml.mqtt_heartbeat_topic {
+                config.mqtt_heartbeat_topic = Some(topic);
+            }
+            if let Some(qos) = ofi_toml.mqtt_qos {
+                config.mqtt_qos = qos;
+            }
+            if let Some(secs) = ofi_toml.mqtt_reconnect_secs {
+                config.mqtt_reconnect_secs = secs;
+            }
+            if let Some(path) = ofi_toml.signal_db_path {
+                config.signal_db_path = Some(path);
+            }
+            if let Some(stagger) = ofi_toml.task_start_stagger_ms {
+                config.task_start_stagger_ms = stagger;
+            }
+            if let Some(grace) = ofi_toml.task_startup_grace_secs {
+                config.task_startup_grace_secs = grace;
+            }
+            if let Some(rank) = ofi_toml.rank_watchlist {
+                config.rank_watchlist = rank;
+            }
+            if let Some(confidence) = ofi_toml.min_execution_confidence {
+                config.min_execution_confidence = confidence;
+            }
+            if let Some(delay) = ofi_toml.reconnect_clean_ms {
+                config.reconnect_clean_ms = delay;
+            }
+            if let Some(delay) = ofi_toml.reconnect_error_ms {
+                config.reconnect_error_ms = delay;
+            }
+            if let Some(spacing) = ofi_toml.reconnect_spacing_ms {
+                config.reconnect_spacing_ms = spacing;
+            }
+            if let Some(jitter) = ofi_toml.reconnect_jitter_ms {
+                config.reconnect_jitter_ms = jitter;
+            }
+            if let Some(stop) = ofi_toml.stop_on_invalid_symbol {
+                config.stop_on_invalid_symbol = stop;
+            }
+            if let Some(require) = ofi_toml.require_all_channels {
+                config.require_all_channels = require;
+            }
+            if let Some(timeout) = ofi_toml.channel_startup_timeout_ms {
+                config.channel_startup_timeout_ms = timeout;
+            }
+            if let Some(timeout) = ofi_toml.deadman_timeout_secs {
+                config.deadman_timeout_secs = timeout;
+            }
+            if let Some(interval) = ofi_toml.position_monitor_interval_secs {
+                config.position_monitor_interval_secs = interval;
+            }
+            if let Some(max) = ofi_toml.max_inflight_position_monitors {
+                config.max_inflight_position_monitors = max;
+            }
+            if let Some(unit) = ofi_toml.timestamp_unit {
+                config.timestamp_unit = unit;
+            }
+            if let Some(source) = ofi_toml.timestamp_source {
+                config.timestamp_source = source;
+            }
+            if let Some(emit) = ofi_toml.emit_no_signal_events {
+                config.emit_no_signal_events = emit;
+            }
+            if let Some(capacity) = ofi_toml.market_data_feed_capacity {
+                config.market_data_feed_capacity = capacity;
+            }
+            if let Some(messages) = ofi_toml.flight_recorder_messages {
+                config.flight_recorder_messages = messages;
+            }
+            if let Some(dir) = ofi_toml.flight_recorder_dir {
+                config.flight_recorder_dir = dir;
+            }
+            if let Some(cap) = ofi_toml.max_daily_signals_per_symbol {
+                config.max_daily_signals_per_symbol = cap;
+            }
+            if let Some(retries) = ofi_toml.executor_max_retries {
+                config.executor_max_retries = retries;
+            }
+            if let Some(backoff) = ofi_toml.executor_retry_backoff_ms {
+                config.executor_retry_backoff_ms = backoff;
+            }
+            if let Some(dir) = ofi_toml.cvd_state_dir {
+                config.cvd_state_dir = Some(dir);
+            }
+            if let Some(window) = ofi_toml.cvd_resume_window_secs {
+                config.cvd_resume_window_secs = window;
+            }
+            if let Some(enabled) = ofi_toml.preflight_checks {
+                config.preflight_checks = enabled;
+            }
+            if let Some(enabled) = ofi_toml.config_audit {
+                config.config_audit = enabled;
+            }
+            if let Some(policy) = ofi_toml.python_unavailable_policy {
+                config.python_unavailable_policy = policy;
+            }
+            if let Some(watchlist) = ofi_toml.fallback_watchlist {
+                config.fallback_watchlist = watchlist;
+            }
+            if let Some(enabled) = ofi_toml.analysis_version_gate {
+                config.analysis_version_gate = enabled;
+            }
+            if let Some(enabled) = ofi_toml.coalesce_analyses {
+                config.coalesce_analyses = enabled;
+            }
+            if let Some(channels) = ofi_toml.channels {
+                config.channels = channels;
+            }
+            if let Some(verify) = ofi_toml.verify_inst_id {
+                config.verify_inst_id = verify;
+            }
+            if let Some(unsubscribe) = ofi_toml.unsubscribe_before_subscribe {
+                config.unsubscribe_before_subscribe = unsubscribe;
+            }
+            if let Some(trades) = ofi_toml.warmup_min_trades {
+                config.warmup_min_trades = trades;
+            }
+            if let Some(levels) = ofi_toml.warmup_min_book_levels {
+                config.warmup_min_book_levels = levels;
+            }
+            if let Some(path) = ofi_toml.credentials_path {
+                config.credentials_path = Some(path);
+            }
+            if let Some(exchanges) = ofi_toml.exchanges {
+                config.exchanges = exchanges;
+            }
+            if let Some(batch) = ofi_toml.analysis_batch_ms {
+                config.analysis_batch_ms = batch;
+            }
+            if let Some(max_updates) = ofi_toml.analysis_batch_max_updates {
+                config.analysis_batch_max_updates = max_updates;
+            }
+            if let Some(cadence) = ofi_toml.analysis_cadence_ms {
+                config.analysis_cadence_ms = cadence;
+            }
+            if let Some(dedupe) = ofi_toml.book_dedupe {
+                config.book_dedupe = dedupe;
+            }
+            if let Some(window) = ofi_toml.error_log_coalesce_ms {
+                config.error_log_coalesce_ms = window;
+            }
+            if let Some(window) = ofi_toml.resync_suppress_ms {
+                config.resync_suppress_ms = window;
+            }
+            if let Some(refresh) = ofi_toml.dashboard_refresh_ms {
+                config.dashboard_refresh_ms = refresh;
+            }
+            if let Some(rate) = ofi_toml.adaptive_skip_max_rate {
+                config.adaptive_skip_max_rate = rate;
+            }
+            if let Some(factor) = ofi_toml.adaptive_skip_max_factor {
+                config.adaptive_skip_max_factor = factor;
+            }
+            if let Some(interval) = ofi_toml.heartbeat_interval_secs {
+                config.heartbeat_interval_secs = interval;
+            }
         }
         
         // Get strategy parameters from [strategy] section for backward compatibility
@@ -149,18 +1353,249 @@ impl OFIConfig {
             if let Some(period) = strategy_toml.lookback_period_ms {
                 config.lookback_period_ms = period;
             }
+            if let Some(quality) = strategy_toml.min_book_quality {
+                config.min_book_quality = quality;
+            }
+            if let Some(rate) = strategy_toml.min_book_update_rate {
+                config.min_book_update_rate = rate;
+            }
+            if let Some(reject) = strategy_toml.reject_crossed_books {
+                config.reject_crossed_books = reject;
+            }
+            if let Some(scaling) = strategy_toml.book_quality_confidence_scaling {
+                config.book_quality_confidence_scaling = scaling;
+            }
+            if let Some(spread) = strategy_toml.book_quality_max_spread_bps {
+                config.book_quality_max_spread_bps = spread;
+            }
+            if let Some(age) = strategy_toml.book_quality_max_age_ms {
+                config.book_quality_max_age_ms = age;
+            }
+            if let Some(use_twa) = strategy_toml.book_quality_use_twa_spread {
+                config.book_quality_use_twa_spread = use_twa;
+            }
+            if let Some(fast_mode) = strategy_toml.fast_mode {
+                config.fast_mode = fast_mode;
+            }
+            if let Some(levels) = strategy_toml.fast_mode_levels {
+                config.fast_mode_levels = levels;
+            }
+            if let Some(confidence) = strategy_toml.fast_mode_signal_confidence {
+                config.fast_mode_signal_confidence = confidence;
+            }
+            if let Some(threshold) = strategy_toml.touch_imbalance_threshold {
+                config.touch_imbalance_threshold = threshold;
+            }
+            if let Some(updates) = strategy_toml.touch_imbalance_updates {
+                config.touch_imbalance_updates = updates;
+            }
+            if let Some(confidence) = strategy_toml.touch_imbalance_confidence {
+                config.touch_imbalance_confidence = confidence;
+            }
+            if let Some(weight) = strategy_toml.replenishment_confidence_weight {
+                config.replenishment_confidence_weight = weight;
+            }
+            if let Some(lookbacks) = strategy_toml.multi_lookback {
+                config.multi_lookback = lookbacks;
+            }
+            if let Some(extreme) = strategy_toml.funding_rate_extreme {
+                config.funding_rate_extreme = extreme;
+            }
+            if let Some(suppress) = strategy_toml.funding_suppress_continuation {
+                config.funding_suppress_continuation = suppress;
+            }
+            if let Some(boost) = strategy_toml.funding_reversal_boost {
+                config.funding_reversal_boost = boost;
+            }
+            if let Some(ttl) = strategy_toml.funding_rate_ttl_secs {
+                config.funding_rate_ttl_secs = ttl;
+            }
+            if let Some(source) = strategy_toml.signal_price_source {
+                config.signal_price_source = source;
+            }
+            if let Some(levels) = strategy_toml.weighted_mid_levels {
+                config.weighted_mid_levels = levels;
+            }
+            if let Some(window) = strategy_toml.volatility_window_ms {
+                config.volatility_window_ms = window;
+            }
+            if let Some(volatility) = strategy_toml.reference_volatility {
+                config.reference_volatility = volatility;
+            }
+            if let Some(multiplier) = strategy_toml.max_volatility_multiplier {
+                config.max_volatility_multiplier = multiplier;
+            }
+            if let Some(multiple) = strategy_toml.imbalance_level_cap_multiple {
+                config.imbalance_level_cap_multiple = multiple;
+            }
+            if let Some(buckets) = strategy_toml.flow_consistency_buckets {
+                config.flow_consistency_buckets = buckets;
+            }
+            if let Some(scaling) = strategy_toml.flow_consistency_confidence_scaling {
+                config.flow_consistency_confidence_scaling = scaling;
+            }
+            if let Some(snapshots) = strategy_toml.imbalance_momentum_snapshots {
+                config.imbalance_momentum_snapshots = snapshots;
+            }
+            if let Some(half_life) = strategy_toml.delta_decay_half_life_ms {
+                config.delta_decay_half_life_ms = half_life;
+            }
+            if let Some(suppress) = strategy_toml.suppress_decaying_imbalance {
+                config.suppress_decaying_imbalance = suppress;
+            }
+            if let Some(bps) = strategy_toml.jump_suppress_bps {
+                config.jump_suppress_bps = bps;
+            }
+            if let Some(cooldown) = strategy_toml.jump_cooldown_ms {
+                config.jump_cooldown_ms = cooldown;
+            }
+            if let Some(bps) = strategy_toml.max_trade_deviation_bps {
+                config.max_trade_deviation_bps = bps;
+            }
+            if let Some(buffer_ms) = strategy_toml.reorder_buffer_ms {
+                config.reorder_buffer_ms = buffer_ms;
+            }
+            if let Some(basis) = strategy_toml.imbalance_basis {
+                config.imbalance_basis = basis;
+            }
+            if let Some(depth) = strategy_toml.imbalance_notional_depth {
+                config.imbalance_notional_depth = depth;
+            }
+            if let Some(interpretation) = strategy_toml.absorption_interpretation {
+                config.absorption_interpretation = interpretation;
+            }
+            if let Some(stickiness) = strategy_toml.direction_stickiness_ms {
+                config.direction_stickiness_ms = stickiness;
+            }
+            if let Some(confidence) = strategy_toml.direction_flip_confidence {
+                config.direction_flip_confidence = confidence;
+            }
+            if let Some(horizon) = strategy_toml.calibration_horizon_ms {
+                config.calibration_horizon_ms = horizon;
+            }
+            if let Some(samples) = strategy_toml.calibration_min_samples {
+                config.calibration_min_samples = samples;
+            }
+            if let Some(confirm) = strategy_toml.signal_confirm_ms {
+                config.signal_confirm_ms = confirm;
+            }
+            if let Some(adapt) = strategy_toml.adapt_thin_books {
+                config.adapt_thin_books = adapt;
+            }
+            if let Some(depth) = strategy_toml.stacked_min_depth {
+                config.stacked_min_depth = depth;
+            }
+            if let Some(explain) = strategy_toml.explain_signals {
+                config.explain_signals = explain;
+            }
+            if let Some(record) = strategy_toml.record_effective_params {
+                config.record_effective_params = record;
+            }
+            if let Some(bps) = strategy_toml.sizing_slippage_bps {
+                config.sizing_slippage_bps = bps;
+            }
+            if let Some(unified) = strategy_toml.unified_event_clock {
+                config.unified_event_clock = unified;
+            }
+            if let Some(notional) = strategy_toml.large_print_notional {
+                config.large_print_notional = notional;
+            }
+            if let Some(count) = strategy_toml.large_print_min_count {
+                config.large_print_min_count = count;
+            }
+            if let Some(count) = strategy_toml.min_buy_trades {
+                config.min_buy_trades = count;
+            }
+            if let Some(count) = strategy_toml.min_sell_trades {
+                config.min_sell_trades = count;
+            }
+            if let Some(boost) = strategy_toml.large_print_confidence_boost {
+                config.large_print_confidence_boost = boost;
+            }
+            if let Some(boost) = strategy_toml.consensus_confidence_boost {
+                config.consensus_confidence_boost = boost;
+            }
+        }
+        
+        // Confidence tiers from [[tier]] entries
+        if let Some(tiers) = toml_config.tier_config {
+            config.confidence_tiers = tiers
+                .into_iter()
+                .map(|tier| ConfidenceTier { min_confidence: tier.min_confidence, label: tier.label })
+                .collect();
+        }
+        
+        // Exchange maintenance windows from [[maintenance]] entries
+        if let Some(windows) = toml_config.maintenance_config {
+            for window in windows {
+                config.maintenance_windows.push(MaintenanceWindow::parse(&window.start, &window.end)?);
+            }
+        }
+        
+        // Order intent mapping from [[intent]] entries
+        if let Some(rules) = toml_config.intent_config {
+            config.intent_rules = rules
+                .into_iter()
+                .map(|rule| IntentRule { signal_type: rule.signal_type, reason_code: rule.reason_code, action: rule.action })
+                .collect();
+        }
+        
+        // Synthetic spread instruments from [[synthetic]] entries
+        if let Some(synthetics) = toml_config.synthetic_config {
+            config.synthetics = synthetics
+                .into_iter()
+                .map(|synthetic| SyntheticInstrument {
+                    name: synthetic.name,
+                    long_leg: synthetic.long_leg,
+                    short_leg: synthetic.short_leg,
+                    ratio: synthetic.ratio.unwrap_or(1.0),
+                })
+                .collect();
+        }
+        
+        // Symbol groups from [[group]] entries; thresholds not given fall back to the strategy's
+        if let Some(groups) = toml_config.group_config {
+            config.groups = groups
+                .into_iter()
+                .map(|group| SymbolGroup {
+                    name: group.name,
+                    members: group.members,
+                    delta_notional_threshold: group.delta_notional_threshold,
+                    imbalance_threshold: group.imbalance_threshold.unwrap_or(config.imbalance_threshold),
+                    min_members: group.min_members.unwrap_or(2),
+                    confidence: group.confidence.unwrap_or(config.reversal_signal_confidence),
+                })
+                .collect();
         }
         
-        // Override only credentials from environment variables (security)
-        if let Ok(api_key) = env::var("BITGET_API_KEY") {
-            config.api_key = api_key;
+        if let Some(bounds) = toml_config.bounds_config {
+            config.field_bounds = bounds;
         }
-        if let Ok(secret_key) = env::var("BITGET_SECRET_KEY") {
-            config.secret_key = secret_key;
+        
+        // Per-symbol overrides from [symbols.<SYMBOL>] sections
+        if let Some(symbols) = toml_config.symbols_config {
+            for (symbol, symbol_toml) in symbols {
+                let entry = config.symbol_overrides.entry(symbol).or_default();
+                if let Some(confidence) = symbol_toml.min_execution_confidence {
+                    entry.min_execution_confidence = Some(confidence);
+                }
+                if let Some(url) = symbol_toml.websocket_url {
+                    entry.websocket_url = Some(url);
+                }
+                if let Some(exchange) = symbol_toml.exchange {
+                    entry.exchange = Some(exchange);
+                }
+                if let Some(strategies) = symbol_toml.strategies {
+                    entry.strategies = Some(strategies);
+                }
+            }
         }
-        if let Ok(passphrase) = env::var("BITGET_PASSPHRASE") {
-            config.passphrase = passphrase;
+        
+        // Credentials from mounted secrets, then environment variables on top (security)
+        if let Some(path) = config.credentials_path.clone() {
+            config.load_credentials_file(&path)?;
         }
+        config.load_credentials_env();
         
         // Validate that all required parameters are provided (not default values)
         if config.websocket_url.is_empty() {
@@ -207,8 +1642,23 @@ impl OFIConfig {
             return Err("exhaustion_signal_confidence must be provided in config.toml".into());
         }
         
+        if config.absorption_confirmation_window_ms > 0 && config.absorption_confirmed_confidence == 0.0 {
+            return Err("absorption_confirmed_confidence must be provided in config.toml when absorption_confirmation_window_ms is set".into());
+        }
+        
         // market_condition_adaptation can be false by default, so no validation needed here
         
+        config.validate_confidence_tiers()?;
+        config.validate_channels()?;
+        config.validate_ranges()?;
+        
+        if let Some(warning) = config.lookback_coverage_warning() {
+            if config.strict_lookback_coverage {
+                return Err(warning.into());
+            }
+            log::warn!("[Rust] {}", warning);
+        }
+        
         Ok(config)
     }
 
@@ -218,28 +1668,35 @@ impl OFIConfig {
         let mut config = Self::default();
         
         // Only credentials are loaded from environment variables
-        config.api_key = env::var("BITGET_API_KEY").unwrap_or_default();
-        config.secret_key = env::var("BITGET_SECRET_KEY").unwrap_or_default();
-        config.passphrase = env::var("BITGET_PASSPHRASE").unwrap_or_default();
+        config.load_credentials_env();
         
         // This function should not be used as it will fail validation without config.toml parameters
         Err("from_env() should not be used - config.toml is required for all parameters".into())
     }
     
-    /// Load configuration from default config.toml file and environment variables
-    pub fn from_default_config() -> Result<Self, Box<dyn std::error::Error>> {
+    /// Path of the default config.toml, the first of the expected locations that exists
+    pub fn default_config_path() -> Option<&'static str> {
         // Try multiple possible paths for config.toml
         let possible_paths = [
             "config/config.toml",           // Relative to current working directory
             "../config/config.toml",        // From src directory to root
             "../../config/config.toml",     // Additional possible path
         ];
-        
-        for path in &possible_paths {
-            if Path::new(path).exists() {
-                // Load non-credential parameters from TOML file and credentials from environment
-                return Self::from_toml_file(path);
-            }
+        possible_paths.into_iter().find(|path| Path::new(path).exists())
+    }
+    
+    /// JSON Schema of the config.toml file layout (sections and keys as written in the file,
+    /// every key optional unless the section requires it)
+    #[cfg(feature = "schema")]
+    pub fn file_schema() -> schemars::schema::RootSchema {
+        schemars::schema_for!(TomlConfig)
+    }
+
+    /// Load configuration from default config.toml file and environment variables
+    pub fn from_default_config() -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some(path) = Self::default_config_path() {
+            // Load non-credential parameters from TOML file and credentials from environment
+            return Self::from_toml_file(path);
         }
         
         // If config file doesn't exist in any of the expected locations, return error
@@ -248,16 +1705,18 @@ impl OFIConfig {
     
     /// Validate configuration parameters
     pub fn validate(&self) -> Result<(), String> {
-        if self.api_key.is_empty() {
-            return Err("API key is required".to_string());
-        }
-        
-        if self.secret_key.is_empty() {
-            return Err("Secret key is required".to_string());
-        }
-        
-        if self.passphrase.is_empty() {
-            return Err("Passphrase is required".to_string());
+        // Every exchange a stream connects to needs its credentials
+        for exchange in self.exchanges_in_use() {
+            let credentials = self.credentials_for(exchange.name()).unwrap_or_default();
+            if credentials.api_key.is_empty() {
+                return Err(format!("API key is required for {}", exchange.name()));
+            }
+            if credentials.secret_key.is_empty() {
+                return Err(format!("Secret key is required for {}", exchange.name()));
+            }
+            if credentials.passphrase.is_empty() {
+                return Err(format!("Passphrase is required for {}", exchange.name()));
+            }
         }
         
         if self.websocket_url.is_empty() {
@@ -280,6 +1739,10 @@ impl OFIConfig {
             return Err("Lookback period must be positive".to_string());
         }
         
+        if self.position_monitor_interval_secs == 0 || self.max_inflight_position_monitors == 0 {
+            return Err("Position monitor interval and max in-flight calls must be positive".to_string());
+        }
+        
         if self.analysis_duration_limit_ms == 0 {
             return Err("Analysis duration limit must be positive".to_string());
         }
@@ -292,6 +1755,18 @@ impl OFIConfig {
             return Err("Trade storage limit must be positive".to_string());
         }
         
+        if self.analysis_trade_limit == 0 {
+            return Err("Analysis trade limit must be positive".to_string());
+        }
+        
+        if !(self.imbalance_notional_depth >= 0.0 && self.imbalance_notional_depth.is_finite()) {
+            return Err("Imbalance notional depth must be a non-negative number".to_string());
+        }
+        
+        if !(self.expected_trade_rate >= 0.0 && self.expected_trade_rate.is_finite()) {
+            return Err("Expected trade rate must be a non-negative number".to_string());
+        }
+        
         if self.strong_signal_confidence <= 0.0 || self.strong_signal_confidence > 1.0 {
             return Err("Strong signal confidence must be between 0 and 1".to_string());
         }
@@ -304,6 +1779,922 @@ impl OFIConfig {
             return Err("Exhaustion signal confidence must be between 0 and 1".to_string());
         }
         
+        if self.absorption_confirmation_window_ms > 0
+            && (self.absorption_confirmed_confidence <= 0.0 || self.absorption_confirmed_confidence > 1.0)
+        {
+            return Err("Absorption confirmed confidence must be between 0 and 1".to_string());
+        }
+        
+        if let Some(confidence) = self.absorption_tentative_confidence {
+            if confidence <= 0.0 || confidence > 1.0 {
+                return Err("Absorption tentative confidence must be between 0 and 1".to_string());
+            }
+        }
+        
+        if !(0.0..=1.0).contains(&self.min_book_quality) {
+            return Err("Minimum book quality must be between 0 and 1".to_string());
+        }
+        
+        if self.jump_suppress_bps < 0.0 {
+            return Err("Jump suppress bps must not be negative".to_string());
+        }
+        
+        if self.max_trade_deviation_bps < 0.0 {
+            return Err("Max trade deviation bps must not be negative".to_string());
+        }
+        
+        if self.imbalance_level_cap_multiple < 0.0 {
+            return Err("Imbalance level cap multiple must not be negative".to_string());
+        }
+        
+        if self.book_quality_max_spread_bps < 0.0 {
+            return Err("Book quality max spread must not be negative".to_string());
+        }
+        
+        if self.fast_mode {
+            if self.fast_mode_levels == 0 {
+                return Err("Fast mode levels must be positive".to_string());
+            }
+            if self.fast_mode_signal_confidence <= 0.0 || self.fast_mode_signal_confidence > 1.0 {
+                return Err("Fast mode signal confidence must be between 0 and 1".to_string());
+            }
+        }
+        
+        if self.touch_imbalance_threshold != 0.0 {
+            if self.touch_imbalance_threshold <= 1.0 || self.touch_imbalance_updates == 0 {
+                return Err("Touch imbalance threshold must be above 1 and updates positive".to_string());
+            }
+            if self.touch_imbalance_confidence <= 0.0 || self.touch_imbalance_confidence > 1.0 {
+                return Err("Touch imbalance confidence must be between 0 and 1".to_string());
+            }
+        }
+        
+        if !(0.0..=1.0).contains(&self.replenishment_confidence_weight) {
+            return Err("Replenishment confidence weight must be between 0 and 1".to_string());
+        }
+        
+        if self.multi_lookback.contains(&0) {
+            return Err("Multi lookback windows must be positive".to_string());
+        }
+        
+        if self.funding_rate_extreme < 0.0 || self.funding_reversal_boost <= 0.0 {
+            return Err("Funding rate extreme must be non-negative and funding reversal boost positive".to_string());
+        }
+        
+        if self.large_print_notional < 0.0 {
+            return Err("Large print notional must be non-negative".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.large_print_confidence_boost) {
+            return Err("Large print confidence boost must be between 0 and 1".to_string());
+        }
+
+        if self.adaptive_skip_max_rate < 0.0 || self.adaptive_skip_max_factor == 0 {
+            return Err("Adaptive skip max rate must be non-negative and max factor positive".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.consensus_confidence_boost) {
+            return Err("Consensus confidence boost must be between 0 and 1".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.direction_flip_confidence) {
+            return Err("Direction flip confidence must be between 0 and 1".to_string());
+        }
+
+        if self.min_book_update_rate < 0.0 {
+            return Err("Minimum book update rate cannot be negative".to_string());
+        }
+
+        if self.weighted_mid_levels == 0 {
+            return Err("Weighted mid levels must be positive".to_string());
+        }
+
+        if self.dashboard_refresh_ms == 0 {
+            return Err("Dashboard refresh interval must be positive".to_string());
+        }
+
+        if self.volatility_window_ms == 0 {
+            return Err("Volatility window must be positive".to_string());
+        }
+
+        if self.reference_volatility < 0.0 {
+            return Err("Reference volatility cannot be negative".to_string());
+        }
+
+        if self.max_volatility_multiplier < 1.0 {
+            return Err("Maximum volatility multiplier must be at least 1".to_string());
+        }
+
+        if self.warmup_min_book_levels == 0 {
+            return Err("Warm-up minimum book levels must be positive".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.min_execution_confidence) {
+            return Err("Minimum execution confidence must be between 0 and 1".to_string());
+        }
+        
+        for (symbol, symbol_override) in &self.symbol_overrides {
+            if let Some(confidence) = symbol_override.min_execution_confidence {
+                if !(0.0..=1.0).contains(&confidence) {
+                    return Err(format!("Minimum execution confidence override for {} must be between 0 and 1", symbol));
+                }
+            }
+            if symbol_override.websocket_url.as_deref().is_some_and(str::is_empty) {
+                return Err(format!("WebSocket URL override for {} must not be empty", symbol));
+            }
+            // The global URL speaks the global exchange's protocol
+            if symbol_override.exchange.is_some_and(|exchange| exchange != self.exchange) && symbol_override.websocket_url.is_none() {
+                return Err(format!("Symbol {} on another exchange than the global one needs its own websocket_url", symbol));
+            }
+            if let Some(code) = symbol_override.strategies.iter().flatten().find(|code| matches!(code, ReasonCode::NoSignal | ReasonCode::GroupFlow)) {
+                return Err(format!("Strategies for {} can't include {:?}, it is not a per-symbol strategy", symbol, code));
+            }
+        }
+        
+        for synthetic in &self.synthetics {
+            if synthetic.ratio <= 0.0 {
+                return Err(format!("Synthetic {} ratio must be positive", synthetic.name));
+            }
+            if synthetic.long_leg == synthetic.short_leg || [&synthetic.long_leg, &synthetic.short_leg].contains(&&synthetic.name) {
+                return Err(format!("Synthetic {} must have two distinct legs, neither named like the synthetic", synthetic.name));
+            }
+        }
+        
+        let mut group_names = std::collections::HashSet::new();
+        for group in &self.groups {
+            if !group_names.insert(&group.name) {
+                return Err(format!("Group {} is defined more than once", group.name));
+            }
+            let mut members = group.members.clone();
+            members.sort();
+            members.dedup();
+            if members.len() < 2 || members.len() != group.members.len() || members.contains(&group.name) {
+                return Err(format!("Group {} must have at least two distinct members, none named like the group", group.name));
+            }
+            if group.min_members == 0 || group.min_members > members.len() {
+                return Err(format!("Group {} min_members must be between 1 and its {} members", group.name, members.len()));
+            }
+            if group.delta_notional_threshold <= 0.0 || group.imbalance_threshold <= 0.0 {
+                return Err(format!("Group {} thresholds must be positive", group.name));
+            }
+            if group.confidence <= 0.0 || group.confidence > 1.0 {
+                return Err(format!("Group {} confidence must be between 0 and 1", group.name));
+            }
+        }
+        
+        let pinned = self.pinned_symbols().len();
+        if pinned > self.max_concurrent_connections() {
+            return Err(format!(
+                "{} synthetic and group member symbols always run, more than the {} concurrent connections allowed",
+                pinned,
+                self.max_concurrent_connections()
+            ));
+        }
+        
+        if self.fallback_watchlist.iter().any(String::is_empty) {
+            return Err("Fallback watchlist symbols must not be empty".to_string());
+        }
+        
+        if self.mqtt_host.is_some() {
+            if self.mqtt_qos > 2 {
+                return Err("MQTT QoS must be 0, 1 or 2".to_string());
+            }
+            if self.mqtt_signal_topic.is_empty() || self.mqtt_heartbeat_topic.as_ref().is_some_and(String::is_empty) {
+                return Err("MQTT topics must not be empty".to_string());
+            }
+            if self.mqtt_client_id.is_empty() || self.mqtt_reconnect_secs == 0 {
+                return Err("MQTT client id must not be empty and mqtt_reconnect_secs must be positive".to_string());
+            }
+        }
+        
+        if self.flight_recorder_messages > 0 && self.flight_recorder_dir.is_empty() {
+            return Err("Flight recorder dir must not be empty when flight_recorder_messages is set".to_string());
+        }
+        
+        self.validate_channels()?;
+        self.validate_ranges()?;
+        
+        Ok(())
+    }
+
+    /// Validate numeric fields against `DEFAULT_FIELD_BOUNDS` and the [bounds.<field>] overrides.
+    /// Unset optional fields are not checked; an override must name a numeric field.
+    pub fn validate_ranges(&self) -> Result<(), String> {
+        let fields = serde_json::to_value(self).map_err(|e| format!("Failed to inspect config: {}", e))?;
+        let mut bounds: Vec<(&str, FieldBounds)> = DEFAULT_FIELD_BOUNDS
+            .iter()
+            .filter(|(field, _, _)| !self.field_bounds.contains_key(*field))
+            .map(|&(field, min, max)| (field, FieldBounds { min: Some(min), max: Some(max) }))
+            .collect();
+        bounds.extend(self.field_bounds.iter().map(|(field, range)| (field.as_str(), *range)));
+        for (field, range) in bounds {
+            let value = match fields.get(field) {
+                Some(serde_json::Value::Null) => continue,
+                Some(value) => value.as_f64().ok_or_else(|| format!("[bounds.{}] does not name a numeric config field", field))?,
+                None => return Err(format!("[bounds.{}] does not name a config field", field)),
+            };
+            let outside = |value: f64| range.min.is_some_and(|min| value < min) || range.max.is_some_and(|max| value > max);
+            let side = |bound: Option<f64>| bound.map_or("unbounded".to_string(), |bound| bound.to_string());
+            if outside(value) {
+                return Err(format!(
+                    "{} = {} is outside its allowed range {}..={}; set [bounds.{}] min/max if this is intended",
+                    field, value, side(range.min), side(range.max), field
+                ));
+            }
+            // Every multi_lookback window is a lookback, and sets how long book flow is retained
+            if field == "lookback_period_ms" {
+                if let Some(&window) = self.multi_lookback.iter().find(|&&window| outside(window as f64)) {
+                    return Err(format!(
+                        "multi_lookback window {} is outside the lookback_period_ms range {}..={}; set [bounds.lookback_period_ms] min/max if this is intended",
+                        window, side(range.min), side(range.max)
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate that the subscribed channels include the ones the engine analyzes, without duplicates
+    pub fn validate_channels(&self) -> Result<(), String> {
+        for required in ["books", "trade"] {
+            if !self.channels.iter().any(|channel| channel == required) {
+                return Err(format!("channels must include \"{}\"", required));
+            }
+        }
+        for (i, channel) in self.channels.iter().enumerate() {
+            if self.channels[..i].contains(channel) {
+                return Err(format!("Channel \"{}\" is listed more than once", channel));
+            }
+        }
+        if self.funding_rate_extreme > 0.0 && !self.channels.iter().any(|channel| channel == "ticker") {
+            return Err("channels must include \"ticker\" when funding_rate_extreme is set".to_string());
+        }
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Estimate whether the retained trades span `lookback_period_ms` at `expected_trade_rate`.
+    /// Analysis sees at most the smaller of `trade_storage_limit` and `analysis_trade_limit` trades,
+    /// so a busy symbol can fill them well before the lookback and silently shorten the delta window.
+    /// Returns a description of the shortfall, or None when covered or the check is disabled.
+    pub fn lookback_coverage_warning(&self) -> Option<String> {
+        if self.expected_trade_rate <= 0.0 {
+            return None;
+        }
+        let retained = self.trade_storage_limit.min(self.analysis_trade_limit);
+        let covered_ms = retained as f64 / self.expected_trade_rate * 1000.0;
+        if covered_ms >= self.lookback_period_ms as f64 {
+            return None;
+        }
+        let needed = (self.lookback_period_ms as f64 / 1000.0 * self.expected_trade_rate).ceil() as usize;
+        Some(format!(
+            "lookback_period_ms = {} is not covered by the retained trades: {} trades at {} trades/s span only ~{:.0}ms; \
+             raise trade_storage_limit and analysis_trade_limit to at least {} or shorten the lookback",
+            self.lookback_period_ms, retained, self.expected_trade_rate, covered_ms, needed
+        ))
+    }
+    
+    /// Validate that confidence tiers are within 0..=1, labelled, and strictly ascending (non-overlapping).
+    /// Tiers only come from [[tier]] tables, so this runs once in `from_toml_file`, not in `validate`.
+    pub fn validate_confidence_tiers(&self) -> Result<(), String> {
+        let mut previous: Option<&ConfidenceTier> = None;
+        for tier in &self.confidence_tiers {
+            if tier.label.is_empty() {
+                return Err("Confidence tier label must not be empty".to_string());
+            }
+            if !(0.0..=1.0).contains(&tier.min_confidence) {
+                return Err(format!("Confidence tier '{}' min_confidence must be between 0 and 1", tier.label));
+            }
+            if let Some(prev) = previous {
+                if tier.min_confidence <= prev.min_confidence {
+                    return Err(format!(
+                        "Confidence tiers must be ordered by ascending min_confidence: '{}' ({}) follows '{}' ({})",
+                        tier.label, tier.min_confidence, prev.label, prev.min_confidence
+                    ));
+                }
+            }
+            previous = Some(tier);
+        }
+        Ok(())
+    }
+
+    /// Credential names of every configured exchange (Bitget always included), e.g. `OKX_API_KEY`
+    fn credential_names(&self) -> Vec<String> {
+        let mut exchanges: Vec<String> = self.exchanges.iter().map(|exchange| exchange.to_uppercase()).collect();
+        if !exchanges.iter().any(|exchange| exchange == "BITGET") {
+            exchanges.push("BITGET".to_string());
+        }
+        exchanges
+            .iter()
+            .flat_map(|exchange| CREDENTIAL_SUFFIXES.iter().map(move |suffix| format!("{}_{}", exchange, suffix)))
+            .collect()
+    }
+
+    /// Store credentials by name (`<EXCHANGE>_<SUFFIX>`) under the lower-cased exchange
+    fn apply_credentials(&mut self, values: HashMap<String, String>) {
+        for (name, value) in values {
+            let Some((exchange, suffix)) = CREDENTIAL_SUFFIXES
+                .iter()
+                .find_map(|suffix| name.strip_suffix(suffix)?.strip_suffix('_').map(|exchange| (exchange.to_lowercase(), *suffix)))
+            else {
+                continue;
+            };
+            let credentials = self.credentials_mut(&exchange);
+            match suffix {
+                "API_KEY" => credentials.api_key = value,
+                "SECRET_KEY" => credentials.secret_key = value,
+                _ => credentials.passphrase = value,
+            }
+        }
+    }
+
+    /// Load credentials of every configured exchange from `<EXCHANGE>_API_KEY`,
+    /// `<EXCHANGE>_SECRET_KEY` and `<EXCHANGE>_PASSPHRASE` env vars, overriding loaded ones
+    pub fn load_credentials_env(&mut self) {
+        let values = self
+            .credential_names()
+            .into_iter()
+            .filter_map(|name| env::var(&name).ok().map(|value| (name, value)))
+            .collect();
+        self.apply_credentials(values);
+    }
+
+    /// Load credentials from a secrets directory (one file per credential, e.g. Docker's
+    /// `/run/secrets/bitget_api_key`) or from a file of `BITGET_API_KEY=...` lines, for every
+    /// configured exchange. Missing entries are left untouched. Errors never include credential values.
+    pub fn load_credentials_file(&mut self, path: &str) -> Result<(), String> {
+        let names = self.credential_names();
+        let path = Path::new(path);
+        let mut values: HashMap<String, String> = HashMap::new();
+        if path.is_dir() {
+            for name in &names {
+                let file = path.join(name.to_lowercase());
+                if file.is_file() {
+                    let value = fs::read_to_string(&file)
+                        .map_err(|e| format!("Failed to read credential file {}: {}", file.display(), e))?;
+                    values.insert(name.clone(), value.trim().to_string());
+                }
+            }
+        } else {
+            let contents = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read credentials file {}: {}", path.display(), e))?;
+            for line in contents.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+                if let Some((key, value)) = line.split_once('=') {
+                    if let Some(name) = names.iter().find(|name| **name == key.trim()) {
+                        values.insert(name.clone(), value.trim().trim_matches('"').to_string());
+                    }
+                }
+            }
+        }
+        self.apply_credentials(values);
+        Ok(())
+    }
+
+    /// Credentials for an exchange (case-insensitive), None if none were loaded for it
+    pub fn credentials_for(&self, exchange: &str) -> Option<ExchangeCredentials> {
+        self.credentials.get(&exchange.to_lowercase()).cloned()
+    }
+
+    /// Credentials for an exchange (case-insensitive) to set, created empty if none were loaded
+    pub fn credentials_mut(&mut self, exchange: &str) -> &mut ExchangeCredentials {
+        self.credentials.entry(exchange.to_lowercase()).or_default()
+    }
+
+    /// Bitget API key, empty if not loaded (single-exchange accessor)
+    pub fn api_key(&self) -> &str {
+        self.credentials.get("bitget").map_or("", |credentials| credentials.api_key.as_str())
+    }
+
+    /// Bitget secret key, empty if not loaded (single-exchange accessor)
+    pub fn secret_key(&self) -> &str {
+        self.credentials.get("bitget").map_or("", |credentials| credentials.secret_key.as_str())
+    }
+
+    /// Bitget passphrase, empty if not loaded (single-exchange accessor)
+    pub fn passphrase(&self) -> &str {
+        self.credentials.get("bitget").map_or("", |credentials| credentials.passphrase.as_str())
+    }
+
+    /// Exchanges the WebSocket streams connect to: the global one and every per-symbol override
+    pub fn exchanges_in_use(&self) -> Vec<Exchange> {
+        let mut exchanges = vec![self.exchange];
+        for exchange in self.symbol_overrides.values().filter_map(|symbol_override| symbol_override.exchange) {
+            if !exchanges.contains(&exchange) {
+                exchanges.push(exchange);
+            }
+        }
+        exchanges
+    }
+
+    /// Every resolved config field with the layer its value came from, given the TOML the config
+    /// was loaded from. Credentials are redacted as in `effective_dump`.
+    pub fn source_audit(&self, toml_contents: &str) -> Result<Vec<ConfigAuditEntry>, String> {
+        let raw: toml::Table = toml::from_str(toml_contents).map_err(|e| format!("Failed to parse config TOML: {}", e))?;
+        // A field is from a section only if the section sets it and the loader reads it there
+        let in_table = |table: &str, keys: &[&str], key: &str| {
+            keys.contains(&key) && raw.get(table).and_then(|value| value.as_table()).is_some_and(|table| table.contains_key(key))
+        };
+        let (ofi_keys, strategy_keys) = (toml_section_keys::<OFITomlConfig>(), toml_section_keys::<StrategyTomlConfig>());
+        let credential_names = self.credential_names();
+        // Env vars are applied over the credentials file, so a set one wins
+        let credential_source = |names: &[String], loaded: bool| {
+            if !loaded {
+                ConfigSource::Default
+            } else if names.iter().any(|name| env::var(name).is_ok_and(|value| !value.is_empty())) {
+                ConfigSource::Env
+            } else if self.credentials_path.is_some() {
+                ConfigSource::CredentialsFile
+            } else {
+                ConfigSource::Default
+            }
+        };
+
+        let dump: serde_json::Value = serde_json::from_str(&self.effective_dump()).map_err(|e| format!("Failed to read config dump: {}", e))?;
+        let fields = dump.as_object().ok_or("Config dump is not a JSON object")?;
+        Ok(fields
+            .iter()
+            .map(|(field, value)| {
+                let source = match field.as_str() {
+                    "credentials" => credential_source(&credential_names, !self.credentials.is_empty()),
+                    "symbol_overrides" if raw.contains_key("symbols") => ConfigSource::Override,
+                    _ if SECTION_FIELDS.iter().any(|(section, name)| name == field && raw.contains_key(*section)) => ConfigSource::TomlSection,
+                    _ if in_table("strategy", strategy_keys, field) => ConfigSource::TomlStrategy,
+                    _ if in_table("ofi", ofi_keys, field) => ConfigSource::TomlOfi,
+                    _ => ConfigSource::Default,
+                };
+                ConfigAuditEntry { field: field.clone(), source, value: value.clone() }
+            })
+            .collect())
+    }
+
+    /// Dump the fully-resolved configuration as pretty JSON with credentials redacted
+    pub fn effective_dump(&self) -> String {
+        let redact = |value: &str| if value.is_empty() { String::new() } else { "<redacted>".to_string() };
+        let redacted = Self {
+            credentials: self
+                .credentials
+                .iter()
+                .map(|(exchange, credentials)| {
+                    let redacted = ExchangeCredentials {
+                        api_key: redact(&credentials.api_key),
+                        secret_key: redact(&credentials.secret_key),
+                        passphrase: redact(&credentials.passphrase),
+                    };
+                    (exchange.clone(), redacted)
+                })
+                .collect(),
+            ..self.clone()
+        };
+        serde_json::to_string_pretty(&redacted)
+            .unwrap_or_else(|e| serde_json::json!({ "error": format!("failed to serialize config: {}", e) }).to_string())
+    }
+
+    /// Get the minimum execution confidence for a symbol, falling back to the global value
+    pub fn min_execution_confidence_for(&self, symbol: &str) -> f64 {
+        self.symbol_overrides
+            .get(symbol)
+            .and_then(|symbol_override| symbol_override.min_execution_confidence)
+            .unwrap_or(self.min_execution_confidence)
+    }
+
+    /// Whether signals from the rule `reason_code` may be emitted for a symbol
+    pub fn strategy_enabled_for(&self, symbol: &str, reason_code: ReasonCode) -> bool {
+        self.symbol_overrides
+            .get(symbol)
+            .and_then(|symbol_override| symbol_override.strategies.as_ref())
+            .map_or(true, |strategies| strategies.contains(&reason_code))
+    }
+
+    /// How long book flow must be kept to analyze `lookback_period_ms` and every `multi_lookback` window
+    pub fn flow_retention_ms(&self, lookback_period_ms: u64) -> u64 {
+        self.multi_lookback.iter().copied().fold(lookback_period_ms, u64::max)
+    }
+
+    /// Whether any strategy may emit for a symbol; false for data-collection-only symbols
+    pub fn analysis_enabled_for(&self, symbol: &str) -> bool {
+        self.symbol_overrides
+            .get(symbol)
+            .and_then(|symbol_override| symbol_override.strategies.as_ref())
+            .map_or(true, |strategies| !strategies.is_empty())
+    }
+
+    /// Get the WebSocket URL for a symbol, falling back to the global value
+    pub fn websocket_url_for(&self, symbol: &str) -> &str {
+        self.symbol_overrides
+            .get(symbol)
+            .and_then(|symbol_override| symbol_override.websocket_url.as_deref())
+            .unwrap_or(&self.websocket_url)
+    }
+
+    /// Get the exchange whose protocol a symbol's stream uses, falling back to the global value
+    pub fn exchange_for(&self, symbol: &str) -> Exchange {
+        self.symbol_overrides
+            .get(symbol)
+            .and_then(|symbol_override| symbol_override.exchange)
+            .unwrap_or(self.exchange)
+    }
+
+    /// Every distinct WebSocket URL in use: the global one and all per-symbol overrides, sorted
+    pub fn websocket_urls(&self) -> Vec<&str> {
+        let mut urls: Vec<&str> = self
+            .symbol_overrides
+            .values()
+            .filter_map(|symbol_override| symbol_override.websocket_url.as_deref())
+            .chain(std::iter::once(self.websocket_url.as_str()))
+            .collect();
+        urls.sort_unstable();
+        urls.dedup();
+        urls
+    }
+
+    /// Concurrent WebSocket connection limit, 20 if not configured
+    pub fn max_concurrent_connections(&self) -> usize {
+        self.max_concurrent_websocket_connections.unwrap_or(20)
+    }
+
+    /// Symbols that always get a task regardless of the screener: synthetic spreads and group
+    /// members, deduplicated in config order
+    pub fn pinned_symbols(&self) -> Vec<String> {
+        let mut pinned: Vec<String> = Vec::new();
+        let synthetics = self.synthetics.iter().map(|synthetic| &synthetic.name);
+        for symbol in synthetics.chain(self.groups.iter().flat_map(|group| &group.members)) {
+            if !pinned.contains(symbol) {
+                pinned.push(symbol.clone());
+            }
+        }
+        pinned
+    }
+
+    /// Get the synthetic instrument named `symbol`, if it is one
+    pub fn synthetic(&self, symbol: &str) -> Option<&SyntheticInstrument> {
+        self.synthetics.iter().find(|synthetic| synthetic.name == symbol)
+    }
+
+    /// Synthetic instruments that have `symbol` as one of their legs
+    pub fn synthetics_with_leg<'a>(&'a self, symbol: &'a str) -> impl Iterator<Item = &'a SyntheticInstrument> + 'a {
+        self.synthetics.iter().filter(move |synthetic| synthetic.long_leg == symbol || synthetic.short_leg == symbol)
+    }
+
+    /// Get the maintenance window covering the given UTC timestamp (milliseconds), if any
+    pub fn active_maintenance_window(&self, now_ms: i64) -> Option<&MaintenanceWindow> {
+        self.maintenance_windows.iter().find(|window| window.contains(now_ms))
+    }
+
+    /// Get the intent action for a signal: a rule matching both signal type and reason code wins
+    /// over one matching the signal type alone; unmatched signals map buys to long, sells to short
+    pub fn intent_action_for(&self, signal_type: SignalType, reason_code: ReasonCode) -> IntentAction {
+        let matching = |specific: bool| {
+            self.intent_rules.iter().find(|rule| {
+                rule.signal_type == signal_type
+                    && match rule.reason_code {
+                        Some(code) => specific && code == reason_code,
+                        None => !specific,
+                    }
+            })
+        };
+        if let Some(rule) = matching(true).or_else(|| matching(false)) {
+            return rule.action;
+        }
+        match signal_type {
+            SignalType::StrongBuy | SignalType::Buy => IntentAction::Long,
+            SignalType::StrongSell | SignalType::Sell => IntentAction::Short,
+            SignalType::NoSignal => IntentAction::Ignore,
+        }
+    }
+
+    /// Get the label of the highest tier whose min_confidence is satisfied by `confidence`
+    pub fn tier_for_confidence(&self, confidence: f64) -> Option<&str> {
+        self.confidence_tiers
+            .iter()
+            .rev()
+            .find(|tier| confidence >= tier.min_confidence)
+            .map(|tier| tier.label.as_str())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiered_config() -> OFIConfig {
+        OFIConfig {
+            confidence_tiers: vec![
+                ConfidenceTier { min_confidence: 0.5, label: "watch".to_string() },
+                ConfidenceTier { min_confidence: 0.7, label: "small".to_string() },
+                ConfidenceTier { min_confidence: 0.9, label: "full".to_string() },
+            ],
+            ..OFIConfig::for_tests()
+        }
+    }
+
+    #[test]
+    fn confidence_maps_to_the_highest_satisfied_tier() {
+        let config = tiered_config();
+        assert_eq!(config.tier_for_confidence(0.3), None);
+        assert_eq!(config.tier_for_confidence(0.5), Some("watch"));
+        assert_eq!(config.tier_for_confidence(0.75), Some("small"));
+        assert_eq!(config.tier_for_confidence(0.9), Some("full"));
+        assert_eq!(config.tier_for_confidence(1.0), Some("full"));
+        assert_eq!(OFIConfig::for_tests().tier_for_confidence(1.0), None, "no tiers, no labels");
+    }
+
+    #[test]
+    fn confidence_tiers_must_be_labelled_in_range_and_ascending() {
+        assert!(tiered_config().validate_confidence_tiers().is_ok());
+
+        let mut config = tiered_config();
+        config.confidence_tiers.swap(0, 1);
+        assert!(config.validate_confidence_tiers().unwrap_err().contains("ascending"));
+
+        let mut config = tiered_config();
+        config.confidence_tiers[1].min_confidence = 0.5;
+        assert!(config.validate_confidence_tiers().is_err(), "overlapping tiers are rejected");
+
+        let mut config = tiered_config();
+        config.confidence_tiers[2].min_confidence = 1.5;
+        assert!(config.validate_confidence_tiers().is_err());
+
+        let mut config = tiered_config();
+        config.confidence_tiers[0].label.clear();
+        assert!(config.validate_confidence_tiers().is_err());
+    }
+
+    #[test]
+    fn timestamp_units_normalize_to_the_same_millisecond() {
+        let millis = 1_700_000_000_123;
+        assert_eq!(TimestampUnit::Seconds.to_millis(1_700_000_000), 1_700_000_000_000);
+        assert_eq!(TimestampUnit::Millis.to_millis(millis), millis);
+        assert_eq!(TimestampUnit::Micros.to_millis(millis * 1_000 + 456), millis);
+        assert_eq!(TimestampUnit::Nanos.to_millis(millis * 1_000_000 + 456_789), millis);
+
+        for raw in [millis, millis * 1_000 + 456, millis * 1_000_000 + 456_789] {
+            assert_eq!(TimestampUnit::Auto.to_millis(raw), millis, "auto-detected unit of {}", raw);
+        }
+        assert_eq!(TimestampUnit::Auto.to_millis(1_700_000_000), 1_700_000_000_000);
+    }
+
+    const HOUR_MS: i64 = 3_600_000;
+    const DAY_MS: i64 = 24 * HOUR_MS;
+
+    #[test]
+    fn daily_maintenance_window_suppresses_only_inside_it() {
+        let window = MaintenanceWindow::parse("02:00", "02:30").unwrap();
+        let config = OFIConfig { maintenance_windows: vec![window], ..OFIConfig::for_tests() };
+        let day = 20_000 * DAY_MS;
+        assert!(config.active_maintenance_window(day + 2 * HOUR_MS).is_some());
+        assert!(config.active_maintenance_window(day + 2 * HOUR_MS + 29 * 60_000).is_some());
+        assert!(config.active_maintenance_window(day + 2 * HOUR_MS + 30 * 60_000).is_none(), "end is exclusive");
+        assert!(config.active_maintenance_window(day + HOUR_MS).is_none());
+    }
+
+    #[test]
+    fn daily_maintenance_window_can_wrap_past_midnight() {
+        let window = MaintenanceWindow::parse("23:50", "00:10:30").unwrap();
+        let day = 20_000 * DAY_MS;
+        assert!(window.contains(day + 23 * HOUR_MS + 55 * 60_000));
+        assert!(window.contains(day + 10 * 60_000 + 29_000));
+        assert!(!window.contains(day + 11 * 60_000));
+        assert!(!window.contains(day + 12 * HOUR_MS));
+        assert_eq!(window.to_string(), "daily 23:50:00-00:10:30 UTC");
+    }
+
+    #[test]
+    fn one_off_maintenance_window_covers_its_range() {
+        let config = OFIConfig {
+            maintenance_windows: vec![MaintenanceWindow::Once { start_ms: 1_000, end_ms: 2_000 }],
+            ..OFIConfig::for_tests()
+        };
+        assert!(config.active_maintenance_window(999).is_none());
+        assert!(config.active_maintenance_window(1_000).is_some());
+        assert!(config.active_maintenance_window(2_000).is_none());
+    }
+
+    #[test]
+    fn malformed_maintenance_windows_are_rejected() {
+        assert!(MaintenanceWindow::parse("02:00", "02:00").is_err());
+        assert!(MaintenanceWindow::parse("24:00", "25:00").is_err());
+        assert!(MaintenanceWindow::parse("02:00", "tomorrow").is_err());
+    }
+
+    #[test]
+    fn effective_dump_shows_resolved_values_and_redacts_secrets() {
+        let mut config = OFIConfig { min_execution_confidence: 0.65, ..OFIConfig::for_tests() };
+        config.symbol_overrides.insert("ETHUSDT".to_string(), SymbolOverride { min_execution_confidence: Some(0.8), ..SymbolOverride::default() });
+        *config.credentials_mut("bitget") = ExchangeCredentials {
+            api_key: "live-key".to_string(),
+            secret_key: "live-secret".to_string(),
+            passphrase: String::new(),
+        };
+
+        let dump: serde_json::Value = serde_json::from_str(&config.effective_dump()).unwrap();
+        assert_eq!(dump["min_execution_confidence"], 0.65);
+        assert_eq!(dump["trade_storage_limit"], 200);
+        assert_eq!(dump["symbol_overrides"]["ETHUSDT"]["min_execution_confidence"], 0.8);
+        assert_eq!(dump["credentials"]["bitget"]["api_key"], "<redacted>");
+        assert_eq!(dump["credentials"]["bitget"]["secret_key"], "<redacted>");
+        assert_eq!(dump["credentials"]["bitget"]["passphrase"], "", "unset secrets stay visibly unset");
+        let text = config.effective_dump();
+        assert!(!text.contains("live-key") && !text.contains("live-secret"));
+        assert_eq!(config.api_key(), "live-key", "dumping doesn't touch the live credentials");
+    }
+
+    #[test]
+    fn per_symbol_minimum_confidence_overrides_the_global_value() {
+        let mut config = OFIConfig { min_execution_confidence: 0.6, ..OFIConfig::for_tests() };
+        config.symbol_overrides.insert("ETHUSDT".to_string(), SymbolOverride { min_execution_confidence: Some(0.8), ..SymbolOverride::default() });
+        config.symbol_overrides.insert("SOLUSDT".to_string(), SymbolOverride { websocket_url: Some("wss://example".to_string()), ..SymbolOverride::default() });
+
+        let confidence = 0.7;
+        assert!(confidence >= config.min_execution_confidence_for("BTCUSDT"));
+        assert!(confidence < config.min_execution_confidence_for("ETHUSDT"));
+        assert_eq!(config.min_execution_confidence_for("SOLUSDT"), 0.6, "an override without the field falls back");
+    }
+
+    #[test]
+    fn credentials_load_from_a_secrets_dir_with_env_taking_precedence() {
+        // A dedicated exchange name keeps the env vars set here away from other tests
+        let dir = std::env::temp_dir().join(format!("ofi-secrets-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("secretstest_api_key"), "file-key\n").unwrap();
+        fs::write(dir.join("secretstest_secret_key"), "file-secret").unwrap();
+
+        let mut config = OFIConfig { exchanges: vec!["secretstest".to_string()], ..OFIConfig::for_tests() };
+        config.load_credentials_file(dir.to_str().unwrap()).unwrap();
+        let credentials = config.credentials_for("secretstest").expect("loaded from files");
+        assert_eq!((credentials.api_key.as_str(), credentials.secret_key.as_str()), ("file-key", "file-secret"));
+        assert_eq!(credentials.passphrase, "", "a missing file leaves the credential unset");
+
+        // Environment variables are applied on top of the files
+        env::set_var("SECRETSTEST_API_KEY", "env-key");
+        config.load_credentials_env();
+        env::remove_var("SECRETSTEST_API_KEY");
+        let credentials = config.credentials_for("secretstest").unwrap();
+        assert_eq!((credentials.api_key.as_str(), credentials.secret_key.as_str()), ("env-key", "file-secret"));
+
+        // A KEY=VALUE file works the same way
+        let file = dir.join("credentials.env");
+        fs::write(&file, "# mounted secrets\nSECRETSTEST_PASSPHRASE=\"file-pass\"\n").unwrap();
+        config.load_credentials_file(file.to_str().unwrap()).unwrap();
+        assert_eq!(config.credentials_for("secretstest").unwrap().passphrase, "file-pass");
+
+        // Read failures name the file but never carry credential values
+        let error = config.load_credentials_file(dir.join("missing").to_str().unwrap()).unwrap_err();
+        assert!(!error.contains("file-key") && !error.contains("env-key"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn source_audit_attributes_each_field_to_its_layer() {
+        let toml = r#"
+            [strategy]
+            imbalance_threshold = 3.0
+
+            [ofi]
+            websocket_url = "wss://ws.bitget.com/v2/ws/public"
+            lookback_period_ms = 5000
+
+            [[tier]]
+            label = "high"
+            min_confidence = 0.8
+
+            [symbols.ETHUSDT]
+            min_execution_confidence = 0.8
+        "#;
+        // A dedicated exchange name keeps the env var set here away from other tests
+        let mut config = OFIConfig { exchanges: vec!["audittest".to_string()], ..OFIConfig::for_tests() };
+        config.credentials_mut("audittest").api_key = "audit-key".to_string();
+        let source_of = |audit: &[ConfigAuditEntry], field: &str| audit.iter().find(|entry| entry.field == field).map(|entry| entry.source);
+
+        env::set_var("AUDITTEST_API_KEY", "audit-key");
+        let audit = config.source_audit(toml);
+        env::remove_var("AUDITTEST_API_KEY");
+        let audit = audit.unwrap();
+        assert_eq!(source_of(&audit, "imbalance_threshold"), Some(ConfigSource::TomlStrategy));
+        assert_eq!(source_of(&audit, "websocket_url"), Some(ConfigSource::TomlOfi));
+        // The loader reads lookback_period_ms from [strategy] only; set in [ofi] it is ignored
+        assert_eq!(source_of(&audit, "lookback_period_ms"), Some(ConfigSource::Default));
+        assert_eq!(source_of(&audit, "confidence_tiers"), Some(ConfigSource::TomlSection));
+        assert_eq!(source_of(&audit, "symbol_overrides"), Some(ConfigSource::Override));
+        assert_eq!(source_of(&audit, "fast_mode"), Some(ConfigSource::Default));
+        assert_eq!(source_of(&audit, "credentials"), Some(ConfigSource::Env));
+
+        // Credentials are redacted in the audit
+        let credentials = audit.iter().find(|entry| entry.field == "credentials").unwrap();
+        assert_eq!(credentials.value["audittest"]["api_key"], "<redacted>");
+
+        // Without the env var they come from the credentials file
+        config.credentials_path = Some("/run/secrets".to_string());
+        assert_eq!(source_of(&config.source_audit(toml).unwrap(), "credentials"), Some(ConfigSource::CredentialsFile));
+        assert!(config.source_audit("[ofi").is_err());
+    }
+
+    #[test]
+    fn undersized_trade_retention_warns_about_the_lookback() {
+        // 100 analyzed trades at 50 trades/s span 2s of a 5s lookback
+        let busy = OFIConfig { expected_trade_rate: 50.0, trade_storage_limit: 200, analysis_trade_limit: 100, ..OFIConfig::for_tests() };
+        let warning = busy.lookback_coverage_warning().expect("lookback not covered");
+        assert!(warning.contains("span only ~2000ms"), "{}", warning);
+        assert!(warning.contains("at least 250"), "{}", warning);
+
+        let covered = OFIConfig { expected_trade_rate: 10.0, ..busy.clone() };
+        assert_eq!(covered.lookback_coverage_warning(), None);
+        let unchecked = OFIConfig { expected_trade_rate: 0.0, ..busy };
+        assert_eq!(unchecked.lookback_coverage_warning(), None);
+    }
+
+    #[test]
+    fn each_exchange_gets_its_own_credentials() {
+        let file = std::env::temp_dir().join(format!("ofi-credentials-{}.env", std::process::id()));
+        fs::write(
+            &file,
+            "BITGET_API_KEY=bitget-key\nBITGET_SECRET_KEY=bitget-secret\nBITGET_PASSPHRASE=bitget-pass\n\
+             OKX_API_KEY=okx-key\nOKX_SECRET_KEY=okx-secret\nOKX_PASSPHRASE=okx-pass\n\
+             KRAKEN_API_KEY=kraken-key\n",
+        )
+        .unwrap();
+
+        let mut config = OFIConfig { exchanges: vec!["okx".to_string()], ..OFIConfig::for_tests() };
+        config.load_credentials_file(file.to_str().unwrap()).unwrap();
+        let bitget = ExchangeCredentials { api_key: "bitget-key".into(), secret_key: "bitget-secret".into(), passphrase: "bitget-pass".into() };
+        let okx = ExchangeCredentials { api_key: "okx-key".into(), secret_key: "okx-secret".into(), passphrase: "okx-pass".into() };
+        // Bitget is loaded even when not listed; lookups ignore case
+        assert_eq!(config.credentials_for("bitget"), Some(bitget.clone()));
+        assert_eq!(config.credentials_for("OKX"), Some(okx));
+        assert_eq!(config.credentials_for("kraken"), None, "exchanges not configured are not loaded");
+        assert!(config.validate().is_ok());
+
+        // The single-exchange default loads Bitget's alone, and a stream's exchange must have a full set
+        let mut single = OFIConfig::for_tests();
+        single.load_credentials_file(file.to_str().unwrap()).unwrap();
+        assert_eq!(single.credentials_for("bitget"), Some(bitget));
+        assert_eq!(single.credentials_for("okx"), None);
+        single.credentials_mut("bitget").passphrase.clear();
+        assert_eq!(single.validate().unwrap_err(), "Passphrase is required for bitget");
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn each_symbol_of_a_mixed_watchlist_uses_its_own_stream() {
+        let mut config = OFIConfig { websocket_url: "wss://ws.bitget.com/v2/ws/public".to_string(), ..OFIConfig::for_tests() };
+        config.symbol_overrides.insert(
+            "ETHUSDT".to_string(),
+            SymbolOverride { websocket_url: Some("wss://eu.ws.bitget.com/v2/ws/public".to_string()), exchange: Some(Exchange::Bitget), ..SymbolOverride::default() },
+        );
+        config.symbol_overrides.insert("SOLUSDT".to_string(), SymbolOverride { min_execution_confidence: Some(0.8), ..SymbolOverride::default() });
+
+        assert_eq!(config.websocket_url_for("ETHUSDT"), "wss://eu.ws.bitget.com/v2/ws/public");
+        // Symbols without a URL override, with or without other overrides, use the global stream
+        assert_eq!(config.websocket_url_for("BTCUSDT"), "wss://ws.bitget.com/v2/ws/public");
+        assert_eq!(config.websocket_url_for("SOLUSDT"), "wss://ws.bitget.com/v2/ws/public");
+        assert_eq!(config.exchange_for("ETHUSDT"), Exchange::Bitget);
+        assert_eq!(config.exchange_for("BTCUSDT"), config.exchange);
+        assert_eq!(config.websocket_urls(), vec!["wss://eu.ws.bitget.com/v2/ws/public", "wss://ws.bitget.com/v2/ws/public"]);
+    }
+
+    #[test]
+    fn numeric_fields_outside_their_range_are_rejected() {
+        assert!(OFIConfig::for_tests().validate_ranges().is_ok());
+        let at_the_bounds = OFIConfig { imbalance_threshold: 100.0, lookback_period_ms: 300_000, ..OFIConfig::for_tests() };
+        assert!(at_the_bounds.validate_ranges().is_ok());
+
+        let absurd_threshold = OFIConfig { imbalance_threshold: 1e9, ..OFIConfig::for_tests() };
+        let error = absurd_threshold.validate_ranges().unwrap_err();
+        assert!(error.starts_with("imbalance_threshold = 1000000000 is outside its allowed range 1..=100"), "{}", error);
+        assert!(absurd_threshold.validate().is_err());
+        let below = OFIConfig { imbalance_threshold: 0.5, ..OFIConfig::for_tests() };
+        assert!(below.validate_ranges().unwrap_err().starts_with("imbalance_threshold = 0.5"));
+
+        // A lookback of three days is rejected unless a [bounds] section widens the range
+        let mut days = OFIConfig { lookback_period_ms: 259_200_000, ..OFIConfig::for_tests() };
+        assert!(days.validate_ranges().unwrap_err().starts_with("lookback_period_ms = 259200000"));
+        days.field_bounds.insert("lookback_period_ms".to_string(), FieldBounds { min: Some(1.0), max: None });
+        assert!(days.validate_ranges().is_ok());
+
+        // An override must name a numeric field
+        let mut typo = OFIConfig::for_tests();
+        typo.field_bounds.insert("lookback_ms".to_string(), FieldBounds::default());
+        assert_eq!(typo.validate_ranges().unwrap_err(), "[bounds.lookback_ms] does not name a config field");
+        let mut text = OFIConfig::for_tests();
+        text.field_bounds.insert("websocket_url".to_string(), FieldBounds::default());
+        assert_eq!(text.validate_ranges().unwrap_err(), "[bounds.websocket_url] does not name a numeric config field");
+    }
+
+    #[test]
+    fn multi_lookback_windows_are_held_to_the_lookback_range() {
+        let within = OFIConfig { multi_lookback: vec![500, 300_000], ..OFIConfig::for_tests() };
+        assert!(within.validate_ranges().is_ok());
+
+        // A day-long window would keep a day of book flow per symbol
+        let mut typo = OFIConfig { multi_lookback: vec![5_000, 86_400_000], ..OFIConfig::for_tests() };
+        assert_eq!(
+            typo.validate_ranges().unwrap_err(),
+            "multi_lookback window 86400000 is outside the lookback_period_ms range 1..=300000; set [bounds.lookback_period_ms] min/max if this is intended"
+        );
+        assert!(typo.validate().is_err());
+
+        // A [bounds.lookback_period_ms] override applies to the windows too
+        typo.field_bounds.insert("lookback_period_ms".to_string(), FieldBounds { min: Some(1.0), max: Some(86_400_000.0) });
+        assert!(typo.validate_ranges().is_ok());
+        typo.field_bounds.insert("lookback_period_ms".to_string(), FieldBounds { min: Some(1_000.0), max: None });
+        typo.multi_lookback = vec![500];
+        assert!(typo.validate_ranges().unwrap_err().starts_with("multi_lookback window 500 is outside the lookback_period_ms range 1000..=unbounded"));
+    }
+}
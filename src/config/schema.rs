@@ -0,0 +1,72 @@
+//! JSON Schema export for the config and signal types (requires the `schema` feature)
+
+use crate::config::OFIConfig;
+use crate::signals::{StrategyParams, TradingSignal};
+use schemars::schema_for;
+
+/// JSON Schemas of the config file, `StrategyParams` and `TradingSignal`, keyed by type name.
+/// `OFIConfig` describes config.toml as written (its `[ofi]`, `[strategy]`, ... sections), not
+/// the flattened struct it is loaded into; keys the file may omit (e.g.
+/// `max_concurrent_websocket_connections`) are not listed as required.
+pub fn json_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "OFIConfig": OFIConfig::file_schema(),
+        "StrategyParams": schema_for!(StrategyParams),
+        "TradingSignal": schema_for!(TradingSignal),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::{ReasonCode, SignalType};
+    use std::path::Path;
+
+    /// The shipped config/config.toml as JSON, the form the schema describes
+    fn shipped_config() -> serde_json::Value {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../config/config.toml");
+        let contents = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+        toml::from_str(&contents).expect("config.toml parses")
+    }
+
+    #[test]
+    fn config_schema_accepts_the_shipped_config_and_rejects_a_bad_one() {
+        let schemas = json_schemas();
+        let schema = &schemas["OFIConfig"];
+        let good = shipped_config();
+        assert!(jsonschema::is_valid(schema, &good));
+
+        let mut wrong_type = good.clone();
+        wrong_type["strategy"]["imbalance_threshold"] = serde_json::json!("high");
+        assert!(!jsonschema::is_valid(schema, &wrong_type));
+
+        let mut bad_tier = good.clone();
+        bad_tier["tier"] = serde_json::json!([{ "min_confidence": 0.5 }]);
+        assert!(!jsonschema::is_valid(schema, &bad_tier), "a tier needs its label");
+
+        // Optional keys may be left out
+        let mut optional_omitted = good;
+        optional_omitted["ofi"].as_object_mut().unwrap().remove("max_concurrent_websocket_connections");
+        assert!(jsonschema::is_valid(schema, &optional_omitted));
+    }
+
+    #[test]
+    fn signal_schema_accepts_an_emitted_signal() {
+        let schemas = json_schemas();
+        let schema = &schemas["TradingSignal"];
+        let signal = TradingSignal {
+            signal_type: SignalType::StrongBuy,
+            reason_code: ReasonCode::StackedImbalance,
+            price: 67012.5,
+            confidence: 0.9,
+            ..TradingSignal::no_signal("BTCUSDT")
+        };
+        let mut emitted = serde_json::to_value(&signal).unwrap();
+        assert!(jsonschema::is_valid(schema, &emitted));
+
+        emitted["signal_type"] = serde_json::json!("Hold");
+        assert!(!jsonschema::is_valid(schema, &emitted));
+        emitted.as_object_mut().unwrap().remove("signal_type");
+        assert!(!jsonschema::is_valid(schema, &emitted), "signal_type is required");
+    }
+}
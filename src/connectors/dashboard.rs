@@ -0,0 +1,235 @@
+//! Live terminal dashboard of the running analysis tasks (`tui` feature)
+//!
+//! Analysis tasks register their engine in a `DashboardRegistry` and record the signals they
+//! forward; the dashboard polls the registered engines every `dashboard_refresh_ms` and renders
+//! one row per symbol. It only reads engine state, so the Sentinel runs exactly as headless.
+
+use crate::data::OrderBookLevel;
+use crate::engine::{HeartbeatMetrics, OFIEngine, SymbolDataStatus};
+use crate::signals::TradingSignal;
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::Constraint;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Row, Table};
+use ratatui::DefaultTerminal;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Engines of the running analysis tasks with each symbol's last forwarded signal
+#[derive(Clone, Default)]
+pub struct DashboardRegistry {
+    entries: Arc<Mutex<HashMap<String, DashboardEntry>>>,
+}
+
+/// A registered symbol's engine and its last forwarded signal
+#[derive(Clone)]
+struct DashboardEntry {
+    engine: OFIEngine,
+    last_signal: Option<TradingSignal>,
+}
+
+impl DashboardRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show `symbol` on the dashboard, read from `engine`
+    pub fn register(&self, symbol: &str, engine: OFIEngine) {
+        self.entries.lock().unwrap().insert(symbol.to_string(), DashboardEntry { engine, last_signal: None });
+    }
+
+    /// Record the latest signal forwarded for its symbol
+    pub fn record_signal(&self, signal: &TradingSignal) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&signal.symbol) {
+            entry.last_signal = Some(signal.clone());
+        }
+    }
+
+    /// Drop a symbol from the dashboard, e.g. when its task stops
+    pub fn remove(&self, symbol: &str) {
+        self.entries.lock().unwrap().remove(symbol);
+    }
+}
+
+/// Everything the dashboard shows for one symbol
+#[derive(Debug, Clone)]
+pub struct SymbolPanel {
+    pub symbol: String,
+    pub best_bid: Option<OrderBookLevel>,
+    pub best_ask: Option<OrderBookLevel>,
+    pub metrics: Option<HeartbeatMetrics>,  // None until the symbol has a book
+    pub status: SymbolDataStatus,
+    pub last_signal: Option<TradingSignal>,
+    pub invalid_symbol: bool,  // The exchange rejected the symbol and its connection stopped
+}
+
+impl SymbolPanel {
+    /// Connection health from data freshness: books older than `max_book_age_ms` are stale
+    pub fn health(&self, max_book_age_ms: u64) -> &'static str {
+        if self.invalid_symbol {
+            return "invalid";
+        }
+        match self.status.book_age_ms {
+            None => "waiting",
+            Some(age) if age > max_book_age_ms => "stale",
+            Some(_) if !self.status.ready => "warming",
+            Some(_) => "live",
+        }
+    }
+}
+
+/// Gather a panel for every registered symbol, sorted by symbol
+pub async fn collect_panels(registry: &DashboardRegistry) -> Vec<SymbolPanel> {
+    // Engines are cloned out so no lock is held across the engine queries
+    let mut entries: Vec<(String, DashboardEntry)> = registry
+        .entries
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(symbol, entry)| (symbol.clone(), entry.clone()))
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut panels = Vec::with_capacity(entries.len());
+    for (symbol, DashboardEntry { engine, last_signal }) in entries {
+        let (best_bid, best_ask) = engine.top_of_book(&symbol).await;
+        panels.push(SymbolPanel {
+            best_bid,
+            best_ask,
+            metrics: engine.current_metrics(&symbol).await,
+            status: engine.symbol_status(&symbol).await,
+            last_signal,
+            invalid_symbol: engine.invalid_symbol_error(&symbol).is_some(),
+            symbol,
+        });
+    }
+    panels
+}
+
+/// Run the dashboard until the user quits with `q`, `Esc` or Ctrl+C, then restore the terminal
+pub async fn run_dashboard(registry: DashboardRegistry, refresh: Duration, max_book_age_ms: u64) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = dashboard_loop(&mut terminal, &registry, refresh, max_book_age_ms).await;
+    ratatui::restore();
+    result
+}
+
+async fn dashboard_loop(terminal: &mut DefaultTerminal, registry: &DashboardRegistry, refresh: Duration, max_book_age_ms: u64) -> Result<()> {
+    let mut ticker = tokio::time::interval(refresh);
+    loop {
+        ticker.tick().await;
+        let panels = collect_panels(registry).await;
+        terminal.draw(|frame| frame.render_widget(panel_table(&panels, max_book_age_ms), frame.area()))?;
+
+        // Raw mode swallows Ctrl+C, so it is handled here along with the quit keys
+        while event::poll(Duration::ZERO)? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn panel_table(panels: &[SymbolPanel], max_book_age_ms: u64) -> Table<'static> {
+    let price = |level: &Option<OrderBookLevel>| level.as_ref().map_or("-".to_string(), |level| format!("{} x {}", level.price, level.quantity));
+    let rows = panels.iter().map(|panel| {
+        let health = panel.health(max_book_age_ms);
+        let health_color = match health {
+            "live" => Color::Green,
+            "warming" | "waiting" => Color::Yellow,
+            _ => Color::Red,
+        };
+        let metrics = panel.metrics.as_ref().map_or_else(
+            || vec!["-".to_string(); 4],
+            |metrics| {
+                vec![
+                    format!("{:.0}", metrics.delta),
+                    format!("{:.2}/{:.2}", metrics.buy_imbalance, metrics.sell_imbalance),
+                    format!("{:.2}", metrics.book_quality),
+                    format!("{:.1}/s", metrics.book_update_rate),
+                ]
+            },
+        );
+        let last_signal = panel.last_signal.as_ref().map_or("-".to_string(), |signal| {
+            format!("{} @ {} ({:.2})", signal.signal_type, signal.price, signal.confidence)
+        });
+        let mut cells = vec![panel.symbol.clone(), health.to_string(), price(&panel.best_bid), price(&panel.best_ask)];
+        cells.extend(metrics);
+        cells.push(panel.status.trade_count.to_string());
+        cells.push(last_signal);
+        Row::new(cells).style(Style::default().fg(health_color))
+    });
+
+    let header = Row::new(["Symbol", "Health", "Bid", "Ask", "Delta", "Imb buy/sell", "Quality", "Updates", "Trades", "Last signal"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Length(14),
+        Constraint::Length(8),
+        Constraint::Length(22),
+        Constraint::Length(22),
+        Constraint::Length(12),
+        Constraint::Length(13),
+        Constraint::Length(8),
+        Constraint::Length(9),
+        Constraint::Length(7),
+        Constraint::Min(20),
+    ];
+    Table::new(rows.collect::<Vec<_>>(), widths)
+        .header(header)
+        .block(Block::bordered().title(format!(" OFI Sentinel: {} symbols (q to quit) ", panels.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{OrderBookSnapshot, TradeData};
+
+    fn engine() -> OFIEngine {
+        crate::engine::analysis_engine(3.0, 1000.0, 5000, crate::config::OFIConfig::for_tests())
+    }
+
+    #[tokio::test]
+    async fn panels_gather_each_registered_symbol_from_its_engine() {
+        let now = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        let level = |price: f64, quantity: f64| OrderBookLevel { price, quantity };
+        let btc = engine();
+        btc.update_order_book(OrderBookSnapshot {
+            symbol: "BTCUSDT".to_string(),
+            bids: vec![level(99.0, 2.0), level(98.0, 5.0)],
+            asks: vec![level(101.0, 3.0)],
+            timestamp: now,
+        })
+        .await;
+        btc.add_trade(TradeData { symbol: "BTCUSDT".to_string(), price: 100.0, quantity: 1.0, side: "buy".to_string(), timestamp: now }).await;
+
+        let registry = DashboardRegistry::new();
+        registry.register("ETHUSDT", engine());
+        registry.register("BTCUSDT", btc);
+        registry.register("SOLUSDT", engine());
+        registry.remove("SOLUSDT");
+        let signal = TradingSignal { price: 100.0, ..TradingSignal::no_signal("BTCUSDT") };
+        registry.record_signal(&signal);
+        registry.record_signal(&TradingSignal::no_signal("XRPUSDT"));
+
+        let panels = collect_panels(&registry).await;
+        let symbols: Vec<&str> = panels.iter().map(|panel| panel.symbol.as_str()).collect();
+        assert_eq!(symbols, ["BTCUSDT", "ETHUSDT"]);
+
+        let btc = &panels[0];
+        assert_eq!(btc.best_bid.as_ref().map(|bid| (bid.price, bid.quantity)), Some((99.0, 2.0)));
+        assert_eq!(btc.best_ask.as_ref().map(|ask| (ask.price, ask.quantity)), Some((101.0, 3.0)));
+        assert_eq!(btc.metrics.as_ref().map(|metrics| metrics.mid_price), Some(100.0));
+        assert_eq!((btc.status.trade_count, btc.status.book_levels), (1, 3));
+        assert_eq!(btc.last_signal.as_ref().map(|signal| signal.price), Some(100.0));
+        assert_eq!(btc.health(5_000), "live");
+
+        let eth = &panels[1];
+        assert!(eth.best_bid.is_none() && eth.metrics.is_none() && eth.last_signal.is_none());
+        assert_eq!(eth.health(5_000), "waiting");
+    }
+}
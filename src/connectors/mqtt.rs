@@ -0,0 +1,149 @@
+//! MQTT publisher for edge/IoT consumers of signals and heartbeats (`mqtt` feature)
+//!
+//! Payloads are JSON: a `TradingSignal` on `mqtt_signal_topic` and a `SymbolHeartbeat` on
+//! `mqtt_heartbeat_topic`, with `{symbol}` in a topic replaced by the symbol. Publishing never
+//! blocks the engine: messages are queued to the MQTT event loop, and dropped with a warning when
+//! its queue is full (e.g. while the broker is unreachable).
+
+use crate::config::OFIConfig;
+use crate::engine::SymbolHeartbeat;
+use crate::signals::TradingSignal;
+use log::{error, info, warn};
+use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet, QoS};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Messages queued for the event loop before publishing starts dropping them
+const REQUEST_CAPACITY: usize = 1000;
+
+/// Cloneable handle for publishing to the broker
+#[derive(Clone)]
+pub struct MqttPublisher {
+    client: AsyncClient,
+    signal_topic: String,
+    heartbeat_topic: Option<String>,
+    qos: QoS,
+}
+
+impl MqttPublisher {
+    /// Create a publisher for the configured broker, None without `mqtt_host`. The returned
+    /// event loop does the network I/O and must be driven by `run_mqtt_event_loop`.
+    pub fn from_config(config: &OFIConfig) -> Option<(Self, EventLoop)> {
+        let host = config.mqtt_host.as_ref()?;
+        let mut options = MqttOptions::new(config.mqtt_client_id.clone(), host.clone(), config.mqtt_port);
+        options.set_keep_alive(Duration::from_secs(30));
+        let (client, event_loop) = AsyncClient::new(options, REQUEST_CAPACITY);
+        let qos = match config.mqtt_qos {
+            0 => QoS::AtMostOnce,
+            1 => QoS::AtLeastOnce,
+            _ => QoS::ExactlyOnce,
+        };
+        let publisher = Self {
+            client,
+            signal_topic: config.mqtt_signal_topic.clone(),
+            heartbeat_topic: config.mqtt_heartbeat_topic.clone(),
+            qos,
+        };
+        Some((publisher, event_loop))
+    }
+
+    /// Publish a signal to the signal topic
+    pub fn publish_signal(&self, signal: &TradingSignal) {
+        self.publish(&self.signal_topic, &signal.symbol, signal);
+    }
+
+    /// Publish a symbol heartbeat, if a heartbeat topic is configured
+    pub fn publish_heartbeat(&self, heartbeat: &SymbolHeartbeat) {
+        if let Some(topic) = &self.heartbeat_topic {
+            self.publish(topic, &heartbeat.symbol, heartbeat);
+        }
+    }
+
+    fn publish<T: Serialize>(&self, topic: &str, symbol: &str, payload: &T) {
+        let payload = match serde_json::to_vec(payload) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("[Rust] Failed to serialize MQTT payload for {}: {}", symbol, e);
+                return;
+            }
+        };
+        let topic = topic.replace("{symbol}", symbol);
+        if let Err(e) = self.client.try_publish(topic.as_str(), self.qos, false, payload) {
+            warn!("[Rust] Dropping MQTT message on {}: {}", topic, e);
+        }
+    }
+}
+
+/// Drive the MQTT connection forever. A broker disconnect or unreachable broker is retried every
+/// `reconnect_delay`; messages queued meanwhile are sent once the connection is back.
+pub async fn run_mqtt_event_loop(mut event_loop: EventLoop, reconnect_delay: Duration) {
+    loop {
+        match event_loop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => info!("[Rust] Connected to MQTT broker."),
+            Ok(_) => {}
+            Err(e) => {
+                warn!("[Rust] MQTT connection error: {}. Reconnecting in {}s...", e, reconnect_delay.as_secs());
+                tokio::time::sleep(reconnect_delay).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::SignalType;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Read one MQTT packet: its fixed header byte and the bytes after the remaining length
+    async fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let header = stream.read_u8().await.unwrap();
+        let (mut remaining, mut shift) = (0usize, 0);
+        loop {
+            let byte = stream.read_u8().await.unwrap();
+            remaining |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        let mut body = vec![0; remaining];
+        stream.read_exact(&mut body).await.unwrap();
+        (header, body)
+    }
+
+    #[tokio::test]
+    async fn signals_reach_the_broker_on_their_topic_after_a_reconnect() {
+        // A mock broker: drops the first connection, then accepts the client and reads its publish
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = OFIConfig {
+            mqtt_host: Some("127.0.0.1".to_string()),
+            mqtt_port: listener.local_addr().unwrap().port(),
+            mqtt_qos: 0,
+            ..OFIConfig::for_tests()
+        };
+        assert!(MqttPublisher::from_config(&OFIConfig::for_tests()).is_none());
+        let (publisher, event_loop) = MqttPublisher::from_config(&config).unwrap();
+        tokio::spawn(run_mqtt_event_loop(event_loop, Duration::from_millis(20)));
+
+        // Queued while the broker is unreachable, sent once the connection is back
+        let signal = TradingSignal { signal_type: SignalType::StrongBuy, price: 67012.5, ..TradingSignal::no_signal("BTCUSDT") };
+        publisher.publish_signal(&signal);
+
+        let broker = async {
+            drop(listener.accept().await.unwrap());
+            let (mut stream, _) = listener.accept().await.unwrap();
+            assert_eq!(read_packet(&mut stream).await.0 >> 4, 1, "CONNECT first");
+            stream.write_all(&[0x20, 0x02, 0x00, 0x00]).await.unwrap();
+            read_packet(&mut stream).await
+        };
+        let (header, body) = tokio::time::timeout(Duration::from_secs(5), broker).await.expect("signal published");
+
+        assert_eq!(header, 0x30, "a QoS 0 PUBLISH");
+        let topic_len = u16::from_be_bytes([body[0], body[1]]) as usize;
+        assert_eq!(std::str::from_utf8(&body[2..2 + topic_len]).unwrap(), "ofi/signals/BTCUSDT");
+        let received: TradingSignal = serde_json::from_slice(&body[2 + topic_len..]).unwrap();
+        assert_eq!((received.symbol.as_str(), received.signal_type, received.price), ("BTCUSDT", SignalType::StrongBuy, 67012.5));
+    }
+}
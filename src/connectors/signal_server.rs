@@ -0,0 +1,143 @@
+//! Local WebSocket server streaming signals and data status to other processes (`signal-server` feature)
+//!
+//! Wire schema: every message is one JSON text frame `{"type": <kind>, "data": <payload>}`:
+//! - `"signal"`: a `TradingSignal` as serialized by serde (actionable signals only)
+//! - `"data_status"`: a `SymbolDataStatus` for the symbol that produced the last signal
+//! - `"heartbeat"`: a periodic `SymbolHeartbeat` per symbol (with `heartbeat_interval_secs`)
+//!
+//! Clients only listen; anything they send is ignored. A client that falls behind the
+//! broadcast buffer skips the missed events rather than stalling the engine.
+
+use crate::engine::{SymbolDataStatus, SymbolHeartbeat};
+use crate::signals::TradingSignal;
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::{accept_async, tungstenite::protocol::Message};
+
+/// One event on the wire
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ServerEvent {
+    Signal(Box<TradingSignal>),
+    DataStatus(SymbolDataStatus),
+    Heartbeat(SymbolHeartbeat),
+}
+
+/// Cloneable handle for publishing events to every connected client
+#[derive(Clone)]
+pub struct SignalBroadcaster {
+    tx: broadcast::Sender<String>,
+}
+
+impl SignalBroadcaster {
+    /// Create a broadcaster buffering up to `capacity` events per lagging client
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Self { tx }
+    }
+
+    /// Publish an event; a no-op while no client is connected
+    pub fn publish(&self, event: &ServerEvent) {
+        if self.tx.receiver_count() == 0 {
+            return;
+        }
+        match serde_json::to_string(event) {
+            Ok(text) => {
+                let _ = self.tx.send(text);
+            }
+            Err(e) => error!("[Rust] Failed to serialize signal server event: {}", e),
+        }
+    }
+
+    /// Number of connected clients
+    pub fn client_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+/// Serve the signal stream on `addr` until the listener fails
+pub async fn run_signal_server(addr: String, broadcaster: SignalBroadcaster) -> Result<()> {
+    let listener = TcpListener::bind(&addr).await?;
+    info!("[Rust] Signal server listening on ws://{}", addr);
+    serve_signals(listener, broadcaster).await
+}
+
+/// Serve the signal stream on an already-bound listener until it fails
+pub async fn serve_signals(listener: TcpListener, broadcaster: SignalBroadcaster) -> Result<()> {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                error!("[Rust] Signal server failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let rx = broadcaster.tx.subscribe();
+        tokio::spawn(async move {
+            if let Err(e) = serve_client(stream, rx).await {
+                warn!("[Rust] Signal server client {} disconnected: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn serve_client(stream: TcpStream, mut rx: broadcast::Receiver<String>) -> Result<()> {
+    let ws_stream = accept_async(stream).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                match event {
+                    Ok(text) => write.send(Message::Text(text.into())).await?,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("[Rust] Signal server client lagging; skipped {} events.", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                }
+            }
+            msg = read.next() => {
+                match msg {
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Err(e)) => return Err(e.into()),
+                    Some(Ok(_)) => {} // Clients only listen
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::SignalType;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn connected_clients_receive_published_signals() {
+        // The server keeps the listener, so nothing else can take its port
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let broadcaster = SignalBroadcaster::new(16);
+        tokio::spawn(serve_signals(listener, broadcaster.clone()));
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr)).await.expect("server accepts connections");
+        while broadcaster.client_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let signal = TradingSignal { signal_type: SignalType::StrongBuy, price: 67012.5, ..TradingSignal::no_signal("BTCUSDT") };
+        broadcaster.publish(&ServerEvent::Signal(Box::new(signal)));
+
+        let frame = tokio::time::timeout(Duration::from_secs(5), client.next()).await.expect("event delivered").unwrap().unwrap();
+        let Message::Text(text) = frame else { panic!("events are text frames") };
+        let wire: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(wire["type"], "signal");
+        let ServerEvent::Signal(received) = serde_json::from_str(&text).unwrap() else { panic!("a signal event") };
+        assert_eq!((received.symbol.as_str(), received.signal_type, received.price), ("BTCUSDT", SignalType::StrongBuy, 67012.5));
+    }
+}
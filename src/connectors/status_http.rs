@@ -0,0 +1,117 @@
+//! Minimal HTTP status endpoint for runtime introspection of the Sentinel
+
+use anyhow::Result;
+use log::{error, info, warn};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A route handler receives the HTTP method and path and returns `Some((status, json_body))`
+/// if it handles the route, or `None` to fall through to a 404. Handlers run on the blocking
+/// thread pool, so they may read config files or write dumps without stalling the runtime.
+pub type StatusHandler = Arc<dyn Fn(&str, &str) -> Option<(u16, String)> + Send + Sync>;
+
+/// Serve the status endpoint on `addr` until the listener fails.
+///
+/// This is intentionally tiny: one request per connection, no keep-alive, JSON bodies only.
+pub async fn run_status_server(addr: String, handler: StatusHandler) -> Result<()> {
+    let listener = TcpListener::bind(&addr).await?;
+    info!("[Rust] Status endpoint listening on http://{}", addr);
+    serve_status(listener, handler).await
+}
+
+/// Serve the status endpoint on an already-bound listener until it fails
+pub async fn serve_status(listener: TcpListener, handler: StatusHandler) -> Result<()> {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                error!("[Rust] Status endpoint failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let handler = Arc::clone(&handler);
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, handler).await {
+                warn!("[Rust] Status endpoint request from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, handler: StatusHandler) -> Result<()> {
+    let mut buf = vec![0u8; 4096];
+    let n = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+
+    // Only the request line matters: "<METHOD> <PATH> HTTP/1.1"
+    let mut parts = request.lines().next().unwrap_or_default().split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let route = (method.clone(), path.clone());
+    let (status, body) = tokio::task::spawn_blocking(move || handler(&route.0, &route.1))
+        .await?
+        .unwrap_or_else(|| (404, serde_json::json!({ "error": format!("no route for {} {}", method, path) }).to_string()));
+
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        500 => "Internal Server Error",
+        _ => "Error",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Send a raw request line and split the response into its status line, headers and body
+    async fn request(addr: std::net::SocketAddr, request_line: &str) -> (String, HashMap<String, String>, String) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(format!("{}\r\nHost: localhost\r\n\r\n", request_line).as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").expect("headers end with a blank line");
+        let mut lines = head.lines();
+        let status_line = lines.next().unwrap().to_string();
+        let headers = lines.filter_map(|line| line.split_once(": ")).map(|(name, value)| (name.to_string(), value.to_string())).collect();
+        (status_line, headers, body.to_string())
+    }
+
+    #[tokio::test]
+    async fn routes_and_unknown_paths_answer_with_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handler: StatusHandler = Arc::new(|method, path| {
+            (method == "GET" && path == "/config").then(|| (200, serde_json::json!({ "fast_mode": false }).to_string()))
+        });
+        tokio::spawn(serve_status(listener, handler));
+
+        let (status_line, headers, body) = request(addr, "GET /config HTTP/1.1").await;
+        assert_eq!(status_line, "HTTP/1.1 200 OK");
+        assert_eq!(headers["Content-Length"], body.len().to_string());
+        let config: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(config["fast_mode"], false);
+
+        // Quotes and backslashes from the client are escaped, not spliced into the JSON
+        let (status_line, headers, body) = request(addr, "GET /no\"such\\route HTTP/1.1").await;
+        assert_eq!(status_line, "HTTP/1.1 404 Not Found");
+        assert_eq!(headers["Content-Length"], body.len().to_string());
+        let error: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(error["error"], "no route for GET /no\"such\\route");
+    }
+}
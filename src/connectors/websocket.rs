@@ -1,15 +1,20 @@
-//! WebSocket client for Bitget API
+//! WebSocket client for exchange public streams (Bitget), one connection per symbol
 
+use crate::config::{Exchange, TimestampSource, TimestampUnit};
 use crate::data::{OrderBookLevel, OrderBookSnapshot, TradeData};
-use crate::engine::OFIEngine;
+use crate::engine::{OFIEngine, SymbolHeartbeat};
 use crate::signals::{SignalType, TradingSignal};
 use anyhow::{anyhow, Result};
 use futures_util::{stream::StreamExt, SinkExt};
 use log::{error, info, warn};
+use rand::Rng;
 use serde::Deserialize;
 use serde_json::json;
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, TryLockError};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
@@ -19,8 +24,8 @@ use url::Url;
 
 #[derive(Deserialize, Debug)]
 struct BitgetWsResponse {
-    #[allow(dead_code)]
     action: Option<String>,
+    event: Option<String>,
     arg: BitgetArg,
     data: Option<serde_json::Value>,
 }
@@ -52,58 +57,492 @@ struct BitgetTradeData {
 
 // --- WebSocket Connection Manager ---
 
+/// Subscription and data delivery status of the books and trade channels for one connection
+#[derive(Debug, Default)]
+struct ChannelHealth {
+    books_subscribed: bool,
+    trade_subscribed: bool,
+    books_delivering: bool,
+    trade_delivering: bool,
+    invalid_symbol: Option<String>,  // Exchange error rejecting a subscribed symbol as nonexistent
+    resynced_at: Option<Instant>,  // Last books snapshot, i.e. (re)subscribe or resync
+}
+
+impl ChannelHealth {
+    fn record_subscribed(&mut self, channel: &str) {
+        match channel {
+            "books" => self.books_subscribed = true,
+            "trade" => self.trade_subscribed = true,
+            _ => {}
+        }
+    }
+
+    fn record_data(&mut self, channel: &str) {
+        match channel {
+            "books" => self.books_delivering = true,
+            "trade" => self.trade_delivering = true,
+            _ => {}
+        }
+    }
+
+    fn all_delivering(&self) -> bool {
+        self.books_delivering && self.trade_delivering
+    }
+
+    /// Channels that have not delivered any data yet, with their subscription state
+    fn silent_channels(&self) -> String {
+        let mut silent = Vec::new();
+        if !self.books_delivering {
+            silent.push(format!("books (subscribed: {})", self.books_subscribed));
+        }
+        if !self.trade_delivering {
+            silent.push(format!("trade (subscribed: {})", self.trade_subscribed));
+        }
+        silent.join(", ")
+    }
+}
+
+/// Coalesces repeated errors: the first occurrence of a message is logged, repeats within
+/// `window` are counted, and the count is logged (with the latest repeat) once the window
+/// closes. Messages are grouped by their text with digit runs normalized, so repeats differing
+/// only in counters, ids or timings coalesce. Pending counts are flushed by `flush_expired`,
+/// which callers drive from a timer, and on drop.
+#[derive(Debug)]
+pub struct ErrorLogThrottle {
+    window: Duration,
+    entries: HashMap<String, ThrottledError>,  // Keyed by normalized message
+}
+
+#[derive(Debug)]
+struct ThrottledError {
+    logged_at: Instant,
+    suppressed: u64,
+    latest: String,  // Most recent suppressed occurrence, logged with the count
+}
+
+impl ErrorLogThrottle {
+    /// A zero `window` logs every occurrence
+    pub fn new(window: Duration) -> Self {
+        Self { window, entries: HashMap::new() }
+    }
+
+    /// The coalescing window
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Log `message` at error level unless a message with the same normalized text was logged
+    /// within the window, in which case it is only counted
+    pub fn error(&mut self, message: String) {
+        if self.window.is_zero() {
+            error!("{}", message);
+            return;
+        }
+        let now = Instant::now();
+        self.flush_expired(now);
+        match self.entries.entry(Self::normalize(&message)) {
+            Entry::Occupied(mut entry) => {
+                let entry = entry.get_mut();
+                entry.suppressed += 1;
+                entry.latest = message;
+            }
+            Entry::Vacant(slot) => {
+                error!("{}", message);
+                slot.insert(ThrottledError { logged_at: now, suppressed: 0, latest: message });
+            }
+        }
+    }
+
+    /// Log the repeat counts of messages whose window has closed by `now` and forget them
+    pub fn flush_expired(&mut self, now: Instant) {
+        let window = self.window;
+        self.entries.retain(|_, entry| {
+            let elapsed = now.duration_since(entry.logged_at);
+            if elapsed < window {
+                return true;
+            }
+            Self::log_repeats(entry, elapsed);
+            false
+        });
+    }
+
+    fn log_repeats(entry: &ThrottledError, elapsed: Duration) {
+        if let Some(line) = Self::repeat_summary(entry, elapsed) {
+            error!("{}", line);
+        }
+    }
+
+    /// The coalesced line for an entry's suppressed repeats, None if there were none
+    fn repeat_summary(entry: &ThrottledError, elapsed: Duration) -> Option<String> {
+        (entry.suppressed > 0).then(|| format!("{} (repeated {} more times in the last {}ms)", entry.latest, entry.suppressed, elapsed.as_millis()))
+    }
+
+    /// Grouping key of a message: its text with every run of digits replaced by `#`
+    fn normalize(message: &str) -> String {
+        let mut key = String::with_capacity(message.len());
+        let mut in_digits = false;
+        for c in message.chars() {
+            if c.is_ascii_digit() {
+                if !in_digits {
+                    key.push('#');
+                }
+                in_digits = true;
+            } else {
+                key.push(c);
+                in_digits = false;
+            }
+        }
+        key
+    }
+}
+
+impl Drop for ErrorLogThrottle {
+    fn drop(&mut self) {
+        let now = Instant::now();
+        for entry in self.entries.values() {
+            Self::log_repeats(entry, now.duration_since(entry.logged_at));
+        }
+    }
+}
+
+/// Adaptive downsampling of message-driven analyses: the data message rate is measured over
+/// one-second windows; above `max_rate` the skip factor doubles (up to `max_factor`), below half
+/// of it the factor halves back towards 1. One analysis runs per `skip_factor` data messages.
+#[derive(Debug)]
+pub struct AdaptiveDownsampler {
+    max_rate: f64,
+    max_factor: u64,
+    skip_factor: u64,
+    window_start: Instant,
+    window_messages: u64,
+    since_analysis: u64,
+}
+
+impl AdaptiveDownsampler {
+    /// A zero `max_rate` disables downsampling
+    pub fn new(max_rate: f64, max_factor: u64) -> Self {
+        Self {
+            max_rate,
+            max_factor: max_factor.max(1),
+            skip_factor: 1,
+            window_start: Instant::now(),
+            window_messages: 0,
+            since_analysis: 0,
+        }
+    }
+
+    /// Current skip factor
+    pub fn skip_factor(&self) -> u64 {
+        self.skip_factor
+    }
+
+    /// Record a data message at `now`; returns whether an analysis is due for it
+    pub fn admit(&mut self, now: Instant) -> bool {
+        if self.max_rate <= 0.0 {
+            return true;
+        }
+        self.window_messages += 1;
+        let elapsed = now.duration_since(self.window_start);
+        if elapsed >= Duration::from_secs(1) {
+            let rate = self.window_messages as f64 / elapsed.as_secs_f64();
+            if rate > self.max_rate {
+                self.skip_factor = (self.skip_factor * 2).min(self.max_factor);
+            } else if rate < self.max_rate / 2.0 {
+                self.skip_factor = (self.skip_factor / 2).max(1);
+            }
+            self.window_start = now;
+            self.window_messages = 0;
+        }
+        self.since_analysis += 1;
+        if self.since_analysis >= self.skip_factor {
+            self.since_analysis = 0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Micro-batching of message-driven analyses: the first data message opens a window of
+/// `window`, and one analysis runs on the merged state when it closes or once `max_updates`
+/// messages have accumulated (0 for no count limit). A zero window disables batching.
+#[derive(Debug)]
+struct AnalysisBatch {
+    window: Duration,
+    max_updates: usize,
+    deadline: Option<tokio::time::Instant>,
+    updates: usize,
+}
+
+impl AnalysisBatch {
+    fn new(window: Duration, max_updates: usize) -> Self {
+        Self { window, max_updates, deadline: None, updates: 0 }
+    }
+
+    fn enabled(&self) -> bool {
+        !self.window.is_zero()
+    }
+
+    /// When the open batch is due, if one is open
+    fn deadline(&self) -> Option<tokio::time::Instant> {
+        self.deadline
+    }
+
+    /// Add a data message received at `now`; returns whether the batch is full and due now
+    fn record(&mut self, now: tokio::time::Instant) -> bool {
+        self.deadline.get_or_insert(now + self.window);
+        self.updates += 1;
+        self.max_updates > 0 && self.updates >= self.max_updates
+    }
+
+    /// Close the batch once its analysis runs
+    fn reset(&mut self) {
+        self.deadline = None;
+        self.updates = 0;
+    }
+}
+
+/// Handler for data on an extra subscribed channel (anything besides `books` and `trade`)
+pub trait ChannelHandler: Send + Sync {
+    /// Called with the `data` array of every message on the channel for `inst_id`
+    fn handle(&self, inst_id: &str, data: &serde_json::Value);
+}
+
+/// How a WebSocket session ended without an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectKind {
+    /// The server sent a Close frame (e.g. deliberate connection rotation)
+    ClosedByServer,
+    /// The stream ended without a Close frame
+    StreamEnded,
+    /// The signal receiver was dropped; nobody is listening any more
+    ReceiverDropped,
+    /// The exchange rejected the symbol as nonexistent (e.g. delisted); reconnecting can't help
+    InvalidSymbol,
+}
+
+/// Delay before reconnecting after a session ended with `result`: `reconnect_clean_ms` after a
+/// clean disconnect, `reconnect_error_ms` after an error, None when the manager should stop
+fn reconnect_delay(result: &Result<DisconnectKind>, config: &crate::config::OFIConfig) -> Option<Duration> {
+    match result {
+        Ok(DisconnectKind::ReceiverDropped | DisconnectKind::InvalidSymbol) => None,
+        Ok(DisconnectKind::ClosedByServer | DisconnectKind::StreamEnded) => Some(Duration::from_millis(config.reconnect_clean_ms)),
+        Err(_) => Some(Duration::from_millis(config.reconnect_error_ms)),
+    }
+}
+
+/// Spreads reconnect attempts of all symbols over time, so a mass disconnect (e.g. exchange
+/// maintenance) doesn't make every manager handshake on the same timer
+pub struct ReconnectScheduler {
+    next_slot: Mutex<Option<Instant>>,  // Earliest time the next reconnect may start
+}
+
+/// Shared by every WebSocket manager in the process
+static RECONNECT_SCHEDULER: ReconnectScheduler = ReconnectScheduler::new();
+
+impl ReconnectScheduler {
+    pub const fn new() -> Self {
+        Self { next_slot: Mutex::new(None) }
+    }
+
+    /// Reserve a reconnect time for a manager that wants to reconnect `delay` after `now`:
+    /// the later of that and `spacing` after the previously reserved attempt. `jitter` adds up
+    /// to that much random delay on top; with zero spacing and jitter this is just `now + delay`.
+    pub fn schedule(&self, now: Instant, delay: Duration, spacing: Duration, jitter: Duration) -> Instant {
+        let jitter = if jitter.is_zero() {
+            Duration::ZERO
+        } else {
+            Duration::from_millis(rand::thread_rng().gen_range(0..=jitter.as_millis() as u64))
+        };
+        let wanted = now + delay + jitter;
+        if spacing.is_zero() {
+            return wanted;
+        }
+        let mut next_slot = self.next_slot.lock().unwrap();
+        let at = match *next_slot {
+            Some(slot) if slot > wanted => slot,
+            _ => wanted,
+        };
+        *next_slot = Some(at + spacing);
+        at
+    }
+}
+
+impl Default for ReconnectScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rolling window of the most recent raw WebSocket data messages per connection (keyed by the
+/// task's symbol, so a synthetic's ring holds both legs in receipt order), kept in memory so the
+/// exact input leading up to a crash can be dumped and replayed. A ring is removed when its task
+/// exits.
+pub struct FlightRecorder {
+    messages: Mutex<BTreeMap<String, VecDeque<(i64, String)>>>,  // Receipt time (ms) and raw text, oldest first
+}
+
+/// Shared by every WebSocket manager in the process
+pub static FLIGHT_RECORDER: FlightRecorder = FlightRecorder::new();
+
+impl FlightRecorder {
+    pub const fn new() -> Self {
+        Self { messages: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Record a raw message for `symbol`, dropping the oldest beyond `capacity`
+    pub fn record(&self, symbol: &str, text: &str, capacity: usize) {
+        let mut messages = self.messages.lock().unwrap_or_else(|e| e.into_inner());
+        let ring = messages.entry(symbol.to_string()).or_default();
+        if ring.len() >= capacity {
+            ring.pop_front();
+        }
+        ring.push_back((chrono::Utc::now().timestamp_millis(), text.to_string()));
+    }
+
+    /// Drop the ring of `symbol`, e.g. once its task has exited
+    pub fn remove(&self, symbol: &str) {
+        self.messages.lock().unwrap_or_else(|e| e.into_inner()).remove(symbol);
+    }
+
+    /// Write every recorded message to a new JSON Lines file in `dir` (one
+    /// `{"symbol", "received_at_ms", "text"}` object per line, in receipt order per symbol) and
+    /// return its path. The recording is kept. Safe to call from a panic hook: a poisoned lock is
+    /// still read, and a lock held by the panicking thread fails the flush instead of deadlocking.
+    pub fn flush(&self, dir: &str) -> std::io::Result<PathBuf> {
+        let messages = match self.messages.try_lock() {
+            Ok(messages) => messages,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return Err(std::io::Error::other("flight recorder is busy")),
+        };
+        let mut contents = String::new();
+        for (symbol, ring) in messages.iter() {
+            for (received_at_ms, text) in ring {
+                contents.push_str(&json!({ "symbol": symbol, "received_at_ms": received_at_ms, "text": text }).to_string());
+                contents.push('\n');
+            }
+        }
+        drop(messages);
+        fs::create_dir_all(dir)?;
+        let path = Path::new(dir).join(format!("flight-{}.jsonl", chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ")));
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+}
+
+impl Default for FlightRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Manages the WebSocket connection, handling automatic reconnections.
 ///
-/// This function will run indefinitely, attempting to reconnect on any disconnection.
+/// The manager reconnects on any disconnection and keeps running until the returned
+/// receiver is dropped, or until the exchange rejects the symbol (with `stop_on_invalid_symbol`).
 /// It returns a receiver channel from which trading signals can be consumed.
 pub async fn run_websocket_manager(
     symbol: String,
     engine: OFIEngine,
 ) -> mpsc::Receiver<TradingSignal> {
+    spawn_websocket_manager(symbol, engine).0
+}
+
+/// Open a WebSocket connection to `url` and close it again, to check the endpoint is reachable
+pub async fn test_handshake(url: &str, timeout: Duration) -> Result<()> {
+    let url = Url::parse(url)?;
+    // The stream is dropped right away, closing the connection
+    let (_, response) = tokio::time::timeout(timeout, connect_async(url.to_string()))
+        .await
+        .map_err(|_| anyhow!("WebSocket handshake timed out after {}ms", timeout.as_millis()))?
+        .map_err(|e| anyhow!("WebSocket connection failed: {}", e))?;
+    info!("[Rust] WebSocket handshake with {} succeeded: {:?}", url, response.status());
+    Ok(())
+}
+
+/// Like `run_websocket_manager`, but also returns the manager task's handle so callers can
+/// cancel it (`abort`) or wait for it to finish after dropping the receiver.
+pub fn spawn_websocket_manager(
+    symbol: String,
+    engine: OFIEngine,
+) -> (mpsc::Receiver<TradingSignal>, tokio::task::JoinHandle<()>) {
     let (tx, rx) = mpsc::channel(1000); // Increase channel capacity to handle bursts of signals
     let tx_for_task = tx.clone();
 
-    tokio::spawn(async move {
+    let handle = tokio::spawn(async move {
         let mut connection_count = 0;
+        let mut error_throttle = ErrorLogThrottle::new(Duration::from_millis(engine.config().error_log_coalesce_ms));
         loop {
+            if tx_for_task.is_closed() {
+                info!("[Rust] Signal receiver for {} dropped. Stopping WebSocket manager.", symbol);
+                break;
+            }
             connection_count += 1;
             info!("[Rust] Attempting to establish WebSocket connection for {} (attempt #{})...", symbol, connection_count);
             
-            let connection_result = connect_and_listen(&symbol, engine.clone(), tx_for_task.clone()).await;
+            let connection_result = connect_and_listen(&symbol, engine.clone(), tx_for_task.clone(), &mut error_throttle).await;
 
-            match connection_result {
-                Ok(_) => {
-                    warn!("[Rust] WebSocket for {} (attempt #{}) disconnected cleanly. Reconnecting in 5 seconds...", symbol, connection_count);
+            // Clean disconnects (e.g. deliberate server rotation) may reconnect faster than errors
+            let next_delay = reconnect_delay(&connection_result, engine.config());
+            let delay = match connection_result {
+                Ok(DisconnectKind::ReceiverDropped) => {
+                    info!("[Rust] Signal receiver for {} dropped. Stopping WebSocket manager.", symbol);
+                    break;
+                }
+                Ok(DisconnectKind::InvalidSymbol) => {
+                    error!("[Rust] INVALID SYMBOL: {} rejected by the exchange (delisted?). Stopping WebSocket manager without reconnecting.", symbol);
+                    break;
+                }
+                Ok(DisconnectKind::ClosedByServer) => {
+                    let delay = next_delay.unwrap_or_default();
+                    warn!("[Rust] WebSocket for {} (attempt #{}) closed by server. Reconnecting in {:?}...", symbol, connection_count, delay);
+                    delay
+                }
+                Ok(DisconnectKind::StreamEnded) => {
+                    let delay = next_delay.unwrap_or_default();
+                    warn!("[Rust] WebSocket for {} (attempt #{}) disconnected cleanly. Reconnecting in {:?}...", symbol, connection_count, delay);
+                    delay
                 }
                 Err(e) => {
-                    error!("[Rust] WebSocket for {} (attempt #{}) disconnected with error: {}. Reconnecting in 5 seconds...", symbol, connection_count, e);
+                    let delay = next_delay.unwrap_or_default();
+                    error_throttle.error(format!("[Rust] WebSocket for {} (attempt #{}) disconnected with error: {}. Reconnecting in {:?}...", symbol, connection_count, e, delay));
+                    delay
                 }
-            }
-            // Wait before attempting to reconnect
-            tokio::time::sleep(Duration::from_secs(5)).await;
+            };
+            // Wait before attempting to reconnect, in a slot spread out from other symbols' reconnects
+            let reconnect_at = RECONNECT_SCHEDULER.schedule(
+                Instant::now(),
+                delay,
+                Duration::from_millis(engine.config().reconnect_spacing_ms),
+                Duration::from_millis(engine.config().reconnect_jitter_ms),
+            );
+            tokio::time::sleep_until(tokio::time::Instant::from_std(reconnect_at)).await;
         }
     });
 
-    rx
+    (rx, handle)
 }
 
 /// Connects to the WebSocket, subscribes to channels, and listens for messages.
 ///
 /// This function will exit upon any disconnection or critical error, leaving the
-/// reconnection logic to the `run_websocket_manager`.
+/// reconnection logic to the `run_websocket_manager`. Read errors, ping failures and
+/// idle timeouts are reported as errors; server closes and stream ends as `DisconnectKind`.
 async fn connect_and_listen(
     symbol: &str,
     engine: OFIEngine,
     signal_tx: mpsc::Sender<TradingSignal>,
-) -> Result<()> {
+    error_throttle: &mut ErrorLogThrottle,
+) -> Result<DisconnectKind> {
     // Track recent signals to prevent duplicates
-    let recent_signals = Arc::new(Mutex::new(HashMap::<String, Instant>::new()));
+    let recent_signals = Arc::new(Mutex::new(HashMap::<String, u64>::new()));
     if symbol.is_empty() || symbol.len() > 20 {
         return Err(anyhow!("Invalid symbol: must be between 1-20 characters"));
     }
 
     let config = engine.config();
-    let url = Url::parse(&config.websocket_url)?;
+    let url = Url::parse(config.websocket_url_for(symbol))?;
+    let exchange = config.exchange_for(symbol);
     let (ws_stream, response) = connect_async(url.to_string())
         .await
         .map_err(|e| anyhow!("WebSocket connection failed: {}", e))?;
@@ -111,61 +550,145 @@ async fn connect_and_listen(
 
     let (mut write, mut read) = ws_stream.split();
 
-    let subscription_msg = json!({
-        "op": "subscribe",
-        "args": [
-            { "instType": "USDT-FUTURES", "channel": "books", "instId": symbol },
-            { "instType": "USDT-FUTURES", "channel": "trade", "instId": symbol }
-        ]
-    });
+    // A synthetic spread is fed by its legs' streams; the engine derives its book and trades
+    let subscribed_symbols: Vec<&str> = match config.synthetic(symbol) {
+        Some(synthetic) => vec![synthetic.long_leg.as_str(), synthetic.short_leg.as_str()],
+        None => vec![symbol],
+    };
+    let subscriptions = subscribed_symbols
+        .iter()
+        .flat_map(|subscribed| subscription_messages(exchange, subscribed, &config.channels, config.unsubscribe_before_subscribe));
 
-    // Send subscription with timeout to avoid hanging
-    let subscribe_result = tokio::time::timeout(Duration::from_secs(10), write.send(Message::Text(subscription_msg.to_string().into()))).await;
-    match subscribe_result {
-        Ok(Ok(())) => {
-            info!("[Rust] Subscribed to order book and trade channels for {}", symbol);
-        }
-        Ok(Err(e)) => {
-            error!("[Rust] Failed to send subscription message: {}", e);
-            return Err(anyhow!("Failed to subscribe: {}", e));
-        }
-        Err(_) => {
-            error!("[Rust] Timeout sending subscription message");
-            return Err(anyhow!("Subscription timeout"));
+    // Send the (optional unsubscribe and) subscription with timeout to avoid hanging
+    for subscription_msg in subscriptions {
+        let subscribe_result = tokio::time::timeout(Duration::from_secs(10), write.send(Message::Text(subscription_msg.to_string().into()))).await;
+        match subscribe_result {
+            Ok(Ok(())) => {
+                info!("[Rust] Sent {} for {} channels of {}", subscription_msg["op"].as_str().unwrap_or_default(), config.channels.join("/"), subscribed_symbols.join("/"));
+            }
+            Ok(Err(e)) => {
+                error!("[Rust] Failed to send subscription message: {}", e);
+                return Err(anyhow!("Failed to subscribe: {}", e));
+            }
+            Err(_) => {
+                error!("[Rust] Timeout sending subscription message");
+                return Err(anyhow!("Subscription timeout"));
+            }
         }
     }
 
     let mut ping_interval = tokio::time::interval(Duration::from_secs(25));
     let mut last_message_time = tokio::time::Instant::now();
+    let connected_at = tokio::time::Instant::now();
+    let mut channels = ChannelHealth::default();
+    // Leg data arrives under the legs' instIds, so a synthetic connection is not verified
+    let expected_symbol = (config.verify_inst_id && config.synthetic(symbol).is_none()).then_some(symbol);
+
+    // Micro-batching: data messages only mark an analysis as due, which then runs once on the
+    // merged state when the batch window closes or enough updates have accumulated
+    let mut batch = AnalysisBatch::new(Duration::from_millis(config.analysis_batch_ms), config.analysis_batch_max_updates);
+    let mut downsampler = AdaptiveDownsampler::new(config.adaptive_skip_max_rate, config.adaptive_skip_max_factor);
+
+    // Cadence mode: analyses run only on a fixed wall-clock tick, independent of message volume.
+    // Missed ticks (a slow analysis) are skipped so later ticks stay on the original grid.
+    let cadence_driven = config.analysis_cadence_ms > 0;
+    let mut cadence_interval = cadence_interval(config.analysis_cadence_ms);
+
+    // Periodic symbol heartbeats for monitoring, independent of signals
+    let heartbeat_sink = engine.symbol_heartbeat_sink().filter(|_| config.heartbeat_interval_secs > 0).cloned();
+    let mut heartbeat_interval = heartbeat_interval(config.heartbeat_interval_secs);
+
+    // Report coalesced error counts when their window closes, not only with the next occurrence
+    let throttle_window = error_throttle.window();
+    let mut throttle_flush_interval = tokio::time::interval(throttle_window.max(Duration::from_millis(1)));
 
     loop {
         tokio::select! {
+            _ = throttle_flush_interval.tick(), if !throttle_window.is_zero() => {
+                error_throttle.flush_expired(Instant::now());
+            }
+
+            // Run the cadence-driven analysis on the current state
+            _ = cadence_interval.tick(), if cadence_driven => {
+                if let Err(e) = analyze_and_forward(symbol, &engine, &signal_tx, Arc::clone(&recent_signals), &channels).await {
+                    error_throttle.error(format!("[Rust] Error analyzing {}: {}. Continuing connection...", symbol, e));
+                }
+            }
+
+            // Run the batched analysis once the window closes
+            _ = tokio::time::sleep_until(batch.deadline().unwrap_or_else(tokio::time::Instant::now)), if batch.deadline().is_some() => {
+                batch.reset();
+                if let Err(e) = analyze_and_forward(symbol, &engine, &signal_tx, Arc::clone(&recent_signals), &channels).await {
+                    error_throttle.error(format!("[Rust] Error analyzing {}: {}. Continuing connection...", symbol, e));
+                }
+            }
+
+            // Emit the symbol heartbeat; dropped rather than waited on if the sink is backed up
+            _ = heartbeat_interval.tick(), if heartbeat_sink.is_some() => {
+                if let Some(sink) = &heartbeat_sink {
+                    send_symbol_heartbeat(&engine, symbol, &channels, sink).await;
+                }
+            }
+
             // Send a ping at a regular interval to keep the connection alive
             _ = ping_interval.tick() => {
                 info!("[Rust] Sending Ping to server.");
                 if write.send(Message::Ping(Vec::new().into())).await.is_err() {
                     error!("[Rust] Failed to send ping. Connection likely closed.");
-                    break; // Exit to trigger reconnection
+                    return Err(anyhow!("Failed to send ping")); // Exit to trigger reconnection
                 }
             }
 
+            // Stop listening once nobody consumes signals any more
+            _ = signal_tx.closed() => {
+                return Ok(DisconnectKind::ReceiverDropped);
+            }
+
             // Process incoming messages from the WebSocket
             msg = read.next() => {
                 match msg {
+                    Some(Ok(Message::Close(close_frame))) => {
+                        warn!("[Rust] Received Close frame for {}: {:?}", symbol, close_frame);
+                        return Ok(DisconnectKind::ClosedByServer); // Exit to trigger reconnection
+                    }
                     Some(Ok(message)) => {
                         last_message_time = tokio::time::Instant::now(); // Reset timer on any message
                         // Don't break the connection on individual message processing errors
-                        if let Err(e) = handle_message(message, symbol, &engine, &signal_tx, Arc::clone(&recent_signals)).await {
-                            error!("[Rust] Error handling message for {}: {}. Continuing connection...", symbol, e);
+                        let ingested = match handle_message(message, symbol, exchange, &engine, &mut channels, expected_symbol, error_throttle).await {
+                            Ok(ingested) => ingested,
+                            Err(e) => {
+                                error_throttle.error(format!("[Rust] Error handling message for {}: {}. Continuing connection...", symbol, e));
+                                false
+                            }
+                        };
+                        if let Some(reason) = channels.invalid_symbol.take() {
+                            engine.mark_invalid_symbol(symbol, reason);
+                            return Ok(DisconnectKind::InvalidSymbol);
+                        }
+                        let analyze_now = ingested && !cadence_driven && if !batch.enabled() {
+                            let due = downsampler.admit(Instant::now());
+                            if downsampler.skip_factor() != engine.analysis_skip_factor() {
+                                info!("[Rust] Message rate for {} changed; analyzing every {} data messages.", symbol, downsampler.skip_factor());
+                                engine.set_analysis_skip_factor(downsampler.skip_factor());
+                            }
+                            due
+                        } else {
+                            batch.record(tokio::time::Instant::now())
+                        };
+                        if analyze_now {
+                            batch.reset();
+                            if let Err(e) = analyze_and_forward(symbol, &engine, &signal_tx, Arc::clone(&recent_signals), &channels).await {
+                                error_throttle.error(format!("[Rust] Error analyzing {}: {}. Continuing connection...", symbol, e));
+                            }
                         }
                     }
                     Some(Err(e)) => {
                         error!("[Rust] Error reading from WebSocket: {}", e);
-                        break; // Exit to trigger reconnection
+                        return Err(anyhow!("Error reading from WebSocket: {}", e)); // Exit to trigger reconnection
                     }
                     None => {
                         warn!("[Rust] WebSocket stream for {} ended.", symbol);
-                        break; // Exit to trigger reconnection
+                        return Ok(DisconnectKind::StreamEnded); // Exit to trigger reconnection
                     }
                 }
             }
@@ -173,103 +696,222 @@ async fn connect_and_listen(
         // Check for connection timeout (no messages received for a long time)
         if last_message_time.elapsed() > Duration::from_secs(120) {
             warn!("[Rust] WebSocket timeout for {}: No message received in 120 seconds.", symbol);
-            break; // Exit to trigger reconnection
+            return Err(anyhow!("No message received in 120 seconds")); // Exit to trigger reconnection
+        }
+        // A required channel that never delivers leaves the engine in a partial state; reconnect
+        let startup_timeout = engine.config().channel_startup_timeout_ms;
+        if engine.config().require_all_channels
+            && startup_timeout > 0
+            && !channels.all_delivering()
+            && connected_at.elapsed() > Duration::from_millis(startup_timeout)
+        {
+            warn!("[Rust] Channels for {} not delivering data after {}ms: {}.", symbol, startup_timeout, channels.silent_channels());
+            return Err(anyhow!("Required channels not delivering data: {}", channels.silent_channels())); // Exit to trigger reconnection
         }
     }
+}
 
-    Ok(())
+/// Ticker for symbol heartbeats, every `interval_secs` (at least 1s) from now
+fn heartbeat_interval(interval_secs: u64) -> tokio::time::Interval {
+    tokio::time::interval(Duration::from_secs(interval_secs.max(1)))
+}
+
+/// Send the symbol's current heartbeat to `sink`; dropped rather than waited on if it is backed up
+async fn send_symbol_heartbeat(engine: &OFIEngine, symbol: &str, channels: &ChannelHealth, sink: &mpsc::Sender<SymbolHeartbeat>) {
+    let _ = sink.try_send(engine.symbol_heartbeat(symbol, channels.all_delivering()).await);
+}
+
+/// Ticker for cadence-driven analyses, every `cadence_ms` (at least 1ms) from now. Ticks missed
+/// by a slow analysis are skipped so later ticks stay on the original grid.
+fn cadence_interval(cadence_ms: u64) -> tokio::time::Interval {
+    let mut interval = tokio::time::interval(Duration::from_millis(cadence_ms.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    interval
+}
+
+/// Messages that set up a symbol's subscriptions on `exchange`, in send order. With
+/// `unsubscribe_first`, an unsubscribe for the same channels precedes the subscribe so
+/// subscriptions lingering server-side from a previous connection can't deliver duplicates.
+pub fn subscription_messages(exchange: Exchange, symbol: &str, channels: &[String], unsubscribe_first: bool) -> Vec<serde_json::Value> {
+    match exchange {
+        Exchange::Bitget => bitget_subscription_messages(symbol, channels, unsubscribe_first),
+    }
+}
+
+fn bitget_subscription_messages(symbol: &str, channels: &[String], unsubscribe_first: bool) -> Vec<serde_json::Value> {
+    let args: Vec<serde_json::Value> = channels
+        .iter()
+        .map(|channel| json!({ "instType": "USDT-FUTURES", "channel": channel, "instId": symbol }))
+        .collect();
+    let ops: &[&str] = if unsubscribe_first { &["unsubscribe", "subscribe"] } else { &["subscribe"] };
+    ops.iter().map(|op| json!({ "op": op, "args": args })).collect()
+}
+
+/// An exchange WebSocket text payload, classified into the path `handle_message` takes
+#[derive(Debug)]
+pub enum ParsedMessage {
+    Pong,
+    /// An `"event":"error"` response from the exchange, with the raw payload
+    ExchangeError(String),
+    SubscribeAck { channel: String, inst_id: String },
+    UnsubscribeAck { channel: String, inst_id: String },
+    /// A book message; `snapshot` when it (re)starts the book rather than updating it
+    OrderBook { book: OrderBookSnapshot, snapshot: bool },
+    Trades(Vec<TradeData>),
+    /// Data on a channel other than `books`/`trade`, dispatched to the engine's channel handlers
+    ChannelData { channel: String, inst_id: String, data: serde_json::Value },
+    /// A well-formed payload that carries nothing to process
+    Ignored,
+    /// A payload that could not be parsed, with the reason
+    Malformed(String),
+}
+
+impl ParsedMessage {
+    /// The instrument a data-carrying message belongs to, `None` for control messages
+    pub fn inst_id(&self) -> Option<&str> {
+        match self {
+            ParsedMessage::OrderBook { book, .. } => Some(&book.symbol),
+            ParsedMessage::Trades(trades) => trades.first().map(|trade| trade.symbol.as_str()),
+            ParsedMessage::ChannelData { inst_id, .. } => Some(inst_id),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a parsed message belongs to `expected_symbol`; control messages always do
+pub fn matches_symbol(parsed: &ParsedMessage, expected_symbol: &str) -> bool {
+    parsed.inst_id().map_or(true, |inst_id| inst_id.eq_ignore_ascii_case(expected_symbol))
+}
+
+/// Parse a WebSocket text payload in `exchange`'s format, normalizing timestamps to milliseconds.
+///
+/// Never panics: anything unparseable is returned as `ParsedMessage::Malformed`.
+pub fn parse_message(exchange: Exchange, text: &str, timestamp_unit: TimestampUnit) -> ParsedMessage {
+    match exchange {
+        Exchange::Bitget => parse_bitget_message(text, timestamp_unit),
+    }
+}
+
+fn parse_bitget_message(text: &str, timestamp_unit: TimestampUnit) -> ParsedMessage {
+    if text.contains("pong") {
+        return ParsedMessage::Pong;
+    }
+    if text.contains("\"event\":\"error\"") {
+        return ParsedMessage::ExchangeError(text.to_string());
+    }
+
+    let response: BitgetWsResponse = match serde_json::from_str(text) {
+        Ok(response) => response,
+        Err(e) => return ParsedMessage::Malformed(format!("invalid envelope: {}", e)),
+    };
+    if response.event.as_deref() == Some("subscribe") {
+        return ParsedMessage::SubscribeAck { channel: response.arg.channel, inst_id: response.arg.inst_id };
+    }
+    if response.event.as_deref() == Some("unsubscribe") {
+        return ParsedMessage::UnsubscribeAck { channel: response.arg.channel, inst_id: response.arg.inst_id };
+    }
+    let data = match response.data {
+        Some(data) => data,
+        None => return ParsedMessage::Ignored,
+    };
+
+    let symbol = &response.arg.inst_id;
+    let parsed = match response.arg.channel.as_str() {
+        "books" => parse_orderbook(data, symbol, timestamp_unit)
+            .map(|book| ParsedMessage::OrderBook { book, snapshot: response.action.as_deref() == Some("snapshot") }),
+        "trade" => parse_trades(data, symbol, timestamp_unit).map(ParsedMessage::Trades),
+        _ => Ok(ParsedMessage::ChannelData { channel: response.arg.channel.clone(), inst_id: symbol.clone(), data }),
+    };
+    parsed.unwrap_or_else(ParsedMessage::Malformed)
+}
+
+/// Whether an `"event":"error"` payload rejects the subscribed instrument as nonexistent, as
+/// Bitget does for delisted or misspelled symbols (code 30001, "... instId:X doesn't exist")
+pub fn is_invalid_symbol_error(raw: &str) -> bool {
+    let Ok(error) = serde_json::from_str::<serde_json::Value>(raw) else {
+        return false;
+    };
+    let code_matches = error["code"].as_u64() == Some(30001) || error["code"].as_str() == Some("30001");
+    let message = error["msg"].as_str().unwrap_or_default();
+    code_matches && message.contains("instId") && message.contains("exist")
 }
 
-/// Handles a single WebSocket message.
+/// Handles a single WebSocket message, storing any book or trade data it carries in the engine.
+/// Returns whether data was ingested, i.e. whether an analysis is due.
+///
+/// Data is stored under the message's own `instId`. With `expected_symbol` set (a single-symbol
+/// connection with `verify_inst_id`), data for any other instrument is logged and dropped.
 async fn handle_message(
     msg: Message,
     symbol: &str,
+    exchange: Exchange,
     engine: &OFIEngine,
-    signal_tx: &mpsc::Sender<TradingSignal>,
-    recent_signals: Arc<Mutex<HashMap<String, Instant>>>,
-) -> Result<()> {
+    channels: &mut ChannelHealth,
+    expected_symbol: Option<&str>,
+    error_throttle: &mut ErrorLogThrottle,
+) -> Result<bool> {
     match msg {
         Message::Text(text) => {
-            if text.contains("pong") {
-                info!("[Rust] Received Pong from server.");
-                return Ok(());
+            let parsed = parse_message(exchange, &text, engine.config().timestamp_unit);
+            if engine.config().flight_recorder_messages > 0 && parsed.inst_id().is_some() {
+                FLIGHT_RECORDER.record(symbol, &text, engine.config().flight_recorder_messages);
             }
-            if text.contains("\"event\":\"error\"") {
-                warn!("[Rust] Received error from Bitget: {}", text);
-                return Ok(());
+            if let Some(expected) = expected_symbol {
+                if !matches_symbol(&parsed, expected) {
+                    warn!("[Rust] Dropping message for {} on the {} connection: instId mismatch.", parsed.inst_id().unwrap_or_default(), expected);
+                    return Ok(false);
+                }
             }
-
-            let parsed_msg: Result<BitgetWsResponse, _> = serde_json::from_str(&text);
-            match parsed_msg {
-                Ok(response) => {
-                    if let Some(data) = response.data {
-                        let channel = &response.arg.channel;
-                        let symbol_from_msg = &response.arg.inst_id;
-
-                        if channel == "books" {
-                            parse_and_update_orderbook(data, symbol_from_msg, engine).await;
-                        } else if channel == "trade" {
-                            parse_and_update_trades(data, symbol_from_msg, engine).await;
-                        }
-
-                        // --- Analyze for signals after every message ---
-                        // Catch any errors during analysis to prevent breaking the connection
-                        let analysis_result = tokio::time::timeout(Duration::from_secs(10), engine.analyze_symbol(symbol)).await;
-                        match analysis_result {
-                            Ok(signal) => {
-                                if !matches!(signal.signal_type, SignalType::NoSignal) {
-                                    // Check for duplicate signals to prevent multiple orders for the same opportunity
-                                    let signal_key = format!("{}_{}", signal.symbol, signal.signal_type);
-                                    let should_send = {
-                                        let mut recent_signals_guard = recent_signals.lock().unwrap();
-                                        let now = Instant::now();
-                                        
-                                        // Remove signals older than 5 seconds
-                                        recent_signals_guard.retain(|_, time| now.duration_since(*time) < Duration::from_secs(5));
-                                        
-                                        // Check if this signal was sent recently
-                                        if recent_signals_guard.contains_key(&signal_key) {
-                                            false // Don't send duplicate
-                                        } else {
-                                            recent_signals_guard.insert(signal_key.clone(), now);
-                                            true // Send new signal
-                                        }
-                                    };
-                                    
-                                    if should_send {
-                                        info!("[Rust] Signal found for {}: {:?}. Sending to handler.", symbol, signal.signal_type);
-                                        // Use a timeout when sending to prevent hanging if the channel is blocked
-                                        let send_result = tokio::time::timeout(Duration::from_secs(5), signal_tx.send(signal)).await;
-                                        match send_result {
-                                            Ok(Ok(())) => {
-                                                // Successfully sent
-                                            }
-                                            Ok(Err(_)) => {
-                                                error!("[Rust] Failed to send signal: receiver has been dropped.");
-                                                return Err(anyhow!("Signal channel closed"));
-                                            }
-                                            Err(_) => {
-                                                error!("[Rust] Timeout sending signal to channel.");
-                                                // Don't break the connection on send timeout, just log and continue
-                                            }
-                                        }
-                                    } else {
-                                        info!("[Rust] Duplicate signal detected for {}, skipping.", signal_key);
-                                    }
-                                }
-                            }
-                            Err(_) => {
-                                error!("[Rust] Timeout during signal analysis for {}", symbol);
-                                // Continue processing other messages despite analysis timeout
-                            }
-                        }
+            match parsed {
+                ParsedMessage::Pong => {
+                    info!("[Rust] Received Pong from server.");
+                    return Ok(false);
+                }
+                ParsedMessage::ExchangeError(raw) => {
+                    warn!("[Rust] Received error from Bitget: {}", raw);
+                    if engine.config().stop_on_invalid_symbol && is_invalid_symbol_error(&raw) {
+                        channels.invalid_symbol = Some(raw);
                     }
+                    return Ok(false);
                 }
-                Err(e) => {
-                    error!("[Rust] Failed to parse WebSocket message: {}. Raw: {}", e, &text[..std::cmp::min(text.len(), 200)]);
+                ParsedMessage::SubscribeAck { channel, inst_id } => {
+                    info!("[Rust] Subscription to {} confirmed for {}", channel, inst_id);
+                    channels.record_subscribed(&channel);
+                    return Ok(false);
+                }
+                ParsedMessage::UnsubscribeAck { channel, inst_id } => {
+                    info!("[Rust] Unsubscription from {} confirmed for {}", channel, inst_id);
+                    return Ok(false);
+                }
+                ParsedMessage::Ignored => return Ok(false),
+                ParsedMessage::Malformed(e) => {
                     // Don't break the connection on parsing errors, just log and continue
+                    error_throttle.error(format!("[Rust] Failed to parse WebSocket message: {}. Raw: {}", e, text.chars().take(200).collect::<String>()));
+                    return Ok(false);
+                }
+                ParsedMessage::OrderBook { book, snapshot } => {
+                    channels.record_data("books");
+                    // A snapshot (re)starts the book; updates after it are still reconciling
+                    if snapshot {
+                        channels.resynced_at = Some(Instant::now());
+                    }
+                    if !engine.update_order_book(book).await {
+                        return Ok(false);
+                    }
+                }
+                ParsedMessage::Trades(trades) => {
+                    channels.record_data("trade");
+                    for trade in trades {
+                        engine.add_trade(trade).await;
+                    }
+                }
+                ParsedMessage::ChannelData { channel, inst_id, data } => {
+                    // Extra channels feed their handlers only; they don't trigger an analysis
+                    engine.dispatch_channel_data(&channel, &inst_id, data).await;
+                    return Ok(false);
                 }
             }
+            return Ok(true);
         }
         Message::Ping(_ping_data) => {
             info!("[Rust] Received Ping from server, sending Pong back.");
@@ -289,52 +931,762 @@ async fn handle_message(
             info!("[Rust] Received a WebSocket frame (unhandled).");
         }
     }
-    Ok(())
+    Ok(false)
 }
 
-async fn parse_and_update_orderbook(data: serde_json::Value, symbol: &str, engine: &OFIEngine) {
-    let book_data: Result<Vec<BitgetOrderBookData>, _> = serde_json::from_value(data);
-    if let Ok(data) = book_data {
-        if let Some(first_book) = data.first() {
-            let bids_result: Result<Vec<OrderBookLevel>, _> = first_book.bids.iter().map(|b| {
-                b[0].parse().and_then(|price| b[1].parse().map(|quantity| OrderBookLevel { price, quantity }))
-            }).collect();
-            let asks_result: Result<Vec<OrderBookLevel>, _> = first_book.asks.iter().map(|a| {
-                a[0].parse().and_then(|price| a[1].parse().map(|quantity| OrderBookLevel { price, quantity }))
-            }).collect();
+/// Analyze the symbol on the engine's current state and forward any actionable signal
+async fn analyze_and_forward(
+    symbol: &str,
+    engine: &OFIEngine,
+    signal_tx: &mpsc::Sender<TradingSignal>,
+    recent_signals: Arc<Mutex<HashMap<String, u64>>>,  // Last forward time (ms, configured clock) per signal key
+    channels: &ChannelHealth,
+) -> Result<()> {
+    // Catch any errors during analysis to prevent breaking the connection
+    let analysis = async {
+        if engine.config().coalesce_analyses {
+            engine.analyze_symbol_coalesced(symbol).await
+        } else {
+            Some(engine.analyze_symbol(symbol).await)
+        }
+    };
+    let analysis_result = tokio::time::timeout(Duration::from_secs(10), analysis).await;
+    match analysis_result {
+        // Coalesced into the analysis already in flight for this symbol
+        Ok(None) => {}
+        Ok(Some(mut signal)) => {
+            if !matches!(signal.signal_type, SignalType::NoSignal) {
+                // Restamp with receipt time so the signal and its duplicate window share one clock
+                if engine.config().timestamp_source == TimestampSource::Local {
+                    signal.timestamp = chrono::Utc::now().timestamp_millis().max(0) as u64;
+                }
 
-            let timestamp = match first_book.ts.parse::<u64>() {
-                Ok(ts) => ts,
-                Err(e) => {
-                    error!("[Rust] Failed to parse order book timestamp '{}': {}. Skipping update.", first_book.ts, e);
-                    return;
+                // Without both channels, delta is computed from no trades (or imbalance from no book)
+                if engine.config().require_all_channels && !channels.all_delivering() {
+                    warn!("[Rust] Signal {:?} for {} suppressed: waiting for data on {}.", signal.signal_type, symbol, channels.silent_channels());
+                    return Ok(());
                 }
-            };
 
-            if let (Ok(bids), Ok(asks)) = (bids_result, asks_result) {
-                let snapshot = OrderBookSnapshot { symbol: symbol.to_string(), bids, asks, timestamp };
-                engine.update_order_book(snapshot).await;
-            } else {
-                error!("[Rust] Failed to parse order book prices/quantities for symbol {}", symbol);
+                // The book may be incomplete while updates reconcile after a snapshot
+                let resync_window = Duration::from_millis(engine.config().resync_suppress_ms);
+                if let Some(elapsed) = channels.resynced_at.map(|at| at.elapsed()).filter(|elapsed| *elapsed < resync_window) {
+                    engine.record_resync_suppression();
+                    info!("[Rust] Signal {:?} for {} suppressed: book resynced {}ms ago.", signal.signal_type, symbol, elapsed.as_millis());
+                    return Ok(());
+                }
+
+                // Suppress forwarding during exchange maintenance; data keeps flowing for warmup
+                if let Some(window) = engine.config().active_maintenance_window(chrono::Utc::now().timestamp_millis()) {
+                    warn!("[Rust] Signal {:?} for {} suppressed: inside exchange maintenance window ({}).", signal.signal_type, symbol, window);
+                    return Ok(());
+                }
+
+                // Drop signals below the (per-symbol) minimum execution confidence
+                let min_confidence = engine.config().min_execution_confidence_for(symbol);
+                if signal.confidence < min_confidence {
+                    info!("[Rust] Signal {:?} for {} dropped: confidence {:.2} below minimum {:.2}.", signal.signal_type, symbol, signal.confidence, min_confidence);
+                    return Ok(());
+                }
+
+                // Check for duplicate signals to prevent multiple orders for the same opportunity
+                // With two-stage absorption the confirmed signal has its own key, so it isn't
+                // deduplicated against an earlier same-direction signal from another rule
+                let signal_key = if engine.config().absorption_confirmation_window_ms > 0 {
+                    format!("{}_{}_{:?}", signal.symbol, signal.signal_type, signal.reason_code)
+                } else {
+                    format!("{}_{}", signal.symbol, signal.signal_type)
+                };
+                let should_send = {
+                    let mut recent_signals_guard = recent_signals.lock().unwrap();
+                    let now = signal.timestamp;
+                    
+                    // Remove signals older than 5 seconds
+                    recent_signals_guard.retain(|_, time| now.saturating_sub(*time) < 5000);
+                    
+                    // Check if this signal was sent recently
+                    if recent_signals_guard.contains_key(&signal_key) {
+                        false // Don't send duplicate
+                    } else {
+                        recent_signals_guard.insert(signal_key.clone(), now);
+                        true // Send new signal
+                    }
+                };
+                
+                if should_send {
+                    info!("[Rust] Signal found for {}: {:?}. Sending to handler.", symbol, signal.signal_type);
+                    if let Some(explanation) = &signal.explanation {
+                        info!("[Rust] Signal explanation for {}: {}", symbol, serde_json::to_string(explanation).unwrap_or_default());
+                    }
+                    // Use a timeout when sending to prevent hanging if the channel is blocked
+                    let send_result = tokio::time::timeout(Duration::from_secs(5), signal_tx.send(signal)).await;
+                    match send_result {
+                        Ok(Ok(())) => {
+                            // Successfully sent
+                        }
+                        Ok(Err(_)) => {
+                            error!("[Rust] Failed to send signal: receiver has been dropped.");
+                            return Err(anyhow!("Signal channel closed"));
+                        }
+                        Err(_) => {
+                            error!("[Rust] Timeout sending signal to channel.");
+                            // Don't break the connection on send timeout, just log and continue
+                        }
+                    }
+                } else {
+                    info!("[Rust] Duplicate signal detected for {}, skipping.", signal_key);
+                }
+            } else if let Some(sink) = engine.observability_sink() {
+                // Low priority: drop the event rather than wait if the sink is backed up
+                let _ = sink.try_send(signal);
             }
         }
-    } else {
-        error!("[Rust] Failed to deserialize order book data for symbol {}", symbol);
+        Err(_) => {
+            error!("[Rust] Timeout during signal analysis for {}", symbol);
+            // Continue processing other messages despite analysis timeout
+        }
     }
+    Ok(())
 }
 
-async fn parse_and_update_trades(data: serde_json::Value, symbol: &str, engine: &OFIEngine) {
-    let trade_data: Result<Vec<BitgetTradeData>, _> = serde_json::from_value(data);
-    if let Ok(data) = trade_data {
-        for trade in data {
-            if let (Ok(price), Ok(quantity), Ok(timestamp)) = (trade.price.parse(), trade.size.parse(), trade.ts.parse()) {
-                let trade_obj = TradeData { symbol: symbol.to_string(), price, quantity, side: trade.side.clone(), timestamp };
-                engine.add_trade(trade_obj).await;
-            } else {
-                error!("[Rust] Failed to parse trade data for symbol {}: price={}, size={}, ts={}", symbol, trade.price, trade.size, trade.ts);
+/// Parse the first order book in a `books` data array
+fn parse_orderbook(data: serde_json::Value, symbol: &str, timestamp_unit: TimestampUnit) -> Result<OrderBookSnapshot, String> {
+    let book_data: Vec<BitgetOrderBookData> = serde_json::from_value(data)
+        .map_err(|e| format!("failed to deserialize order book data for symbol {}: {}", symbol, e))?;
+    let first_book = book_data
+        .first()
+        .ok_or_else(|| format!("empty order book data for symbol {}", symbol))?;
+
+    let bids_result: Result<Vec<OrderBookLevel>, _> = first_book.bids.iter().map(|b| {
+        b[0].parse().and_then(|price| b[1].parse().map(|quantity| OrderBookLevel { price, quantity }))
+    }).collect();
+    let asks_result: Result<Vec<OrderBookLevel>, _> = first_book.asks.iter().map(|a| {
+        a[0].parse().and_then(|price| a[1].parse().map(|quantity| OrderBookLevel { price, quantity }))
+    }).collect();
+
+    let timestamp = match first_book.ts.parse::<u64>() {
+        Ok(ts) => timestamp_unit.to_millis(ts),
+        Err(e) => return Err(format!("failed to parse order book timestamp '{}': {}", first_book.ts, e)),
+    };
+
+    match (bids_result, asks_result) {
+        (Ok(bids), Ok(asks)) => Ok(OrderBookSnapshot { symbol: symbol.to_string(), bids, asks, timestamp }),
+        _ => Err(format!("failed to parse order book prices/quantities for symbol {}", symbol)),
+    }
+}
+
+/// Parse a `trade` data array; individual unparseable trades are logged and skipped
+fn parse_trades(data: serde_json::Value, symbol: &str, timestamp_unit: TimestampUnit) -> Result<Vec<TradeData>, String> {
+    let trade_data: Vec<BitgetTradeData> = serde_json::from_value(data)
+        .map_err(|e| format!("failed to deserialize trade data for symbol {}: {}", symbol, e))?;
+
+    let mut trades = Vec::with_capacity(trade_data.len());
+    for trade in trade_data {
+        if let (Ok(price), Ok(quantity), Ok(raw_ts)) = (trade.price.parse(), trade.size.parse(), trade.ts.parse::<u64>()) {
+            let timestamp = timestamp_unit.to_millis(raw_ts);
+            trades.push(TradeData { symbol: symbol.to_string(), price, quantity, side: trade.side.clone(), timestamp });
+        } else {
+            error!("[Rust] Failed to parse trade data for symbol {}: price={}, size={}, ts={}", symbol, trade.price, trade.size, trade.ts);
+        }
+    }
+    Ok(trades)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Load a captured Bitget payload from `tests/fixtures/bitget/<name>.json`
+    fn load_fixture(name: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/bitget").join(format!("{}.json", name));
+        fs::read_to_string(&path).unwrap_or_else(|e| panic!("failed to read fixture {}: {}", path.display(), e))
+    }
+
+    fn parse_fixture(name: &str) -> ParsedMessage {
+        parse_message(Exchange::Bitget, &load_fixture(name), TimestampUnit::Auto)
+    }
+
+    fn test_engine() -> OFIEngine {
+        crate::engine::analysis_engine(3.0, 1000.0, 5000, crate::config::OFIConfig::for_tests())
+    }
+
+    /// An engine holding a stacked bid book and a large buy print, which analyzes to a StrongBuy
+    async fn strong_buy_engine(config: crate::config::OFIConfig) -> OFIEngine {
+        let engine = crate::engine::analysis_engine(3.0, 1000.0, 5000, config);
+        let level = |price: f64, quantity: f64| OrderBookLevel { price, quantity };
+        engine
+            .update_order_book(OrderBookSnapshot {
+                symbol: "BTCUSDT".to_string(),
+                bids: (0..5).map(|i| level(99.9 - i as f64 * 0.1, 50.0)).collect(),
+                asks: (0..5).map(|i| level(100.0 + i as f64 * 0.1, 1.0)).collect(),
+                timestamp: 10_000,
+            })
+            .await;
+        engine
+            .add_trade(TradeData { symbol: "BTCUSDT".to_string(), price: 100.0, quantity: 500.0, side: "buy".to_string(), timestamp: 9_500 })
+            .await;
+        engine
+    }
+
+    /// Channel state of a connection on which both books and trade deliver
+    fn delivering_channels() -> ChannelHealth {
+        ChannelHealth { books_subscribed: true, trade_subscribed: true, books_delivering: true, trade_delivering: true, ..ChannelHealth::default() }
+    }
+
+    /// Run `analyze_and_forward` once and return the signal it forwarded, if any
+    async fn forward(engine: &OFIEngine, channels: &ChannelHealth, recent_signals: Arc<Mutex<HashMap<String, u64>>>) -> Option<TradingSignal> {
+        let (tx, mut rx) = mpsc::channel(8);
+        analyze_and_forward("BTCUSDT", engine, &tx, recent_signals, channels).await.expect("analysis succeeds");
+        rx.try_recv().ok()
+    }
+
+    /// Feed a fixture through `handle_message` as the `BTCUSDT` task would receive it
+    async fn feed_fixture(engine: &OFIEngine, channels: &mut ChannelHealth, name: &str, expected_symbol: Option<&str>) -> bool {
+        let mut error_throttle = ErrorLogThrottle::new(Duration::from_secs(60));
+        let msg = Message::Text(load_fixture(name).into());
+        handle_message(msg, "BTCUSDT", Exchange::Bitget, engine, channels, expected_symbol, &mut error_throttle)
+            .await
+            .expect("fixture messages don't end the connection")
+    }
+
+    #[test]
+    fn books_snapshot_is_an_order_book() {
+        let ParsedMessage::OrderBook { book, snapshot: true } = parse_fixture("books_snapshot") else {
+            panic!("books_snapshot should parse as an order book snapshot");
+        };
+        assert_eq!(book.symbol, "BTCUSDT");
+        assert_eq!((book.bids.len(), book.asks.len()), (5, 5));
+        assert_eq!(book.timestamp, 1727083571321);
+        assert_eq!((book.bids[0].price, book.bids[0].quantity), (67012.0, 0.842));
+        assert_eq!((book.asks[0].price, book.asks[0].quantity), (67012.5, 1.204));
+    }
+
+    #[test]
+    fn books_update_is_an_order_book() {
+        let ParsedMessage::OrderBook { book, snapshot: false } = parse_fixture("books_update") else {
+            panic!("books_update should parse as an order book update");
+        };
+        assert_eq!((book.bids.len(), book.asks.len()), (1, 2));
+        assert_eq!(book.timestamp, 1727083571412);
+        assert_eq!(book.asks[1].quantity, 0.0);
+    }
+
+    #[test]
+    fn trade_is_trades() {
+        let ParsedMessage::Trades(trades) = parse_fixture("trade") else {
+            panic!("trade should parse as trades");
+        };
+        assert_eq!(trades.len(), 2);
+        assert_eq!((trades[0].side.as_str(), trades[0].quantity, trades[0].price), ("buy", 0.015, 67012.5));
+        assert_eq!((trades[1].side.as_str(), trades[1].quantity, trades[1].price), ("sell", 0.120, 67012.0));
+        assert_eq!(trades[0].timestamp, 1727083571530);
+        assert!(trades.iter().all(|trade| trade.symbol == "BTCUSDT"));
+    }
+
+    #[test]
+    fn subscribe_ack_is_classified() {
+        match parse_fixture("subscribe_ack") {
+            ParsedMessage::SubscribeAck { channel, inst_id } => assert_eq!((channel.as_str(), inst_id.as_str()), ("books", "BTCUSDT")),
+            other => panic!("subscribe_ack parsed as {:?}", other),
+        }
+    }
+
+    #[test]
+    fn error_is_an_invalid_symbol_error() {
+        let ParsedMessage::ExchangeError(raw) = parse_fixture("error") else {
+            panic!("error should parse as an exchange error");
+        };
+        assert!(is_invalid_symbol_error(&raw));
+    }
+
+    #[test]
+    fn pong_is_classified() {
+        assert!(matches!(parse_fixture("pong"), ParsedMessage::Pong));
+    }
+
+    #[test]
+    fn malformed_payloads_are_reported_not_panicked_on() {
+        assert!(matches!(parse_fixture("malformed_books"), ParsedMessage::Malformed(_)));
+        assert!(matches!(parse_fixture("malformed_truncated"), ParsedMessage::Malformed(_)));
+    }
+
+    #[test]
+    fn control_messages_have_no_inst_id() {
+        assert_eq!(parse_fixture("subscribe_ack").inst_id(), None);
+        assert_eq!(parse_fixture("pong").inst_id(), None);
+        assert_eq!(parse_fixture("books_snapshot").inst_id(), Some("BTCUSDT"));
+    }
+
+    #[derive(Default)]
+    struct RecordingHandler {
+        received: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    impl ChannelHandler for RecordingHandler {
+        fn handle(&self, inst_id: &str, data: &serde_json::Value) {
+            self.received.lock().unwrap().push((inst_id.to_string(), data.clone()));
+        }
+    }
+
+    #[test]
+    fn ticker_is_channel_data() {
+        match parse_fixture("ticker") {
+            ParsedMessage::ChannelData { channel, inst_id, data } => {
+                assert_eq!((channel.as_str(), inst_id.as_str()), ("ticker", "BTCUSDT"));
+                assert_eq!(data[0]["fundingRate"], "0.0001");
             }
+            other => panic!("ticker parsed as {:?}", other),
         }
-    } else {
-        error!("[Rust] Failed to deserialize trade data for symbol {}", symbol);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn extra_channel_reaches_its_handler_alongside_books_and_trades() {
+        let handler = Arc::new(RecordingHandler::default());
+        let engine = test_engine().with_channel_handler("ticker", handler.clone());
+        let mut channels = ChannelHealth::default();
+
+        assert!(feed_fixture(&engine, &mut channels, "books_snapshot", None).await);
+        assert!(!feed_fixture(&engine, &mut channels, "ticker", None).await, "extra channel data doesn't trigger an analysis");
+        assert!(feed_fixture(&engine, &mut channels, "trade", None).await);
+
+        {
+            let received = handler.received.lock().unwrap();
+            assert_eq!(received.len(), 1);
+            assert_eq!(received[0].0, "BTCUSDT");
+            assert_eq!(received[0].1[0]["lastPr"], "67012.5");
+        }
+        assert_eq!(engine.latest_channel_data("ticker", "BTCUSDT").await, None, "handled data isn't also stored");
+
+        let status = engine.symbol_status("BTCUSDT").await;
+        assert_eq!((status.book_levels, status.trade_count), (10, 2));
+        assert!(channels.all_delivering());
+    }
+
+    #[tokio::test]
+    async fn extra_channel_without_handler_keeps_its_latest_payload() {
+        let engine = test_engine();
+        let mut channels = ChannelHealth::default();
+
+        assert!(!feed_fixture(&engine, &mut channels, "ticker", None).await);
+        let stored = engine.latest_channel_data("ticker", "BTCUSDT").await.expect("ticker payload stored");
+        assert_eq!(stored[0]["markPrice"], "67013.1");
+        assert!(engine.active_symbols().await.is_empty(), "extra channels don't create book or trade data");
+    }
+
+    #[test]
+    fn misrouted_trade_does_not_match_the_subscribed_symbol() {
+        let parsed = parse_fixture("trade_misrouted");
+        assert_eq!(parsed.inst_id(), Some("ETHUSDT"));
+        assert!(!matches_symbol(&parsed, "BTCUSDT"));
+        assert!(matches_symbol(&parsed, "ethusdt"), "instId comparison ignores case");
+        assert!(matches_symbol(&parse_fixture("pong"), "BTCUSDT"), "control messages always match");
+    }
+
+    #[tokio::test]
+    async fn mismatched_inst_id_is_dropped_in_single_symbol_mode() {
+        let engine = test_engine();
+        let mut channels = ChannelHealth::default();
+
+        assert!(!feed_fixture(&engine, &mut channels, "trade_misrouted", Some("BTCUSDT")).await);
+        assert!(engine.active_symbols().await.is_empty());
+        assert!(!channels.trade_delivering, "a dropped message doesn't count as delivery");
+
+        assert!(feed_fixture(&engine, &mut channels, "trade", Some("BTCUSDT")).await);
+        assert_eq!(engine.active_symbols().await, vec!["BTCUSDT".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn multiplexed_messages_are_stored_under_their_own_inst_id() {
+        let engine = test_engine();
+        let mut channels = ChannelHealth::default();
+
+        assert!(feed_fixture(&engine, &mut channels, "trade_misrouted", None).await);
+        assert_eq!(engine.symbol_status("ETHUSDT").await.trade_count, 1);
+        assert_eq!(engine.symbol_status("BTCUSDT").await.trade_count, 0);
+    }
+
+    #[test]
+    fn out_of_order_trades_keep_their_arrival_order() {
+        let ParsedMessage::Trades(trades) = parse_fixture("trade_out_of_order") else {
+            panic!("trade_out_of_order should parse as trades");
+        };
+        let timestamps: Vec<u64> = trades.iter().map(|trade| trade.timestamp).collect();
+        assert_eq!(timestamps, vec![1727083571655, 1727083571631, 1727083571643]);
+    }
+
+    #[test]
+    fn rate_limit_error_is_not_an_invalid_symbol_error() {
+        let ParsedMessage::ExchangeError(raw) = parse_fixture("error_rate_limit") else {
+            panic!("error_rate_limit should parse as an exchange error");
+        };
+        assert!(!is_invalid_symbol_error(&raw));
+    }
+
+    #[tokio::test]
+    async fn invalid_symbol_error_flags_the_connection_to_stop() {
+        let engine = test_engine();
+        let mut channels = ChannelHealth::default();
+
+        assert!(!feed_fixture(&engine, &mut channels, "error_rate_limit", None).await);
+        assert_eq!(channels.invalid_symbol, None, "transient errors keep reconnecting");
+
+        assert!(!feed_fixture(&engine, &mut channels, "error", None).await);
+        let reason = channels.invalid_symbol.take().expect("invalid symbol flagged");
+        engine.mark_invalid_symbol("BTCUSDT", reason.clone());
+        assert_eq!(engine.invalid_symbol_error("BTCUSDT"), Some(reason));
+        assert_eq!(engine.invalid_symbol_error("ETHUSDT"), None);
+    }
+
+    #[tokio::test]
+    async fn invalid_symbol_error_is_only_logged_when_stopping_is_disabled() {
+        let config = crate::config::OFIConfig { stop_on_invalid_symbol: false, ..crate::config::OFIConfig::for_tests() };
+        let engine = crate::engine::analysis_engine(3.0, 1000.0, 5000, config);
+        let mut channels = ChannelHealth::default();
+
+        assert!(!feed_fixture(&engine, &mut channels, "error", None).await);
+        assert_eq!(channels.invalid_symbol, None);
+    }
+
+    #[test]
+    fn each_disconnect_kind_uses_its_reconnect_delay() {
+        let config = crate::config::OFIConfig { reconnect_clean_ms: 250, reconnect_error_ms: 5_000, ..crate::config::OFIConfig::for_tests() };
+        let clean = Some(Duration::from_millis(250));
+        assert_eq!(reconnect_delay(&Ok(DisconnectKind::ClosedByServer), &config), clean);
+        assert_eq!(reconnect_delay(&Ok(DisconnectKind::StreamEnded), &config), clean);
+        assert_eq!(reconnect_delay(&Err(anyhow!("read timeout")), &config), Some(Duration::from_millis(5_000)));
+        assert_eq!(reconnect_delay(&Ok(DisconnectKind::ReceiverDropped), &config), None);
+        assert_eq!(reconnect_delay(&Ok(DisconnectKind::InvalidSymbol), &config), None);
+    }
+
+    #[tokio::test]
+    async fn signals_wait_for_every_required_channel() {
+        let config = crate::config::OFIConfig { require_all_channels: true, ..crate::config::OFIConfig::for_tests() };
+        let engine = strong_buy_engine(config).await;
+
+        let books_only = ChannelHealth { books_subscribed: true, trade_subscribed: true, books_delivering: true, ..ChannelHealth::default() };
+        assert!(forward(&engine, &books_only, Arc::default()).await.is_none());
+        assert_eq!(books_only.silent_channels(), "trade (subscribed: true)");
+
+        let signal = forward(&engine, &delivering_channels(), Arc::default()).await.expect("signal forwarded once both deliver");
+        assert_eq!(signal.signal_type, SignalType::StrongBuy);
+    }
+
+    #[tokio::test]
+    async fn partial_channels_are_allowed_without_the_flag() {
+        let engine = strong_buy_engine(crate::config::OFIConfig::for_tests()).await;
+        let books_only = ChannelHealth { books_delivering: true, ..ChannelHealth::default() };
+        assert!(forward(&engine, &books_only, Arc::default()).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn no_signal_reaches_the_observability_sink_but_never_execution() {
+        let (sink_tx, mut sink_rx) = mpsc::channel(8);
+        let engine = test_engine().with_observability_sink(sink_tx);
+        let level = |price: f64, quantity: f64| OrderBookLevel { price, quantity };
+        engine
+            .update_order_book(OrderBookSnapshot {
+                symbol: "BTCUSDT".to_string(),
+                bids: vec![level(99.9, 1.0)],
+                asks: vec![level(100.0, 1.0)],
+                timestamp: 10_000,
+            })
+            .await;
+
+        assert!(forward(&engine, &delivering_channels(), Arc::default()).await.is_none());
+        let event = sink_rx.try_recv().expect("NoSignal result sent to the sink");
+        assert_eq!(event.signal_type, SignalType::NoSignal);
+        assert!(event.reason.contains("buy imbalance"), "reason carries the metrics: {}", event.reason);
+
+        let (sink_tx, mut sink_rx) = mpsc::channel(8);
+        let engine = strong_buy_engine(crate::config::OFIConfig::for_tests()).await.with_observability_sink(sink_tx);
+        assert!(forward(&engine, &delivering_channels(), Arc::default()).await.is_some());
+        assert!(sink_rx.try_recv().is_err(), "actionable signals only go to execution");
+    }
+
+    #[tokio::test]
+    async fn manager_task_stops_once_its_receiver_is_dropped() {
+        // Nothing listens on the discard port, so every connection attempt fails fast
+        let config = crate::config::OFIConfig {
+            websocket_url: "ws://127.0.0.1:9".to_string(),
+            reconnect_error_ms: 10,
+            reconnect_spacing_ms: 0,
+            reconnect_jitter_ms: 0,
+            ..crate::config::OFIConfig::for_tests()
+        };
+        let engine = crate::engine::analysis_engine(3.0, 1000.0, 5000, config);
+        let (signal_rx, manager) = spawn_websocket_manager("BTCUSDT".to_string(), engine);
+
+        // As when an analysis future resolves and drops its receiver
+        drop(signal_rx);
+        tokio::time::timeout(Duration::from_secs(5), manager)
+            .await
+            .expect("manager stops reconnecting after the receiver is dropped")
+            .expect("manager task doesn't panic");
+    }
+
+    #[test]
+    fn a_burst_within_one_batch_window_runs_one_analysis() {
+        let start = tokio::time::Instant::now();
+        let window = Duration::from_millis(50);
+        let mut batch = AnalysisBatch::new(window, 0);
+        assert!(batch.enabled());
+        assert_eq!(batch.deadline(), None);
+
+        // A rapid burst only opens the window; the analysis waits for its deadline
+        for i in 0..20 {
+            assert!(!batch.record(start + Duration::from_millis(i)));
+        }
+        assert_eq!(batch.deadline(), Some(start + window), "the window opens with the first message");
+        batch.reset();
+        assert_eq!(batch.deadline(), None);
+
+        // With a count limit the batch is due once enough updates have accumulated
+        let mut bounded = AnalysisBatch::new(window, 5);
+        let due: Vec<bool> = (0..5).map(|i| bounded.record(start + Duration::from_millis(i))).collect();
+        assert_eq!(due, vec![false, false, false, false, true]);
+
+        assert!(!AnalysisBatch::new(Duration::ZERO, 5).enabled());
+    }
+
+    #[test]
+    fn unsubscribe_is_sent_before_subscribe_when_enabled() {
+        let channels = vec!["books".to_string(), "trade".to_string()];
+        let ops = |messages: Vec<serde_json::Value>| messages.iter().map(|message| message["op"].as_str().unwrap().to_string()).collect::<Vec<_>>();
+
+        assert_eq!(ops(subscription_messages(Exchange::Bitget, "BTCUSDT", &channels, false)), vec!["subscribe"]);
+        let messages = subscription_messages(Exchange::Bitget, "BTCUSDT", &channels, true);
+        assert_eq!(ops(messages.clone()), vec!["unsubscribe", "subscribe"]);
+        // Both cover the same channels of the symbol
+        assert_eq!(messages[0]["args"], messages[1]["args"]);
+        assert_eq!(messages[0]["args"][1]["channel"], "trade");
+        assert_eq!(messages[0]["args"][0]["instId"], "BTCUSDT");
+
+        let ack = r#"{"event":"unsubscribe","arg":{"instType":"USDT-FUTURES","channel":"books","instId":"BTCUSDT"}}"#;
+        let ParsedMessage::UnsubscribeAck { channel, inst_id } = parse_message(Exchange::Bitget, ack, TimestampUnit::Auto) else {
+            panic!("unsubscribe ack should parse as such");
+        };
+        assert_eq!((channel.as_str(), inst_id.as_str()), ("books", "BTCUSDT"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cadence_analyses_stay_on_the_grid_regardless_of_load() {
+        let start = tokio::time::Instant::now();
+        let at = |instant: tokio::time::Instant| instant.duration_since(start).as_millis();
+        let mut cadence = cadence_interval(250);
+
+        let ticks = [at(cadence.tick().await), at(cadence.tick().await), at(cadence.tick().await)];
+        assert_eq!(ticks, [0, 250, 500]);
+
+        // A slow analysis overruns two ticks: one runs late, the missed one is dropped, and the
+        // cadence resumes on the original grid rather than bursting to catch up
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert_eq!(at(cadence.tick().await), 750);
+        assert_eq!(at(tokio::time::Instant::now()), 1100, "the late tick runs immediately");
+        assert_eq!(at(cadence.tick().await), 1250);
+    }
+
+    #[test]
+    fn repeated_errors_within_the_window_coalesce_into_one_counted_line() {
+        let mut throttle = ErrorLogThrottle::new(Duration::from_secs(60));
+        for offset in [12, 340, 7] {
+            throttle.error(format!("[Rust] Failed to parse WebSocket message: EOF at offset {}", offset));
+        }
+        throttle.error("[Rust] Received error from Bitget: rate limited".to_string());
+        assert_eq!(throttle.entries.len(), 2, "messages differing only in numbers share an entry");
+
+        let key = ErrorLogThrottle::normalize("[Rust] Failed to parse WebSocket message: EOF at offset 1");
+        let entry = &throttle.entries[&key];
+        assert_eq!(
+            ErrorLogThrottle::repeat_summary(entry, Duration::from_secs(60)).as_deref(),
+            Some("[Rust] Failed to parse WebSocket message: EOF at offset 7 (repeated 2 more times in the last 60000ms)")
+        );
+        let once = &throttle.entries[&ErrorLogThrottle::normalize("[Rust] Received error from Bitget: rate limited")];
+        assert_eq!(ErrorLogThrottle::repeat_summary(once, Duration::from_secs(60)), None, "a single occurrence has nothing to add");
+
+        // Closing the window flushes the counts, and the next occurrence is logged afresh
+        throttle.flush_expired(Instant::now() + Duration::from_secs(61));
+        assert!(throttle.entries.is_empty());
+
+        let mut unthrottled = ErrorLogThrottle::new(Duration::ZERO);
+        unthrottled.error("[Rust] Error reading from WebSocket: reset".to_string());
+        assert!(unthrottled.entries.is_empty(), "a zero window logs every occurrence");
+    }
+
+    #[test]
+    fn skip_factor_rises_with_a_high_message_rate_and_backs_off() {
+        let mut downsampler = AdaptiveDownsampler::new(100.0, 8);
+        let start = downsampler.window_start;
+        let mut now = start;
+        // Feed `count` messages spread evenly over one second; returns the analyses admitted
+        let mut second = |downsampler: &mut AdaptiveDownsampler, count: u32| {
+            let step = Duration::from_secs(1) / count;
+            (0..count).filter(|_| {
+                now += step;
+                downsampler.admit(now)
+            })
+            .count()
+        };
+
+        assert_eq!(second(&mut downsampler, 50), 50, "below the limit every message is analyzed");
+        assert_eq!(downsampler.skip_factor(), 1);
+
+        // 1000 messages/s against a 100/s limit: the factor doubles per window up to the cap
+        second(&mut downsampler, 1_000);
+        assert_eq!(downsampler.skip_factor(), 2);
+        let admitted = second(&mut downsampler, 1_000);
+        assert_eq!(downsampler.skip_factor(), 4);
+        assert!(admitted < 1_000 / 2 + 2, "{} analyses", admitted);
+        second(&mut downsampler, 1_000);
+        second(&mut downsampler, 1_000);
+        assert_eq!(downsampler.skip_factor(), 8, "capped at the maximum factor");
+
+        // Once the rate subsides the factor halves back
+        second(&mut downsampler, 20);
+        assert_eq!(downsampler.skip_factor(), 4);
+
+        let mut disabled = AdaptiveDownsampler::new(0.0, 8);
+        assert!((0..1_000).all(|_| disabled.admit(start)));
+
+        // The effective factor is exposed on the engine for its metrics
+        let engine = test_engine();
+        engine.set_analysis_skip_factor(downsampler.skip_factor());
+        assert_eq!(engine.analysis_skip_factor(), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn heartbeats_arrive_at_the_configured_cadence_with_current_metrics() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let engine = strong_buy_engine(crate::config::OFIConfig::for_tests()).await.with_symbol_heartbeat_sink(tx);
+        let sink = engine.symbol_heartbeat_sink().unwrap().clone();
+        let start = tokio::time::Instant::now();
+        let mut interval = heartbeat_interval(5);
+
+        let mut ticks = Vec::new();
+        for _ in 0..3 {
+            ticks.push(interval.tick().await.duration_since(start).as_secs());
+            send_symbol_heartbeat(&engine, "BTCUSDT", &delivering_channels(), &sink).await;
+        }
+        assert_eq!(ticks, vec![0, 5, 10]);
+
+        for _ in 0..3 {
+            let heartbeat = rx.try_recv().expect("one heartbeat per tick");
+            assert_eq!(heartbeat.symbol, "BTCUSDT");
+            assert!(heartbeat.channels_delivering);
+            assert_eq!(heartbeat.metrics.expect("the symbol has a book").delta, 50_000.0);
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn exchange_time_stamps_the_signal_and_its_duplicate_window() {
+        let config = crate::config::OFIConfig { timestamp_source: TimestampSource::Exchange, ..crate::config::OFIConfig::for_tests() };
+        let engine = strong_buy_engine(config).await;
+        let recent_signals: Arc<Mutex<HashMap<String, u64>>> = Arc::default();
+
+        let signal = forward(&engine, &delivering_channels(), Arc::clone(&recent_signals)).await.expect("first signal forwarded");
+        assert_eq!(signal.timestamp, 10_000);
+        let stamped: Vec<u64> = recent_signals.lock().unwrap().values().copied().collect();
+        assert_eq!(stamped, vec![10_000]);
+        assert!(forward(&engine, &delivering_channels(), Arc::clone(&recent_signals)).await.is_none());
+
+        // Five seconds of exchange time since the last forward, not of wall time, expire the entry
+        recent_signals.lock().unwrap().values_mut().for_each(|time| *time = 4_000);
+        assert!(forward(&engine, &delivering_channels(), recent_signals).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn local_time_stamps_the_signal_and_its_duplicate_window() {
+        let config = crate::config::OFIConfig { timestamp_source: TimestampSource::Local, ..crate::config::OFIConfig::for_tests() };
+        let engine = strong_buy_engine(config).await;
+        let recent_signals: Arc<Mutex<HashMap<String, u64>>> = Arc::default();
+
+        let before = chrono::Utc::now().timestamp_millis() as u64;
+        let signal = forward(&engine, &delivering_channels(), Arc::clone(&recent_signals)).await.expect("first signal forwarded");
+        assert!(signal.timestamp >= before);
+        let stamped: Vec<u64> = recent_signals.lock().unwrap().values().copied().collect();
+        assert_eq!(stamped, vec![signal.timestamp]);
+        assert!(forward(&engine, &delivering_channels(), Arc::clone(&recent_signals)).await.is_none());
+
+        // An entry stamped in exchange time is long expired on the local clock
+        recent_signals.lock().unwrap().values_mut().for_each(|time| *time = 10_000);
+        assert!(forward(&engine, &delivering_channels(), recent_signals).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn a_books_snapshot_starts_the_resync_window_and_updates_do_not() {
+        let engine = test_engine();
+        let mut channels = ChannelHealth::default();
+        feed_fixture(&engine, &mut channels, "books_snapshot", None).await;
+        let resynced_at = channels.resynced_at.expect("snapshot marks a resync");
+
+        feed_fixture(&engine, &mut channels, "books_update", None).await;
+        assert_eq!(channels.resynced_at, Some(resynced_at));
+    }
+
+    #[tokio::test]
+    async fn signals_are_withheld_for_the_window_after_a_resync() {
+        let config = crate::config::OFIConfig { resync_suppress_ms: 2_000, ..crate::config::OFIConfig::for_tests() };
+        let engine = strong_buy_engine(config).await;
+
+        let just_resynced = ChannelHealth { resynced_at: Some(Instant::now()), ..delivering_channels() };
+        assert!(forward(&engine, &just_resynced, Arc::default()).await.is_none());
+        assert!(forward(&engine, &just_resynced, Arc::default()).await.is_none());
+        assert_eq!(engine.resync_suppressions(), 2);
+
+        // Data was still ingested; once the window has passed the signal goes out
+        let settled = ChannelHealth { resynced_at: Instant::now().checked_sub(Duration::from_millis(2_000)), ..delivering_channels() };
+        let signal = forward(&engine, &settled, Arc::default()).await.expect("forwarded after the window");
+        assert_eq!(signal.signal_type, SignalType::StrongBuy);
+        assert_eq!(engine.resync_suppressions(), 2);
+    }
+
+    #[test]
+    fn simultaneous_drops_reconnect_spread_over_time() {
+        let scheduler = ReconnectScheduler::new();
+        let now = Instant::now();
+        let delay = Duration::from_secs(5);
+        let spacing = Duration::from_millis(100);
+
+        // Fifty managers dropped at once all ask for a slot together
+        let mut slots: Vec<Instant> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..50).map(|_| scope.spawn(|| scheduler.schedule(now, delay, spacing, Duration::ZERO))).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+        slots.sort();
+        assert_eq!(slots[0], now + delay);
+        assert!(slots.windows(2).all(|pair| pair[1] - pair[0] == spacing), "one attempt per spacing interval");
+        assert_eq!(*slots.last().unwrap(), now + delay + spacing * 49);
+
+        // Once the burst has passed, a lone reconnect isn't held back
+        let later = now + Duration::from_secs(60);
+        assert_eq!(scheduler.schedule(later, delay, spacing, Duration::ZERO), later + delay);
+
+        // Without spacing the scheduler is a plain timer, and jitter stays within its bound
+        let unspaced = ReconnectScheduler::new();
+        assert_eq!(unspaced.schedule(now, delay, Duration::ZERO, Duration::ZERO), now + delay);
+        let jittered = unspaced.schedule(now, delay, Duration::ZERO, Duration::from_millis(500));
+        assert!(jittered >= now + delay && jittered <= now + delay + Duration::from_millis(500));
+    }
+
+    #[test]
+    fn flight_recorder_keeps_the_last_messages_and_flushes_them_in_order() {
+        let recorder = FlightRecorder::new();
+        for i in 1..=5 {
+            recorder.record("BTCUSDT", &format!("btc-{}", i), 3);
+        }
+        recorder.record("ETHUSDT", "eth-1", 3);
+
+        let dir = std::env::temp_dir().join(format!("ofi-flight-{}", std::process::id()));
+        let path = recorder.flush(dir.to_str().unwrap()).unwrap();
+        let lines: Vec<serde_json::Value> = fs::read_to_string(&path).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let recorded: Vec<(&str, &str)> = lines.iter().map(|line| (line["symbol"].as_str().unwrap(), line["text"].as_str().unwrap())).collect();
+        // The two oldest BTCUSDT messages fell out of its ring of three
+        assert_eq!(recorded, vec![("BTCUSDT", "btc-3"), ("BTCUSDT", "btc-4"), ("BTCUSDT", "btc-5"), ("ETHUSDT", "eth-1")]);
+        assert!(lines[..3].windows(2).all(|pair| pair[0]["received_at_ms"].as_i64() <= pair[1]["received_at_ms"].as_i64()));
+
+        // Flushing keeps the recording; an exited task's ring is dropped
+        recorder.remove("BTCUSDT");
+        let path = recorder.flush(dir.to_str().unwrap()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
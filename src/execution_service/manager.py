@@ -809,6 +809,8 @@ class TradeManager:
             return {
                 "status": "success", 
                 "symbol": symbol, 
+                "action": "open",  # What the order did to the position: "open", "reduce" (size = remaining) or "close"
+                "side": side,
                 "size": position_size,
                 "entry_price": price,
                 "stop_loss_price": stop_loss_price,
@@ -835,20 +837,50 @@ class TradeManager:
 trade_manager = TradeManager()
 
 
+# Results of recent signals keyed by idempotency key, so retries from Rust never submit twice
+IDEMPOTENCY_TTL_SECONDS = 600
+_idempotency_results: Dict[str, tuple] = {}
+_idempotency_lock = threading.Lock()
+
+
 def handle_trade_signal(signal: Dict):
     """Wrapper fungsi sederhana untuk dipanggil dari Rust."""
-    import threading
     import queue
     
+    # Dedupe retried signals: return the recorded result, or report an attempt still in flight
+    idempotency_key = signal.get('idempotency_key')
+    if idempotency_key:
+        with _idempotency_lock:
+            now = time.time()
+            for key in [k for k, (ts, _) in _idempotency_results.items() if now - ts > IDEMPOTENCY_TTL_SECONDS]:
+                del _idempotency_results[key]
+            if idempotency_key in _idempotency_results:
+                _, previous = _idempotency_results[idempotency_key]
+                print(f"[Python Executor] Duplicate signal {idempotency_key}, not submitting again")
+                if previous is None:
+                    return {'status': 'error', 'reason': 'Previous attempt still in progress', 'transient': True}
+                return previous
+            _idempotency_results[idempotency_key] = (now, None)
+    
     # Create a queue to get the result from the thread
     result_queue = queue.Queue()
     
     def execute_in_thread():
         try:
             result = trade_manager.execute_trade(signal)
-            result_queue.put(('success', result))
+            status = 'success'
         except Exception as e:
-            result_queue.put(('error', {'status': 'error', 'reason': str(e)}))
+            result = {'status': 'error', 'reason': str(e), 'transient': True}
+            status = 'error'
+        # Recorded here so a result arriving after the Rust-side timeout still dedupes retries
+        if idempotency_key:
+            with _idempotency_lock:
+                if status == 'error':
+                    # Exceptions are retryable; release the key so the retry can run
+                    _idempotency_results.pop(idempotency_key, None)
+                else:
+                    _idempotency_results[idempotency_key] = (time.time(), result)
+        result_queue.put((status, result))
     
     # Start the execution in a separate thread to avoid blocking
     execution_thread = threading.Thread(target=execute_in_thread, daemon=True)
@@ -859,7 +891,8 @@ def handle_trade_signal(signal: Dict):
         status, result = result_queue.get(timeout=30)  # 30 second timeout
         return result
     except queue.Empty:
-        return {'status': 'error', 'reason': 'Trade execution timed out'}
+        # The order may still complete in the background; the key stays reserved until it does
+        return {'status': 'error', 'reason': 'Trade execution timed out', 'transient': True}
 
 
 def run_periodic_position_check():
@@ -873,11 +906,32 @@ def run_periodic_position_check():
         active_positions = trade_manager.get_active_positions()
         print(f"[Python Executor] Currently tracking {len(active_positions)} active positions locally")
         
-        # If you need more specific periodic monitoring tasks, add them here
-        # For now, just return success
-        return {"status": "success", "message": "Position check completed", "summary": summary}
+        # Report open positions so the Rust position store can reconcile its local view
+        positions = [
+            (symbol, data.get('side', ''), float(data.get('size', 0.0)), float(data.get('entry_price', 0.0)))
+            for symbol, data in active_positions.items()
+        ]
+        return {"status": "success", "message": "Position check completed", "summary": summary, "positions": positions}
     except Exception as e:
         print(f"[Python Executor] Error during periodic position check: {e}")
         import traceback
         traceback.print_exc()
-        return {"status": "error", "reason": str(e)}
\ No newline at end of file
+        return {"status": "error", "reason": str(e)}
+
+def flatten_all_positions():
+    """Close every tracked position - called from Rust by the dead-man's switch."""
+    closed, failed = [], []
+    for symbol in list(trade_manager.get_active_positions().keys()):
+        try:
+            result = trade_manager.close_position(symbol, close_all=True)
+            if result.get('status') == 'success':
+                closed.append(symbol)
+            else:
+                failed.append(symbol)
+        except Exception as e:
+            print(f"[Python Executor] Error flattening position {symbol}: {e}")
+            failed.append(symbol)
+    print(f"[Python Executor] Flatten all positions: closed={closed}, failed={failed}")
+    if failed:
+        return {"status": "error", "reason": f"Failed to close: {', '.join(failed)}", "closed": closed}
+    return {"status": "success", "closed": closed}
@@ -0,0 +1,200 @@
+//! Rust-side cache of open positions reported by the Python executor
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// An open position as last reported by the executor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub symbol: String,
+    pub side: String, // "buy" or "sell"
+    pub size: f64,
+    pub entry_price: f64,
+    pub updated_at: i64, // Milliseconds since epoch of the last change
+}
+
+/// What an executed order did to a symbol's position, as reported by the executor
+#[derive(Debug, Clone, PartialEq)]
+pub enum PositionChange {
+    Open { side: String, size: f64, entry_price: f64 },
+    Reduce { size: f64 }, // Remaining size
+    Close,
+}
+
+impl PositionChange {
+    /// Parse the executor's `action`; None for unknown actions, which are left to `reconcile`
+    pub fn from_report(action: &str, side: Option<String>, size: f64, entry_price: f64) -> Option<Self> {
+        match action {
+            "open" => side.map(|side| PositionChange::Open { side, size, entry_price }),
+            "reduce" => Some(PositionChange::Reduce { size }),
+            "close" => Some(PositionChange::Close),
+            _ => None,
+        }
+    }
+}
+
+/// Shared, synchronously queryable store of open positions.
+///
+/// Updated locally from executor results so suppression and sizing decisions don't need a
+/// Python round-trip; `reconcile` replaces the local view with the executor's periodic report
+/// so any drift is corrected.
+#[derive(Debug, Clone, Default)]
+pub struct PositionStore {
+    positions: Arc<RwLock<HashMap<String, Position>>>,
+}
+
+impl PositionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a newly opened position, replacing any previous entry for the symbol
+    pub fn record_open(&self, symbol: &str, side: &str, size: f64, entry_price: f64) {
+        let position = Position {
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            size,
+            entry_price,
+            updated_at: chrono::Utc::now().timestamp_millis(),
+        };
+        self.positions.write().unwrap().insert(symbol.to_string(), position);
+    }
+
+    /// Update the size of an existing position; a size of zero closes it
+    pub fn record_update(&self, symbol: &str, size: f64) {
+        if size == 0.0 {
+            self.record_close(symbol);
+            return;
+        }
+        if let Some(position) = self.positions.write().unwrap().get_mut(symbol) {
+            position.size = size;
+            position.updated_at = chrono::Utc::now().timestamp_millis();
+        }
+    }
+
+    /// Record that a position was closed
+    pub fn record_close(&self, symbol: &str) -> Option<Position> {
+        self.positions.write().unwrap().remove(symbol)
+    }
+
+    /// Apply an executed order's reported change to the symbol's position
+    pub fn apply(&self, symbol: &str, change: PositionChange) {
+        match change {
+            PositionChange::Open { side, size, entry_price } => self.record_open(symbol, &side, size, entry_price),
+            PositionChange::Reduce { size } => self.record_update(symbol, size),
+            PositionChange::Close => {
+                self.record_close(symbol);
+            }
+        }
+    }
+
+    /// Get the open position for a symbol, if any
+    pub fn get(&self, symbol: &str) -> Option<Position> {
+        self.positions.read().unwrap().get(symbol).cloned()
+    }
+
+    /// Check whether a position is open for a symbol
+    pub fn has_position(&self, symbol: &str) -> bool {
+        self.positions.read().unwrap().contains_key(symbol)
+    }
+
+    /// Get all open positions
+    pub fn all(&self) -> Vec<Position> {
+        self.positions.read().unwrap().values().cloned().collect()
+    }
+
+    /// Replace the local view with the executor's authoritative positions.
+    /// Returns the number of symbols whose local state had drifted.
+    pub fn reconcile(&self, reported: Vec<Position>) -> usize {
+        let reported: HashMap<String, Position> = reported
+            .into_iter()
+            .map(|position| (position.symbol.clone(), position))
+            .collect();
+        let mut positions = self.positions.write().unwrap();
+
+        let mut drifted = 0;
+        for (symbol, local) in positions.iter() {
+            match reported.get(symbol) {
+                None => {
+                    warn!("[Rust] Position {} closed on executor but still tracked locally; removing.", symbol);
+                    drifted += 1;
+                }
+                Some(remote) if remote.side != local.side || remote.size != local.size => {
+                    warn!("[Rust] Position {} drifted (local {} {}, executor {} {}); using executor state.", symbol, local.side, local.size, remote.side, remote.size);
+                    drifted += 1;
+                }
+                Some(_) => {}
+            }
+        }
+        for symbol in reported.keys() {
+            if !positions.contains_key(symbol) {
+                info!("[Rust] Position {} reported by executor but not tracked locally; adding.", symbol);
+                drifted += 1;
+            }
+        }
+
+        *positions = reported;
+        drifted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(symbol: &str, side: &str, size: f64) -> Position {
+        Position { symbol: symbol.to_string(), side: side.to_string(), size, entry_price: 100.0, updated_at: 0 }
+    }
+
+    #[test]
+    fn executor_reports_parse_into_changes() {
+        assert_eq!(
+            PositionChange::from_report("open", Some("buy".to_string()), 2.0, 100.0),
+            Some(PositionChange::Open { side: "buy".to_string(), size: 2.0, entry_price: 100.0 })
+        );
+        assert_eq!(PositionChange::from_report("open", None, 2.0, 100.0), None, "an open needs a side");
+        assert_eq!(PositionChange::from_report("reduce", None, 0.5, 0.0), Some(PositionChange::Reduce { size: 0.5 }));
+        assert_eq!(PositionChange::from_report("close", None, 0.0, 0.0), Some(PositionChange::Close));
+        assert_eq!(PositionChange::from_report("hold", None, 0.0, 0.0), None);
+    }
+
+    #[test]
+    fn open_update_and_close_transitions() {
+        let store = PositionStore::new();
+        store.apply("BTCUSDT", PositionChange::Open { side: "buy".to_string(), size: 2.0, entry_price: 100.0 });
+        assert!(store.has_position("BTCUSDT"));
+        assert_eq!(store.get("BTCUSDT").map(|position| (position.side, position.size)), Some(("buy".to_string(), 2.0)));
+
+        store.apply("BTCUSDT", PositionChange::Reduce { size: 0.5 });
+        assert_eq!(store.get("BTCUSDT").map(|position| position.size), Some(0.5));
+        assert_eq!(store.get("BTCUSDT").map(|position| position.entry_price), Some(100.0), "reducing keeps the entry");
+
+        store.apply("BTCUSDT", PositionChange::Reduce { size: 0.0 });
+        assert!(!store.has_position("BTCUSDT"), "reducing to zero closes");
+
+        store.apply("ETHUSDT", PositionChange::Open { side: "sell".to_string(), size: 1.0, entry_price: 2_000.0 });
+        store.apply("ETHUSDT", PositionChange::Close);
+        assert!(store.all().is_empty());
+        store.record_update("ETHUSDT", 3.0);
+        assert!(!store.has_position("ETHUSDT"), "updating an untracked symbol doesn't open it");
+    }
+
+    #[test]
+    fn reconcile_overrides_local_drift() {
+        let store = PositionStore::new();
+        store.reconcile(vec![position("BTCUSDT", "buy", 1.0), position("ETHUSDT", "sell", 2.0), position("SOLUSDT", "buy", 3.0)]);
+        assert_eq!(store.all().len(), 3);
+
+        // Executor: BTC unchanged, ETH resized, SOL closed, XRP opened without a local record
+        let drifted = store.reconcile(vec![position("BTCUSDT", "buy", 1.0), position("ETHUSDT", "sell", 0.5), position("XRPUSDT", "buy", 10.0)]);
+        assert_eq!(drifted, 3);
+        assert_eq!(store.get("ETHUSDT").map(|position| position.size), Some(0.5));
+        assert!(!store.has_position("SOLUSDT"));
+        assert!(store.has_position("XRPUSDT"));
+
+        let drifted = store.reconcile(vec![position("BTCUSDT", "buy", 1.0), position("ETHUSDT", "sell", 0.5), position("XRPUSDT", "buy", 10.0)]);
+        assert_eq!(drifted, 0);
+    }
+}
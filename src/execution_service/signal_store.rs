@@ -0,0 +1,201 @@
+//! SQLite store of emitted signals for later analysis and reporting (`sqlite` feature)
+
+use crate::engine::HeartbeatMetrics;
+use crate::signals::TradingSignal;
+use anyhow::Result;
+use rusqlite::{params, Connection, TransactionBehavior};
+use std::sync::{Arc, Mutex};
+
+/// Schema migrations in order; `PRAGMA user_version` records how many have been applied.
+/// Append new migrations, never edit applied ones. Pending migrations and the version bump are
+/// applied in one transaction, so a failure leaves the schema as it was.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS signals (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        symbol TEXT NOT NULL,
+        signal_type TEXT NOT NULL,
+        price REAL NOT NULL,
+        confidence REAL NOT NULL,
+        reason_code TEXT NOT NULL,
+        reason TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        tier TEXT,
+        metrics TEXT
+    );
+    CREATE INDEX IF NOT EXISTS signals_symbol_timestamp ON signals (symbol, timestamp);",
+    "ALTER TABLE signals ADD COLUMN effective_params TEXT;",
+    // metrics held the explain-mode explanation; it moves to its own column and metrics now
+    // always holds the flow metrics snapshot
+    "ALTER TABLE signals ADD COLUMN explanation TEXT;
+    UPDATE signals SET explanation = metrics, metrics = NULL;",
+];
+
+/// A signal row as stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredSignal {
+    pub symbol: String,
+    pub signal_type: String,
+    pub price: f64,
+    pub confidence: f64,
+    pub reason_code: String,
+    pub reason: String,
+    pub timestamp: u64,
+    pub tier: Option<String>,
+    pub metrics: Option<String>,  // The symbol's flow metrics when the signal was recorded as JSON, None for group signals
+    pub explanation: Option<String>,  // The signal's explanation as JSON, recorded in explain mode
+    pub effective_params: Option<String>,  // The signal's EffectiveParams as JSON, with record_effective_params
+}
+
+/// Shared handle to the signal database; writes are serialized on one connection
+#[derive(Clone)]
+pub struct SignalStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SignalStore {
+    /// Open (or create) the database at `path` and bring its schema up to date
+    pub fn open(path: &str) -> Result<Self> {
+        let mut conn = Connection::open(path)?;
+        // Immediate: another process opening the same file waits instead of migrating too
+        let transaction = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let version: usize = transaction.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version < MIGRATIONS.len() {
+            for migration in &MIGRATIONS[version..] {
+                transaction.execute_batch(migration)?;
+            }
+            transaction.execute_batch(&format!("PRAGMA user_version = {}", MIGRATIONS.len()))?;
+        }
+        transaction.commit()?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    /// Insert a signal with the flow metrics of its symbol (None for signals without a single
+    /// book, e.g. group signals). Blocking; call from `spawn_blocking` in async code.
+    pub fn insert(&self, signal: &TradingSignal, metrics: Option<&HeartbeatMetrics>) -> Result<()> {
+        let metrics = metrics.map(serde_json::to_string).transpose()?;
+        let explanation = signal.explanation.as_ref().map(serde_json::to_string).transpose()?;
+        let effective_params = signal.effective_params.as_ref().map(serde_json::to_string).transpose()?;
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO signals (symbol, signal_type, price, confidence, reason_code, reason, timestamp, tier, metrics, explanation, effective_params)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                signal.symbol,
+                format!("{:?}", signal.signal_type),
+                signal.price,
+                signal.confidence,
+                format!("{:?}", signal.reason_code),
+                signal.reason,
+                signal.timestamp as i64,
+                signal.tier,
+                metrics,
+                explanation,
+                effective_params,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Most recent signals for a symbol, newest first
+    pub fn recent(&self, symbol: &str, limit: usize) -> Result<Vec<StoredSignal>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT symbol, signal_type, price, confidence, reason_code, reason, timestamp, tier, metrics, explanation, effective_params
+             FROM signals WHERE symbol = ?1 ORDER BY timestamp DESC, id DESC LIMIT ?2",
+        )?;
+        let rows = statement.query_map(params![symbol, limit as i64], |row| {
+            Ok(StoredSignal {
+                symbol: row.get(0)?,
+                signal_type: row.get(1)?,
+                price: row.get(2)?,
+                confidence: row.get(3)?,
+                reason_code: row.get(4)?,
+                reason: row.get(5)?,
+                timestamp: row.get::<_, i64>(6)?.max(0) as u64,
+                tier: row.get(7)?,
+                metrics: row.get(8)?,
+                explanation: row.get(9)?,
+                effective_params: row.get(10)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::{ReasonCode, SignalType};
+
+    fn signal(symbol: &str, signal_type: SignalType, price: f64, timestamp: u64) -> TradingSignal {
+        TradingSignal {
+            signal_type,
+            price,
+            confidence: 0.8,
+            reason_code: ReasonCode::StackedImbalance,
+            timestamp,
+            tier: Some("full".to_string()),
+            ..TradingSignal::no_signal_with_reason(symbol, "Stacked buy imbalances")
+        }
+    }
+
+    fn metrics() -> HeartbeatMetrics {
+        HeartbeatMetrics {
+            mid_price: 67012.25,
+            delta: 50_000.0,
+            cumulative_delta: 120_000.0,
+            buy_imbalance: 4.2,
+            sell_imbalance: 0.3,
+            book_quality: 0.9,
+            book_update_rate: 10.0,
+        }
+    }
+
+    #[test]
+    fn stored_signals_are_queried_back_newest_first() {
+        let store = SignalStore::open(":memory:").unwrap();
+        store.insert(&signal("BTCUSDT", SignalType::StrongBuy, 67012.5, 1_000), Some(&metrics())).unwrap();
+        store.insert(&signal("ETHUSDT", SignalType::Sell, 2641.4, 1_500), Some(&metrics())).unwrap();
+        store.insert(&signal("BTCUSDT", SignalType::Buy, 67020.0, 2_000), None).unwrap();
+
+        let rows = store.recent("BTCUSDT", 10).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].signal_type.as_str(), rows[0].price, rows[0].timestamp), ("Buy", 67020.0, 2_000));
+        assert_eq!(rows[0].metrics, None);
+        let first = &rows[1];
+        assert_eq!((first.signal_type.as_str(), first.reason_code.as_str(), first.confidence), ("StrongBuy", "StackedImbalance", 0.8));
+        assert_eq!((first.reason.as_str(), first.tier.as_deref()), ("Stacked buy imbalances", Some("full")));
+        let stored_metrics: serde_json::Value = serde_json::from_str(first.metrics.as_deref().unwrap()).unwrap();
+        assert_eq!(stored_metrics["delta"], 50_000.0);
+        assert_eq!((first.explanation.as_deref(), first.effective_params.as_deref()), (None, None));
+        assert_eq!(store.recent("BTCUSDT", 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn an_old_database_is_migrated_in_place() {
+        let path = std::env::temp_dir().join(format!("ofi-signals-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            // A database at the first schema version, whose metrics column held the explanation
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(MIGRATIONS[0]).unwrap();
+            conn.execute_batch("PRAGMA user_version = 1").unwrap();
+            conn.execute(
+                "INSERT INTO signals (symbol, signal_type, price, confidence, reason_code, reason, timestamp, metrics)
+                 VALUES ('BTCUSDT', 'Buy', 100.0, 0.7, 'Absorption', 'old', 500, '{\"delta\":1.0}')",
+                [],
+            )
+            .unwrap();
+        }
+
+        let store = SignalStore::open(path.to_str().unwrap()).unwrap();
+        let old = &store.recent("BTCUSDT", 10).unwrap()[0];
+        assert_eq!((old.metrics.as_deref(), old.explanation.as_deref()), (None, Some("{\"delta\":1.0}")));
+        let version: usize = store.conn.lock().unwrap().query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+
+        // Reopening an up-to-date database applies nothing again
+        drop(store);
+        assert_eq!(SignalStore::open(path.to_str().unwrap()).unwrap().recent("BTCUSDT", 10).unwrap().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
-use tokio::sync::{mpsc, Semaphore};
+use tokio::sync::{mpsc, watch, Semaphore};
 use tokio::time::{interval, Duration as TokioDuration};
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::thread;
 use std::sync::mpsc as sync_mpsc;
@@ -11,10 +12,21 @@ use colored::*;
 use log::{error, info, warn};
 
 // Import from our library crate
-use ofi_engine_rust::config::OFIConfig;
-use ofi_engine_rust::engine::OFIEngine;
-use ofi_engine_rust::signals::StrategyParams;
-use ofi_engine_rust::websocket::run_websocket_manager;
+use ofi_engine_rust::config::{OFIConfig, PythonUnavailablePolicy, SymbolGroup};
+use ofi_engine_rust::engine::{Heartbeat, MemoryBudget, OFIEngine, ReadinessBoard};
+use ofi_engine_rust::group::GroupRegistry;
+use ofi_engine_rust::position_store::{Position, PositionChange, PositionStore};
+use ofi_engine_rust::signals::{SignalType, StrategyParams, TradingSignal as LibTradingSignal};
+#[cfg(feature = "mqtt")]
+use ofi_engine_rust::mqtt::{run_mqtt_event_loop, MqttPublisher};
+#[cfg(feature = "signal-server")]
+use ofi_engine_rust::signal_server::{run_signal_server, ServerEvent, SignalBroadcaster};
+#[cfg(feature = "sqlite")]
+use ofi_engine_rust::signal_store::SignalStore;
+#[cfg(feature = "tui")]
+use ofi_engine_rust::dashboard::{run_dashboard, DashboardRegistry};
+use ofi_engine_rust::status_http::{run_status_server, StatusHandler};
+use ofi_engine_rust::websocket::{run_websocket_manager, test_handshake, FLIGHT_RECORDER};
 
 use pyo3::prelude::*;
 
@@ -25,9 +37,15 @@ pub struct TradingSignal {
     pub symbol: String,
     pub signal_type: String, // e.g., "StrongBuy", "StrongSell"
     pub price: f64,
+    pub tier: Option<String>, // Confidence tier label from the engine, if tiers are configured
+    pub max_advisable_size: Option<f64>, // Notional the book absorbs within sizing_slippage_bps, if configured
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+// Log file used while the terminal dashboard owns the screen
+#[cfg(feature = "tui")]
+const DASHBOARD_LOG_PATH: &str = "sentinel-dashboard.log";
+
 // Function to call Python Screener
 fn call_python_screener() -> PyResult<Vec<String>> {
     Python::with_gil(|py| {
@@ -43,12 +61,265 @@ fn call_python_screener() -> PyResult<Vec<String>> {
     })
 }
 
-// Function to call Python Execution Service with timeout
-fn call_python_executor(signal: TradingSignal) -> PyResult<()> {
+// Python modules the binary calls into: screener for the watchlist, execution service for signals
+const PYTHON_MODULES: [(&str, &str); 2] = [("python screener", "screener.screener"), ("python executor", "execution_service.manager")];
+
+// Import every Python module the binary needs, with the error of each one that failed
+fn python_import_failures() -> Vec<String> {
+    PYTHON_MODULES
+        .iter()
+        .filter_map(|(_, module)| {
+            Python::with_gil(|py| PyModule::import_bound(py, *module).map(|_| ()))
+                .err()
+                .map(|e| format!("{}: {}", module, e))
+        })
+        .collect()
+}
+
+// What startup does about Python modules that failed to import, per python_unavailable_policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PythonStartup {
+    Available,
+    Exit,
+    Fallback,
+    Degraded,
+}
+
+fn python_startup(policy: PythonUnavailablePolicy, python_failures: &[String]) -> PythonStartup {
+    if python_failures.is_empty() {
+        return PythonStartup::Available;
+    }
+    match policy {
+        PythonUnavailablePolicy::FailFast => PythonStartup::Exit,
+        PythonUnavailablePolicy::Fallback => PythonStartup::Fallback,
+        PythonUnavailablePolicy::Ignore => PythonStartup::Degraded,
+    }
+}
+
+// Watchlist candidates of one refresh: the fallback watchlist without Python, otherwise the
+// screener's, empty when the screener call fails
+fn refresh_candidates<E: std::fmt::Display>(
+    python_fallback: bool,
+    fallback_watchlist: &[String],
+    screener: impl FnOnce() -> Result<Vec<String>, E>,
+) -> Vec<String> {
+    if python_fallback {
+        return fallback_watchlist.to_vec();
+    }
+    screener().unwrap_or_else(|e| {
+        error!("[SENTINEL] Gagal mendapatkan kandidat dari Python: {}. Menggunakan watchlist kosong.", e);
+        Vec::new()
+    })
+}
+
+// Result of one startup pre-flight check
+struct PreflightCheck {
+    name: &'static str,
+    passed: bool,
+    detail: String,
+}
+
+// Startup pre-flight: validate config, test the handshake of every configured WebSocket endpoint
+// and, when python_unavailable_policy is FailFast, require the Python modules, so misconfiguration
+// fails fast instead of surfacing later per symbol. Under Ignore and Fallback missing Python is
+// handled by the policy at startup, not failed here.
+async fn run_preflight(config: &OFIConfig, python_failures: &[String]) -> Vec<PreflightCheck> {
+    let mut checks = Vec::new();
+
+    let validation = config.validate();
+    checks.push(PreflightCheck {
+        name: "config",
+        passed: validation.is_ok(),
+        detail: validation.err().unwrap_or_else(|| "valid".to_string()),
+    });
+
+    for url in config.websocket_urls() {
+        let handshake = test_handshake(url, TokioDuration::from_secs(10)).await;
+        checks.push(PreflightCheck {
+            name: "websocket",
+            passed: handshake.is_ok(),
+            detail: match handshake {
+                Ok(()) => format!("reachable: {}", url),
+                Err(e) => format!("{}: {}", url, e),
+            },
+        });
+    }
+
+    if config.python_unavailable_policy == PythonUnavailablePolicy::FailFast {
+        checks.push(PreflightCheck {
+            name: "python",
+            passed: python_failures.is_empty(),
+            detail: if python_failures.is_empty() {
+                format!("imported {}", PYTHON_MODULES.map(|(_, module)| module).join(", "))
+            } else {
+                format!("failed to import {}", python_failures.join("; "))
+            },
+        });
+    }
+
+    checks
+}
+
+// Keep the screener's ranking, dropping duplicates, and cut it to the `limit` top-ranked symbols
+fn ranked_watchlist(candidates: Vec<String>, limit: usize) -> Vec<String> {
+    let mut ranked: Vec<String> = Vec::with_capacity(limit.min(candidates.len()));
+    for candidate in candidates {
+        if ranked.len() >= limit {
+            break;
+        }
+        if !ranked.contains(&candidate) {
+            ranked.push(candidate);
+        }
+    }
+    ranked
+}
+
+// Startup delay of the `index`-th (0-based) task started by one watchlist refresh
+fn task_start_delay(stagger_ms: u64, index: u64) -> TokioDuration {
+    TokioDuration::from_millis(stagger_ms.saturating_mul(index))
+}
+
+// Whether a task started (or, when staggered, due to start) at `started_at` is still in its
+// startup grace at `now`, and so exempt from being stopped on a watchlist refresh
+fn in_startup_grace(started_at: std::time::Instant, now: std::time::Instant, grace: TokioDuration) -> bool {
+    now.saturating_duration_since(started_at) < grace
+}
+
+// Start a task with `spawn` for every candidate that has none, passing its index among the tasks
+// started by this call. Each slot is reserved before its spawn, so the spawn decision and the
+// registration can't be separated and a symbol never has two tasks; candidates whose previous task
+// has not stopped yet are held back until a later refresh.
+fn start_missing_tasks<T, S>(running: &mut HashMap<String, T>, stopping: &HashMap<String, S>, candidates: &[String], mut spawn: impl FnMut(&str, u64) -> T) {
+    let mut new_task_index: u64 = 0;
+    for candidate in candidates {
+        if stopping.contains_key(candidate) {
+            warn!("[SENTINEL-WARN] Task lama untuk {} belum berhenti. Task baru ditunda ke refresh berikutnya.", candidate);
+            continue;
+        }
+        if let Entry::Vacant(slot) = running.entry(candidate.clone()) {
+            slot.insert(spawn(candidate, new_task_index));
+            new_task_index += 1;
+        }
+    }
+}
+
+// Per-symbol count of signals forwarded for execution on the current UTC day
+#[derive(Default)]
+struct DailySignalCounter {
+    day: Option<chrono::NaiveDate>,
+    counts: HashMap<String, u32>,
+}
+
+impl DailySignalCounter {
+    // Count a signal for `symbol` on `day` if it is within `cap`; counts reset when the day changes
+    fn try_record(&mut self, symbol: &str, day: chrono::NaiveDate, cap: u32) -> bool {
+        if self.day != Some(day) {
+            self.day = Some(day);
+            self.counts.clear();
+        }
+        let count = self.counts.entry(symbol.to_string()).or_insert(0);
+        if *count >= cap {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
+
+// Bounds in-flight position monitor calls: a tick that finds every permit held is skipped and
+// counted instead of queued behind the slow call
+struct MonitorGate {
+    inflight: Arc<Semaphore>,
+    skipped_ticks: u64,
+}
+
+impl MonitorGate {
+    fn new(max_inflight: usize) -> Self {
+        MonitorGate { inflight: Arc::new(Semaphore::new(max_inflight)), skipped_ticks: 0 }
+    }
+
+    // Permit for one monitor call, held until the call finishes; None counts a skipped tick
+    fn try_start(&mut self) -> Option<tokio::sync::OwnedSemaphorePermit> {
+        let permit = Arc::clone(&self.inflight).try_acquire_owned().ok();
+        if permit.is_none() {
+            self.skipped_ticks += 1;
+        }
+        permit
+    }
+}
+
+// Idempotency key for a signal: identical across retries of the same signal, so the Python
+// side can dedupe order submission
+fn idempotency_key(signal: &TradingSignal) -> String {
+    format!(
+        "{}-{}-{:.5e}-{}",
+        signal.symbol,
+        signal.signal_type,
+        signal.price, // Price bucket: six significant digits
+        signal.timestamp.timestamp_millis()
+    )
+}
+
+// Function to call Python Execution Service with timeout, retrying transient failures with backoff.
+// Each attempt blocks on Python for up to 30s, so it runs on the blocking pool and the backoff is an
+// async sleep; no Tokio worker is parked while retrying.
+async fn call_python_executor(signal: TradingSignal, positions: PositionStore, max_retries: u32, backoff_ms: u64) -> PyResult<()> {
+    let key = idempotency_key(&signal);
+    let symbol = signal.symbol.clone();
+    retry_executor(&symbol, &key, max_retries, backoff_ms, |attempt_key| {
+        let (attempt_signal, attempt_positions) = (signal.clone(), positions.clone());
+        async move {
+            tokio::task::spawn_blocking(move || call_python_executor_once(&attempt_signal, &attempt_key, &attempt_positions))
+                .await
+                .unwrap_or_else(|e| Err(pyo3::exceptions::PyRuntimeError::new_err(format!("Executor task failed: {}", e))))
+        }
+    })
+    .await
+}
+
+// Retry loop around executor attempts. Every attempt gets the same idempotency key; an attempt
+// returns Ok(true) on a transient failure worth retrying.
+async fn retry_executor<F, Fut>(symbol: &str, key: &str, max_retries: u32, backoff_ms: u64, mut attempt_fn: F) -> PyResult<()>
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = PyResult<bool>>,
+{
+    let mut attempt = 0;
+    loop {
+        // Python exceptions (missing module or function, bad arguments) won't go away on retry
+        match attempt_fn(key.to_string()).await {
+            Ok(false) => return Ok(()),
+            Ok(true) => {}
+            Err(e) => return Err(e),
+        }
+        if attempt >= max_retries {
+            if attempt > 0 {
+                warn!("[SENTINEL-WARN] Executor untuk {} gagal setelah {} percobaan ulang (key {})", symbol, attempt, key);
+            }
+            return Ok(());
+        }
+        attempt += 1;
+        let delay = executor_retry_delay(backoff_ms, attempt);
+        warn!("[SENTINEL-WARN] Transient executor failure for {}. Retry {}/{} in {:?} (key {})", symbol, attempt, max_retries, delay, key);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+// Backoff before retry `attempt` (1-based): `backoff_ms` doubled per earlier retry
+fn executor_retry_delay(backoff_ms: u64, attempt: u32) -> TokioDuration {
+    TokioDuration::from_millis(backoff_ms.saturating_mul(1 << attempt.saturating_sub(1).min(16)))
+}
+
+// Single executor attempt. Returns Ok(true) on a transient failure worth retrying (reported
+// `transient` by the executor, or a timeout); business-logic rejections return Ok(false) and
+// Python exceptions are returned as errors.
+fn call_python_executor_once(signal: &TradingSignal, idempotency_key: &str, positions: &PositionStore) -> PyResult<bool> {
     let (tx, rx) = sync_mpsc::channel();
     
     // Spawn a thread to execute the Python call
     let signal_clone = signal.clone();
+    let key = idempotency_key.to_string();
+    let positions = positions.clone();
     let _handle = thread::spawn(move || {
         let result = Python::with_gil(|py| {
             let executor = PyModule::import_bound(py, "execution_service.manager")?;
@@ -56,7 +327,10 @@ fn call_python_executor(signal: TradingSignal) -> PyResult<()> {
             signal_dict.set_item("symbol", &signal_clone.symbol)?;
             signal_dict.set_item("signal_type", &signal_clone.signal_type)?;
             signal_dict.set_item("price", signal_clone.price)?;
+            signal_dict.set_item("tier", &signal_clone.tier)?;
+            signal_dict.set_item("max_advisable_size", signal_clone.max_advisable_size)?;
             signal_dict.set_item("timestamp", signal_clone.timestamp.to_rfc3339())?;
+            signal_dict.set_item("idempotency_key", &key)?;
 
             let result = executor.getattr("handle_trade_signal")?.call1((signal_dict,))?;
 
@@ -70,11 +344,31 @@ fn call_python_executor(signal: TradingSignal) -> PyResult<()> {
                                 Err(_) => "Failed to get reason key from Python dict".to_string(),
                             };
                             warn!("[SENTINEL-WARN] Eksekusi trade gagal di Python dengan alasan: {}", reason);
+                            let transient = result_dict
+                                .get_item("transient")?
+                                .and_then(|v| v.extract::<bool>().ok())
+                                .unwrap_or(false);
+                            return Ok(transient);
+                        } else if status_str == "success" {
+                            // Track the position locally so it can be queried without Python. The
+                            // executor reports what the order did; a signal's direction alone can't
+                            // tell opening a short from closing a long.
+                            let action = result_dict.get_item("action")?.and_then(|v| v.extract::<String>().ok()).unwrap_or_default();
+                            let side = result_dict.get_item("side")?.and_then(|v| v.extract::<String>().ok());
+                            let size = result_dict.get_item("size")?.and_then(|v| v.extract::<f64>().ok()).unwrap_or(0.0);
+                            let entry_price = result_dict
+                                .get_item("entry_price")?
+                                .and_then(|v| v.extract::<f64>().ok())
+                                .unwrap_or(signal_clone.price);
+                            match PositionChange::from_report(&action, side, size, entry_price) {
+                                Some(change) => positions.apply(&signal_clone.symbol, change),
+                                None => warn!("[SENTINEL-WARN] Executor tidak melaporkan aksi posisi yang dikenal untuk {} ('{}'). Menunggu rekonsiliasi.", signal_clone.symbol, action),
+                            }
                         }
                     }
                 }
             }
-            Ok(())
+            Ok(false)
         });
         
         // Send the result through the channel
@@ -87,13 +381,13 @@ fn call_python_executor(signal: TradingSignal) -> PyResult<()> {
         Err(_) => {
             warn!("[SENTINEL-WARN] Python executor call timed out after 30 seconds for symbol {}", signal.symbol);
             // Note: We can't actually kill the thread here, but at least we don't block the main loop
-            Ok(())
+            Ok(true)
         }
     }
 }
 
 // Function to call Python Position Monitor with timeout
-fn call_python_position_monitor() -> PyResult<()> {
+fn call_python_position_monitor(positions: PositionStore) -> PyResult<()> {
     let (tx, rx) = sync_mpsc::channel();
     
     // Spawn a thread to execute the Python call
@@ -113,6 +407,20 @@ fn call_python_position_monitor() -> PyResult<()> {
                                 Err(_) => "Failed to get reason key from Python dict".to_string(),
                             };
                             warn!("[SENTINEL-WARN] Position monitoring failed in Python with reason: {}", reason);
+                        } else if status_str == "success" {
+                            // The executor is authoritative: override any local drift
+                            if let Ok(Some(reported)) = result_dict.get_item("positions") {
+                                let reported: Vec<(String, String, f64, f64)> = reported.extract()?;
+                                let now = chrono::Utc::now().timestamp_millis();
+                                let reported = reported
+                                    .into_iter()
+                                    .map(|(symbol, side, size, entry_price)| Position { symbol, side, size, entry_price, updated_at: now })
+                                    .collect();
+                                let drifted = positions.reconcile(reported);
+                                if drifted > 0 {
+                                    warn!("[SENTINEL-WARN] Position store reconciled {} drifted position(s) with Python", drifted);
+                                }
+                            }
                         }
                     }
                 }
@@ -133,13 +441,143 @@ fn call_python_position_monitor() -> PyResult<()> {
         }
     }
 }
+// Function to ask the Python executor to close every open position (dead-man's switch)
+fn call_python_flatten_all() -> PyResult<()> {
+    let (tx, rx) = sync_mpsc::channel();
+
+    let _handle = thread::spawn(move || {
+        let result = Python::with_gil(|py| {
+            let executor = PyModule::import_bound(py, "execution_service.manager")?;
+            let result = executor.getattr("flatten_all_positions")?.call0()?;
+
+            if let Ok(result_dict) = result.downcast::<pyo3::types::PyDict>() {
+                if let Ok(Some(status)) = result_dict.get_item("status") {
+                    if let Ok(status_str) = status.extract::<String>() {
+                        if status_str == "error" {
+                            let reason = match result_dict.get_item("reason") {
+                                Ok(Some(r)) => r.extract().unwrap_or_else(|_| "Could not extract reason".to_string()),
+                                Ok(None) => "No reason provided".to_string(),
+                                Err(_) => "Failed to get reason key from Python dict".to_string(),
+                            };
+                            error!("[SENTINEL-CRITICAL] Flatten all positions failed in Python with reason: {}", reason);
+                        }
+                    }
+                }
+            }
+            Ok(())
+        });
+
+        let _ = tx.send(result);
+    });
+
+    match rx.recv_timeout(StdDuration::from_secs(30)) {
+        Ok(result) => result,
+        Err(_) => {
+            error!("[SENTINEL-CRITICAL] Python flatten all positions call timed out after 30 seconds");
+            Ok(())
+        }
+    }
+}
+
+// Dead-man's switch state: trips once per stall and re-arms when the heartbeat recovers
+struct DeadmanSwitch {
+    timeout_ms: i64,
+    tripped: bool,
+}
+
+impl DeadmanSwitch {
+    fn new(timeout_secs: u64) -> Self {
+        Self { timeout_ms: timeout_secs.saturating_mul(1000).min(i64::MAX as u64) as i64, tripped: false }
+    }
+
+    // Observe the heartbeat's age; true exactly once per stall, when it first exceeds the timeout
+    fn observe(&mut self, age_ms: i64) -> bool {
+        if age_ms <= self.timeout_ms {
+            if self.tripped {
+                warn!("[SENTINEL-WARN] Engine heartbeat recovered. Dead-man's switch re-armed.");
+                self.tripped = false;
+            }
+            return false;
+        }
+        if self.tripped {
+            return false;
+        }
+        self.tripped = true;
+        true
+    }
+
+    // Re-arm without a recovery, e.g. while no task is expected to beat
+    fn rearm(&mut self) {
+        self.tripped = false;
+    }
+}
+
+/// Watchdog for the dead-man's switch: flattens all positions once per stall when the
+/// heartbeat has not been refreshed for `timeout_secs`, and re-arms when it recovers.
+/// Only armed while an analysis task is running; with none (empty watchlist, paused refresh)
+/// the heartbeat is kept fresh, so the first task to start gets the full timeout.
+async fn run_deadman_watchdog(heartbeat: Heartbeat, timeout_secs: u64) {
+    let mut check_timer = interval(TokioDuration::from_secs(1));
+    let mut switch = DeadmanSwitch::new(timeout_secs);
+
+    loop {
+        check_timer.tick().await;
+        if heartbeat.live_sources() == 0 {
+            heartbeat.beat();
+            switch.rearm();
+            continue;
+        }
+        let age_ms = heartbeat.age_ms();
+        if !switch.observe(age_ms) {
+            continue;
+        }
+        error!("[SENTINEL-CRITICAL] Engine heartbeat stale for {}ms (limit {}s). Flattening all positions!", age_ms, timeout_secs);
+        if let Err(e) = tokio::task::spawn_blocking(call_python_flatten_all).await.unwrap_or(Ok(())) {
+            error!("[SENTINEL-CRITICAL] Gagal memanggil flatten Python: {}", e);
+        }
+    }
+}
+
+// Whether a periodic loop's tick should run, given its pause flag
+fn tick_enabled(paused: &watch::Receiver<bool>) -> bool {
+    !*paused.borrow()
+}
+
+// JSON body describing a timer pause/resume transition for the status endpoint
+fn timer_state(timer: &str, was_paused: bool, paused: bool) -> String {
+    serde_json::json!({ "timer": timer, "was_paused": was_paused, "paused": paused }).to_string()
+}
+
+// State shared by all analysis tasks, cloned into each one
+#[derive(Clone)]
+struct TaskShared {
+    heartbeat: Heartbeat,
+    observability_tx: Option<mpsc::Sender<LibTradingSignal>>,
+    memory_budget: Option<MemoryBudget>,
+    readiness: ReadinessBoard,
+    invalid_symbols: Arc<std::sync::Mutex<HashSet<String>>>,  // Symbols the exchange rejected, skipped on refresh
+    groups: Option<GroupRegistry>,  // Engines readable by the group evaluator, when [[group]] entries are configured
+    #[cfg(feature = "signal-server")]
+    signal_server: Option<SignalBroadcaster>,
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<MqttPublisher>,
+    #[cfg(feature = "sqlite")]
+    signal_store: Option<SignalStore>,
+    #[cfg(feature = "tui")]
+    dashboard: Option<DashboardRegistry>,
+}
+
 /// This task uses the robust `run_websocket_manager` for continuous data analysis.
 async fn spawn_analysis_task(
     symbol: String,
     signal_tx: mpsc::Sender<TradingSignal>,
-    mut shutdown_rx: mpsc::Receiver<()>
+    mut shutdown_rx: mpsc::Receiver<()>,
+    shared: TaskShared,
 ) {
+    let readiness = shared.readiness;
     info!("[TASK] Starting analysis task for {}", symbol);
+    // Arms the dead-man's switch for as long as this task runs
+    let _heartbeat_source = shared.heartbeat.register_source();
 
     // 1. Initialize configuration and engine for this symbol
     let config = match OFIConfig::from_default_config() {
@@ -157,10 +595,36 @@ async fn spawn_analysis_task(
         lookback_period_ms: config.lookback_period_ms,
         market_condition_multiplier: 1.0, // Default multiplier
     };
-    let engine = OFIEngine::new(params, config.clone());
+    let mut engine = OFIEngine::new(params, config.clone())
+        .with_heartbeat(shared.heartbeat)
+        .with_readiness_board(readiness.clone());
+    if let Some(tx) = shared.observability_tx {
+        engine = engine.with_observability_sink(tx);
+    }
+    if let Some(budget) = shared.memory_budget {
+        engine = engine.with_memory_budget(budget);
+    }
+    // Periodic symbol heartbeats. The sender is held for the task's lifetime, so the receiver
+    // just stays pending when heartbeats are disabled.
+    let (symbol_heartbeat_tx, mut symbol_heartbeat_rx) = mpsc::channel(16);
+    if config.heartbeat_interval_secs > 0 {
+        engine = engine.with_symbol_heartbeat_sink(symbol_heartbeat_tx.clone());
+    }
+
+    #[cfg(feature = "tui")]
+    if let Some(dashboard) = &shared.dashboard {
+        dashboard.register(&symbol, engine.clone());
+    }
+    if let Some(groups) = &shared.groups {
+        groups.register(&symbol, engine.clone());
+    }
+
+    // Resume session CVD from a recent previous run of this symbol's task
+    engine.resume_session_cvd(&symbol).await;
+    let mut cvd_persist_timer = interval(TokioDuration::from_secs(30));
 
     // 2. Start the websocket manager and get the receiver for library-internal signals
-    let mut lib_signal_rx = run_websocket_manager(symbol.clone(), engine).await;
+    let mut lib_signal_rx = run_websocket_manager(symbol.clone(), engine.clone()).await;
     info!("[TASK] WebSocket manager running for {}. Waiting for signals...", symbol);
 
     // 3. Main loop for this task: listen for signals or shutdown command
@@ -172,16 +636,81 @@ async fn spawn_analysis_task(
                 break; // Exit the loop to terminate the task
             },
 
+            // Periodically persist session CVD so an abrupt stop loses little
+            _ = cvd_persist_timer.tick() => {
+                engine.persist_session_cvd(&symbol).await;
+            },
+
+            // Log symbol heartbeats and stream them to signal server clients and MQTT
+            Some(symbol_heartbeat) = symbol_heartbeat_rx.recv() => {
+                info!(
+                    "[TASK] Heartbeat {}: ready={}, channels={}, trades={}, cvd={:.0}",
+                    symbol, symbol_heartbeat.status.ready, symbol_heartbeat.channels_delivering, symbol_heartbeat.status.trade_count, symbol_heartbeat.session_cvd
+                );
+                #[cfg(feature = "mqtt")]
+                if let Some(mqtt) = &shared.mqtt {
+                    mqtt.publish_heartbeat(&symbol_heartbeat);
+                }
+                #[cfg(feature = "signal-server")]
+                if let Some(server) = &shared.signal_server {
+                    server.publish(&ServerEvent::Heartbeat(symbol_heartbeat));
+                }
+            },
+
             // Listen for a signal from the websocket manager
-            Some(lib_signal) = lib_signal_rx.recv() => {
+            lib_signal = lib_signal_rx.recv() => {
+                // The manager only stops on its own when the exchange rejected the symbol
+                let Some(lib_signal) = lib_signal else {
+                    match engine.invalid_symbol_error(&symbol) {
+                        Some(error) => {
+                            error!("[TASK] {} ditolak exchange sebagai simbol tidak valid (delisting?): {}. Task dihentikan dan simbol dilewati pada refresh berikutnya.", symbol, error);
+                            shared.invalid_symbols.lock().unwrap().insert(symbol.clone());
+                        }
+                        None => error!("[TASK] WebSocket manager untuk {} berhenti. Task dihentikan.", symbol),
+                    }
+                    break;
+                };
                 info!("[TASK] Signal ditemukan untuk {}: {:?}", symbol, lib_signal.signal_type);
+                #[cfg(feature = "tui")]
+                if let Some(dashboard) = &shared.dashboard {
+                    dashboard.record_signal(&lib_signal);
+                }
+
+                // Stream the signal and the symbol's data status to signal server clients
+                #[cfg(feature = "signal-server")]
+                if let Some(server) = &shared.signal_server {
+                    server.publish(&ServerEvent::Signal(Box::new(lib_signal.clone())));
+                    if let Some(status) = engine.symbol_data_status().await.into_iter().find(|status| status.symbol == symbol) {
+                        server.publish(&ServerEvent::DataStatus(status));
+                    }
+                }
+
+                #[cfg(feature = "mqtt")]
+                if let Some(mqtt) = &shared.mqtt {
+                    mqtt.publish_signal(&lib_signal);
+                }
+
+                // Record the signal off the async runtime; a failed write never blocks the signal
+                #[cfg(feature = "sqlite")]
+                if let Some(store) = shared.signal_store.clone() {
+                    let stored_signal = lib_signal.clone();
+                    let metrics = engine.current_metrics(&symbol).await;
+                    tokio::task::spawn_blocking(move || {
+                        if let Err(e) = store.insert(&stored_signal, metrics.as_ref()) {
+                            error!("[TASK] Gagal menyimpan sinyal {} ke database: {}", stored_signal.symbol, e);
+                        }
+                    });
+                }
 
                 // Convert from the library's signal type to the main application's signal type
                 let app_signal = TradingSignal {
                     symbol: lib_signal.symbol,
                     signal_type: format!("{:?}", lib_signal.signal_type),
                     price: lib_signal.price,
-                    timestamp: chrono::Utc::now(), // Use current time for the final signal event
+                    tier: lib_signal.tier,
+                    max_advisable_size: lib_signal.max_advisable_size,
+                    // Already stamped with the configured timestamp_source
+                    timestamp: chrono::DateTime::from_timestamp_millis(lib_signal.timestamp as i64).unwrap_or_else(chrono::Utc::now),
                 };
 
                 // Forward the converted signal to the main sentinel loop
@@ -192,17 +721,67 @@ async fn spawn_analysis_task(
             }
         }
     }
+    engine.flush_reorder_buffer(&symbol).await;
+    engine.persist_session_cvd(&symbol).await;
+    readiness.remove(&symbol);
+    FLIGHT_RECORDER.remove(&symbol);
+    #[cfg(feature = "tui")]
+    if let Some(dashboard) = &shared.dashboard {
+        dashboard.remove(&symbol);
+    }
+    if let Some(groups) = &shared.groups {
+        groups.remove(&symbol);
+    }
     info!("[TASK] Analysis task for {} has been terminated.", symbol);
 }
 
+// Evaluate every configured group each cycle from its members' engines. Group signals have no
+// single instrument to trade, so they are logged and recorded/streamed, not sent to execution;
+// only a change into a new direction is reported.
+#[cfg_attr(not(any(feature = "signal-server", feature = "mqtt", feature = "sqlite")), allow(unused_variables))]
+async fn run_group_signals(groups: Vec<SymbolGroup>, registry: GroupRegistry, cycle: TokioDuration, shared: TaskShared) {
+    let mut ticker = interval(cycle);
+    let mut last_signal_types: HashMap<String, SignalType> = HashMap::new();
+    loop {
+        ticker.tick().await;
+        let now = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        for group in &groups {
+            let signal = registry.evaluate(group, now).await;
+            if last_signal_types.insert(group.name.clone(), signal.signal_type) == Some(signal.signal_type)
+                || signal.signal_type == SignalType::NoSignal
+            {
+                continue;
+            }
+            info!("[SENTINEL] Sinyal grup {}: {:?} (confidence {:.2}). {}", group.name, signal.signal_type, signal.confidence, signal.reason);
+
+            #[cfg(feature = "signal-server")]
+            if let Some(server) = &shared.signal_server {
+                server.publish(&ServerEvent::Signal(Box::new(signal.clone())));
+            }
+            #[cfg(feature = "mqtt")]
+            if let Some(mqtt) = &shared.mqtt {
+                mqtt.publish_signal(&signal);
+            }
+            #[cfg(feature = "sqlite")]
+            if let Some(store) = shared.signal_store.clone() {
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = store.insert(&signal, None) {
+                        error!("[SENTINEL] Gagal menyimpan sinyal grup {} ke database: {}", signal.symbol, e);
+                    }
+                });
+            }
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Install the default crypto provider
-    if let Err(e) = rustls::crypto::ring::default_provider().install_default() {
-        eprintln!("Failed to install default crypto provider: {:?}", e);
-    }
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
-        .format(|buf, record| {
+    // Install the default crypto provider (shared with the Python module, safe to call twice)
+    ofi_engine_rust::initialize_crypto_provider();
+    // `--tui` shows the live dashboard; logs then go to a file so they don't garble it
+    let dashboard_mode = std::env::args().any(|arg| arg == "--tui");
+    let mut logger = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    logger.format(|buf, record| {
             use std::io::Write;
             let timestamp = chrono::Local::now().format("%H:%M:%S");
             let level = record.level();
@@ -214,71 +793,430 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 log::Level::Trace => "TRACE".cyan().bold(),
             };
             writeln!(buf, "[{}] [{}] [{}] {}", timestamp, level_str, record.target(), record.args())
-        })
-        .init();
+        });
+    #[cfg(feature = "tui")]
+    if dashboard_mode {
+        match std::fs::File::create(DASHBOARD_LOG_PATH) {
+            Ok(file) => {
+                logger.target(env_logger::Target::Pipe(Box::new(file)));
+            }
+            Err(e) => eprintln!("[SENTINEL] Gagal membuat {}: {}. Log tetap ke terminal.", DASHBOARD_LOG_PATH, e),
+        }
+    }
+    logger.init();
+    #[cfg(not(feature = "tui"))]
+    if dashboard_mode {
+        warn!("[SENTINEL-WARN] --tui diminta, tetapi binary dibangun tanpa fitur tui. Berjalan headless.");
+    }
+    // `--trace-flame <file>`: analysis cycle spans as folded stacks, for profiling with a flamegraph
+    let trace_flame_path = std::env::args().skip_while(|arg| arg != "--trace-flame").nth(1);
+    #[cfg(feature = "trace-spans")]
+    let _flame_guard = trace_flame_path.and_then(|path| match tracing_flame::FlameLayer::with_file(&path) {
+        Ok((layer, guard)) => {
+            use tracing_subscriber::prelude::*;
+            tracing_subscriber::registry().with(layer).init();
+            info!("[SENTINEL] Span analisis ditulis ke {} (folded stacks).", path);
+            Some(guard)
+        }
+        Err(e) => {
+            warn!("[SENTINEL-WARN] Gagal membuat {}: {}. Span tidak direkam.", path, e);
+            None
+        }
+    });
+    #[cfg(not(feature = "trace-spans"))]
+    if trace_flame_path.is_some() {
+        warn!("[SENTINEL-WARN] --trace-flame diminta, tetapi binary dibangun tanpa fitur trace-spans. Span tidak direkam.");
+    }
 
     let config = OFIConfig::from_default_config()?;
-    let max_concurrent_tasks = config.max_concurrent_websocket_connections.unwrap_or(20);
+
+    // Dump the flight recorder before the default panic report, so a crash leaves its input behind
+    if config.flight_recorder_messages > 0 {
+        let flight_recorder_dir = config.flight_recorder_dir.clone();
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            match FLIGHT_RECORDER.flush(&flight_recorder_dir) {
+                Ok(path) => eprintln!("[SENTINEL-CRITICAL] Panic: pesan mentah terakhir disimpan ke {}", path.display()),
+                Err(e) => eprintln!("[SENTINEL-CRITICAL] Panic: gagal menyimpan flight recorder: {}", e),
+            }
+            default_hook(info);
+        }));
+    }
+
+    // Which layer every setting came from, for debugging layered config
+    if config.config_audit {
+        let audit = OFIConfig::default_config_path()
+            .ok_or_else(|| "config.toml tidak ditemukan".to_string())
+            .and_then(|path| std::fs::read_to_string(path).map_err(|e| e.to_string()))
+            .and_then(|contents| config.source_audit(&contents));
+        match audit {
+            Ok(entries) => {
+                for entry in entries {
+                    info!("[SENTINEL] Config {} = {} ({})", entry.field, entry.value, entry.source);
+                }
+            }
+            Err(e) => warn!("[SENTINEL-WARN] Audit config gagal: {}", e),
+        }
+    }
+
+    // Python screener/executor availability, checked once and handled per the configured policy
+    let python_failures = python_import_failures();
+
+    if config.preflight_checks {
+        info!("[SENTINEL] Menjalankan pre-flight checks...");
+        let checks = run_preflight(&config, &python_failures).await;
+        for check in &checks {
+            if check.passed {
+                info!("[SENTINEL] [PASS] {}: {}", check.name, check.detail);
+            } else {
+                error!("[SENTINEL-CRITICAL] [FAIL] {}: {}", check.name, check.detail);
+            }
+        }
+        let failed = checks.iter().filter(|check| !check.passed).count();
+        if failed > 0 {
+            error!("[SENTINEL-CRITICAL] Pre-flight gagal: {}/{} checks failed. Exiting.", failed, checks.len());
+            std::process::exit(1);
+        }
+        info!("[SENTINEL] Pre-flight OK: {}/{} checks passed.", checks.len(), checks.len());
+    }
+
+    for failure in &python_failures {
+        error!("[SENTINEL-CRITICAL] Modul Python gagal diimpor: {}", failure);
+    }
+    let python_startup = python_startup(config.python_unavailable_policy, &python_failures);
+    match python_startup {
+        PythonStartup::Available => {}
+        PythonStartup::Exit => {
+            error!("[SENTINEL-CRITICAL] Python tidak tersedia dan python_unavailable_policy = FailFast. Exiting.");
+            std::process::exit(1);
+        }
+        PythonStartup::Fallback => {
+            warn!("[SENTINEL-WARN] Python tidak tersedia. Fallback: fallback_watchlist ({} simbol) dan dry-run tanpa eksekusi.", config.fallback_watchlist.len());
+        }
+        PythonStartup::Degraded => {
+            warn!("[SENTINEL-WARN] Python tidak tersedia. Tetap berjalan; screener dan executor akan gagal per panggilan.");
+        }
+    }
+    let python_fallback = python_startup == PythonStartup::Fallback;
+    let max_concurrent_tasks = config.max_concurrent_connections();
     let task_semaphore = Arc::new(Semaphore::new(max_concurrent_tasks));
     let (signal_tx, mut signal_rx) = mpsc::channel(100);
-    let mut running_tasks: HashMap<String, (tokio::task::JoinHandle<()>, mpsc::Sender<()>)> = HashMap::new();
+    // Running tasks with their (staggered) start time, used for the startup grace period
+    let mut running_tasks: HashMap<String, (tokio::task::JoinHandle<()>, mpsc::Sender<()>, std::time::Instant)> = HashMap::new();
+    // Tasks that did not stop in time; their symbol is not respawned until the old task has finished,
+    // so a symbol never has two concurrent tasks
+    let mut stopping_tasks: HashMap<String, tokio::task::JoinHandle<()>> = HashMap::new();
+    let task_startup_grace = TokioDuration::from_secs(config.task_startup_grace_secs);
     let mut watchlist_refresh_timer = interval(TokioDuration::from_secs(900));
 
     info!("[SENTINEL] Setting up periodic position monitoring...");
-    let mut position_monitor_timer = interval(TokioDuration::from_secs(60)); // Every 60 seconds
+    let mut position_monitor_timer = interval(TokioDuration::from_secs(config.position_monitor_interval_secs));
+    // Bounds concurrent monitor calls; a tick finding no permit is skipped instead of piling up
+    let mut monitor_gate = MonitorGate::new(config.max_inflight_position_monitors);
+    let position_store = PositionStore::new(); // Local view of open positions, reconciled by the monitor
+    let mut daily_signals = DailySignalCounter::default(); // Signals forwarded per symbol today (UTC)
+
+    // Optional dead-man's switch: analysis tasks refresh the heartbeat, the watchdog flattens on stall
+    let heartbeat = Heartbeat::new();
+    if config.deadman_timeout_secs > 0 {
+        info!("[SENTINEL] Dead-man's switch aktif: timeout {} detik.", config.deadman_timeout_secs);
+        tokio::spawn(run_deadman_watchdog(heartbeat.clone(), config.deadman_timeout_secs));
+    }
+
+    // Optional global memory cap shared by all analysis tasks, evicting least recently active symbols
+    let memory_budget = (config.global_entry_limit > 0).then(|| MemoryBudget::new(config.global_entry_limit));
+
+    // Warm-up readiness per symbol, published by analysis tasks; execution waits until a symbol is ready
+    let readiness = ReadinessBoard::new();
+
+    // Optional observability sink: latest NoSignal result per symbol, kept apart from the execution path
+    let last_no_signal: Arc<std::sync::Mutex<HashMap<String, LibTradingSignal>>> = Arc::new(std::sync::Mutex::new(HashMap::new()));
+    let observability_tx = if config.emit_no_signal_events {
+        let (tx, mut rx) = mpsc::channel::<LibTradingSignal>(1000);
+        let sink = Arc::clone(&last_no_signal);
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                sink.lock().unwrap().insert(event.symbol.clone(), event);
+            }
+        });
+        Some(tx)
+    } else {
+        None
+    };
+
+    // Optional signal server streaming signals to other processes over a local WebSocket
+    #[cfg(feature = "signal-server")]
+    let signal_server = config.signal_server_addr.clone().map(|addr| {
+        let broadcaster = SignalBroadcaster::new(1000);
+        let server = broadcaster.clone();
+        tokio::spawn(async move {
+            if let Err(e) = run_signal_server(addr, server).await {
+                error!("[SENTINEL] Signal server berhenti: {}", e);
+            }
+        });
+        broadcaster
+    });
+    #[cfg(not(feature = "signal-server"))]
+    if config.signal_server_addr.is_some() {
+        warn!("[SENTINEL-WARN] signal_server_addr diset, tetapi binary dibangun tanpa fitur signal-server. Diabaikan.");
+    }
+
+    // Optional MQTT publisher for edge consumers; the event loop reconnects on broker disconnects
+    #[cfg(feature = "mqtt")]
+    let mqtt = MqttPublisher::from_config(&config).map(|(publisher, event_loop)| {
+        info!("[SENTINEL] Sinyal dipublikasikan ke broker MQTT {}:{}", config.mqtt_host.as_deref().unwrap_or_default(), config.mqtt_port);
+        tokio::spawn(run_mqtt_event_loop(event_loop, TokioDuration::from_secs(config.mqtt_reconnect_secs)));
+        publisher
+    });
+    #[cfg(not(feature = "mqtt"))]
+    if config.mqtt_host.is_some() {
+        warn!("[SENTINEL-WARN] mqtt_host diset, tetapi binary dibangun tanpa fitur mqtt. Diabaikan.");
+    }
+
+    // Optional SQLite store of emitted signals
+    #[cfg(feature = "sqlite")]
+    let signal_store = match &config.signal_db_path {
+        Some(path) => match SignalStore::open(path) {
+            Ok(store) => {
+                info!("[SENTINEL] Sinyal disimpan ke database {}", path);
+                Some(store)
+            }
+            Err(e) => {
+                error!("[SENTINEL] Gagal membuka database sinyal {}: {}. Sinyal tidak disimpan.", path, e);
+                None
+            }
+        },
+        None => None,
+    };
+    #[cfg(not(feature = "sqlite"))]
+    if config.signal_db_path.is_some() {
+        warn!("[SENTINEL-WARN] signal_db_path diset, tetapi binary dibangun tanpa fitur sqlite. Diabaikan.");
+    }
+
+    let task_shared = TaskShared {
+        heartbeat: heartbeat.clone(),
+        observability_tx,
+        memory_budget,
+        readiness: readiness.clone(),
+        invalid_symbols: Arc::new(std::sync::Mutex::new(HashSet::new())),
+        groups: (!config.groups.is_empty()).then(GroupRegistry::new),
+        #[cfg(feature = "signal-server")]
+        signal_server,
+        #[cfg(feature = "mqtt")]
+        mqtt,
+        #[cfg(feature = "sqlite")]
+        signal_store,
+        #[cfg(feature = "tui")]
+        dashboard: dashboard_mode.then(DashboardRegistry::new),
+    };
+
+    // Optional group (sector) signals aggregated across the members' analysis tasks
+    if let Some(registry) = task_shared.groups.clone() {
+        info!("[SENTINEL] {} grup simbol dievaluasi setiap {}ms.", config.groups.len(), config.analysis_duration_per_cycle_ms);
+        let cycle = TokioDuration::from_millis(config.analysis_duration_per_cycle_ms);
+        tokio::spawn(run_group_signals(config.groups.clone(), registry, cycle, task_shared.clone()));
+    }
+
+    // Optional terminal dashboard; quitting it shuts the Sentinel down like Ctrl+C
+    #[cfg(feature = "tui")]
+    let (dashboard_quit_tx, dashboard_quit_rx) = tokio::sync::oneshot::channel::<()>();
+    #[cfg(feature = "tui")]
+    if let Some(dashboard) = task_shared.dashboard.clone() {
+        let refresh = TokioDuration::from_millis(config.dashboard_refresh_ms);
+        let max_book_age_ms = config.book_quality_max_age_ms;
+        tokio::spawn(async move {
+            if let Err(e) = run_dashboard(dashboard, refresh, max_book_age_ms).await {
+                error!("[SENTINEL] Dashboard berhenti: {}", e);
+            }
+            let _ = dashboard_quit_tx.send(());
+        });
+    }
+
+    // Pause flags for the periodic loops; toggled at runtime through the status endpoint
+    let (watchlist_pause_tx, watchlist_paused) = watch::channel(false);
+    let (monitor_pause_tx, monitor_paused) = watch::channel(false);
+
+    // Optional status endpoint for runtime introspection
+    if let Some(addr) = config.status_http_addr.clone() {
+        let status_config = config.clone();
+        let status_positions = position_store.clone();
+        let status_no_signal = Arc::clone(&last_no_signal);
+        let status_readiness = readiness.clone();
+        let handler: StatusHandler = Arc::new(move |method, path| match (method, path) {
+            // Re-read so the dump shows what tasks started from now on run with, after edits to config.toml
+            ("GET", "/config") => Some(match OFIConfig::from_default_config() {
+                Ok(live_config) => (200, live_config.effective_dump()),
+                Err(e) => (500, serde_json::json!({ "error": e.to_string() }).to_string()),
+            }),
+            ("GET", "/positions") => Some((200, serde_json::to_string_pretty(&status_positions.all()).unwrap_or_default())),
+            ("GET", "/no-signal") => Some((200, serde_json::to_string_pretty(&*status_no_signal.lock().unwrap()).unwrap_or_default())),
+            ("GET", "/ready") => Some((200, serde_json::to_string_pretty(&status_readiness.snapshot()).unwrap_or_default())),
+            ("GET", "/timers") => Some((200, format!(
+                "{{\"watchlist_refresh_paused\": {}, \"position_monitor_paused\": {}}}",
+                *watchlist_pause_tx.borrow(),
+                *monitor_pause_tx.borrow()
+            ))),
+            ("POST", "/timers/watchlist/pause") => Some((200, timer_state("watchlist_refresh", watchlist_pause_tx.send_replace(true), true))),
+            ("POST", "/timers/watchlist/resume") => Some((200, timer_state("watchlist_refresh", watchlist_pause_tx.send_replace(false), false))),
+            ("POST", "/timers/position-monitor/pause") => Some((200, timer_state("position_monitor", monitor_pause_tx.send_replace(true), true))),
+            ("POST", "/timers/position-monitor/resume") => Some((200, timer_state("position_monitor", monitor_pause_tx.send_replace(false), false))),
+            ("POST", "/flight-recorder/flush") if status_config.flight_recorder_messages > 0 => {
+                Some(match FLIGHT_RECORDER.flush(&status_config.flight_recorder_dir) {
+                    Ok(path) => (200, serde_json::json!({ "path": path.display().to_string() }).to_string()),
+                    Err(e) => (500, serde_json::json!({ "error": e.to_string() }).to_string()),
+                })
+            }
+            _ => None,
+        });
+        tokio::spawn(async move {
+            if let Err(e) = run_status_server(addr, handler).await {
+                error!("[SENTINEL] Status endpoint berhenti: {}", e);
+            }
+        });
+    }
 
     info!("[SENTINEL] OFI Sentinel Dimulai. Maksimum koneksi simultan: {}", max_concurrent_tasks);
 
+    #[cfg(not(feature = "tui"))]
+    let shutdown = tokio::signal::ctrl_c();
+    // Without the dashboard nothing is ever sent on the quit channel
+    #[cfg(feature = "tui")]
+    let shutdown = async {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            Ok(()) = dashboard_quit_rx => {}
+        }
+    };
+    tokio::pin!(shutdown);
+
     loop {
         tokio::select! {
             _ = watchlist_refresh_timer.tick() => {
+                if !tick_enabled(&watchlist_paused) {
+                    info!("[SENTINEL] Watchlist refresh dijeda. Melewati tick ini.");
+                    continue;
+                }
                 info!("[SENTINEL] Waktunya menyegarkan watchlist...");
-                let new_candidates = call_python_screener().unwrap_or_else(|e| {
-                    error!("[SENTINEL] Gagal mendapatkan kandidat dari Python: {}. Menggunakan watchlist kosong.", e);
-                    Vec::new()
-                });
+                let mut new_candidates = refresh_candidates(python_fallback, &config.fallback_watchlist, call_python_screener);
+                // Synthetic spreads and group members always run alongside the screener's candidates
+                let pinned = config.pinned_symbols();
+                if config.rank_watchlist {
+                    // Ranked mode: pinned symbols and tasks still in their startup grace (which are
+                    // never stopped below) take their connections first, and only the top candidates
+                    // that fit the remaining permits keep or get one, so lower-ranked running tasks
+                    // are stopped below to make room
+                    let mut reserved = pinned;
+                    for (symbol, (_, _, started_at)) in &running_tasks {
+                        if in_startup_grace(*started_at, std::time::Instant::now(), task_startup_grace) && !reserved.contains(symbol) {
+                            reserved.push(symbol.clone());
+                        }
+                    }
+                    let screened: Vec<String> = new_candidates.into_iter().filter(|symbol| !reserved.contains(symbol)).collect();
+                    let total = screened.len();
+                    let ranked = ranked_watchlist(screened, max_concurrent_tasks.saturating_sub(reserved.len()));
+                    if ranked.len() < total {
+                        info!("[SENTINEL] Watchlist diperingkat: {} dari {} kandidat teratas dipakai ({} slot tetap).", ranked.len(), total, reserved.len());
+                    }
+                    new_candidates = reserved;
+                    new_candidates.extend(ranked);
+                } else {
+                    for symbol in pinned {
+                        if !new_candidates.contains(&symbol) {
+                            new_candidates.push(symbol);
+                        }
+                    }
+                }
+
+                // Rejected symbols stay out while the screener still lists them; once it drops them
+                // the mark is cleared, so a later relisting is picked up again
+                {
+                    let mut invalid_symbols = task_shared.invalid_symbols.lock().unwrap();
+                    invalid_symbols.retain(|symbol| new_candidates.contains(symbol));
+                    for symbol in invalid_symbols.iter() {
+                        warn!("[SENTINEL-WARN] {} ditandai tidak valid oleh exchange. Dilewati pada refresh ini.", symbol);
+                        // The stopped task would otherwise hold its slot
+                        if running_tasks.get(symbol).is_some_and(|(handle, _, _)| handle.is_finished()) {
+                            running_tasks.remove(symbol);
+                        }
+                    }
+                    new_candidates.retain(|symbol| !invalid_symbols.contains(symbol));
+                }
 
                 let mut symbols_to_stop = Vec::new();
-                for symbol in running_tasks.keys() {
+                for (symbol, (_, _, started_at)) in &running_tasks {
                     if !new_candidates.contains(symbol) {
+                        // Tasks still starting up are left alone so slow connects don't cause churn
+                        if in_startup_grace(*started_at, std::time::Instant::now(), task_startup_grace) {
+                            info!("[SENTINEL] Task untuk {} masih dalam masa startup grace. Tidak dihentikan pada refresh ini.", symbol);
+                            continue;
+                        }
                         symbols_to_stop.push(symbol.clone());
                     }
                 }
 
                 for symbol in symbols_to_stop {
                     info!("[SENTINEL] Menghentikan task untuk simbol: {}", symbol);
-                    if let Some((handle, shutdown_tx)) = running_tasks.remove(&symbol) {
+                    if let Some((mut handle, shutdown_tx, _)) = running_tasks.remove(&symbol) {
                         let _ = shutdown_tx.send(()).await;
-                        match tokio::time::timeout(TokioDuration::from_secs(5), handle).await {
+                        match tokio::time::timeout(TokioDuration::from_secs(5), &mut handle).await {
                             Ok(_) => info!("[SENTINEL] Task untuk {} berhasil dihentikan.", symbol),
-                            Err(_) => warn!("[SENTINEL-WARN] Task untuk {} gagal berhenti dalam 5 detik.", symbol),
+                            Err(_) => {
+                                warn!("[SENTINEL-WARN] Task untuk {} gagal berhenti dalam 5 detik.", symbol);
+                                stopping_tasks.insert(symbol, handle);
+                            }
                         }
                     }
                 }
+                stopping_tasks.retain(|_, handle| !handle.is_finished());
 
-                for candidate in &new_candidates {
-                    if !running_tasks.contains_key(candidate) {
-                        info!("[SENTINEL] Memulai task baru untuk: {}", candidate);
-                        let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
-                        let semaphore = Arc::clone(&task_semaphore);
-                        let tx = signal_tx.clone();
-                        let symbol_clone = candidate.clone();
+                // New tasks are staggered so sockets and subscriptions come up gradually
+                start_missing_tasks(&mut running_tasks, &stopping_tasks, &new_candidates, |candidate, new_task_index| {
+                    info!("[SENTINEL] Memulai task baru untuk: {}", candidate);
+                    let (shutdown_tx, mut shutdown_rx) = mpsc::channel(1);
+                    let semaphore = Arc::clone(&task_semaphore);
+                    let tx = signal_tx.clone();
+                    let symbol_clone = candidate.to_string();
+                    let shared = task_shared.clone();
+                    let start_delay = task_start_delay(config.task_start_stagger_ms, new_task_index);
 
-                        let task_handle = tokio::spawn(async move {
-                            let _permit = semaphore.acquire().await.expect("Semaphore should not be closed");
-                            spawn_analysis_task(symbol_clone, tx, shutdown_rx).await;
-                        });
+                    let task_handle = tokio::spawn(async move {
+                        // Shutdown during the stagger delay or the wait for a permit ends the
+                        // task before it starts, so Ctrl+C doesn't wait the delay out
+                        let waited = tokio::select! {
+                            permit = async {
+                                if !start_delay.is_zero() {
+                                    tokio::time::sleep(start_delay).await;
+                                }
+                                semaphore.acquire().await
+                            } => permit,
+                            _ = shutdown_rx.recv() => return,
+                        };
+                        let _permit = waited.expect("Semaphore should not be closed");
+                        spawn_analysis_task(symbol_clone, tx, shutdown_rx, shared).await;
+                    });
 
-                        running_tasks.insert(candidate.clone(), (task_handle, shutdown_tx));
-                    }
-                }
+                    (task_handle, shutdown_tx, std::time::Instant::now() + start_delay)
+                });
                 info!("[SENTINEL] Sisa kuota task: {}/{}", task_semaphore.available_permits(), max_concurrent_tasks);
             },
 
             _ = position_monitor_timer.tick() => {
+                if !tick_enabled(&monitor_paused) {
+                    info!("[SENTINEL] Position monitoring paused. Skipping this tick.");
+                    continue;
+                }
+                if python_fallback {
+                    continue;
+                }
+                let Some(permit) = monitor_gate.try_start() else {
+                    warn!("[SENTINEL-WARN] Position monitor sebelumnya masih berjalan. Tick dilewati ({} total).", monitor_gate.skipped_ticks);
+                    continue;
+                };
                 info!("[SENTINEL] Running periodic position monitoring...");
-                tokio::spawn(async {
-                    if let Err(e) = call_python_position_monitor() {
+                let positions = position_store.clone();
+                tokio::spawn(async move {
+                    let _permit = permit;
+                    if let Err(e) = call_python_position_monitor(positions) {
                         error!("[SENTINEL] Gagal memanggil position monitor Python: {}. Melanjutkan...", e);
                     }
                 });
@@ -286,14 +1224,344 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             Some(signal) = signal_rx.recv() => {
                 info!("[SENTINEL] Menerima sinyal: {:?}", signal);
+                // A synthetic spread is no tradable instrument: its signals are recorded/streamed by
+                // the analysis task, but executing them would send the spread's name as a symbol
+                if config.synthetic(&signal.symbol).is_some() {
+                    info!("[SENTINEL] Sinyal {} untuk spread sintetis {} tidak dieksekusi.", signal.signal_type, signal.symbol);
+                    continue;
+                }
+                if !readiness.is_ready(&signal.symbol) {
+                    warn!("[SENTINEL-WARN] {} belum siap (warm-up). Sinyal {} tidak dieksekusi.", signal.symbol, signal.signal_type);
+                    continue;
+                }
+                // Safe-mode: hard cap on signals forwarded per symbol per UTC day
+                if config.max_daily_signals_per_symbol > 0
+                    && !daily_signals.try_record(&signal.symbol, signal.timestamp.date_naive(), config.max_daily_signals_per_symbol)
+                {
+                    warn!("[SENTINEL-WARN] Batas harian {} sinyal untuk {} tercapai. Sinyal {} tidak dieksekusi.", config.max_daily_signals_per_symbol, signal.symbol, signal.signal_type);
+                    continue;
+                }
+                // Fallback without Python: signals are only logged
+                if python_fallback {
+                    info!("[SENTINEL] [DRY-RUN] Sinyal {} {} @ {} tidak dieksekusi (Python tidak tersedia).", signal.signal_type, signal.symbol, signal.price);
+                    continue;
+                }
                 // Spawn a task to handle the Python execution to avoid blocking the main loop
                 let signal_clone = signal.clone();
+                let positions = position_store.clone();
+                let (max_retries, backoff_ms) = (config.executor_max_retries, config.executor_retry_backoff_ms);
                 tokio::spawn(async move {
-                    if let Err(e) = call_python_executor(signal_clone) {
+                    if let Err(e) = call_python_executor(signal_clone, positions, max_retries, backoff_ms).await {
                         error!("[SENTINEL] Gagal memanggil executor Python: {}. Melanjutkan...", e);
                     }
                 });
+            },
+
+            _ = &mut shutdown => {
+                info!("[SENTINEL] Menerima sinyal shutdown. Menghentikan timer dan semua task...");
+                break;
+            }
+        }
+    }
+
+    // Timers are dropped with the loop; stop analysis tasks before exiting
+    for (symbol, (handle, shutdown_tx, _)) in running_tasks.drain() {
+        let _ = shutdown_tx.send(()).await;
+        if tokio::time::timeout(TokioDuration::from_secs(5), handle).await.is_err() {
+            warn!("[SENTINEL-WARN] Task untuk {} gagal berhenti dalam 5 detik.", symbol);
+        }
+    }
+    for (_, handle) in stopping_tasks.drain() {
+        handle.abort();
+    }
+    info!("[SENTINEL] OFI Sentinel berhenti.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(price: f64) -> TradingSignal {
+        TradingSignal {
+            symbol: "BTCUSDT".to_string(),
+            signal_type: "StrongBuy".to_string(),
+            price,
+            tier: None,
+            max_advisable_size: None,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_executor_failures_are_retried_with_one_idempotency_key() {
+        let signal = signal(67012.5);
+        let key = idempotency_key(&signal);
+        assert_eq!(key, idempotency_key(&signal.clone()), "key is derived from the signal alone");
+
+        // Mock executor: fails transiently twice, then accepts the order
+        let mut seen_keys = Vec::new();
+        let result = retry_executor(&signal.symbol, &key, 3, 10, |attempt_key| {
+            seen_keys.push(attempt_key);
+            let transient = seen_keys.len() <= 2;
+            async move { Ok(transient) }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(seen_keys.len(), 3, "two retries after the initial attempt");
+        assert!(seen_keys.iter().all(|attempt_key| *attempt_key == key));
+    }
+
+    #[tokio::test]
+    async fn rejections_and_exceptions_are_not_retried() {
+        let mut attempts = 0;
+        let rejected = retry_executor("BTCUSDT", "key", 3, 10, |_| {
+            attempts += 1;
+            async { Ok(false) }
+        })
+        .await;
+        assert!(rejected.is_ok());
+        assert_eq!(attempts, 1, "a business-logic rejection is final");
+
+        let mut attempts = 0;
+        let raised = retry_executor("BTCUSDT", "key", 3, 10, |_| {
+            attempts += 1;
+            async { Err(pyo3::exceptions::PyRuntimeError::new_err("no module named execution_service")) }
+        })
+        .await;
+        assert!(raised.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn retries_stop_after_the_configured_count_and_back_off_exponentially() {
+        let mut attempts = 0;
+        let result = retry_executor("BTCUSDT", "key", 2, 100, |_| {
+            attempts += 1;
+            async { Ok(true) }
+        })
+        .await;
+        assert!(result.is_ok(), "exhausted retries are logged, not raised");
+        assert_eq!(attempts, 3);
+        assert_eq!((1..=3).map(|attempt| executor_retry_delay(100, attempt)).collect::<Vec<_>>(), [100, 200, 400].map(TokioDuration::from_millis));
+    }
+
+    #[test]
+    fn new_tasks_start_spaced_by_the_stagger_interval() {
+        let delays: Vec<TokioDuration> = (0..4).map(|index| task_start_delay(250, index)).collect();
+        assert_eq!(delays, [0, 250, 500, 750].map(TokioDuration::from_millis));
+        assert!(delays.windows(2).all(|pair| pair[1] - pair[0] == TokioDuration::from_millis(250)));
+    }
+
+    #[test]
+    fn zero_stagger_starts_every_task_at_once() {
+        assert!((0..20).all(|index| task_start_delay(0, index).is_zero()));
+    }
+
+    #[test]
+    fn paused_timer_skips_its_ticks_until_resumed() {
+        let (pause_tx, paused) = watch::channel(false);
+        assert!(tick_enabled(&paused));
+
+        assert!(!pause_tx.send_replace(true), "was running");
+        assert!(!tick_enabled(&paused));
+        assert!(pause_tx.send_replace(true), "pausing twice is a no-op");
+        assert!(!tick_enabled(&paused));
+
+        assert!(pause_tx.send_replace(false));
+        assert!(tick_enabled(&paused));
+    }
+
+    #[test]
+    fn timer_state_reports_the_transition() {
+        let state: serde_json::Value = serde_json::from_str(&timer_state("position_monitor", false, true)).unwrap();
+        assert_eq!(state, serde_json::json!({ "timer": "position_monitor", "was_paused": false, "paused": true }));
+        // Names are escaped, not spliced into the JSON
+        let state: serde_json::Value = serde_json::from_str(&timer_state("a\"b", true, false)).unwrap();
+        assert_eq!(state["timer"], "a\"b");
+    }
+
+    #[test]
+    fn stalled_heartbeat_flattens_exactly_once_after_the_timeout() {
+        let mut switch = DeadmanSwitch::new(10);
+        // One check per second while the heartbeat goes stale
+        let flattens = (0..30).filter(|second| switch.observe(second * 1000)).count();
+        assert_eq!(flattens, 1);
+
+        let mut switch = DeadmanSwitch::new(10);
+        assert!(!switch.observe(10_000), "the timeout itself is still fresh");
+        assert!(switch.observe(10_001));
+        assert!(!switch.observe(60_000));
+
+        // A huge configured timeout saturates instead of wrapping to a negative one
+        assert!(!DeadmanSwitch::new(u64::MAX).observe(i64::MAX));
+    }
+
+    #[test]
+    fn deadman_switch_rearms_after_recovery() {
+        let mut switch = DeadmanSwitch::new(10);
+        assert!(switch.observe(11_000));
+        assert!(!switch.observe(200), "recovered");
+        assert!(switch.observe(12_000), "a second stall flattens again");
+
+        switch.rearm();
+        assert!(switch.observe(13_000));
+    }
+
+    #[tokio::test]
+    async fn preflight_reports_a_reachable_endpoint_and_a_missing_module() {
+        // Mock exchange: accepts every WebSocket handshake
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let _ = tokio_tungstenite::accept_async(stream).await;
             }
+        });
+        let config = OFIConfig { websocket_url: url, python_unavailable_policy: PythonUnavailablePolicy::FailFast, ..OFIConfig::default() };
+        let failures = vec!["execution_service.manager: ModuleNotFoundError: No module named 'execution_service'".to_string()];
+
+        let checks = run_preflight(&config, &failures).await;
+        let check = |name: &str| checks.iter().find(|check| check.name == name).unwrap_or_else(|| panic!("no {} check", name));
+        assert!(!check("config").passed, "required settings are missing from the default config");
+        assert!(check("websocket").passed, "{}", check("websocket").detail);
+        assert!(!check("python").passed);
+        assert!(check("python").detail.contains("execution_service.manager"));
+
+        // Under Fallback, missing Python is handled by the policy rather than failed here
+        let fallback = OFIConfig { python_unavailable_policy: PythonUnavailablePolicy::Fallback, ..config };
+        assert!(run_preflight(&fallback, &failures).await.iter().all(|check| check.name != "python"));
+    }
+
+    #[tokio::test]
+    async fn preflight_fails_an_unreachable_endpoint() {
+        // Mock endpoint: accepts connections and closes them without a WebSocket handshake
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                drop(stream);
+            }
+        });
+        let config = OFIConfig { websocket_url: format!("ws://{}", addr), ..OFIConfig::default() };
+        let checks = run_preflight(&config, &[]).await;
+        let websocket = checks.iter().find(|check| check.name == "websocket").unwrap();
+        assert!(!websocket.passed);
+        assert!(websocket.detail.contains(&addr.to_string()));
+    }
+
+    #[test]
+    fn tasks_within_the_startup_grace_are_not_stopped() {
+        let grace = TokioDuration::from_secs(30);
+        let started = std::time::Instant::now();
+
+        // A refresh shortly after the start leaves the task alone
+        assert!(in_startup_grace(started, started + TokioDuration::from_secs(5), grace));
+        // A staggered task that hasn't started yet is in its grace too
+        assert!(in_startup_grace(started + TokioDuration::from_secs(10), started, grace));
+        // Once the grace has passed the task is subject to the refresh again
+        assert!(!in_startup_grace(started, started + grace, grace));
+        assert!(!in_startup_grace(started, started + TokioDuration::from_secs(60), grace));
+        // No grace configured: never exempt
+        assert!(!in_startup_grace(started, started, TokioDuration::ZERO));
+    }
+
+    #[test]
+    fn daily_signal_cap_is_enforced_within_a_day_and_resets_at_midnight() {
+        let mut counter = DailySignalCounter::default();
+        let day = chrono::NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        let next_day = day.succ_opt().unwrap();
+
+        assert!(counter.try_record("BTCUSDT", day, 2));
+        assert!(counter.try_record("BTCUSDT", day, 2));
+        assert!(!counter.try_record("BTCUSDT", day, 2), "third signal of the day is over the cap");
+        // The cap is per symbol
+        assert!(counter.try_record("ETHUSDT", day, 2));
+
+        // Crossing UTC midnight resets every symbol's count
+        assert!(counter.try_record("BTCUSDT", next_day, 2));
+        assert!(counter.try_record("BTCUSDT", next_day, 2));
+        assert!(!counter.try_record("BTCUSDT", next_day, 2));
+    }
+
+    #[test]
+    fn top_ranked_candidates_get_the_connections() {
+        let candidates: Vec<String> = ["SOLUSDT", "BTCUSDT", "SOLUSDT", "ETHUSDT", "XRPUSDT", "DOGEUSDT"].iter().map(|s| s.to_string()).collect();
+
+        // Three permits: the screener's top three distinct symbols, in its order
+        assert_eq!(ranked_watchlist(candidates.clone(), 3), vec!["SOLUSDT", "BTCUSDT", "ETHUSDT"]);
+        // Enough permits keeps every distinct candidate
+        assert_eq!(ranked_watchlist(candidates.clone(), 10).len(), 5);
+        assert!(ranked_watchlist(candidates, 0).is_empty());
+    }
+
+    #[test]
+    fn near_simultaneous_refreshes_start_one_task_per_symbol() {
+        let mut running: HashMap<String, u64> = HashMap::new();
+        let stopping: HashMap<String, ()> = HashMap::new();
+        let mut spawned = Vec::new();
+        let candidates: Vec<String> = ["SOLUSDT", "SOLUSDT", "BTCUSDT"].iter().map(|s| s.to_string()).collect();
+
+        // Two refreshes back to back, the first listing the new symbol twice
+        for _ in 0..2 {
+            start_missing_tasks(&mut running, &stopping, &candidates, |candidate, index| {
+                spawned.push(candidate.to_string());
+                index
+            });
         }
+        assert_eq!(spawned, vec!["SOLUSDT", "BTCUSDT"]);
+        assert_eq!((running["SOLUSDT"], running["BTCUSDT"]), (0, 1), "staggered by start order");
+
+        // A symbol whose old task hasn't stopped is held back
+        let stopping: HashMap<String, ()> = HashMap::from([("ETHUSDT".to_string(), ())]);
+        start_missing_tasks(&mut running, &stopping, &["ETHUSDT".to_string()], |_, index| index);
+        assert!(!running.contains_key("ETHUSDT"));
+    }
+
+    #[test]
+    fn missing_python_modules_are_handled_per_the_policy() {
+        let failures = vec!["screener.screener: ModuleNotFoundError: No module named 'screener'".to_string()];
+        let fallback_watchlist = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+
+        assert_eq!(python_startup(PythonUnavailablePolicy::FailFast, &failures), PythonStartup::Exit);
+        assert_eq!(python_startup(PythonUnavailablePolicy::Ignore, &failures), PythonStartup::Degraded);
+        assert_eq!(python_startup(PythonUnavailablePolicy::Fallback, &failures), PythonStartup::Fallback);
+        // With every module importable the policy never applies
+        for policy in [PythonUnavailablePolicy::FailFast, PythonUnavailablePolicy::Fallback, PythonUnavailablePolicy::Ignore] {
+            assert_eq!(python_startup(policy, &[]), PythonStartup::Available);
+        }
+
+        // Fallback analyzes the native watchlist without calling the screener
+        let candidates = refresh_candidates(true, &fallback_watchlist, || -> Result<Vec<String>, String> { panic!("screener called in fallback") });
+        assert_eq!(candidates, fallback_watchlist);
+        // Otherwise a failing screener leaves the watchlist empty
+        assert!(refresh_candidates(false, &fallback_watchlist, || Err::<Vec<String>, _>("No module named 'screener'")).is_empty());
+        assert_eq!(refresh_candidates(false, &[], || Ok::<_, String>(vec!["SOLUSDT".to_string()])), vec!["SOLUSDT"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn overlapping_monitor_ticks_are_skipped_not_queued() {
+        let mut gate = MonitorGate::new(1);
+        let started = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut timer = interval(TokioDuration::from_secs(60));
+
+        // Ticks at 0s, 60s, 120s and 180s; the mock monitor call takes 150s
+        for _ in 0..4 {
+            timer.tick().await;
+            let Some(permit) = gate.try_start() else { continue };
+            let started = Arc::clone(&started);
+            tokio::spawn(async move {
+                let _permit = permit;
+                started.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                tokio::time::sleep(TokioDuration::from_secs(150)).await;
+            });
+            tokio::task::yield_now().await;
+        }
+
+        // The ticks at 60s and 120s found the first call running; the one at 180s started anew
+        assert_eq!(gate.skipped_ticks, 2);
+        assert_eq!(started.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(gate.inflight.available_permits(), 0, "only the second call is in flight");
     }
 }
@@ -0,0 +1,8 @@
+//! Print the JSON Schemas of the config and signal types
+//!
+//! Usage: cargo run --features schema --bin schema > schemas.json
+
+fn main() {
+    let schemas = ofi_engine_rust::schema::json_schemas();
+    println!("{}", serde_json::to_string_pretty(&schemas).expect("JSON Schema should serialize"));
+}
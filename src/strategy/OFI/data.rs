@@ -5,9 +5,11 @@
 use crate::config::OFIConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
 /// Represents a level in the order book
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OrderBookLevel {
     pub price: f64,
     pub quantity: f64,
@@ -33,10 +35,35 @@ pub struct TradeData {
     pub timestamp: u64,
 }
 
+/// Order flow imbalance contribution of one book update: top-of-book size added on the bid
+/// and removed from the ask count positive, the reverse counts negative
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BookFlowEvent {
+    pub timestamp: u64,
+    pub ofi: f64,
+    pub imbalance: f64,  // ln(bid / ask notional) of the updated book, 0.0 when a side has no notional
+    pub spread_bps: f64,  // Spread of the updated book in basis points of its mid, 0.0 without a positive mid
+    pub touch_imbalance: f64,  // Best bid size / best ask size of the updated book
+    pub bid_price: f64,  // Touch of the updated book, for replenishment tracking
+    pub bid_size: f64,
+    pub ask_price: f64,
+    pub ask_size: f64,
+}
+
+/// A normalized market data update as the engine stored it, fanned out to raw feed subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MarketDataEvent {
+    OrderBook(OrderBookSnapshot),
+    Trade(TradeData),
+}
+
 /// In-memory storage for order book data
 #[derive(Debug, Clone, Default)]
 pub struct OrderBookStorage {
     pub books: HashMap<String, OrderBookSnapshot>,
+    pub flow_events: HashMap<String, Vec<BookFlowEvent>>,  // Per-symbol, oldest first
+    pub versions: HashMap<String, u64>,  // Per-symbol book version, incremented on every update
+    pub update_times: HashMap<String, Vec<u64>>,  // Per-symbol book timestamps within the lookback, oldest first
 }
 
 impl OrderBookStorage {
@@ -45,18 +72,59 @@ impl OrderBookStorage {
     }
 
     pub fn update_order_book(&mut self, book: OrderBookSnapshot) {
+        *self.versions.entry(book.symbol.clone()).or_insert(0) += 1;
         self.books.insert(book.symbol.clone(), book);
     }
 
+    /// Version of the stored book for a symbol, 0 if none was ever stored
+    pub fn get_version(&self, symbol: &str) -> u64 {
+        self.versions.get(symbol).copied().unwrap_or(0)
+    }
+
     pub fn get_order_book(&self, symbol: &str) -> Option<&OrderBookSnapshot> {
         self.books.get(symbol)
     }
+
+    /// Append a flow event, dropping the symbol's events older than `cutoff`
+    pub fn add_flow_event(&mut self, symbol: &str, event: BookFlowEvent, cutoff: u64) {
+        let events = self.flow_events.entry(symbol.to_string()).or_default();
+        events.push(event);
+        events.retain(|event| event.timestamp >= cutoff);
+    }
+
+    pub fn get_flow_events(&self, symbol: &str) -> &[BookFlowEvent] {
+        self.flow_events.get(symbol).map_or(&[], |events| events.as_slice())
+    }
+
+    /// Record a book update time, dropping the symbol's update times older than `cutoff`
+    pub fn record_update_time(&mut self, symbol: &str, timestamp: u64, cutoff: u64) {
+        let times = self.update_times.entry(symbol.to_string()).or_default();
+        times.push(timestamp);
+        times.retain(|time| *time >= cutoff);
+    }
+
+    /// Book updates per second in the `window_ms` before `now`, measured from the oldest update
+    /// in the window to `now` so a stalled feed decays towards 0. 0.0 with fewer than two updates.
+    pub fn update_rate(&self, symbol: &str, now: u64, window_ms: u64) -> f64 {
+        let cutoff = now.saturating_sub(window_ms);
+        let recent: Vec<u64> = self.update_times.get(symbol).map_or_else(Vec::new, |times| {
+            times.iter().copied().filter(|time| *time >= cutoff && *time <= now).collect()
+        });
+        match recent.first() {
+            Some(oldest) if recent.len() >= 2 => (recent.len() - 1) as f64 * 1000.0 / now.saturating_sub(*oldest).max(1) as f64,
+            _ => 0.0,
+        }
+    }
 }
 
 /// In-memory storage for trade data
 #[derive(Debug, Clone, Default)]
 pub struct TradeStorage {
     pub trades: HashMap<String, Vec<TradeData>>,
+    pub session_cvd: HashMap<String, f64>,  // Running cumulative delta since the task started (or resumed)
+    pub versions: HashMap<String, u64>,  // Per-symbol trade version, incremented on every added trade
+    pub reorder_buffers: HashMap<String, Vec<TradeData>>,  // Trades held back for `reorder_buffer_ms`, not yet applied
+    pub newest_timestamps: HashMap<String, u64>,  // Newest trade timestamp seen per symbol, drives the reorder buffer release
 }
 
 impl TradeStorage {
@@ -65,6 +133,46 @@ impl TradeStorage {
     }
 
     pub fn add_trade(&mut self, trade: TradeData, config: &OFIConfig) {
+        if config.reorder_buffer_ms == 0 {
+            self.apply_trade(trade, config);
+            return;
+        }
+
+        // Hold the trade until the stream has moved `reorder_buffer_ms` past it, then release
+        // everything that old in timestamp order. A trade arriving later than that is applied late.
+        let symbol = trade.symbol.clone();
+        let newest = self.newest_timestamps.entry(symbol.clone()).or_insert(0);
+        *newest = (*newest).max(trade.timestamp);
+        let release_before = newest.saturating_sub(config.reorder_buffer_ms);
+
+        let buffer = self.reorder_buffers.entry(symbol).or_default();
+        buffer.push(trade);
+        buffer.sort_by_key(|trade| trade.timestamp);
+        let held = buffer.partition_point(|trade| trade.timestamp <= release_before);
+        let released: Vec<TradeData> = buffer.drain(..held).collect();
+        for trade in released {
+            self.apply_trade(trade, config);
+        }
+    }
+
+    /// Apply every trade still held in the symbol's reorder buffer, in timestamp order
+    pub fn flush_reorder_buffer(&mut self, symbol: &str, config: &OFIConfig) {
+        if let Some(buffer) = self.reorder_buffers.remove(symbol) {
+            for trade in buffer {
+                self.apply_trade(trade, config);
+            }
+        }
+    }
+
+    fn apply_trade(&mut self, trade: TradeData, config: &OFIConfig) {
+        let signed_notional = match trade.side.as_str() {
+            "buy" => trade.price * trade.quantity,
+            "sell" => -trade.price * trade.quantity,
+            _ => 0.0,
+        };
+        *self.session_cvd.entry(trade.symbol.clone()).or_insert(0.0) += signed_notional;
+        *self.versions.entry(trade.symbol.clone()).or_insert(0) += 1;
+
         let entry = self.trades.entry(trade.symbol.clone()).or_default();
         entry.push(trade);
         // Keep only the last N trades to prevent memory leak, using config value
@@ -77,10 +185,151 @@ impl TradeStorage {
         self.trades.get(symbol)
     }
 
+    /// Version of the stored trades for a symbol, 0 if none was ever added
+    pub fn get_version(&self, symbol: &str) -> u64 {
+        self.versions.get(symbol).copied().unwrap_or(0)
+    }
+
+    pub fn get_session_cvd(&self, symbol: &str) -> f64 {
+        self.session_cvd.get(symbol).copied().unwrap_or(0.0)
+    }
+
+    pub fn set_session_cvd(&mut self, symbol: &str, cvd: f64) {
+        self.session_cvd.insert(symbol.to_string(), cvd);
+    }
+
     pub fn get_recent_trades(&self, symbol: &str, limit: usize) -> Vec<&TradeData> {
         self.trades
             .get(symbol)
             .map(|trades| trades.iter().rev().take(limit).collect())
             .unwrap_or_else(Vec::new)
     }
-}
\ No newline at end of file
+}
+
+/// Session cumulative delta persisted per symbol so a restarted task can resume it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedCvd {
+    pub symbol: String,
+    pub cvd: f64,
+    pub updated_at_ms: i64,
+}
+
+impl PersistedCvd {
+    fn path(dir: &str, symbol: &str) -> std::path::PathBuf {
+        Path::new(dir).join(format!("{}.cvd.json", symbol))
+    }
+
+    /// Load the persisted state for `symbol` from `dir`, if any
+    pub fn load(dir: &str, symbol: &str) -> Option<Self> {
+        let contents = fs::read_to_string(Self::path(dir, symbol)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Whether this state is recent enough at `now_ms` to resume, i.e. at most `window_secs` old
+    pub fn is_fresh(&self, now_ms: i64, window_secs: u64) -> bool {
+        now_ms - self.updated_at_ms <= window_secs.saturating_mul(1000) as i64
+    }
+
+    /// Write this state to `dir`, replacing any previous state for the symbol
+    pub fn save(&self, dir: &str) -> std::io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let contents = serde_json::to_string(self).map_err(std::io::Error::other)?;
+        // Write then rename so a crash mid-write never leaves a truncated file
+        let path = Self::path(dir, &self.symbol);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, contents)?;
+        fs::rename(tmp_path, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(timestamp: u64, side: &str, quantity: f64) -> TradeData {
+        TradeData { symbol: "BTCUSDT".to_string(), price: 100.0, quantity, side: side.to_string(), timestamp }
+    }
+
+    /// Feed trades through storage, flush the buffer, and return the applied trades' timestamps
+    /// with the running CVD after each applied trade
+    fn feed(trades: &[TradeData], reorder_buffer_ms: u64) -> (Vec<u64>, Vec<f64>) {
+        let config = OFIConfig { reorder_buffer_ms, ..OFIConfig::for_tests() };
+        let mut storage = TradeStorage::new();
+        for trade in trades {
+            storage.add_trade(trade.clone(), &config);
+        }
+        storage.flush_reorder_buffer("BTCUSDT", &config);
+
+        let applied = storage.get_trades("BTCUSDT").cloned().unwrap_or_default();
+        let mut cvd = 0.0;
+        let running_cvd = applied
+            .iter()
+            .map(|trade| {
+                cvd += if trade.side == "buy" { trade.price * trade.quantity } else { -trade.price * trade.quantity };
+                cvd
+            })
+            .collect();
+        assert_eq!(storage.get_session_cvd("BTCUSDT"), cvd);
+        (applied.iter().map(|trade| trade.timestamp).collect(), running_cvd)
+    }
+
+    #[test]
+    fn reorder_buffer_applies_trades_in_timestamp_order() {
+        let arrivals = [trade(1_000, "buy", 1.0), trade(1_030, "sell", 2.0), trade(1_010, "buy", 3.0), trade(1_100, "buy", 1.0)];
+        let mut sorted = arrivals.to_vec();
+        sorted.sort_by_key(|trade| trade.timestamp);
+
+        let (applied, buffered_cvd) = feed(&arrivals, 50);
+        assert_eq!(applied, vec![1_000, 1_010, 1_030, 1_100]);
+        assert_eq!(buffered_cvd, feed(&sorted, 0).1);
+        assert_eq!(buffered_cvd, vec![100.0, 400.0, 200.0, 300.0]);
+    }
+
+    #[test]
+    fn reorder_buffer_holds_trades_until_the_stream_moves_past_them() {
+        let config = OFIConfig { reorder_buffer_ms: 50, ..OFIConfig::for_tests() };
+        let mut storage = TradeStorage::new();
+        storage.add_trade(trade(1_000, "buy", 1.0), &config);
+        assert_eq!(storage.get_session_cvd("BTCUSDT"), 0.0);
+        storage.add_trade(trade(1_049, "buy", 1.0), &config);
+        assert_eq!(storage.get_session_cvd("BTCUSDT"), 0.0);
+        storage.add_trade(trade(1_050, "buy", 1.0), &config);
+        assert_eq!(storage.get_session_cvd("BTCUSDT"), 100.0);
+        storage.flush_reorder_buffer("BTCUSDT", &config);
+        assert_eq!(storage.get_session_cvd("BTCUSDT"), 300.0);
+        assert!(!storage.reorder_buffers.contains_key("BTCUSDT"));
+    }
+
+    #[test]
+    fn without_reorder_buffer_trades_apply_in_arrival_order() {
+        let (applied, cvd) = feed(&[trade(1_030, "sell", 2.0), trade(1_000, "buy", 1.0)], 0);
+        assert_eq!(applied, vec![1_030, 1_000]);
+        assert_eq!(cvd, vec![-200.0, -100.0]);
+    }
+
+    fn persisted(cvd: f64, updated_at_ms: i64) -> PersistedCvd {
+        PersistedCvd { symbol: "BTCUSDT".to_string(), cvd, updated_at_ms }
+    }
+
+    #[test]
+    fn persisted_cvd_resumes_only_within_the_window() {
+        let state = persisted(12_500.0, 1_000_000);
+        assert!(state.is_fresh(1_000_000, 900));
+        assert!(state.is_fresh(1_000_000 + 900_000, 900), "the window's end is inclusive");
+        assert!(!state.is_fresh(1_000_000 + 900_001, 900));
+        assert!(!state.is_fresh(1_000_001, 0));
+    }
+
+    #[test]
+    fn persisted_cvd_round_trips_through_its_directory() {
+        let dir = std::env::temp_dir().join(format!("cvd-state-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        persisted(-3_200.5, 1_000_000).save(dir).unwrap();
+        persisted(12_500.0, 2_000_000).save(dir).unwrap();
+
+        let loaded = PersistedCvd::load(dir, "BTCUSDT").expect("state saved");
+        assert_eq!((loaded.cvd, loaded.updated_at_ms), (12_500.0, 2_000_000), "the latest save replaces the previous one");
+        assert!(PersistedCvd::load(dir, "ETHUSDT").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -2,22 +2,342 @@
 
 #![allow(dead_code)]
 
-use crate::config::OFIConfig;
-use crate::data::{OrderBookSnapshot, OrderBookStorage, TradeData, TradeStorage};
-use crate::signals::{detect_signals, StrategyParams, TradingSignal};
-use crate::websocket::run_websocket_manager;
+use crate::config::{BookDedupe, OFIConfig, SignalPriceSource};
+use crate::data::{MarketDataEvent, OrderBookLevel, OrderBookSnapshot, OrderBookStorage, PersistedCvd, TradeData, TradeStorage};
+use crate::funding::{apply_funding_gate, FundingRateCache};
+use crate::ofi::{book_flow_event, calculate_delta_since, calculate_ofi_metrics, event_clock, realized_volatility};
+use crate::signals::{detect_signals_multi_lookback, gate_strong_on_update_rate, max_advisable_size, signal_price, ReasonCode, SignalType, StrategyParams, TradingSignal};
+use crate::synthetic::{combine_books, synthetic_trade};
+use crate::websocket::{spawn_websocket_manager, ChannelHandler};
 use anyhow::{anyhow, Result};
-use log::{error, info};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, mpsc, Mutex};
 use tokio::time::timeout;
 
+/// Absorption awaiting confirmation by a delta flip in the reversal direction
+#[derive(Debug, Clone)]
+struct PendingAbsorption {
+    direction: SignalType,
+    detected_at: u64,
+    reason: String,
+}
+
+/// Candidate signal that must keep re-qualifying for `signal_confirm_ms` before it is emitted
+#[derive(Debug, Clone)]
+struct PendingCandidate {
+    signal_type: SignalType,
+    first_seen: u64,
+}
+
+/// Last mid and post-jump cooldown for one symbol
+#[derive(Debug, Clone, Default)]
+struct JumpState {
+    last_mid: f64,
+    suppressed_until: u64,
+}
+
+/// Direction and time of the last directional signal for one symbol
+#[derive(Debug, Clone, Copy)]
+struct DirectionState {
+    direction: i8,
+    emitted_at: u64,
+}
+
+/// A directional signal awaiting resolution after `calibration_horizon_ms`
+#[derive(Debug, Clone, Copy)]
+struct PendingOutcome {
+    signal_type: SignalType,
+    entry_mid: f64,
+    emitted_at: u64,
+}
+
+/// Outstanding and resolved signal outcomes for one symbol
+#[derive(Debug, Clone, Default)]
+struct SymbolOutcomes {
+    pending: Vec<PendingOutcome>,  // At most one per signal type
+    resolved: HashMap<SignalType, (u64, u64)>,  // (favorable, total) per signal type
+}
+
+/// Blend a raw confidence with the empirical hit-rate of `total` resolved signals, weighting the
+/// hit-rate by `total / (total + min_samples)`. Below `min_samples` the raw confidence is kept.
+pub fn calibrate_confidence(raw: f64, favorable: u64, total: u64, min_samples: u64) -> f64 {
+    if total == 0 || total < min_samples {
+        return raw;
+    }
+    let hit_rate = favorable as f64 / total as f64;
+    let weight = total as f64 / (total + min_samples) as f64;
+    ((1.0 - weight) * raw + weight * hit_rate).clamp(0.0, 1.0)
+}
+
+/// Per-symbol view of the data currently held by an engine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolDataStatus {
+    pub symbol: String,
+    pub trade_count: usize,
+    pub book_levels: usize,  // Bid + ask levels in the stored book, 0 if no book
+    pub book_age_ms: Option<u64>,  // Time since the stored book's exchange timestamp, None if no book
+    pub ready: bool,  // Warm-up criteria met, see `OFIEngine::is_ready`
+}
+
+/// Marks a symbol's analysis as in flight until dropped
+struct InFlightSlot<'a> {
+    in_flight: &'a std::sync::Mutex<HashMap<String, bool>>,
+    symbol: &'a str,
+}
+
+impl Drop for InFlightSlot<'_> {
+    fn drop(&mut self) {
+        self.in_flight.lock().unwrap().remove(self.symbol);
+    }
+}
+
+/// Periodic per-symbol state event for monitoring, emitted whether or not a signal fires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolHeartbeat {
+    pub symbol: String,
+    pub timestamp: u64,  // Wall-clock time of the heartbeat in milliseconds
+    pub status: SymbolDataStatus,
+    pub metrics: Option<HeartbeatMetrics>,  // None until the symbol has a book
+    pub session_cvd: f64,
+    pub channels_delivering: bool,  // Both the books and trade channels have delivered data on this connection
+    pub analysis_skip_factor: u64,
+    pub resync_suppressions: u64,  // Signals withheld after book resyncs so far
+}
+
+/// Current flow metrics carried by a heartbeat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatMetrics {
+    pub mid_price: f64,
+    pub delta: f64,
+    pub cumulative_delta: f64,
+    pub buy_imbalance: f64,
+    pub sell_imbalance: f64,
+    pub book_quality: f64,
+    pub book_update_rate: f64,  // Book updates per second over the lookback
+}
+
+/// Warm-up readiness per symbol as of each engine's last analysis, shared across engines
+#[derive(Debug, Clone, Default)]
+pub struct ReadinessBoard {
+    symbols: Arc<std::sync::Mutex<HashMap<String, bool>>>,
+}
+
+impl ReadinessBoard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a symbol's readiness, returning whether it was ready before
+    fn set(&self, symbol: &str, ready: bool) -> bool {
+        self.symbols.lock().unwrap().insert(symbol.to_string(), ready).unwrap_or(false)
+    }
+
+    /// Whether the symbol was ready at its last analysis; unknown symbols are not ready
+    pub fn is_ready(&self, symbol: &str) -> bool {
+        self.symbols.lock().unwrap().get(symbol).copied().unwrap_or(false)
+    }
+
+    /// Forget a symbol, e.g. when its task stops
+    pub fn remove(&self, symbol: &str) {
+        self.symbols.lock().unwrap().remove(symbol);
+    }
+
+    /// Readiness of every known symbol, sorted by symbol
+    pub fn snapshot(&self) -> BTreeMap<String, bool> {
+        self.symbols.lock().unwrap().iter().map(|(symbol, ready)| (symbol.clone(), *ready)).collect()
+    }
+}
+
+/// Liveness heartbeat refreshed by every analysis cycle, shared across engines
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    last_beat_ms: Arc<AtomicI64>,
+    live_sources: Arc<AtomicUsize>,  // Analysis tasks currently expected to beat
+}
+
+impl Heartbeat {
+    /// Create a heartbeat that counts as fresh from now
+    pub fn new() -> Self {
+        Self {
+            last_beat_ms: Arc::new(AtomicI64::new(chrono::Utc::now().timestamp_millis())),
+            live_sources: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Register a task that is expected to beat for as long as the returned guard is held
+    pub fn register_source(&self) -> HeartbeatSource {
+        self.live_sources.fetch_add(1, Ordering::Relaxed);
+        HeartbeatSource { live_sources: Arc::clone(&self.live_sources) }
+    }
+
+    /// Number of registered tasks; with none, a stale heartbeat means idle rather than stalled
+    pub fn live_sources(&self) -> usize {
+        self.live_sources.load(Ordering::Relaxed)
+    }
+
+    /// Record that an analysis cycle ran
+    pub fn beat(&self) {
+        self.last_beat_ms.store(chrono::Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    /// Milliseconds since the last beat
+    pub fn age_ms(&self) -> i64 {
+        chrono::Utc::now().timestamp_millis() - self.last_beat_ms.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A task's registration with a `Heartbeat`, released on drop
+#[derive(Debug)]
+pub struct HeartbeatSource {
+    live_sources: Arc<AtomicUsize>,
+}
+
+impl Drop for HeartbeatSource {
+    fn drop(&mut self) {
+        self.live_sources.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Stored entries, last activity and storage handles of one symbol tracked by a memory budget
+struct BudgetEntry {
+    trade_entries: usize,
+    book_entries: usize,
+    last_activity: u64,  // Budget-wide activity sequence number, higher is more recent
+    order_book_storage: Arc<Mutex<OrderBookStorage>>,
+    trade_storage: Arc<Mutex<TradeStorage>>,
+}
+
+/// Symbol whose data the budget evicted, with the storage it must be removed from
+struct Eviction {
+    symbol: String,
+    order_book_storage: Arc<Mutex<OrderBookStorage>>,
+    trade_storage: Arc<Mutex<TradeStorage>>,
+}
+
+/// Global cap on stored trades + book levels across all symbols, shared across engines.
+///
+/// When the total exceeds the cap, the least-recently-active symbols' trades and books are
+/// evicted until it fits again; the symbol currently being written is never evicted.
+#[derive(Clone)]
+pub struct MemoryBudget {
+    max_entries: usize,
+    symbols: Arc<std::sync::Mutex<HashMap<String, BudgetEntry>>>,
+    // A counter rather than wall-clock time: symbols active within the same millisecond must
+    // still be ordered
+    activity_sequence: Arc<AtomicU64>,
+}
+
+impl MemoryBudget {
+    pub fn new(max_entries: usize) -> Self {
+        Self { max_entries, symbols: Arc::new(std::sync::Mutex::new(HashMap::new())), activity_sequence: Arc::new(AtomicU64::new(0)) }
+    }
+
+    /// Total trades + book levels currently tracked
+    pub fn total_entries(&self) -> usize {
+        self.symbols.lock().unwrap().values().map(|entry| entry.trade_entries + entry.book_entries).sum()
+    }
+
+    /// Symbols currently tracked, least recently active first
+    pub fn tracked_symbols(&self) -> Vec<String> {
+        let symbols = self.symbols.lock().unwrap();
+        let mut tracked: Vec<(&String, u64)> = symbols.iter().map(|(symbol, entry)| (symbol, entry.last_activity)).collect();
+        tracked.sort_by_key(|&(_, last_activity)| last_activity);
+        tracked.into_iter().map(|(symbol, _)| symbol.clone()).collect()
+    }
+
+    /// Record the stored entry counts of `symbol` (None = unchanged), mark it active and pick
+    /// the symbols to evict if the budget is now exceeded
+    fn record(
+        &self,
+        symbol: &str,
+        trade_entries: Option<usize>,
+        book_entries: Option<usize>,
+        order_book_storage: &Arc<Mutex<OrderBookStorage>>,
+        trade_storage: &Arc<Mutex<TradeStorage>>,
+    ) -> Vec<Eviction> {
+        let mut symbols = self.symbols.lock().unwrap();
+        let entry = symbols.entry(symbol.to_string()).or_insert_with(|| BudgetEntry {
+            trade_entries: 0,
+            book_entries: 0,
+            last_activity: 0,
+            order_book_storage: order_book_storage.clone(),
+            trade_storage: trade_storage.clone(),
+        });
+        if let Some(count) = trade_entries {
+            entry.trade_entries = count;
+        }
+        if let Some(count) = book_entries {
+            entry.book_entries = count;
+        }
+        entry.last_activity = self.activity_sequence.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let mut total: usize = symbols.values().map(|entry| entry.trade_entries + entry.book_entries).sum();
+        if total <= self.max_entries {
+            return Vec::new();
+        }
+        let mut candidates: Vec<(String, u64)> = symbols
+            .iter()
+            .filter(|(candidate, _)| candidate.as_str() != symbol)
+            .map(|(candidate, entry)| (candidate.clone(), entry.last_activity))
+            .collect();
+        candidates.sort_by_key(|&(_, last_activity)| last_activity);
+
+        let mut evictions = Vec::new();
+        for (candidate, _) in candidates {
+            if total <= self.max_entries {
+                break;
+            }
+            if let Some(entry) = symbols.remove(&candidate) {
+                total -= entry.trade_entries + entry.book_entries;
+                evictions.push(Eviction {
+                    symbol: candidate,
+                    order_book_storage: entry.order_book_storage,
+                    trade_storage: entry.trade_storage,
+                });
+            }
+        }
+        evictions
+    }
+}
+
 /// OFI Analysis Engine - acts as a state manager
 #[derive(Clone)]
 pub struct OFIEngine {
     order_book_storage: Arc<Mutex<OrderBookStorage>>,
     trade_storage: Arc<Mutex<TradeStorage>>,
+    pending_absorptions: Arc<Mutex<HashMap<String, PendingAbsorption>>>,
+    pending_candidates: Arc<Mutex<HashMap<String, PendingCandidate>>>,
+    analyzed_versions: Arc<Mutex<HashMap<String, (u64, u64)>>>,  // (book, trade) version of the last analysis per symbol
+    analyses_in_flight: Arc<std::sync::Mutex<HashMap<String, bool>>>,  // Symbols being analyzed, flagged when a trigger arrived meanwhile
+    heartbeat: Option<Heartbeat>,
+    observability_tx: Option<mpsc::Sender<TradingSignal>>,
+    symbol_heartbeat_tx: Option<mpsc::Sender<SymbolHeartbeat>>,
+    market_data_tx: Option<broadcast::Sender<MarketDataEvent>>,  // Raw feed, with `market_data_feed_capacity` > 0
+    jump_states: Arc<Mutex<HashMap<String, JumpState>>>,
+    jump_suppressions: Arc<AtomicU64>,
+    resync_suppressions: Arc<AtomicU64>,  // Signals withheld within `resync_suppress_ms` of a book snapshot
+    last_directions: Arc<Mutex<HashMap<String, DirectionState>>>,
+    outcomes: Arc<Mutex<HashMap<String, SymbolOutcomes>>>,
+    rejected_trades: Arc<AtomicU64>,
+    redundant_books: Arc<AtomicU64>,  // Books skipped by `book_dedupe`
+    analysis_skip_factor: Arc<AtomicU64>,  // Effective adaptive downsampling factor, 1 = analyze every message
+    invalid_symbols: Arc<std::sync::Mutex<HashMap<String, String>>>,  // Symbols the exchange rejected, with its error
+    memory_budget: Option<MemoryBudget>,
+    readiness: Option<ReadinessBoard>,
+    channel_handlers: HashMap<String, Arc<dyn ChannelHandler>>,  // Keyed by channel name
+    funding_rates: Option<Arc<FundingRateCache>>,  // Fed by the `ticker` channel, with `funding_rate_extreme` > 0
+    channel_data: Arc<Mutex<HashMap<(String, String), serde_json::Value>>>,  // Latest payload per (channel, symbol) without a handler
     strategy_params: StrategyParams,
     config: OFIConfig,
 }
@@ -25,36 +345,503 @@ pub struct OFIEngine {
 impl OFIEngine {
     /// Create a new OFI engine with specific strategy parameters and configuration
     pub fn new(params: StrategyParams, config: OFIConfig) -> Self {
+        let funding_rates = (config.funding_rate_extreme > 0.0)
+            .then(|| Arc::new(FundingRateCache::new(Duration::from_secs(config.funding_rate_ttl_secs))));
+        let mut channel_handlers: HashMap<String, Arc<dyn ChannelHandler>> = HashMap::new();
+        if let Some(funding_rates) = &funding_rates {
+            channel_handlers.insert("ticker".to_string(), funding_rates.clone());
+        }
         Self {
             order_book_storage: Arc::new(Mutex::new(OrderBookStorage::new())),
             trade_storage: Arc::new(Mutex::new(TradeStorage::new())),
+            pending_absorptions: Arc::new(Mutex::new(HashMap::new())),
+            pending_candidates: Arc::new(Mutex::new(HashMap::new())),
+            analyzed_versions: Arc::new(Mutex::new(HashMap::new())),
+            analyses_in_flight: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            heartbeat: None,
+            observability_tx: None,
+            symbol_heartbeat_tx: None,
+            market_data_tx: (config.market_data_feed_capacity > 0).then(|| broadcast::channel(config.market_data_feed_capacity).0),
+            jump_states: Arc::new(Mutex::new(HashMap::new())),
+            jump_suppressions: Arc::new(AtomicU64::new(0)),
+            resync_suppressions: Arc::new(AtomicU64::new(0)),
+            last_directions: Arc::new(Mutex::new(HashMap::new())),
+            outcomes: Arc::new(Mutex::new(HashMap::new())),
+            rejected_trades: Arc::new(AtomicU64::new(0)),
+            redundant_books: Arc::new(AtomicU64::new(0)),
+            analysis_skip_factor: Arc::new(AtomicU64::new(1)),
+            invalid_symbols: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            // Engine-local budget; share one across engines with `with_memory_budget`
+            memory_budget: (config.global_entry_limit > 0).then(|| MemoryBudget::new(config.global_entry_limit)),
+            readiness: None,
+            channel_handlers,
+            funding_rates,
+            channel_data: Arc::new(Mutex::new(HashMap::new())),
             strategy_params: params,
             config,
         }
     }
 
+    /// Refresh the given heartbeat on every analysis cycle
+    pub fn with_heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// Count this engine's stored data against the given budget instead of its own
+    pub fn with_memory_budget(mut self, budget: MemoryBudget) -> Self {
+        self.memory_budget = Some(budget);
+        self
+    }
+
+    /// Publish this engine's warm-up readiness to the given board on every analysis
+    pub fn with_readiness_board(mut self, board: ReadinessBoard) -> Self {
+        self.readiness = Some(board);
+        self
+    }
+
+    /// Route data on an extra subscribed channel (see `channels` in config.toml) to `handler`
+    pub fn with_channel_handler(mut self, channel: &str, handler: Arc<dyn ChannelHandler>) -> Self {
+        self.channel_handlers.insert(channel.to_string(), handler);
+        self
+    }
+
+    /// Hand extra channel data to its handler, or keep it as the channel's latest payload
+    pub async fn dispatch_channel_data(&self, channel: &str, symbol: &str, data: serde_json::Value) {
+        match self.channel_handlers.get(channel) {
+            Some(handler) => handler.handle(symbol, &data),
+            None => {
+                self.channel_data.lock().await.insert((channel.to_string(), symbol.to_string()), data);
+            }
+        }
+    }
+
+    /// Latest payload received on an extra channel without a handler
+    pub async fn latest_channel_data(&self, channel: &str, symbol: &str) -> Option<serde_json::Value> {
+        self.channel_data.lock().await.get(&(channel.to_string(), symbol.to_string())).cloned()
+    }
+
+    /// Forward NoSignal results to the given observability sink (never to execution)
+    pub fn with_observability_sink(mut self, tx: mpsc::Sender<TradingSignal>) -> Self {
+        self.observability_tx = Some(tx);
+        self
+    }
+
+    /// Observability sink for NoSignal results, if one is attached
+    pub fn observability_sink(&self) -> Option<&mpsc::Sender<TradingSignal>> {
+        self.observability_tx.as_ref()
+    }
+
+    /// Send a `SymbolHeartbeat` to the given sink every `heartbeat_interval_secs`
+    pub fn with_symbol_heartbeat_sink(mut self, tx: mpsc::Sender<SymbolHeartbeat>) -> Self {
+        self.symbol_heartbeat_tx = Some(tx);
+        self
+    }
+
+    /// Sink for periodic symbol heartbeats, if one is attached
+    pub fn symbol_heartbeat_sink(&self) -> Option<&mpsc::Sender<SymbolHeartbeat>> {
+        self.symbol_heartbeat_tx.as_ref()
+    }
+
+    /// Subscribe to the normalized books and trades this engine accepts, None unless
+    /// `market_data_feed_capacity` is set.
+    ///
+    /// Events are published after the off-book trade filter and `book_dedupe`, before storage and
+    /// analysis, and never block it: a subscriber more than `market_data_feed_capacity` events
+    /// behind loses the oldest ones and its next `recv` returns `RecvError::Lagged(skipped)` before
+    /// resuming with the oldest event still buffered. Events sent while nobody is subscribed are
+    /// dropped.
+    pub fn subscribe_market_data(&self) -> Option<broadcast::Receiver<MarketDataEvent>> {
+        self.market_data_tx.as_ref().map(broadcast::Sender::subscribe)
+    }
+
+    fn publish_market_data(&self, event: impl FnOnce() -> MarketDataEvent) {
+        if let Some(tx) = self.market_data_tx.as_ref().filter(|tx| tx.receiver_count() > 0) {
+            let _ = tx.send(event());
+        }
+    }
+
+    /// Current state of a symbol for a heartbeat event
+    pub async fn symbol_heartbeat(&self, symbol: &str, channels_delivering: bool) -> SymbolHeartbeat {
+        SymbolHeartbeat {
+            symbol: symbol.to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis().max(0) as u64,
+            status: self.symbol_status(symbol).await,
+            metrics: self.current_metrics(symbol).await,
+            session_cvd: self.session_cvd(symbol).await,
+            channels_delivering,
+            analysis_skip_factor: self.analysis_skip_factor(),
+            resync_suppressions: self.resync_suppressions(),
+        }
+    }
+
     /// Get reference to the configuration
     pub fn config(&self) -> &OFIConfig {
         &self.config
     }
 
-    /// Update order book data
-    pub async fn update_order_book(&self, book: OrderBookSnapshot) {
-        let mut storage = self.order_book_storage.lock().await;
+    /// Update order book data. Returns false if the book was skipped as redundant by
+    /// `book_dedupe`, in which case no analysis is due.
+    pub async fn update_order_book(&self, book: OrderBookSnapshot) -> bool {
+        let symbol = book.symbol.clone();
+        let book_entries = book.bids.len() + book.asks.len();
+        {
+            let mut storage = self.order_book_storage.lock().await;
+            if self.config.book_dedupe != BookDedupe::Off {
+                if let Some(stored) = storage.books.get_mut(&symbol) {
+                    if stored.bids == book.bids && stored.asks == book.asks {
+                        let redundant = match self.config.book_dedupe {
+                            BookDedupe::Levels => {
+                                stored.timestamp = stored.timestamp.max(book.timestamp);
+                                true
+                            }
+                            _ => stored.timestamp == book.timestamp,
+                        };
+                        if redundant {
+                            self.redundant_books.fetch_add(1, Ordering::Relaxed);
+                            return false;
+                        }
+                    }
+                }
+            }
+            self.publish_market_data(|| MarketDataEvent::OrderBook(book.clone()));
+            self.store_book(&mut storage, book);
+
+            // Re-derive the synthetic spreads this symbol is a leg of, once both legs have a book
+            for synthetic in self.config.synthetics_with_leg(&symbol) {
+                let combined = match (storage.get_order_book(&synthetic.long_leg), storage.get_order_book(&synthetic.short_leg)) {
+                    (Some(long), Some(short)) => combine_books(synthetic, long, short),
+                    _ => continue,
+                };
+                self.store_book(&mut storage, combined);
+            }
+        }
+        self.charge_memory_budget(&symbol, None, Some(book_entries)).await;
+        true
+    }
+
+    fn store_book(&self, storage: &mut OrderBookStorage, book: OrderBookSnapshot) {
+        // Order flow imbalance is measured between consecutive books, kept for the longest lookback
+        let event = storage.get_order_book(&book.symbol).and_then(|prev| book_flow_event(prev, &book));
+        let cutoff = book.timestamp.saturating_sub(self.config.flow_retention_ms(self.strategy_params.lookback_period_ms));
+        if let Some(event) = event {
+            storage.add_flow_event(&book.symbol, event, cutoff);
+        }
+        storage.record_update_time(&book.symbol, book.timestamp, cutoff);
         storage.update_order_book(book);
     }
 
     /// Add trade data
     pub async fn add_trade(&self, trade: TradeData) {
-        let mut storage = self.trade_storage.lock().await;
-        storage.add_trade(trade, &self.config);
+        if self.config.max_trade_deviation_bps > 0.0 && self.is_off_book_trade(&trade).await {
+            let rejected = self.rejected_trades.fetch_add(1, Ordering::Relaxed) + 1;
+            if rejected == 1 || rejected % 100 == 0 {
+                warn!("[Rust] Dropped trade on {} at {} outside {}bps of mid ({} rejected so far)", trade.symbol, trade.price, self.config.max_trade_deviation_bps, rejected);
+            }
+            return;
+        }
+        self.publish_market_data(|| MarketDataEvent::Trade(trade.clone()));
+        let symbol = trade.symbol.clone();
+        let synthetic_trades: Vec<TradeData> = if self.config.synthetics.is_empty() {
+            Vec::new()
+        } else {
+            let order_book_storage = self.order_book_storage.lock().await;
+            self.config
+                .synthetics_with_leg(&symbol)
+                .filter_map(|synthetic| {
+                    let other_leg = if synthetic.long_leg == symbol { &synthetic.short_leg } else { &synthetic.long_leg };
+                    synthetic_trade(synthetic, &trade, order_book_storage.get_order_book(other_leg)?)
+                })
+                .collect()
+        };
+        let trade_entries = {
+            let mut storage = self.trade_storage.lock().await;
+            storage.add_trade(trade, &self.config);
+            for synthetic_trade in synthetic_trades {
+                storage.add_trade(synthetic_trade, &self.config);
+            }
+            storage.get_trades(&symbol).map_or(0, |trades| trades.len())
+        };
+        self.charge_memory_budget(&symbol, Some(trade_entries), None).await;
+    }
+
+    /// Record a symbol's stored entries against the memory budget and evict whatever it selects.
+    /// Called with no storage lock held, since evictions may lock another engine's storage.
+    async fn charge_memory_budget(&self, symbol: &str, trade_entries: Option<usize>, book_entries: Option<usize>) {
+        let budget = match &self.memory_budget {
+            Some(budget) => budget,
+            None => return,
+        };
+        let evictions = budget.record(symbol, trade_entries, book_entries, &self.order_book_storage, &self.trade_storage);
+        for eviction in evictions {
+            {
+                let mut trade_storage = eviction.trade_storage.lock().await;
+                trade_storage.trades.remove(&eviction.symbol);
+                trade_storage.reorder_buffers.remove(&eviction.symbol);
+            }
+            let mut order_book_storage = eviction.order_book_storage.lock().await;
+            order_book_storage.books.remove(&eviction.symbol);
+            order_book_storage.flow_events.remove(&eviction.symbol);
+            order_book_storage.update_times.remove(&eviction.symbol);
+            info!("[Rust] Memory budget of {} entries exceeded; evicted data of least recently active symbol {}", budget.max_entries, eviction.symbol);
+        }
+    }
+
+    /// Symbols with an order book or trades stored, sorted
+    pub async fn active_symbols(&self) -> Vec<String> {
+        self.symbol_data_status().await.into_iter().map(|status| status.symbol).collect()
+    }
+
+    /// Flow metrics of a symbol on its current data, None until it has a book
+    pub async fn current_metrics(&self, symbol: &str) -> Option<HeartbeatMetrics> {
+        let order_book_storage = self.order_book_storage.lock().await;
+        let trade_storage = self.trade_storage.lock().await;
+        let book = order_book_storage.get_order_book(symbol)?;
+        let trades = trade_storage.get_recent_trades(symbol, self.config.analysis_trade_limit);
+        let flow_events = order_book_storage.get_flow_events(symbol);
+        let ofi_metrics = calculate_ofi_metrics(book, &trades, flow_events, self.strategy_params.lookback_period_ms, &self.config);
+        Some(HeartbeatMetrics {
+            mid_price: signal_price(book, &trades, SignalPriceSource::Mid, self.config.weighted_mid_levels),
+            delta: ofi_metrics.delta,
+            cumulative_delta: ofi_metrics.cumulative_delta,
+            buy_imbalance: ofi_metrics.buy_imbalance,
+            sell_imbalance: ofi_metrics.sell_imbalance,
+            book_quality: ofi_metrics.book_quality,
+            book_update_rate: order_book_storage.update_rate(symbol, ofi_metrics.timestamp, self.strategy_params.lookback_period_ms),
+        })
+    }
+
+    /// Resting notional (price * quantity) of both sides of a symbol's stored book, 0.0 without a book
+    pub async fn book_notional(&self, symbol: &str) -> f64 {
+        let order_book_storage = self.order_book_storage.lock().await;
+        order_book_storage.get_order_book(symbol).map_or(0.0, |book| {
+            book.bids.iter().chain(&book.asks).map(|level| level.price * level.quantity).sum()
+        })
+    }
+
+    /// Best bid and best ask of a symbol's stored book
+    pub async fn top_of_book(&self, symbol: &str) -> (Option<OrderBookLevel>, Option<OrderBookLevel>) {
+        let order_book_storage = self.order_book_storage.lock().await;
+        match order_book_storage.get_order_book(symbol) {
+            Some(book) => (book.bids.first().cloned(), book.asks.first().cloned()),
+            None => (None, None),
+        }
+    }
+
+    /// Trade count and book freshness of one symbol, empty if the engine holds no data for it
+    pub async fn symbol_status(&self, symbol: &str) -> SymbolDataStatus {
+        self.symbol_data_status()
+            .await
+            .into_iter()
+            .find(|status| status.symbol == symbol)
+            .unwrap_or_else(|| SymbolDataStatus { symbol: symbol.to_string(), trade_count: 0, book_levels: 0, book_age_ms: None, ready: false })
+    }
+
+    /// Trade count and book freshness for every symbol with stored data, sorted by symbol.
+    /// A symbol with trades but no book (or the reverse) is subscribed but only partially fed.
+    pub async fn symbol_data_status(&self) -> Vec<SymbolDataStatus> {
+        let order_book_storage = self.order_book_storage.lock().await;
+        let trade_storage = self.trade_storage.lock().await;
+        let now = chrono::Utc::now().timestamp_millis().max(0) as u64;
+
+        let mut symbols: Vec<&String> = order_book_storage.books.keys().chain(trade_storage.trades.keys()).collect();
+        symbols.sort();
+        symbols.dedup();
+
+        symbols
+            .into_iter()
+            .map(|symbol| {
+                let book = order_book_storage.get_order_book(symbol);
+                SymbolDataStatus {
+                    symbol: symbol.clone(),
+                    trade_count: trade_storage.get_trades(symbol).map_or(0, |trades| trades.len()),
+                    book_levels: book.map_or(0, |book| book.bids.len() + book.asks.len()),
+                    book_age_ms: book.map(|book| now.saturating_sub(book.timestamp)),
+                    ready: self.warmed_up(book, trade_storage.get_trades(symbol).map_or(0, |trades| trades.len())),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether a symbol's engine holds enough data for meaningful signals: a book with at least
+    /// `warmup_min_book_levels` levels on both sides and `warmup_min_trades` trades in the window
+    pub async fn is_ready(&self, symbol: &str) -> bool {
+        let order_book_storage = self.order_book_storage.lock().await;
+        let trade_storage = self.trade_storage.lock().await;
+        self.warmed_up(order_book_storage.get_order_book(symbol), trade_storage.get_trades(symbol).map_or(0, |trades| trades.len()))
+    }
+
+    fn warmed_up(&self, book: Option<&OrderBookSnapshot>, trade_count: usize) -> bool {
+        let min_levels = self.config.warmup_min_book_levels;
+        book.is_some_and(|book| !book.bids.is_empty() && !book.asks.is_empty() && book.bids.len() >= min_levels && book.asks.len() >= min_levels)
+            && trade_count >= self.config.warmup_min_trades
+    }
+
+    /// Check whether a trade prints further from the current mid than `max_trade_deviation_bps`.
+    /// Trades are accepted while there is no two-sided book to compare against.
+    async fn is_off_book_trade(&self, trade: &TradeData) -> bool {
+        let storage = self.order_book_storage.lock().await;
+        let book = match storage.get_order_book(&trade.symbol) {
+            Some(book) if !book.bids.is_empty() && !book.asks.is_empty() => book,
+            _ => return false,
+        };
+        let mid = (book.bids[0].price + book.asks[0].price) / 2.0;
+        mid > 0.0 && ((trade.price - mid) / mid).abs() * 10_000.0 > self.config.max_trade_deviation_bps
+    }
+
+    /// Number of trades dropped for printing too far from the book so far
+    pub fn rejected_trades(&self) -> u64 {
+        self.rejected_trades.load(Ordering::Relaxed)
+    }
+
+    /// Number of order books skipped as redundant by `book_dedupe` so far
+    pub fn redundant_books(&self) -> u64 {
+        self.redundant_books.load(Ordering::Relaxed)
+    }
+
+    /// Record that the exchange rejected `symbol` as nonexistent, with the raw error
+    pub(crate) fn mark_invalid_symbol(&self, symbol: &str, error: String) {
+        self.invalid_symbols.lock().unwrap().insert(symbol.to_string(), error);
+    }
+
+    /// The exchange error that rejected `symbol` as nonexistent, if its WebSocket manager stopped for that
+    pub fn invalid_symbol_error(&self, symbol: &str) -> Option<String> {
+        self.invalid_symbols.lock().unwrap().get(symbol).cloned()
+    }
+
+    /// Realized volatility of the symbol's stored trades over `window_ms` up to its newest trade:
+    /// the square root of summed squared trade-to-trade log returns. None with fewer than two returns.
+    pub async fn realized_volatility(&self, symbol: &str, window_ms: u64) -> Option<f64> {
+        let trade_storage = self.trade_storage.lock().await;
+        let trades: Vec<&TradeData> = trade_storage.get_trades(symbol)?.iter().collect();
+        let now = trades.iter().map(|trade| trade.timestamp).max()?;
+        realized_volatility(&trades, now, window_ms)
+    }
+
+    /// Current adaptive downsampling factor: one analysis per this many data messages
+    pub fn analysis_skip_factor(&self) -> u64 {
+        self.analysis_skip_factor.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_analysis_skip_factor(&self, factor: u64) {
+        self.analysis_skip_factor.store(factor, Ordering::Relaxed);
+    }
+
+    /// Number of signals suppressed by the price jump guard so far
+    pub fn jump_suppressions(&self) -> u64 {
+        self.jump_suppressions.load(Ordering::Relaxed)
+    }
+
+    /// Number of signals withheld after a book resync (`resync_suppress_ms`) so far
+    pub fn resync_suppressions(&self) -> u64 {
+        self.resync_suppressions.load(Ordering::Relaxed)
+    }
+
+    /// Count a signal withheld after a book resync
+    pub(crate) fn record_resync_suppression(&self) {
+        self.resync_suppressions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Apply the trades still held by `reorder_buffer_ms` for a symbol, e.g. before shutdown
+    pub async fn flush_reorder_buffer(&self, symbol: &str) {
+        self.trade_storage.lock().await.flush_reorder_buffer(symbol, &self.config);
+    }
+
+    /// Running cumulative delta for a symbol since its task started (or resumed)
+    pub async fn session_cvd(&self, symbol: &str) -> f64 {
+        self.trade_storage.lock().await.get_session_cvd(symbol)
+    }
+
+    /// Resume the persisted session CVD for a symbol if it is within `cvd_resume_window_secs`
+    pub async fn resume_session_cvd(&self, symbol: &str) {
+        let dir = match &self.config.cvd_state_dir {
+            Some(dir) => dir,
+            None => return,
+        };
+        let persisted = match PersistedCvd::load(dir, symbol) {
+            Some(persisted) => persisted,
+            None => return,
+        };
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let age_ms = now_ms - persisted.updated_at_ms;
+        if !persisted.is_fresh(now_ms, self.config.cvd_resume_window_secs) {
+            info!("[Rust] Persisted CVD for {} is {}s old, beyond the resume window. Starting fresh.", symbol, age_ms / 1000);
+            return;
+        }
+        info!("[Rust] Resuming CVD {:.0} for {} persisted {}s ago.", persisted.cvd, symbol, age_ms / 1000);
+        self.trade_storage.lock().await.set_session_cvd(symbol, persisted.cvd);
+    }
+
+    /// Persist the session CVD for a symbol, if `cvd_state_dir` is configured
+    pub async fn persist_session_cvd(&self, symbol: &str) {
+        let dir = match &self.config.cvd_state_dir {
+            Some(dir) => dir,
+            None => return,
+        };
+        let persisted = PersistedCvd {
+            symbol: symbol.to_string(),
+            cvd: self.session_cvd(symbol).await,
+            updated_at_ms: chrono::Utc::now().timestamp_millis(),
+        };
+        if let Err(e) = persisted.save(dir) {
+            warn!("[Rust] Failed to persist CVD for {}: {}", symbol, e);
+        }
+    }
+
+    /// Like `analyze_symbol`, but with at most one analysis per symbol in flight. A call arriving
+    /// while one runs returns None and makes the running call analyze again once done, so the
+    /// triggers coalesce into one rerun on the latest state and only its result is returned.
+    pub async fn analyze_symbol_coalesced(&self, symbol: &str) -> Option<TradingSignal> {
+        {
+            let mut in_flight = self.analyses_in_flight.lock().unwrap();
+            if let Some(rerun) = in_flight.get_mut(symbol) {
+                *rerun = true;
+                return None;
+            }
+            in_flight.insert(symbol.to_string(), false);
+        }
+        // Frees the symbol even if this future is dropped mid-analysis (e.g. on timeout)
+        let _slot = InFlightSlot { in_flight: &self.analyses_in_flight, symbol };
+        loop {
+            let signal = self.analyze_symbol(symbol).await;
+            let mut in_flight = self.analyses_in_flight.lock().unwrap();
+            match in_flight.get_mut(symbol) {
+                Some(rerun) if *rerun => *rerun = false,
+                _ => return Some(signal),
+            }
+        }
     }
 
     /// Analyze a symbol for trading signals based on current stored data
     pub async fn analyze_symbol(&self, symbol: &str) -> TradingSignal {
+        // An entered span can't be held across awaits; the cycle's root span follows the future instead
+        #[cfg(feature = "trace-spans")]
+        let analysis = tracing::Instrument::instrument(self.run_analysis(symbol), tracing::info_span!("analyze_symbol", symbol = %symbol));
+        #[cfg(not(feature = "trace-spans"))]
+        let analysis = self.run_analysis(symbol);
+        analysis.await
+    }
+
+    async fn run_analysis(&self, symbol: &str) -> TradingSignal {
+        if let Some(heartbeat) = &self.heartbeat {
+            heartbeat.beat();
+        }
+
         let order_book_storage = self.order_book_storage.lock().await;
         let trade_storage = self.trade_storage.lock().await;
 
+        if let Some(board) = &self.readiness {
+            let ready = self.warmed_up(order_book_storage.get_order_book(symbol), trade_storage.get_trades(symbol).map_or(0, |trades| trades.len()));
+            if board.set(symbol, ready) != ready {
+                info!("[Rust] {} is {} ready for trading", symbol, if ready { "now" } else { "no longer" });
+            }
+        }
+
+        // Data-collection-only symbols keep ingesting but are never analyzed
+        if !self.config.analysis_enabled_for(symbol) {
+            return TradingSignal::no_signal_with_reason(symbol, "No strategy enabled for symbol (data collection only)");
+        }
+
         let order_book = match order_book_storage.get_order_book(symbol) {
             Some(book) => book.clone(),
             None => return TradingSignal::no_signal_with_reason(symbol, "No order book data"),
@@ -65,18 +852,323 @@ impl OFIEngine {
              return TradingSignal::no_signal_with_reason(symbol, "Order book is empty");
         }
 
-        let recent_trades = trade_storage.get_recent_trades(symbol, 100);
+        // With the version gate, book strategies run once per book version and nothing runs
+        // when neither the book nor the trades changed since the last analysis
+        let mut book_strategies = true;
+        if self.config.analysis_version_gate {
+            let versions = (order_book_storage.get_version(symbol), trade_storage.get_version(symbol));
+            let previous = self.analyzed_versions.lock().await.insert(symbol.to_string(), versions);
+            if let Some((book_version, trade_version)) = previous {
+                if (book_version, trade_version) == versions {
+                    return TradingSignal::no_signal_with_reason(symbol, "No new book or trade data since last analysis");
+                }
+                book_strategies = book_version != versions.0;
+            }
+        }
+
+        let recent_trades = trade_storage.get_recent_trades(symbol, self.config.analysis_trade_limit);
+
+        // Scale thresholds by realized volatility relative to the reference; calmer markets lower them
+        let mut params = self.strategy_params.clone();
+        if self.config.market_condition_adaptation && self.config.reference_volatility > 0.0 {
+            let now = event_clock(&order_book, &recent_trades, self.config.unified_event_clock);
+            if let Some(volatility) = realized_volatility(&recent_trades, now, self.config.volatility_window_ms) {
+                let max = self.config.max_volatility_multiplier;
+                params.market_condition_multiplier *= (volatility / self.config.reference_volatility).clamp(1.0 / max, max);
+            }
+        }
 
         // Detect signals
-        detect_signals(
-            &order_book, 
-            &recent_trades, 
-            &self.strategy_params,
-            self.config.strong_signal_confidence,
-            self.config.reversal_signal_confidence,
-            self.config.exhaustion_signal_confidence
+        let mut signal = {
+            trace_span!("detect_signals");
+            detect_signals_multi_lookback(
+                &order_book,
+                &recent_trades,
+                order_book_storage.get_flow_events(symbol),
+                &params,
+                &self.config,
+                book_strategies,
+            )
+        };
+        if signal.signal_type != SignalType::NoSignal && !self.config.strategy_enabled_for(symbol, signal.reason_code) {
+            let reason = format!("{:?} {} not enabled for {}", signal.reason_code, signal.signal_type, symbol);
+            signal = TradingSignal::no_signal_at(symbol, signal.price, signal.timestamp, reason);
+        }
+
+        // Crowded positioning: extreme funding suppresses continuation into it, boosts reversals
+        if let Some(funding_rates) = &self.funding_rates {
+            signal = apply_funding_gate(signal, funding_rates.get(symbol), &self.config);
+        }
+
+        // A slowly updating book suggests an illiquid or stalled feed; it can't back a Strong signal
+        if self.config.min_book_update_rate > 0.0 {
+            let rate = order_book_storage.update_rate(symbol, signal.timestamp, self.strategy_params.lookback_period_ms);
+            signal = gate_strong_on_update_rate(signal, rate, self.config.min_book_update_rate);
+        }
+
+        // Two-stage absorption: hold absorption back until delta flips in the reversal direction
+        if self.config.absorption_confirmation_window_ms > 0 {
+            let mut pending = self.pending_absorptions.lock().await;
+            trace_span!("confirm_absorption");
+            signal = self.confirm_absorption(&mut pending, signal, &order_book, &recent_trades);
+        }
+
+        // Hold candidates back until they have kept qualifying for the confirmation delay. A trade-only
+        // analysis skipped the book strategies, so its NoSignal doesn't disqualify a candidate.
+        let requalified = book_strategies || !matches!(signal.signal_type, SignalType::NoSignal);
+        if self.config.signal_confirm_ms > 0 && requalified {
+            let mut candidates = self.pending_candidates.lock().await;
+            trace_span!("confirm_candidate");
+            // The book timestamp stands still on trade-only updates; trades must advance the clock
+            let now = event_clock(&order_book, &recent_trades, true);
+            signal = self.confirm_candidate(&mut candidates, signal, &order_book.symbol, now);
+        }
+
+        // Suppress signals while the book settles after a sharp price jump
+        if self.config.jump_suppress_bps > 0.0 {
+            let mut jump_states = self.jump_states.lock().await;
+            trace_span!("jump_check");
+            if let Some(suppressed) = self.check_price_jump(&mut jump_states, &signal, &order_book, &recent_trades) {
+                return suppressed;
+            }
+        }
+
+        // Damp flip-flopping: an opposite-direction signal shortly after a directional one needs more confidence
+        if self.config.direction_stickiness_ms > 0 {
+            let mut last_directions = self.last_directions.lock().await;
+            trace_span!("direction_stickiness");
+            signal = self.apply_direction_stickiness(&mut last_directions, signal);
+        }
+
+        // Resolve earlier signals against the current mid and recalibrate confidence from their hit-rate
+        if self.config.calibration_horizon_ms > 0 {
+            let mut outcomes = self.outcomes.lock().await;
+            trace_span!("calibration");
+            let mid = (order_book.bids[0].price + order_book.asks[0].price) / 2.0;
+            signal = self.calibrate_with_outcomes(outcomes.entry(symbol.to_string()).or_default(), signal, mid, order_book.timestamp);
+        }
+
+        // Annotate actionable signals with their confidence tier label
+        if !matches!(signal.signal_type, SignalType::NoSignal) {
+            signal.tier = self.config.tier_for_confidence(signal.confidence).map(str::to_string);
+        }
+
+        signal
+    }
+
+    /// Detect a jump beyond `jump_suppress_bps` between consecutive mids or the two latest trades
+    /// and start a `jump_cooldown_ms` cooldown. Returns a NoSignal replacing an actionable
+    /// signal that falls inside the cooldown.
+    fn check_price_jump(
+        &self,
+        jump_states: &mut HashMap<String, JumpState>,
+        signal: &TradingSignal,
+        order_book: &OrderBookSnapshot,
+        trades: &[&TradeData],
+    ) -> Option<TradingSignal> {
+        let symbol = &order_book.symbol;
+        let now = order_book.timestamp;
+        let mid = (order_book.bids[0].price + order_book.asks[0].price) / 2.0;
+        let jump_bps = |from: f64, to: f64| if from > 0.0 { ((to - from) / from).abs() * 10_000.0 } else { 0.0 };
+
+        let state = jump_states.entry(symbol.clone()).or_default();
+        // Trades are newest first
+        let trade_jump = match trades {
+            [latest, previous, ..] => jump_bps(previous.price, latest.price),
+            _ => 0.0,
+        };
+        let jump = jump_bps(state.last_mid, mid).max(trade_jump);
+        state.last_mid = mid;
+
+        if jump > self.config.jump_suppress_bps {
+            info!("[Rust] Price jump of {:.1}bps on {}. Suppressing signals for {}ms.", jump, symbol, self.config.jump_cooldown_ms);
+            state.suppressed_until = now + self.config.jump_cooldown_ms;
+        }
+
+        if now < state.suppressed_until && !matches!(signal.signal_type, SignalType::NoSignal) {
+            self.jump_suppressions.fetch_add(1, Ordering::Relaxed);
+            return Some(TradingSignal::no_signal_at(
+                symbol,
+                signal.price,
+                signal.timestamp,
+                format!("{:?} suppressed: cooling down after a price jump", signal.signal_type),
+            ));
+        }
+        None
+    }
+
+    /// Resolve pending outcomes older than `calibration_horizon_ms` (favorable if the mid moved in
+    /// the signal's direction), then blend a directional signal's confidence with its type's
+    /// hit-rate on this symbol and start tracking its outcome
+    fn calibrate_with_outcomes(&self, outcomes: &mut SymbolOutcomes, mut signal: TradingSignal, mid: f64, now: u64) -> TradingSignal {
+        let horizon = self.config.calibration_horizon_ms;
+        let mut resolved = Vec::new();
+        outcomes.pending.retain(|pending| {
+            let due = now.saturating_sub(pending.emitted_at) >= horizon;
+            if due {
+                resolved.push(*pending);
+            }
+            !due
+        });
+        for pending in resolved {
+            let favorable = (mid - pending.entry_mid) * f64::from(pending.signal_type.direction()) > 0.0;
+            let stats = outcomes.resolved.entry(pending.signal_type).or_insert((0, 0));
+            stats.0 += u64::from(favorable);
+            stats.1 += 1;
+        }
+
+        if signal.signal_type.direction() == 0 {
+            return signal;
+        }
+        let (favorable, total) = outcomes.resolved.get(&signal.signal_type).copied().unwrap_or((0, 0));
+        signal.confidence = calibrate_confidence(signal.confidence, favorable, total, self.config.calibration_min_samples);
+        if !outcomes.pending.iter().any(|pending| pending.signal_type == signal.signal_type) {
+            outcomes.pending.push(PendingOutcome { signal_type: signal.signal_type, entry_mid: mid, emitted_at: now });
+        }
+        signal
+    }
+
+    /// Resolved (favorable, total) outcomes of a signal type on a symbol
+    pub async fn signal_outcomes(&self, symbol: &str, signal_type: SignalType) -> (u64, u64) {
+        self.outcomes.lock().await.get(symbol).and_then(|outcomes| outcomes.resolved.get(&signal_type).copied()).unwrap_or((0, 0))
+    }
+
+    /// Within `direction_stickiness_ms` of the last directional signal, replace an opposite-direction
+    /// signal with a NoSignal unless its confidence reaches `direction_flip_confidence`
+    fn apply_direction_stickiness(&self, last_directions: &mut HashMap<String, DirectionState>, signal: TradingSignal) -> TradingSignal {
+        let direction = signal.signal_type.direction();
+        if direction == 0 {
+            return signal;
+        }
+        let now = signal.timestamp;
+        if let Some(last) = last_directions.get(&signal.symbol) {
+            let within_window = now.saturating_sub(last.emitted_at) < self.config.direction_stickiness_ms;
+            let clears_bar = self.config.direction_flip_confidence > 0.0 && signal.confidence >= self.config.direction_flip_confidence;
+            if last.direction != direction && within_window && !clears_bar {
+                info!("[Rust] {:?} for {} suppressed: {}ms after an opposite signal, confidence {:.2} below flip bar {:.2}.", signal.signal_type, signal.symbol, now.saturating_sub(last.emitted_at), signal.confidence, self.config.direction_flip_confidence);
+                return TradingSignal::no_signal_at(
+                    &signal.symbol,
+                    signal.price,
+                    signal.timestamp,
+                    format!("{:?} suppressed: opposite direction inside the stickiness window", signal.signal_type),
+                );
+            }
+        }
+        last_directions.insert(signal.symbol.clone(), DirectionState { direction, emitted_at: now });
+        signal
+    }
+
+    /// Emit a signal only once the same signal type has qualified on every analysis for
+    /// `signal_confirm_ms`; a candidate is discarded as soon as an analysis no longer produces it.
+    /// `now` is the analysis' event time (latest of book and trade timestamps).
+    fn confirm_candidate(
+        &self,
+        candidates: &mut HashMap<String, PendingCandidate>,
+        signal: TradingSignal,
+        symbol: &str,
+        now: u64,
+    ) -> TradingSignal {
+        if matches!(signal.signal_type, SignalType::NoSignal) {
+            if let Some(candidate) = candidates.remove(symbol) {
+                info!("[Rust] {:?} candidate for {} discarded after {}ms: no longer qualifies.", candidate.signal_type, symbol, now.saturating_sub(candidate.first_seen));
+            }
+            return signal;
+        }
+
+        let candidate = candidates
+            .entry(symbol.to_string())
+            .and_modify(|candidate| {
+                if candidate.signal_type != signal.signal_type {
+                    *candidate = PendingCandidate { signal_type: signal.signal_type, first_seen: now };
+                }
+            })
+            .or_insert(PendingCandidate { signal_type: signal.signal_type, first_seen: now });
+
+        let held_ms = now.saturating_sub(candidate.first_seen);
+        if held_ms >= self.config.signal_confirm_ms {
+            return signal;
+        }
+        TradingSignal::no_signal_at(
+            symbol,
+            signal.price,
+            signal.timestamp,
+            format!("{:?} candidate awaiting confirmation ({}ms of {}ms)", signal.signal_type, held_ms, self.config.signal_confirm_ms),
         )
     }
+
+    /// Resolve pending absorptions against subsequent trades and defer fresh ones.
+    ///
+    /// A confirmed absorption is emitted at `absorption_confirmed_confidence`. A fresh absorption
+    /// is either emitted as a tentative signal or suppressed until confirmed, and is dropped once
+    /// `absorption_confirmation_window_ms` passes without a flip. Confirmation is delta in the
+    /// signal's direction, so a continuation absorption is confirmed by the aggressors persisting.
+    fn confirm_absorption(
+        &self,
+        pending: &mut HashMap<String, PendingAbsorption>,
+        signal: TradingSignal,
+        order_book: &OrderBookSnapshot,
+        trades: &[&TradeData],
+    ) -> TradingSignal {
+        let symbol = &order_book.symbol;
+        let now = order_book.timestamp;
+        let window_ms = self.config.absorption_confirmation_window_ms;
+
+        if let Some(absorption) = pending.get(symbol).cloned() {
+            if now.saturating_sub(absorption.detected_at) > window_ms {
+                info!("[Rust] Absorption for {} expired after {}ms without delta flip confirmation.", symbol, window_ms);
+                pending.remove(symbol);
+            } else {
+                let delta_since = calculate_delta_since(trades, absorption.detected_at);
+                let flipped = match absorption.direction {
+                    SignalType::Buy => delta_since > 0.0,
+                    SignalType::Sell => delta_since < 0.0,
+                    _ => false,
+                };
+                if flipped {
+                    pending.remove(symbol);
+                    return TradingSignal {
+                        symbol: symbol.clone(),
+                        signal_type: absorption.direction,
+                        price: signal.price,
+                        confidence: self.config.absorption_confirmed_confidence,
+                        reason: format!("{} Confirmed by delta flip ({:.0}).", absorption.reason, delta_since),
+                        reason_code: ReasonCode::AbsorptionConfirmed,
+                        timestamp: now,
+                        tier: None,
+                        explanation: None,
+                        corroborated_by: Vec::new(),
+                        effective_params: None,
+                        max_advisable_size: max_advisable_size(order_book, absorption.direction, &self.config),
+                        chosen_lookback_ms: None,
+                    };
+                }
+            }
+        }
+
+        if signal.reason_code != ReasonCode::Absorption {
+            return signal;
+        }
+
+        // Keep the earliest detection unless the absorption direction changed
+        let replace = pending
+            .get(symbol)
+            .map_or(true, |absorption| absorption.direction != signal.signal_type);
+        if replace {
+            pending.insert(
+                symbol.clone(),
+                PendingAbsorption { direction: signal.signal_type, detected_at: now, reason: signal.reason.clone() },
+            );
+        }
+
+        match self.config.absorption_tentative_confidence {
+            Some(confidence) => TradingSignal {
+                confidence,
+                reason: format!("Tentative, awaiting delta flip: {}", signal.reason),
+                ..signal
+            },
+            None => TradingSignal::no_signal_with_reason(symbol, "Absorption detected, awaiting delta flip confirmation"),
+        }
+    }
 }
 
 // Helper function to run analysis with a specific configuration (used by Python bindings)
@@ -88,7 +1180,20 @@ pub async fn run_analysis_with_config(
     lookback_period_ms: u64,
     config: crate::config::OFIConfig,
 ) -> Result<Option<TradingSignal>> {
-    // Input validation
+    validate_analysis_request(&symbol, imbalance_ratio, duration_ms, delta_threshold, lookback_period_ms, &config)?;
+    let engine = analysis_engine(imbalance_ratio, delta_threshold, lookback_period_ms, config);
+    run_analysis_on_engine(symbol, duration_ms, engine).await
+}
+
+/// Validate the inputs of a single analysis request
+pub fn validate_analysis_request(
+    symbol: &str,
+    imbalance_ratio: f64,
+    duration_ms: u64,
+    delta_threshold: f64,
+    lookback_period_ms: u64,
+    config: &OFIConfig,
+) -> Result<()> {
     if symbol.is_empty() { return Err(anyhow!("Symbol cannot be empty")); }
     if symbol.len() > 20 { return Err(anyhow!("Symbol is too long: max 20 characters")); }
     if !symbol.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '/') {
@@ -102,9 +1207,11 @@ pub async fn run_analysis_with_config(
     if lookback_period_ms == 0 || lookback_period_ms > 300000 { // 5 minutes max
         return Err(anyhow!("Lookback period must be between 1ms and 5 minutes"));
     }
+    Ok(())
+}
 
-    info!("[Rust] Starting analysis for {} for {}ms", symbol, duration_ms);
-
+/// Build the engine for a single analysis request
+pub fn analysis_engine(imbalance_ratio: f64, delta_threshold: f64, lookback_period_ms: u64, config: OFIConfig) -> OFIEngine {
     let params = crate::signals::StrategyParams {
         imbalance_threshold: imbalance_ratio,
         absorption_threshold: config.absorption_threshold,
@@ -112,14 +1219,27 @@ pub async fn run_analysis_with_config(
         lookback_period_ms,
         market_condition_multiplier: 1.0,
     };
+    OFIEngine::new(params, config)
+}
+
+/// Run a single analysis on an existing engine, so the caller can inspect the engine's data afterwards
+pub async fn run_analysis_on_engine(
+    symbol: String,
+    duration_ms: u64,
+    engine: OFIEngine,
+) -> Result<Option<TradingSignal>> {
+    info!("[Rust] Starting analysis for {} for {}ms", symbol, duration_ms);
 
-    let engine = OFIEngine::new(params, config);
     let analysis_duration = Duration::from_millis(duration_ms);
 
     // Run the WebSocket manager and wait for the first signal within a timeout
-    let mut signal_rx = run_websocket_manager(symbol.clone(), engine).await;
+    let (mut signal_rx, manager) = spawn_websocket_manager(symbol.clone(), engine);
+    let result = timeout(analysis_duration, signal_rx.recv()).await;
 
-    match timeout(analysis_duration, signal_rx.recv()).await {
+    // The manager would otherwise keep its connection open after this call returns
+    manager.abort();
+
+    match result {
         Ok(Some(signal)) => {
             // A signal was received within the time limit.
             if matches!(signal.signal_type, crate::signals::SignalType::NoSignal) {
@@ -169,4 +1289,648 @@ pub async fn run_analysis(
         lookback_period_ms,
         config,
     ).await
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::OrderBookLevel;
+
+    fn level(price: f64, quantity: f64) -> OrderBookLevel {
+        OrderBookLevel { price, quantity }
+    }
+
+    fn book(timestamp: u64) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            symbol: "BTCUSDT".to_string(),
+            bids: vec![level(99.0, 10.0), level(98.0, 10.0)],
+            asks: vec![level(101.0, 10.0), level(102.0, 10.0)],
+            timestamp,
+        }
+    }
+
+    fn trade(timestamp: u64, side: &str, quantity: f64) -> TradeData {
+        TradeData { symbol: "BTCUSDT".to_string(), price: 100.0, quantity, side: side.to_string(), timestamp }
+    }
+
+    fn engine(config: OFIConfig) -> OFIEngine {
+        analysis_engine(config.imbalance_threshold, config.delta_threshold, config.lookback_period_ms, config)
+    }
+
+    fn absorption(signal_type: SignalType, timestamp: u64) -> TradingSignal {
+        TradingSignal {
+            signal_type,
+            confidence: 0.7,
+            reason_code: ReasonCode::Absorption,
+            timestamp,
+            ..TradingSignal::no_signal_with_reason("BTCUSDT", "Absorption at the bid")
+        }
+    }
+
+    fn two_stage_absorption_engine() -> OFIEngine {
+        engine(OFIConfig {
+            absorption_confirmation_window_ms: 1_000,
+            absorption_confirmed_confidence: 0.95,
+            ..OFIConfig::for_tests()
+        })
+    }
+
+    #[test]
+    fn absorption_is_emitted_once_delta_flips_within_the_window() {
+        let engine = two_stage_absorption_engine();
+        let mut pending = HashMap::new();
+
+        let held = engine.confirm_absorption(&mut pending, absorption(SignalType::Buy, 1_000), &book(1_000), &[]);
+        assert_eq!(held.signal_type, SignalType::NoSignal, "fresh absorption waits for confirmation");
+        assert!(pending.contains_key("BTCUSDT"));
+
+        let flip = [trade(900, "sell", 5.0), trade(1_200, "buy", 3.0), trade(1_400, "sell", 1.0)];
+        let trades: Vec<&TradeData> = flip.iter().collect();
+        let confirmed = engine.confirm_absorption(&mut pending, TradingSignal::no_signal("BTCUSDT"), &book(1_500), &trades);
+        assert_eq!(confirmed.signal_type, SignalType::Buy);
+        assert_eq!(confirmed.reason_code, ReasonCode::AbsorptionConfirmed);
+        assert_eq!(confirmed.confidence, 0.95);
+        assert_eq!(confirmed.timestamp, 1_500);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn absorption_without_a_flip_expires_unemitted() {
+        let engine = two_stage_absorption_engine();
+        let mut pending = HashMap::new();
+        engine.confirm_absorption(&mut pending, absorption(SignalType::Buy, 1_000), &book(1_000), &[]);
+
+        let continuation = [trade(1_200, "sell", 3.0)];
+        let trades: Vec<&TradeData> = continuation.iter().collect();
+        let still_held = engine.confirm_absorption(&mut pending, TradingSignal::no_signal("BTCUSDT"), &book(1_800), &trades);
+        assert_eq!(still_held.signal_type, SignalType::NoSignal);
+        assert!(pending.contains_key("BTCUSDT"), "selling pressure alone doesn't confirm a bid absorption");
+
+        let expired = engine.confirm_absorption(&mut pending, TradingSignal::no_signal("BTCUSDT"), &book(2_100), &trades);
+        assert_eq!(expired.signal_type, SignalType::NoSignal);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn tentative_confidence_emits_absorption_before_confirmation() {
+        let engine = engine(OFIConfig {
+            absorption_confirmation_window_ms: 1_000,
+            absorption_confirmed_confidence: 0.95,
+            absorption_tentative_confidence: Some(0.4),
+            ..OFIConfig::for_tests()
+        });
+        let mut pending = HashMap::new();
+        let tentative = engine.confirm_absorption(&mut pending, absorption(SignalType::Sell, 1_000), &book(1_000), &[]);
+        assert_eq!((tentative.signal_type, tentative.confidence), (SignalType::Sell, 0.4));
+        assert!(tentative.reason.starts_with("Tentative"));
+    }
+
+    #[tokio::test]
+    async fn analysis_trade_limit_bounds_the_trades_analysed() {
+        let delta_with_limit = |limit: usize| async move {
+            let engine = engine(OFIConfig { analysis_trade_limit: limit, ..OFIConfig::for_tests() });
+            engine.update_order_book(book(10_000)).await;
+            for i in 0..10 {
+                engine.add_trade(trade(9_000 + i * 10, "buy", 1.0)).await;
+            }
+            engine.current_metrics("BTCUSDT").await.expect("book stored").delta
+        };
+
+        let all = delta_with_limit(100).await;
+        assert!(all > 0.0);
+        assert_eq!(delta_with_limit(3).await * 10.0, all * 3.0, "only the 3 most recent trades reach the analysis");
+        assert_eq!(delta_with_limit(10).await, all);
+    }
+
+    /// A two-tick book centred on `mid`
+    fn book_at(mid: f64, timestamp: u64) -> OrderBookSnapshot {
+        OrderBookSnapshot { bids: vec![level(mid - 0.5, 10.0)], asks: vec![level(mid + 0.5, 10.0)], ..book(timestamp) }
+    }
+
+    fn jump_guarded_engine() -> OFIEngine {
+        engine(OFIConfig { jump_suppress_bps: 100.0, jump_cooldown_ms: 2_000, ..OFIConfig::for_tests() })
+    }
+
+    #[test]
+    fn mid_jump_suppresses_signals_until_the_cooldown_ends() {
+        let engine = jump_guarded_engine();
+        let mut states = HashMap::new();
+        let buy = |timestamp| absorption(SignalType::Buy, timestamp);
+
+        assert!(engine.check_price_jump(&mut states, &buy(1_000), &book_at(100.0, 1_000), &[]).is_none());
+        let suppressed = engine
+            .check_price_jump(&mut states, &buy(1_100), &book_at(105.0, 1_100), &[])
+            .expect("a 500bps gap suppresses the signal");
+        assert_eq!(suppressed.signal_type, SignalType::NoSignal);
+        assert!(suppressed.reason.contains("price jump"));
+
+        assert!(engine.check_price_jump(&mut states, &buy(3_000), &book_at(105.0, 3_000), &[]).is_some(), "still cooling down");
+        assert!(engine.check_price_jump(&mut states, &buy(3_100), &book_at(105.0, 3_100), &[]).is_none(), "resumes after the cooldown");
+        assert_eq!(engine.jump_suppressions(), 2);
+    }
+
+    #[test]
+    fn trade_price_gap_starts_a_cooldown_and_no_signal_is_not_counted() {
+        let engine = jump_guarded_engine();
+        let mut states = HashMap::new();
+        engine.check_price_jump(&mut states, &TradingSignal::no_signal("BTCUSDT"), &book_at(100.0, 1_000), &[]);
+
+        // Newest first: the last print is 3% above the one before it
+        let gap = [TradeData { price: 103.0, ..trade(1_090, "buy", 1.0) }, trade(1_080, "buy", 1.0)];
+        let trades: Vec<&TradeData> = gap.iter().collect();
+        assert!(engine.check_price_jump(&mut states, &TradingSignal::no_signal("BTCUSDT"), &book_at(100.0, 1_100), &trades).is_none());
+        assert!(engine.check_price_jump(&mut states, &absorption(SignalType::Sell, 1_200), &book_at(100.0, 1_200), &[]).is_some());
+        assert_eq!(engine.jump_suppressions(), 1);
+    }
+
+    #[tokio::test]
+    async fn active_symbols_lists_every_symbol_with_its_counts() {
+        let engine = engine(OFIConfig::for_tests());
+        engine.update_order_book(book(10_000)).await;
+        for timestamp in [9_000, 9_100, 9_200] {
+            engine.add_trade(trade(timestamp, "buy", 1.0)).await;
+        }
+        // Subscribed but only the trade channel delivers
+        for timestamp in [9_000, 9_100] {
+            engine.add_trade(TradeData { symbol: "ETHUSDT".to_string(), ..trade(timestamp, "sell", 1.0) }).await;
+        }
+
+        assert_eq!(engine.active_symbols().await, ["BTCUSDT", "ETHUSDT"]);
+        let status = engine.symbol_data_status().await;
+        assert_eq!((status[0].trade_count, status[0].book_levels), (3, 4));
+        assert!(status[0].book_age_ms.is_some());
+        assert_eq!((status[1].trade_count, status[1].book_levels, status[1].book_age_ms), (2, 0, None));
+
+        let unknown = engine.symbol_status("SOLUSDT").await;
+        assert_eq!((unknown.trade_count, unknown.book_levels, unknown.ready), (0, 0, false));
+    }
+
+    #[tokio::test]
+    async fn memory_budget_evicts_the_least_recently_active_symbols() {
+        // Room for five symbols holding a four-level book and two trades each
+        let engine = engine(OFIConfig { global_entry_limit: 30, ..OFIConfig::for_tests() });
+        let symbol_book = |symbol: &str, timestamp: u64| OrderBookSnapshot { symbol: symbol.to_string(), ..book(timestamp) };
+        for i in 0..8u64 {
+            let symbol = format!("SYM{}", i);
+            engine.update_order_book(symbol_book(&symbol, 10_000 + i)).await;
+            for timestamp in [9_000, 9_100] {
+                engine.add_trade(TradeData { symbol: symbol.clone(), ..trade(timestamp, "buy", 1.0) }).await;
+            }
+            // SYM0 keeps receiving books, so it stays the most recently active
+            let mut refreshed = symbol_book("SYM0", 20_000 + i);
+            refreshed.bids[0].quantity += i as f64;
+            engine.update_order_book(refreshed).await;
+        }
+
+        assert_eq!(engine.active_symbols().await, ["SYM0", "SYM4", "SYM5", "SYM6", "SYM7"]);
+        let budget = engine.memory_budget.as_ref().unwrap();
+        assert!(budget.total_entries() <= 30);
+        assert_eq!(budget.tracked_symbols().last().map(String::as_str), Some("SYM0"));
+    }
+
+    #[tokio::test]
+    async fn off_book_trades_never_reach_delta() {
+        let engine = engine(OFIConfig { max_trade_deviation_bps: 50.0, ..OFIConfig::for_tests() });
+        engine.update_order_book(book(10_000)).await;
+        engine.add_trade(TradeData { price: 100.2, ..trade(9_000, "buy", 2.0) }).await;
+        engine.add_trade(TradeData { price: 110.0, ..trade(9_100, "buy", 50.0) }).await;
+        engine.add_trade(TradeData { price: 90.0, ..trade(9_200, "sell", 50.0) }).await;
+        engine.add_trade(TradeData { price: 99.9, ..trade(9_300, "sell", 1.0) }).await;
+
+        assert_eq!(engine.rejected_trades(), 2);
+        let expected_delta = 100.2 * 2.0 - 99.9;
+        assert!((engine.session_cvd("BTCUSDT").await - expected_delta).abs() < 1e-9);
+        assert!((engine.current_metrics("BTCUSDT").await.unwrap().delta - expected_delta).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn trades_are_accepted_while_there_is_no_book_to_compare() {
+        let engine = engine(OFIConfig { max_trade_deviation_bps: 50.0, ..OFIConfig::for_tests() });
+        engine.add_trade(TradeData { price: 250.0, ..trade(9_000, "buy", 1.0) }).await;
+        assert_eq!(engine.rejected_trades(), 0);
+        assert_eq!(engine.symbol_status("BTCUSDT").await.trade_count, 1);
+    }
+
+    fn confirming_engine() -> OFIEngine {
+        engine(OFIConfig { signal_confirm_ms: 500, ..OFIConfig::for_tests() })
+    }
+
+    #[test]
+    fn candidate_persisting_through_the_window_is_emitted() {
+        let engine = confirming_engine();
+        let mut candidates = HashMap::new();
+        let buy = |timestamp| absorption(SignalType::Buy, timestamp);
+
+        let first = engine.confirm_candidate(&mut candidates, buy(1_000), "BTCUSDT", 1_000);
+        assert_eq!(first.signal_type, SignalType::NoSignal, "the first qualifying tick is held");
+        assert!(first.reason.contains("awaiting confirmation"));
+        assert_eq!(engine.confirm_candidate(&mut candidates, buy(1_300), "BTCUSDT", 1_300).signal_type, SignalType::NoSignal);
+        let confirmed = engine.confirm_candidate(&mut candidates, buy(1_500), "BTCUSDT", 1_500);
+        assert_eq!((confirmed.signal_type, confirmed.timestamp), (SignalType::Buy, 1_500));
+    }
+
+    #[test]
+    fn candidate_flickering_out_is_discarded() {
+        let engine = confirming_engine();
+        let mut candidates = HashMap::new();
+        engine.confirm_candidate(&mut candidates, absorption(SignalType::Buy, 1_000), "BTCUSDT", 1_000);
+        engine.confirm_candidate(&mut candidates, TradingSignal::no_signal("BTCUSDT"), "BTCUSDT", 1_200);
+        assert!(candidates.is_empty());
+
+        // Requalifying restarts the window, as does switching direction
+        assert_eq!(engine.confirm_candidate(&mut candidates, absorption(SignalType::Buy, 1_600), "BTCUSDT", 1_600).signal_type, SignalType::NoSignal);
+        assert_eq!(engine.confirm_candidate(&mut candidates, absorption(SignalType::Sell, 2_100), "BTCUSDT", 2_100).signal_type, SignalType::NoSignal);
+        assert_eq!(engine.confirm_candidate(&mut candidates, absorption(SignalType::Sell, 2_600), "BTCUSDT", 2_600).signal_type, SignalType::Sell);
+    }
+
+    /// Five bid levels far larger than the asks, which analyzes to a StrongBuy with enough buying
+    fn stacked_bid_book(timestamp: u64) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            bids: (0..5).map(|i| level(99.9 - i as f64 * 0.1, 50.0)).collect(),
+            asks: (0..5).map(|i| level(100.0 + i as f64 * 0.1, 1.0)).collect(),
+            ..book(timestamp)
+        }
+    }
+
+    #[tokio::test]
+    async fn book_strategies_run_once_per_book_version() {
+        let engine = engine(OFIConfig { analysis_version_gate: true, ..OFIConfig::for_tests() });
+        engine.update_order_book(stacked_bid_book(10_000)).await;
+        engine.add_trade(trade(9_500, "buy", 600.0)).await;
+
+        let mut stacked_signals = 0;
+        for step in 0..6u64 {
+            // Trade-only messages interleave with every book update
+            if step % 3 == 0 && step > 0 {
+                let mut next = stacked_bid_book(10_000 + step * 100);
+                next.bids[0].quantity += step as f64;
+                engine.update_order_book(next).await;
+            } else if step > 0 {
+                engine.add_trade(trade(9_500 + step * 100, "buy", 1.0)).await;
+            }
+            let signal = engine.analyze_symbol("BTCUSDT").await;
+            if signal.reason_code == ReasonCode::StackedImbalance {
+                stacked_signals += 1;
+            }
+        }
+        assert_eq!(stacked_signals, 2, "one per book version, however many trades arrive");
+
+        let unchanged = engine.analyze_symbol("BTCUSDT").await;
+        assert_eq!(unchanged.signal_type, SignalType::NoSignal);
+        assert!(unchanged.reason.contains("No new book or trade data"));
+    }
+
+    #[tokio::test]
+    async fn readiness_flips_once_warm_up_data_has_accumulated() {
+        let board = ReadinessBoard::new();
+        let engine = engine(OFIConfig { warmup_min_trades: 3, warmup_min_book_levels: 2, ..OFIConfig::for_tests() })
+            .with_readiness_board(board.clone());
+        assert!(!engine.is_ready("BTCUSDT").await, "no data yet");
+
+        // A one-level book is too shallow
+        let mut shallow = book(10_000);
+        shallow.bids.truncate(1);
+        engine.update_order_book(shallow).await;
+        assert!(!engine.is_ready("BTCUSDT").await);
+
+        engine.update_order_book(book(10_100)).await;
+        for timestamp in [9_000, 9_100] {
+            engine.add_trade(trade(timestamp, "buy", 1.0)).await;
+        }
+        assert!(!engine.is_ready("BTCUSDT").await, "two of three trades");
+        engine.analyze_symbol("BTCUSDT").await;
+        assert!(!board.is_ready("BTCUSDT"));
+
+        engine.add_trade(trade(9_200, "buy", 1.0)).await;
+        assert!(engine.is_ready("BTCUSDT").await);
+        engine.analyze_symbol("BTCUSDT").await;
+        assert!(board.is_ready("BTCUSDT"), "published on the next analysis");
+    }
+
+    #[test]
+    fn opposite_signals_inside_the_stickiness_window_need_the_flip_bar() {
+        let engine = engine(OFIConfig { direction_stickiness_ms: 1_000, direction_flip_confidence: 0.9, ..OFIConfig::for_tests() });
+        let mut last_directions = HashMap::new();
+        let directional = |signal_type: SignalType, confidence: f64, timestamp: u64| TradingSignal { confidence, ..absorption(signal_type, timestamp) };
+
+        let buy = engine.apply_direction_stickiness(&mut last_directions, directional(SignalType::Buy, 0.7, 1_000));
+        assert_eq!(buy.signal_type, SignalType::Buy);
+
+        // Alternating quickly: the sell is held back at ordinary confidence
+        let sell = engine.apply_direction_stickiness(&mut last_directions, directional(SignalType::Sell, 0.7, 1_200));
+        assert_eq!(sell.signal_type, SignalType::NoSignal);
+        // The same direction again is unaffected
+        let again = engine.apply_direction_stickiness(&mut last_directions, directional(SignalType::StrongBuy, 0.7, 1_300));
+        assert_eq!(again.signal_type, SignalType::StrongBuy);
+
+        // Clearing the elevated bar flips straight away
+        let confident_sell = engine.apply_direction_stickiness(&mut last_directions, directional(SignalType::Sell, 0.95, 1_400));
+        assert_eq!(confident_sell.signal_type, SignalType::Sell);
+
+        // After the window the opposite direction needs no more than usual
+        let late_buy = engine.apply_direction_stickiness(&mut last_directions, directional(SignalType::Buy, 0.7, 2_400));
+        assert_eq!(late_buy.signal_type, SignalType::Buy);
+    }
+
+    #[test]
+    fn calibration_follows_a_sampled_history_and_defers_without_one() {
+        // Too few outcomes: the raw confidence stands
+        assert_eq!(calibrate_confidence(0.6, 4, 5, 20), 0.6);
+        assert_eq!(calibrate_confidence(0.6, 0, 0, 0), 0.6);
+
+        // A strong favorable history pulls the confidence up, more so with more samples
+        let sampled = calibrate_confidence(0.6, 18, 20, 20);
+        let well_sampled = calibrate_confidence(0.6, 180, 200, 20);
+        assert!((sampled - 0.75).abs() < 1e-9, "{}", sampled);
+        assert!(well_sampled > sampled && well_sampled < 0.9);
+
+        // An unfavorable history pulls it down
+        assert!(calibrate_confidence(0.6, 4, 20, 20) < 0.6);
+    }
+
+    #[tokio::test]
+    async fn duplicate_snapshots_are_skipped_per_the_dedupe_policy() {
+        let exact = engine(OFIConfig { book_dedupe: BookDedupe::Exact, ..OFIConfig::for_tests() });
+        assert!(exact.update_order_book(book(10_000)).await);
+        assert!(!exact.update_order_book(book(10_000)).await, "identical resend is skipped");
+        assert_eq!(exact.redundant_books(), 1);
+        // Under Exact a new timestamp is a legitimate update
+        assert!(exact.update_order_book(book(10_100)).await);
+        let mut changed = book(10_100);
+        changed.bids[0].quantity = 11.0;
+        assert!(exact.update_order_book(changed).await);
+        assert_eq!(exact.redundant_books(), 1);
+
+        // Under Levels the same levels are skipped whatever the timestamp, which still advances
+        let levels = engine(OFIConfig { book_dedupe: BookDedupe::Levels, ..OFIConfig::for_tests() });
+        assert!(levels.update_order_book(book(10_000)).await);
+        assert!(!levels.update_order_book(book(10_500)).await);
+        assert_eq!(levels.redundant_books(), 1);
+        assert_eq!(levels.order_book_storage.lock().await.get_order_book("BTCUSDT").unwrap().timestamp, 10_500);
+
+        let off = engine(OFIConfig::for_tests());
+        assert!(off.update_order_book(book(10_000)).await && off.update_order_book(book(10_000)).await);
+        assert_eq!(off.redundant_books(), 0);
+    }
+
+    #[tokio::test]
+    async fn feeding_two_legs_derives_the_synthetic_mid_and_delta() {
+        let spread = crate::config::SyntheticInstrument { name: "BTC-ETH".to_string(), long_leg: "BTCUSDT".to_string(), short_leg: "ETHUSDT".to_string(), ratio: 20.0 };
+        let engine = engine(OFIConfig { synthetics: vec![spread], ..OFIConfig::for_tests() });
+        let leg = |symbol: &str, bid: f64, ask: f64| OrderBookSnapshot { symbol: symbol.to_string(), bids: vec![level(bid, 2.0)], asks: vec![level(ask, 2.0)], timestamp: 10_000 };
+        let leg_trade = |symbol: &str, side: &str, quantity: f64, price: f64| TradeData { symbol: symbol.to_string(), price, quantity, side: side.to_string(), timestamp: 9_500 };
+
+        engine.update_order_book(leg("BTCUSDT", 60_000.0, 60_010.0)).await;
+        assert!(engine.current_metrics("BTC-ETH").await.is_none(), "one leg is not enough");
+        engine.update_order_book(leg("ETHUSDT", 2_990.0, 2_991.0)).await;
+        engine.add_trade(leg_trade("BTCUSDT", "buy", 1.0, 60_010.0)).await;
+        engine.add_trade(leg_trade("ETHUSDT", "buy", 20.0, 2_991.0)).await;
+
+        let storage = engine.order_book_storage.lock().await;
+        let book = storage.get_order_book("BTC-ETH").expect("synthetic book");
+        assert_eq!((book.bids[0].price + book.asks[0].price) / 2.0, 195.0);
+        drop(storage);
+        // A 200 spread buy from the long leg and a 185 spread sell from the short leg
+        assert_eq!(engine.current_metrics("BTC-ETH").await.unwrap().delta, 15.0);
+    }
+
+    #[tokio::test]
+    async fn overlapping_triggers_coalesce_into_one_analysis_of_the_latest_state() {
+        let engine = engine(OFIConfig::for_tests());
+        engine.update_order_book(book(10_000)).await;
+
+        // Hold the book so the first analysis is in flight when the next trigger arrives
+        let mut storage = engine.order_book_storage.lock().await;
+        let mut first = Box::pin(engine.analyze_symbol_coalesced("BTCUSDT"));
+        assert!(tokio::time::timeout(Duration::from_millis(20), &mut first).await.is_err(), "blocked on the book");
+        assert!(engine.analyze_symbol_coalesced("BTCUSDT").await.is_none(), "the overlapping trigger is coalesced");
+
+        // Newer data arrives while the first analysis waits; the result reflects it
+        let mut latest = book(10_500);
+        latest.bids = vec![level(199.0, 10.0)];
+        latest.asks = vec![level(201.0, 10.0)];
+        storage.update_order_book(latest);
+        drop(storage);
+
+        let signal = first.await.expect("the in-flight call returns the result");
+        assert_eq!(signal.price, 200.0);
+        assert!(engine.analyses_in_flight.lock().unwrap().is_empty(), "the symbol is free again");
+    }
+
+    #[tokio::test]
+    async fn strong_signals_need_the_minimum_book_update_rate() {
+        let config = OFIConfig { delta_threshold: 1000.0, min_book_update_rate: 2.0, ..OFIConfig::for_tests() };
+
+        // Ten updates a second over the lookback
+        let fast = engine(config.clone());
+        for step in 0..=40u64 {
+            let mut next = stacked_bid_book(6_000 + step * 100);
+            next.bids[0].quantity += step as f64;
+            fast.update_order_book(next).await;
+        }
+        fast.add_trade(trade(9_500, "buy", 500.0)).await;
+        let rate = fast.current_metrics("BTCUSDT").await.expect("book stored").book_update_rate;
+        assert!((rate - 10.0).abs() < 0.01, "measured {}/s", rate);
+        assert_eq!(fast.analyze_symbol("BTCUSDT").await.signal_type, SignalType::StrongBuy);
+
+        // One update in the lookback, the previous one seconds before it
+        let slow = engine(config);
+        slow.update_order_book(stacked_bid_book(2_000)).await;
+        slow.update_order_book(stacked_bid_book(10_000)).await;
+        slow.add_trade(trade(9_500, "buy", 500.0)).await;
+        assert_eq!(slow.current_metrics("BTCUSDT").await.expect("book stored").book_update_rate, 0.0);
+        let signal = slow.analyze_symbol("BTCUSDT").await;
+        assert_eq!(signal.signal_type, SignalType::Buy);
+        assert!(signal.reason.contains("downgraded from StrongBuy"), "{}", signal.reason);
+    }
+
+    #[tokio::test]
+    async fn recorded_params_reproduce_the_signal_with_its_multiplier() {
+        let config = OFIConfig {
+            record_effective_params: true,
+            market_condition_adaptation: true,
+            reference_volatility: 0.01,
+            delta_threshold: 1000.0,
+            ..OFIConfig::for_tests()
+        };
+        let engine = engine(config.clone());
+        let book = stacked_bid_book(10_000);
+        let prints = [
+            TradeData { price: 100.0, ..trade(9_000, "buy", 500.0) },
+            TradeData { price: 101.0, ..trade(9_200, "buy", 500.0) },
+            TradeData { price: 100.0, ..trade(9_400, "buy", 500.0) },
+        ];
+        engine.update_order_book(book.clone()).await;
+        for print in &prints {
+            engine.add_trade(print.clone()).await;
+        }
+
+        let signal = engine.analyze_symbol("BTCUSDT").await;
+        assert_ne!(signal.signal_type, SignalType::NoSignal);
+        let recorded = signal.effective_params.clone().expect("params recorded with the signal");
+
+        // The multiplier is the realized volatility against the reference, within the cap
+        let volatility = ((101.0f64 / 100.0).ln().powi(2) + (100.0f64 / 101.0).ln().powi(2)).sqrt();
+        assert!((recorded.market_condition_multiplier - volatility / 0.01).abs() < 1e-9);
+        assert_eq!(recorded.adjusted_delta_threshold, 1000.0 * recorded.market_condition_multiplier);
+        assert_eq!((recorded.imbalance_threshold, recorded.delta_threshold, recorded.lookback_period_ms), (3.0, 1000.0, 5000));
+
+        // detect_signals with the recorded params and settings gives the same signal
+        let mut replay_config = OFIConfig::for_tests();
+        recorded.apply_to(&mut replay_config);
+        let trades: Vec<&TradeData> = prints.iter().rev().collect();
+        let replayed = crate::signals::detect_signals(&book, &trades, &[], &recorded.strategy_params(), &replay_config);
+        assert_eq!((replayed.signal_type, replayed.reason_code, replayed.confidence), (signal.signal_type, signal.reason_code, signal.confidence));
+        assert_eq!(replayed.effective_params, None, "recording is off in the replay config");
+    }
+
+    #[tokio::test]
+    async fn data_collection_only_symbols_ingest_without_analysis() {
+        let mut config = OFIConfig { delta_threshold: 1000.0, ..OFIConfig::for_tests() };
+        let collect_only = crate::config::SymbolOverride { strategies: Some(Vec::new()), ..crate::config::SymbolOverride::default() };
+        config.symbol_overrides.insert("BTCUSDT".to_string(), collect_only);
+        let engine = engine(config);
+        assert!(!engine.config().analysis_enabled_for("BTCUSDT"));
+        assert!(engine.config().analysis_enabled_for("ETHUSDT"));
+
+        engine.update_order_book(stacked_bid_book(10_000)).await;
+        engine.add_trade(trade(9_500, "buy", 600.0)).await;
+        let signal = engine.analyze_symbol("BTCUSDT").await;
+        assert_eq!(signal.signal_type, SignalType::NoSignal);
+        assert!(signal.reason.contains("data collection only"), "{}", signal.reason);
+
+        // Data keeps arriving and is stored
+        engine.add_trade(trade(9_600, "buy", 1.0)).await;
+        let status = engine.symbol_status("BTCUSDT").await;
+        assert_eq!((status.trade_count, status.book_levels), (2, 10));
+        assert!(engine.current_metrics("BTCUSDT").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn market_data_subscribers_receive_the_events_the_engine_accepted() {
+        assert!(engine(OFIConfig::for_tests()).subscribe_market_data().is_none(), "no feed unless a capacity is set");
+        let small_feed = engine(OFIConfig { market_data_feed_capacity: 2, ..OFIConfig::for_tests() });
+
+        let engine = engine(OFIConfig { market_data_feed_capacity: 16, max_trade_deviation_bps: 50.0, ..OFIConfig::for_tests() });
+        let mut feed = engine.subscribe_market_data().unwrap();
+        engine.update_order_book(book(10_000)).await;
+        engine.add_trade(TradeData { price: 100.2, ..trade(10_100, "buy", 2.0) }).await;
+        // Rejected as off-book, so neither stored nor fanned out
+        engine.add_trade(TradeData { price: 110.0, ..trade(10_200, "buy", 50.0) }).await;
+        engine.add_trade(trade(10_300, "sell", 1.0)).await;
+
+        match feed.try_recv().unwrap() {
+            MarketDataEvent::OrderBook(received) => {
+                assert_eq!(received.timestamp, 10_000);
+                assert_eq!(received.bids, book(10_000).bids);
+                assert_eq!(received.asks, book(10_000).asks);
+            }
+            other => panic!("expected the book, got {:?}", other),
+        }
+        let trades: Vec<(u64, f64)> = std::iter::from_fn(|| feed.try_recv().ok())
+            .map(|event| match event {
+                MarketDataEvent::Trade(trade) => (trade.timestamp, trade.price),
+                other => panic!("expected a trade, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(trades, vec![(10_100, 100.2), (10_300, 100.0)]);
+        assert_eq!(engine.rejected_trades(), 1);
+
+        // A subscriber further behind than the capacity loses the oldest events
+        let mut lagging = small_feed.subscribe_market_data().unwrap();
+        for timestamp in [1_000, 1_100, 1_200] {
+            small_feed.add_trade(trade(timestamp, "buy", 1.0)).await;
+        }
+        assert!(matches!(lagging.try_recv(), Err(broadcast::error::TryRecvError::Lagged(1))));
+        assert!(matches!(lagging.try_recv(), Ok(MarketDataEvent::Trade(trade)) if trade.timestamp == 1_100));
+    }
+
+    #[tokio::test]
+    async fn extreme_funding_suppresses_continuation_into_the_crowded_side() {
+        let engine = engine(OFIConfig { delta_threshold: 1000.0, funding_rate_extreme: 0.0005, ..OFIConfig::for_tests() });
+        engine.update_order_book(stacked_bid_book(10_000)).await;
+        engine.add_trade(trade(9_500, "buy", 500.0)).await;
+        let ticker = |rate: &str| serde_json::json!([{ "instId": "BTCUSDT", "fundingRate": rate }]);
+
+        // No usable funding rate yet: the gate stays out of the way
+        engine.dispatch_channel_data("ticker", "BTCUSDT", ticker("n/a")).await;
+        assert_eq!(engine.analyze_symbol("BTCUSDT").await.signal_type, SignalType::StrongBuy);
+
+        // Longs paying 0.1%: a continuation long joins the crowded side
+        engine.dispatch_channel_data("ticker", "BTCUSDT", ticker("0.001")).await;
+        let suppressed = engine.analyze_symbol("BTCUSDT").await;
+        assert_eq!(suppressed.signal_type, SignalType::NoSignal);
+        assert!(suppressed.reason.contains("suppressed: extreme funding rate 0.1000%"), "{}", suppressed.reason);
+
+        // Shorts crowded instead, or funding within bounds: the continuation stands
+        for rate in ["-0.001", "0.0001"] {
+            engine.dispatch_channel_data("ticker", "BTCUSDT", ticker(rate)).await;
+            assert_eq!(engine.analyze_symbol("BTCUSDT").await.signal_type, SignalType::StrongBuy, "funding {}", rate);
+        }
+    }
+
+    /// A created span as (name, parent name)
+    type RecordedSpan = (&'static str, Option<&'static str>);
+
+    /// Records each span created
+    struct SpanRecorder(Arc<std::sync::Mutex<Vec<RecordedSpan>>>);
+
+    impl<S> tracing_subscriber::Layer<S> for SpanRecorder
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(&self, _: &tracing::span::Attributes<'_>, id: &tracing::span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+            let span = ctx.span(id).expect("span just created");
+            let parent = span.parent().map(|parent| parent.name());
+            self.0.lock().unwrap().push((span.name(), parent));
+        }
+    }
+
+    #[cfg(feature = "trace-spans")]
+    #[tokio::test]
+    async fn analysis_steps_are_traced_as_nested_spans() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let spans = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(SpanRecorder(Arc::clone(&spans)));
+        // The test runtime is single-threaded, so the thread-local default sees every poll
+        let _default = tracing::subscriber::set_default(subscriber);
+
+        let engine = engine(OFIConfig { absorption_confirmation_window_ms: 1_000, ..OFIConfig::for_tests() });
+        engine.update_order_book(book(10_000)).await;
+        engine.add_trade(trade(9_500, "sell", 30.0)).await;
+        engine.analyze_symbol("BTCUSDT").await;
+
+        let spans = spans.lock().unwrap().clone();
+        assert_eq!(spans[0], ("analyze_symbol", None), "{:?}", spans);
+        for expected in [
+            ("detect_signals", Some("analyze_symbol")),
+            ("ofi_metrics", Some("detect_signals")),
+            ("stacked_imbalance", Some("detect_signals")),
+            ("absorption", Some("detect_signals")),
+            ("confirm_absorption", Some("analyze_symbol")),
+        ] {
+            assert!(spans.contains(&expected), "{:?} missing from {:?}", expected, spans);
+        }
+        // Steps that are configured off leave no span
+        for disabled in ["confirm_candidate", "jump_check", "direction_stickiness", "calibration", "fast_mode"] {
+            assert!(spans.iter().all(|(name, _)| *name != disabled), "{} traced", disabled);
+        }
+    }
+
+    #[cfg(not(feature = "trace-spans"))]
+    #[tokio::test]
+    async fn analysis_steps_are_not_traced_without_the_feature() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let spans = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(SpanRecorder(Arc::clone(&spans)));
+        let _default = tracing::subscriber::set_default(subscriber);
+
+        let engine = engine(OFIConfig { absorption_confirmation_window_ms: 1_000, ..OFIConfig::for_tests() });
+        engine.update_order_book(book(10_000)).await;
+        engine.add_trade(trade(9_500, "sell", 30.0)).await;
+        engine.analyze_symbol("BTCUSDT").await;
+
+        assert!(spans.lock().unwrap().is_empty(), "{:?}", spans.lock().unwrap());
+    }
+}
@@ -0,0 +1,120 @@
+//! Funding rate gate for perpetual futures
+//!
+//! An extreme funding rate means one side is crowded and paying to stay in, which tends to
+//! precede mean reversion: continuation signals joining the crowded side are suppressed and
+//! reversal signals against it are boosted. Rates come from the `ticker` channel and expire after
+//! `funding_rate_ttl_secs`; without a fresh rate the gate does nothing.
+
+use crate::config::OFIConfig;
+use crate::signals::{ReasonCode, TradingSignal};
+use crate::websocket::ChannelHandler;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Latest funding rate per symbol with the time it was received
+pub struct FundingRateCache {
+    rates: Mutex<HashMap<String, (f64, Instant)>>,
+    ttl: Duration,
+}
+
+impl FundingRateCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { rates: Mutex::new(HashMap::new()), ttl }
+    }
+
+    pub fn set(&self, symbol: &str, rate: f64) {
+        self.rates.lock().unwrap().insert(symbol.to_string(), (rate, Instant::now()));
+    }
+
+    /// The symbol's funding rate, None if none was received within the TTL
+    pub fn get(&self, symbol: &str) -> Option<f64> {
+        let rates = self.rates.lock().unwrap();
+        rates.get(symbol).filter(|(_, received)| received.elapsed() <= self.ttl).map(|(rate, _)| *rate)
+    }
+}
+
+impl ChannelHandler for FundingRateCache {
+    /// Bitget `ticker` data carries the current rate as a decimal string in `fundingRate`;
+    /// messages without a parsable one are ignored
+    fn handle(&self, inst_id: &str, data: &serde_json::Value) {
+        let rate = data
+            .as_array()
+            .and_then(|tickers| tickers.first())
+            .and_then(|ticker| ticker.get("fundingRate"))
+            .and_then(|rate| rate.as_str())
+            .and_then(|rate| rate.parse::<f64>().ok());
+        if let Some(rate) = rate {
+            self.set(inst_id, rate);
+        }
+    }
+}
+
+/// Apply the funding gate: with |rate| at least `funding_rate_extreme`, a continuation signal in
+/// the crowded direction (long for positive funding) becomes NoSignal when
+/// `funding_suppress_continuation` is set, and a reversal signal against it has its confidence
+/// multiplied by `funding_reversal_boost` (capped at 1). No rate leaves the signal unchanged.
+pub fn apply_funding_gate(mut signal: TradingSignal, funding_rate: Option<f64>, config: &OFIConfig) -> TradingSignal {
+    let rate = match funding_rate {
+        Some(rate) if config.funding_rate_extreme > 0.0 && rate.abs() >= config.funding_rate_extreme => rate,
+        _ => return signal,
+    };
+    let crowded = if rate > 0.0 { 1 } else { -1 };
+    let direction = signal.signal_type.direction();
+    match signal.reason_code {
+        ReasonCode::StackedImbalance | ReasonCode::TopOfBookImbalance | ReasonCode::TouchImbalance
+            if config.funding_suppress_continuation && direction == crowded =>
+        {
+            let reason = format!("{:?} {} suppressed: extreme funding rate {:.4}%", signal.reason_code, signal.signal_type, rate * 100.0);
+            TradingSignal::no_signal_at(&signal.symbol, signal.price, signal.timestamp, reason)
+        }
+        ReasonCode::Absorption | ReasonCode::AbsorptionConfirmed | ReasonCode::Exhaustion if direction == -crowded => {
+            signal.confidence = (signal.confidence * config.funding_reversal_boost).min(1.0);
+            signal
+        }
+        _ => signal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::SignalType;
+
+    fn signal(signal_type: SignalType, reason_code: ReasonCode, confidence: f64) -> TradingSignal {
+        TradingSignal { signal_type, reason_code, confidence, ..TradingSignal::no_signal("BTCUSDT") }
+    }
+
+    #[test]
+    fn extreme_funding_boosts_reversals_against_the_crowd() {
+        let config = OFIConfig { funding_rate_extreme: 0.0005, funding_reversal_boost: 1.25, ..OFIConfig::for_tests() };
+
+        // Longs crowded: a sell absorption fades them, a buy absorption joins them
+        let fade = apply_funding_gate(signal(SignalType::Sell, ReasonCode::Absorption, 0.7), Some(0.001), &config);
+        assert!((fade.confidence - 0.875).abs() < 1e-9);
+        assert_eq!(apply_funding_gate(signal(SignalType::Buy, ReasonCode::Absorption, 0.7), Some(0.001), &config).confidence, 0.7);
+        assert_eq!(apply_funding_gate(signal(SignalType::Buy, ReasonCode::Exhaustion, 0.9), Some(-0.002), &config).confidence, 1.0, "capped at 1");
+
+        // Without a rate, below the extreme or with suppression off, nothing changes
+        let continuation = signal(SignalType::StrongBuy, ReasonCode::StackedImbalance, 0.9);
+        assert_eq!(apply_funding_gate(continuation.clone(), None, &config).signal_type, SignalType::StrongBuy);
+        assert_eq!(apply_funding_gate(continuation.clone(), Some(0.0004), &config).signal_type, SignalType::StrongBuy);
+        let allowed = OFIConfig { funding_suppress_continuation: false, ..config };
+        assert_eq!(apply_funding_gate(continuation, Some(0.001), &allowed).signal_type, SignalType::StrongBuy);
+    }
+
+    #[test]
+    fn cached_rates_expire_after_the_ttl() {
+        let cache = FundingRateCache::new(Duration::from_secs(60));
+        cache.handle("BTCUSDT", &serde_json::json!([{ "fundingRate": "0.0012" }]));
+        cache.handle("ETHUSDT", &serde_json::json!([{ "lastPr": "2500" }]));
+        assert_eq!(cache.get("BTCUSDT"), Some(0.0012));
+        assert_eq!(cache.get("ETHUSDT"), None, "no rate in the message");
+
+        // With no TTL a rate is stale as soon as any time has passed
+        let expired = FundingRateCache::new(Duration::ZERO);
+        expired.handle("BTCUSDT", &serde_json::json!([{ "fundingRate": "0.0012" }]));
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(expired.get("BTCUSDT"), None, "a stale rate is ignored");
+    }
+}
@@ -0,0 +1,229 @@
+//! Group (sector) signals: order flow aggregated across a basket of correlated symbols
+//!
+//! Each member contributes its net delta in notional terms, so members of very different price
+//! are comparable, and its imbalance weighted by its resting book notional, so thin books don't
+//! sway the group as much as deep ones. Members trading against each other cancel out.
+
+use crate::config::SymbolGroup;
+use crate::engine::OFIEngine;
+use crate::signals::{ReasonCode, SignalType, TradingSignal};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// One member's current flow, as read from its engine
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberFlow {
+    pub symbol: String,
+    pub mid_price: f64,
+    pub delta: f64,  // Net delta over the lookback in base units
+    pub buy_imbalance: f64,
+    pub sell_imbalance: f64,
+    pub book_notional: f64,  // Resting notional of the member's book, the imbalance weight
+}
+
+/// Flow of a group, combined from its members
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupFlow {
+    pub net_notional_delta: f64,  // Sum of the members' delta * mid
+    pub buy_imbalance: f64,  // Book-notional-weighted mean of the members' buy imbalance
+    pub sell_imbalance: f64,  // Book-notional-weighted mean of the members' sell imbalance
+    pub members: usize,
+}
+
+/// Combine member flows; None without members or when no member has resting notional
+pub fn aggregate_flows(members: &[MemberFlow]) -> Option<GroupFlow> {
+    let total_weight: f64 = members.iter().map(|member| member.book_notional).sum();
+    if members.is_empty() || total_weight <= 0.0 {
+        return None;
+    }
+    let weighted = |value: fn(&MemberFlow) -> f64| members.iter().map(|member| value(member) * member.book_notional).sum::<f64>() / total_weight;
+    Some(GroupFlow {
+        net_notional_delta: members.iter().map(|member| member.delta * member.mid_price).sum(),
+        buy_imbalance: weighted(|member| member.buy_imbalance),
+        sell_imbalance: weighted(|member| member.sell_imbalance),
+        members: members.len(),
+    })
+}
+
+/// Group-level signal from the members' flows. A Buy needs the net notional delta at or above the
+/// group's threshold and the weighted buy imbalance at or above its imbalance threshold; Sell
+/// mirrors it. The signal carries the group name as symbol and no price, as the basket has none.
+pub fn group_signal(group: &SymbolGroup, members: &[MemberFlow], timestamp: u64) -> TradingSignal {
+    if members.len() < group.min_members {
+        return TradingSignal::no_signal_at(
+            &group.name,
+            0.0,
+            timestamp,
+            format!("Only {} of {} group members have data (need {})", members.len(), group.members.len(), group.min_members),
+        );
+    }
+    let Some(flow) = aggregate_flows(members) else {
+        return TradingSignal::no_signal_at(&group.name, 0.0, timestamp, "Group members have no resting notional".to_string());
+    };
+
+    let signal_type = if flow.net_notional_delta >= group.delta_notional_threshold && flow.buy_imbalance >= group.imbalance_threshold {
+        SignalType::Buy
+    } else if flow.net_notional_delta <= -group.delta_notional_threshold && flow.sell_imbalance >= group.imbalance_threshold {
+        SignalType::Sell
+    } else {
+        return TradingSignal::no_signal_at(
+            &group.name,
+            0.0,
+            timestamp,
+            format!(
+                "No group signal (net notional delta {:.0}, weighted buy imbalance {:.2}, sell imbalance {:.2}, {} members)",
+                flow.net_notional_delta, flow.buy_imbalance, flow.sell_imbalance, flow.members
+            ),
+        );
+    };
+    let imbalance = if signal_type == SignalType::Buy { flow.buy_imbalance } else { flow.sell_imbalance };
+    TradingSignal {
+        signal_type,
+        confidence: group.confidence,
+        reason: format!(
+            "Group flow: net notional delta {:.0} with weighted imbalance {:.2} across {} members",
+            flow.net_notional_delta, imbalance, flow.members
+        ),
+        reason_code: ReasonCode::GroupFlow,
+        ..TradingSignal::no_signal_at(&group.name, 0.0, timestamp, String::new())
+    }
+}
+
+/// Engines of the running analysis tasks, so group members can be read across tasks
+#[derive(Clone, Default)]
+pub struct GroupRegistry {
+    engines: Arc<Mutex<HashMap<String, OFIEngine>>>,
+}
+
+impl GroupRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `symbol`'s flow available to groups, read from `engine`
+    pub fn register(&self, symbol: &str, engine: OFIEngine) {
+        self.engines.lock().unwrap().insert(symbol.to_string(), engine);
+    }
+
+    /// Forget a symbol, e.g. when its task stops
+    pub fn remove(&self, symbol: &str) {
+        self.engines.lock().unwrap().remove(symbol);
+    }
+
+    /// Current flow of every group member that is running and has a book
+    pub async fn member_flows(&self, group: &SymbolGroup) -> Vec<MemberFlow> {
+        // Engines are cloned out so no lock is held across the engine queries
+        let engines: Vec<(String, OFIEngine)> = {
+            let registered = self.engines.lock().unwrap();
+            group
+                .members
+                .iter()
+                .filter_map(|symbol| registered.get(symbol).map(|engine| (symbol.clone(), engine.clone())))
+                .collect()
+        };
+
+        let mut flows = Vec::with_capacity(engines.len());
+        for (symbol, engine) in engines {
+            if let Some(metrics) = engine.current_metrics(&symbol).await {
+                flows.push(MemberFlow {
+                    book_notional: engine.book_notional(&symbol).await,
+                    mid_price: metrics.mid_price,
+                    delta: metrics.delta,
+                    buy_imbalance: metrics.buy_imbalance,
+                    sell_imbalance: metrics.sell_imbalance,
+                    symbol,
+                });
+            }
+        }
+        flows
+    }
+
+    /// Evaluate a group on its members' current flow
+    pub async fn evaluate(&self, group: &SymbolGroup, timestamp: u64) -> TradingSignal {
+        let flows = self.member_flows(group).await;
+        group_signal(group, &flows, timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{OrderBookLevel, OrderBookSnapshot};
+
+    fn l1_group() -> SymbolGroup {
+        SymbolGroup {
+            name: "L1".to_string(),
+            members: vec!["BTCUSDT".to_string(), "ETHUSDT".to_string(), "SOLUSDT".to_string()],
+            delta_notional_threshold: 50_000.0,
+            imbalance_threshold: 2.0,
+            min_members: 2,
+            confidence: 0.6,
+        }
+    }
+
+    fn member(symbol: &str, mid_price: f64, delta: f64, buy_imbalance: f64, book_notional: f64) -> MemberFlow {
+        MemberFlow { symbol: symbol.to_string(), mid_price, delta, buy_imbalance, sell_imbalance: 1.0 / buy_imbalance, book_notional }
+    }
+
+    #[test]
+    fn aligned_members_add_up_to_a_group_signal() {
+        // 50k + 20k of net buying; imbalance weighted 3:1 by book notional
+        let members = [member("BTCUSDT", 100_000.0, 0.5, 3.0, 3_000_000.0), member("ETHUSDT", 2_000.0, 10.0, 1.0, 1_000_000.0)];
+        let flow = aggregate_flows(&members).unwrap();
+        assert_eq!(flow.net_notional_delta, 70_000.0);
+        assert_eq!(flow.buy_imbalance, 2.5);
+
+        let signal = group_signal(&l1_group(), &members, 1_000);
+        assert_eq!((signal.symbol.as_str(), signal.signal_type, signal.reason_code), ("L1", SignalType::Buy, ReasonCode::GroupFlow));
+        assert_eq!((signal.confidence, signal.timestamp), (0.6, 1_000));
+    }
+
+    #[test]
+    fn divergent_members_cancel_out() {
+        // Selling in ETH offsets buying in BTC that alone would clear the threshold
+        let members = [member("BTCUSDT", 100_000.0, 0.5, 3.0, 3_000_000.0), member("ETHUSDT", 2_000.0, -30.0, 0.5, 1_000_000.0)];
+        assert_eq!(aggregate_flows(&members).unwrap().net_notional_delta, -10_000.0);
+        let signal = group_signal(&l1_group(), &members, 1_000);
+        assert_eq!(signal.signal_type, SignalType::NoSignal);
+        assert!(signal.reason.contains("net notional delta -10000"), "{}", signal.reason);
+    }
+
+    #[test]
+    fn thin_books_barely_sway_the_group_imbalance() {
+        let members = [member("BTCUSDT", 100_000.0, 1.0, 1.0, 9_900_000.0), member("SOLUSDT", 150.0, 100.0, 50.0, 100_000.0)];
+        let flow = aggregate_flows(&members).unwrap();
+        assert!((flow.buy_imbalance - 1.49).abs() < 1e-9);
+        assert_eq!(group_signal(&l1_group(), &members, 1_000).signal_type, SignalType::NoSignal);
+    }
+
+    #[test]
+    fn groups_wait_for_enough_members_with_data() {
+        let members = [member("BTCUSDT", 100_000.0, 5.0, 4.0, 3_000_000.0)];
+        let signal = group_signal(&l1_group(), &members, 1_000);
+        assert_eq!(signal.signal_type, SignalType::NoSignal);
+        assert!(signal.reason.contains("Only 1 of 3"), "{}", signal.reason);
+        assert_eq!(aggregate_flows(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn registry_reads_the_flow_of_running_members() {
+        let level = |price: f64, quantity: f64| OrderBookLevel { price, quantity };
+        let engine = crate::engine::analysis_engine(3.0, 1000.0, 5000, crate::config::OFIConfig::for_tests());
+        engine
+            .update_order_book(OrderBookSnapshot { symbol: "ETHUSDT".to_string(), bids: vec![level(1_999.0, 30.0)], asks: vec![level(2_001.0, 10.0)], timestamp: 1_000 })
+            .await;
+        let registry = GroupRegistry::new();
+        registry.register("ETHUSDT", engine.clone());
+        registry.register("SOLUSDT", engine);
+        registry.register("XRPUSDT", crate::engine::analysis_engine(3.0, 1000.0, 5000, crate::config::OFIConfig::for_tests()));
+
+        // SOLUSDT runs but has no book yet, BTCUSDT isn't running, XRPUSDT isn't a member
+        let flows = registry.member_flows(&l1_group()).await;
+        assert_eq!(flows.len(), 1);
+        assert_eq!((flows[0].symbol.as_str(), flows[0].mid_price, flows[0].book_notional), ("ETHUSDT", 2_000.0, 79_980.0));
+        assert_eq!(flows[0].buy_imbalance, 1_999.0 * 30.0 / 20_010.0);
+
+        registry.remove("ETHUSDT");
+        assert!(registry.member_flows(&l1_group()).await.is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+//! Conversion of trading signals into executor-agnostic order intents
+
+use crate::config::{IntentAction, OFIConfig};
+use crate::signals::{ReasonCode, TradingSignal};
+use serde::{Deserialize, Serialize};
+
+/// A structured order intent that non-Python executors can consume directly
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderIntent {
+    pub side: IntentAction,  // Never Ignore; ignored signals produce no intent
+    pub symbol: String,
+    pub suggested_size_tier: Option<String>,  // Confidence tier label of the signal, if tiers are configured
+    pub reference_price: f64,
+    pub reason_code: ReasonCode,
+    pub confidence: f64,
+    pub timestamp: u64,
+}
+
+impl OrderIntent {
+    /// Serialize the intent as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| format!("{{\"error\": \"failed to serialize intent: {}\"}}", e))
+    }
+}
+
+/// Builds order intents from signals using the configured [[intent]] mapping
+pub struct OrderIntentBuilder<'a> {
+    config: &'a OFIConfig,
+}
+
+impl<'a> OrderIntentBuilder<'a> {
+    pub fn new(config: &'a OFIConfig) -> Self {
+        Self { config }
+    }
+
+    /// Map a signal to an order intent, or None when the mapping ignores it
+    pub fn build(&self, signal: &TradingSignal) -> Option<OrderIntent> {
+        let side = self.config.intent_action_for(signal.signal_type, signal.reason_code);
+        if side == IntentAction::Ignore {
+            return None;
+        }
+        Some(OrderIntent {
+            side,
+            symbol: signal.symbol.clone(),
+            suggested_size_tier: signal.tier.clone(),
+            reference_price: signal.price,
+            reason_code: signal.reason_code,
+            confidence: signal.confidence,
+            timestamp: signal.timestamp,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IntentRule;
+    use crate::signals::SignalType;
+
+    fn signal(signal_type: SignalType, reason_code: ReasonCode) -> TradingSignal {
+        TradingSignal {
+            signal_type,
+            reason_code,
+            price: 67012.5,
+            confidence: 0.85,
+            timestamp: 1_700_000_000_000,
+            tier: Some("full".to_string()),
+            ..TradingSignal::no_signal("BTCUSDT")
+        }
+    }
+
+    fn side(config: &OFIConfig, signal_type: SignalType, reason_code: ReasonCode) -> Option<IntentAction> {
+        OrderIntentBuilder::new(config).build(&signal(signal_type, reason_code)).map(|intent| intent.side)
+    }
+
+    #[test]
+    fn each_signal_type_maps_to_its_default_intent() {
+        let config = OFIConfig::for_tests();
+        assert_eq!(side(&config, SignalType::StrongBuy, ReasonCode::StackedImbalance), Some(IntentAction::Long));
+        assert_eq!(side(&config, SignalType::Buy, ReasonCode::Absorption), Some(IntentAction::Long));
+        assert_eq!(side(&config, SignalType::StrongSell, ReasonCode::StackedImbalance), Some(IntentAction::Short));
+        assert_eq!(side(&config, SignalType::Sell, ReasonCode::Exhaustion), Some(IntentAction::Short));
+        assert_eq!(side(&config, SignalType::NoSignal, ReasonCode::NoSignal), None);
+
+        let intent = OrderIntentBuilder::new(&config).build(&signal(SignalType::StrongBuy, ReasonCode::StackedImbalance)).unwrap();
+        assert_eq!(intent.symbol, "BTCUSDT");
+        assert_eq!((intent.reference_price, intent.confidence, intent.timestamp), (67012.5, 0.85, 1_700_000_000_000));
+        assert_eq!((intent.suggested_size_tier.as_deref(), intent.reason_code), (Some("full"), ReasonCode::StackedImbalance));
+    }
+
+    #[test]
+    fn configured_rules_override_the_default_mapping() {
+        let rule = |signal_type, reason_code, action| IntentRule { signal_type, reason_code, action };
+        let config = OFIConfig {
+            intent_rules: vec![
+                rule(SignalType::Sell, None, IntentAction::Ignore),
+                rule(SignalType::Sell, Some(ReasonCode::Exhaustion), IntentAction::Reduce),
+            ],
+            ..OFIConfig::for_tests()
+        };
+        assert_eq!(side(&config, SignalType::Sell, ReasonCode::Exhaustion), Some(IntentAction::Reduce), "the reason-specific rule wins");
+        assert_eq!(side(&config, SignalType::Sell, ReasonCode::Absorption), None, "ignored signals produce no intent");
+        assert_eq!(side(&config, SignalType::StrongSell, ReasonCode::StackedImbalance), Some(IntentAction::Short));
+    }
+}
@@ -2,7 +2,8 @@
 
 #![allow(dead_code)]
 
-use crate::data::{OrderBookSnapshot, TradeData};
+use crate::config::{ImbalanceBasis, OFIConfig};
+use crate::data::{BookFlowEvent, OrderBookLevel, OrderBookSnapshot, TradeData};
 use serde::{Deserialize, Serialize};
 
 /// Represents OFI metrics
@@ -13,6 +14,25 @@ pub struct OFIMetrics {
     pub cumulative_delta: f64,   // Cumulative order flow delta
     pub buy_imbalance: f64,      // Buy side imbalance ratio
     pub sell_imbalance: f64,     // Sell side imbalance ratio
+    pub capped_buy_imbalance: f64,   // Buy imbalance with each level's notional capped (iceberg filter)
+    pub capped_sell_imbalance: f64,  // Sell imbalance with each level's notional capped (iceberg filter)
+    pub book_quality: f64,       // 0..1 score combining depth, spread, freshness and spoofing checks
+    pub flow_consistency: f64,   // 0..1 fraction of lookback sub-intervals whose delta shares the net delta's sign
+    pub true_ofi: f64,           // Net order flow imbalance (top-of-book size changes) over the lookback
+    pub large_buy_prints: usize,  // Aggressive buys of at least `large_print_notional` in the lookback
+    pub large_sell_prints: usize, // Aggressive sells of at least `large_print_notional` in the lookback
+    pub buy_trades: usize,       // Aggressive buys in the lookback
+    pub sell_trades: usize,      // Aggressive sells in the lookback
+    pub weighted_mid: f64,       // Size-weighted price of the top `weighted_mid_levels` levels per side, 0.0 without a two-sided book
+    pub realized_volatility: f64,  // Realized volatility over `volatility_window_ms`, 0.0 with too few trades (not computed in fast mode)
+    pub twa_spread: f64,         // Time-weighted average spread (bps) over the lookback, the current spread without book history
+    pub touch_imbalance: f64,    // Best bid size / best ask size of the current book
+    pub bid_replenishment: f64,  // Best bid size restored per second after depletion over the lookback
+    pub ask_replenishment: f64,  // Best ask size restored per second after depletion over the lookback
+    pub bid_refill_ratio: f64,   // Fraction of depleted best bid size restored over the lookback, 1.0 when none was depleted
+    pub ask_refill_ratio: f64,   // Fraction of depleted best ask size restored over the lookback, 1.0 when none was depleted
+    pub imbalance_momentum: f64, // Book imbalance change per second over the last `imbalance_momentum_snapshots` books, 0.0 when off
+    pub one_sided: bool,         // A book side has no notional; the resting imbalance ratios are then 0.0, not balance
     pub timestamp: u64,          // Timestamp of calculation
 }
 
@@ -20,9 +40,11 @@ pub struct OFIMetrics {
 pub fn calculate_ofi_metrics(
     order_book: &OrderBookSnapshot,
     trades: &[&TradeData],
+    flow_events: &[BookFlowEvent],
     lookback_period_ms: u64,
+    config: &OFIConfig,
 ) -> OFIMetrics {
-    let now = order_book.timestamp;
+    let now = event_clock(order_book, trades, config.unified_event_clock);
     let cutoff_time = now.saturating_sub(lookback_period_ms);
     
     // Filter trades within lookback period
@@ -32,12 +54,34 @@ pub fn calculate_ofi_metrics(
         .copied()
         .collect();
     
-    // Calculate delta and cumulative delta
-    let delta = calculate_delta(&recent_trades);
+    // Calculate delta (optionally time-decayed) and cumulative delta
+    let delta = lookback_delta(&recent_trades, now, config.delta_decay_half_life_ms);
     let cumulative_delta = calculate_cumulative_delta(&recent_trades);
     
-    // Calculate imbalances
-    let (buy_imbalance, sell_imbalance) = calculate_imbalances(order_book);
+    // Calculate imbalances, raw and with outlier levels capped
+    let (true_ofi, flow_buy_imbalance, flow_sell_imbalance) = calculate_flow_imbalance(flow_events, cutoff_time);
+    let (bid_replenishment, bid_refill_ratio) = replenishment(flow_events, cutoff_time, true);
+    let (ask_replenishment, ask_refill_ratio) = replenishment(flow_events, cutoff_time, false);
+    let (buy_imbalance, sell_imbalance) = match config.imbalance_basis {
+        ImbalanceBasis::Resting if config.imbalance_notional_depth > 0.0 => calculate_imbalances_notional(order_book, config.imbalance_notional_depth),
+        ImbalanceBasis::Resting => calculate_imbalances(order_book),
+        ImbalanceBasis::Flow => (flow_buy_imbalance, flow_sell_imbalance),
+    };
+    let notional_cap = level_notional_cap(order_book, config.imbalance_level_cap_multiple);
+    let (capped_buy_imbalance, capped_sell_imbalance) = calculate_imbalances_capped(order_book, usize::MAX, notional_cap);
+    
+    let twa_spread = time_weighted_spread(flow_events, cutoff_time, now).or_else(|| spread_bps(order_book)).unwrap_or(0.0);
+    let book_quality = book_quality(
+        order_book,
+        trades,
+        config.book_quality_use_twa_spread.then_some(twa_spread),
+        config.book_quality_max_spread_bps,
+        config.book_quality_max_age_ms,
+    );
+    
+    let flow_consistency = flow_consistency(&recent_trades, cutoff_time, lookback_period_ms, config.flow_consistency_buckets);
+    let (large_buy_prints, large_sell_prints) = count_large_prints(&recent_trades, config.large_print_notional);
+    let (buy_trades, sell_trades) = count_trades(&recent_trades);
     
     OFIMetrics {
         symbol: order_book.symbol.clone(),
@@ -45,10 +89,371 @@ pub fn calculate_ofi_metrics(
         cumulative_delta,
         buy_imbalance,
         sell_imbalance,
+        capped_buy_imbalance,
+        capped_sell_imbalance,
+        book_quality,
+        flow_consistency,
+        true_ofi,
+        large_buy_prints,
+        large_sell_prints,
+        buy_trades,
+        sell_trades,
+        weighted_mid: weighted_mid(order_book, config.weighted_mid_levels).unwrap_or(0.0),
+        realized_volatility: realized_volatility(trades, now, config.volatility_window_ms).unwrap_or(0.0),
+        twa_spread,
+        touch_imbalance: touch_imbalance(order_book),
+        bid_replenishment,
+        ask_replenishment,
+        bid_refill_ratio,
+        ask_refill_ratio,
+        imbalance_momentum: imbalance_momentum(flow_events, config.imbalance_momentum_snapshots),
+        one_sided: is_one_sided(order_book),
         timestamp: now,
     }
 }
 
+/// "Now" for an analysis: the book's timestamp, or with `unified` the latest of the book and
+/// trade timestamps, so a book lagging behind the trades doesn't anchor the lookback in the past
+pub fn event_clock(order_book: &OrderBookSnapshot, trades: &[&TradeData], unified: bool) -> u64 {
+    if !unified {
+        return order_book.timestamp;
+    }
+    trades.iter().map(|trade| trade.timestamp).fold(order_book.timestamp, u64::max)
+}
+
+/// Order flow imbalance contribution of moving from `prev` to `next` (Cont, Kukanov & Stoikov).
+///
+/// A higher best bid counts its full size as added, an unchanged one the size change, a lower one
+/// the previous size as removed; the ask side mirrors this with the opposite sign.
+/// None if either book lacks a bid or an ask.
+pub fn book_flow_event(prev: &OrderBookSnapshot, next: &OrderBookSnapshot) -> Option<BookFlowEvent> {
+    let (prev_bid, prev_ask) = (prev.bids.first()?, prev.asks.first()?);
+    let (next_bid, next_ask) = (next.bids.first()?, next.asks.first()?);
+
+    let bid_flow = if next_bid.price > prev_bid.price {
+        next_bid.quantity
+    } else if next_bid.price == prev_bid.price {
+        next_bid.quantity - prev_bid.quantity
+    } else {
+        -prev_bid.quantity
+    };
+    let ask_flow = if next_ask.price < prev_ask.price {
+        next_ask.quantity
+    } else if next_ask.price == prev_ask.price {
+        next_ask.quantity - prev_ask.quantity
+    } else {
+        -prev_ask.quantity
+    };
+
+    let side_notional = |levels: &[OrderBookLevel]| levels.iter().map(|level| level.price * level.quantity).sum::<f64>();
+    let (bid_notional, ask_notional) = (side_notional(&next.bids), side_notional(&next.asks));
+    let imbalance = if bid_notional > 0.0 && ask_notional > 0.0 { (bid_notional / ask_notional).ln() } else { 0.0 };
+
+    Some(BookFlowEvent {
+        timestamp: next.timestamp,
+        ofi: bid_flow - ask_flow,
+        imbalance,
+        spread_bps: spread_bps(next).unwrap_or(0.0),
+        touch_imbalance: touch_imbalance(next),
+        bid_price: next_bid.price,
+        bid_size: next_bid.quantity,
+        ask_price: next_ask.price,
+        ask_size: next_ask.quantity,
+    })
+}
+
+/// Replenishment of one side's touch over the events at or after `cutoff`, as (size restored per
+/// second, fraction of depleted size restored). Size taken off the touch (fills or cancels) is a
+/// deficit; size added back at the same price while a deficit remains counts as restored. A new
+/// touch price starts over, since that is the book moving rather than refilling. (0.0, 1.0) when
+/// nothing was depleted.
+pub fn replenishment(flow_events: &[BookFlowEvent], cutoff: u64, bid_side: bool) -> (f64, f64) {
+    let window: Vec<(u64, f64, f64)> = flow_events
+        .iter()
+        .filter(|event| event.timestamp >= cutoff)
+        .map(|event| if bid_side { (event.timestamp, event.bid_price, event.bid_size) } else { (event.timestamp, event.ask_price, event.ask_size) })
+        .collect();
+    let (mut deficit, mut depleted, mut restored) = (0.0, 0.0, 0.0);
+    for pair in window.windows(2) {
+        let ((_, prev_price, prev_size), (_, next_price, next_size)) = (pair[0], pair[1]);
+        if next_price != prev_price {
+            deficit = 0.0;
+            continue;
+        }
+        let change = next_size - prev_size;
+        if change < 0.0 {
+            deficit -= change;
+            depleted -= change;
+        } else {
+            let refill = change.min(deficit);
+            deficit -= refill;
+            restored += refill;
+        }
+    }
+    let elapsed_secs = match (window.first(), window.last()) {
+        (Some(first), Some(last)) => last.0.saturating_sub(first.0) as f64 / 1000.0,
+        _ => 0.0,
+    };
+    let speed = if elapsed_secs > 0.0 { restored / elapsed_secs } else { 0.0 };
+    let ratio = if depleted > 0.0 { restored / depleted } else { 1.0 };
+    (speed, ratio)
+}
+
+/// Best bid size over best ask size; 0.0 without a bid or an ask with positive size
+pub fn touch_imbalance(order_book: &OrderBookSnapshot) -> f64 {
+    match (order_book.bids.first(), order_book.asks.first()) {
+        (Some(bid), Some(ask)) if ask.quantity > 0.0 => bid.quantity / ask.quantity,
+        _ => 0.0,
+    }
+}
+
+/// Direction of touch pressure held over the last `updates` books: 1 if every one had a touch
+/// imbalance of at least `threshold`, -1 if every one was at most 1 / `threshold`, else 0
+/// (also with fewer recorded updates)
+pub fn sustained_touch_pressure(flow_events: &[BookFlowEvent], threshold: f64, updates: usize) -> i8 {
+    if updates == 0 || flow_events.len() < updates || threshold <= 0.0 {
+        return 0;
+    }
+    let recent = &flow_events[flow_events.len() - updates..];
+    if recent.iter().all(|event| event.touch_imbalance >= threshold) {
+        1
+    } else if recent.iter().all(|event| event.touch_imbalance > 0.0 && event.touch_imbalance <= 1.0 / threshold) {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Spread of a book in basis points of its mid, None without a bid and an ask or a positive mid
+pub fn spread_bps(order_book: &OrderBookSnapshot) -> Option<f64> {
+    let (bid, ask) = (order_book.bids.first()?.price, order_book.asks.first()?.price);
+    let mid = (bid + ask) / 2.0;
+    (mid > 0.0).then(|| (ask - bid) / mid * 10_000.0)
+}
+
+/// Time-weighted average spread (bps) of the book updates from `cutoff` to `now`: each update's
+/// spread counts for as long as it stood, the latest one until `now`. With no elapsed time (a
+/// single update at `now`) it is the latest spread; None without updates in the window.
+pub fn time_weighted_spread(flow_events: &[BookFlowEvent], cutoff: u64, now: u64) -> Option<f64> {
+    let window: Vec<&BookFlowEvent> = flow_events.iter().filter(|event| event.timestamp >= cutoff).collect();
+    let latest = window.last()?;
+    let (weighted, duration) = window
+        .iter()
+        .zip(window.iter().skip(1).map(|event| event.timestamp).chain(std::iter::once(now.max(latest.timestamp))))
+        .fold((0.0, 0u64), |(weighted, duration), (event, until)| {
+            let held = until.saturating_sub(event.timestamp);
+            (weighted + event.spread_bps * held as f64, duration + held)
+        });
+    Some(if duration > 0 { weighted / duration as f64 } else { latest.spread_bps })
+}
+
+/// Rate of change of the book imbalance over the last `snapshots` book updates, in log-ratio
+/// units per second (least-squares slope). Positive means pressure building on the bid or fading
+/// on the ask; negative the reverse. 0.0 with fewer than two updates or no elapsed time.
+pub fn imbalance_momentum(flow_events: &[BookFlowEvent], snapshots: usize) -> f64 {
+    let window = &flow_events[flow_events.len().saturating_sub(snapshots)..];
+    if window.len() < 2 {
+        return 0.0;
+    }
+    let origin = window[0].timestamp;
+    let points: Vec<(f64, f64)> = window
+        .iter()
+        .map(|event| (event.timestamp.saturating_sub(origin) as f64 / 1000.0, event.imbalance))
+        .collect();
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_imbalance = points.iter().map(|(_, imbalance)| imbalance).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(t, imbalance)| (t - mean_t) * (imbalance - mean_imbalance)).sum();
+    let variance: f64 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+    if variance > 0.0 { covariance / variance } else { 0.0 }
+}
+
+/// Net order flow imbalance of the events at or after `cutoff_time`, with buy/sell imbalance
+/// ratios of positive to negative flow (infinite when the flow is entirely one-sided)
+fn calculate_flow_imbalance(flow_events: &[BookFlowEvent], cutoff_time: u64) -> (f64, f64, f64) {
+    let (buy_flow, sell_flow) = flow_events
+        .iter()
+        .filter(|event| event.timestamp >= cutoff_time)
+        .fold((0.0, 0.0), |(buy, sell), event| {
+            if event.ofi > 0.0 { (buy + event.ofi, sell) } else { (buy, sell - event.ofi) }
+        });
+    let ratio = |numerator: f64, denominator: f64| {
+        if denominator > 0.0 {
+            numerator / denominator
+        } else if numerator > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        }
+    };
+    (buy_flow - sell_flow, ratio(buy_flow, sell_flow), ratio(sell_flow, buy_flow))
+}
+
+/// Calculate minimal OFI metrics for fast mode.
+///
+/// Only the trade delta and the imbalance (over the top `fast_mode_levels` of each side, the first
+/// `imbalance_notional_depth` of the book when set, or from flow) are computed;
+/// book quality is not evaluated and reported as 1.0.
+pub fn calculate_fast_ofi_metrics(
+    order_book: &OrderBookSnapshot,
+    trades: &[&TradeData],
+    flow_events: &[BookFlowEvent],
+    lookback_period_ms: u64,
+    config: &OFIConfig,
+) -> OFIMetrics {
+    let levels = config.fast_mode_levels;
+    let now = event_clock(order_book, trades, config.unified_event_clock);
+    let cutoff_time = now.saturating_sub(lookback_period_ms);
+    
+    let recent_trades: Vec<&TradeData> = trades
+        .iter()
+        .filter(|trade| trade.timestamp >= cutoff_time)
+        .copied()
+        .collect();
+    
+    let delta = lookback_delta(&recent_trades, now, config.delta_decay_half_life_ms);
+    let (buy_trades, sell_trades) = count_trades(&recent_trades);
+    let (true_ofi, flow_buy_imbalance, flow_sell_imbalance) = calculate_flow_imbalance(flow_events, cutoff_time);
+    let (bid_replenishment, bid_refill_ratio) = replenishment(flow_events, cutoff_time, true);
+    let (ask_replenishment, ask_refill_ratio) = replenishment(flow_events, cutoff_time, false);
+    let (buy_imbalance, sell_imbalance) = match config.imbalance_basis {
+        ImbalanceBasis::Resting if config.imbalance_notional_depth > 0.0 => calculate_imbalances_notional(order_book, config.imbalance_notional_depth),
+        ImbalanceBasis::Resting => calculate_imbalances_top(order_book, levels),
+        ImbalanceBasis::Flow => (flow_buy_imbalance, flow_sell_imbalance),
+    };
+    
+    OFIMetrics {
+        symbol: order_book.symbol.clone(),
+        delta,
+        cumulative_delta: delta,
+        buy_imbalance,
+        sell_imbalance,
+        capped_buy_imbalance: buy_imbalance,
+        capped_sell_imbalance: sell_imbalance,
+        book_quality: 1.0,
+        flow_consistency: 1.0,
+        true_ofi,
+        large_buy_prints: 0,
+        large_sell_prints: 0,
+        buy_trades,
+        sell_trades,
+        weighted_mid: weighted_mid(order_book, levels).unwrap_or(0.0),
+        realized_volatility: 0.0,
+        twa_spread: time_weighted_spread(flow_events, cutoff_time, now).or_else(|| spread_bps(order_book)).unwrap_or(0.0),
+        touch_imbalance: touch_imbalance(order_book),
+        bid_replenishment,
+        ask_replenishment,
+        bid_refill_ratio,
+        ask_refill_ratio,
+        imbalance_momentum: imbalance_momentum(flow_events, config.imbalance_momentum_snapshots),
+        one_sided: is_one_sided(order_book),
+        timestamp: now,
+    }
+}
+
+/// Number of levels per side a book needs for full depth credit in `book_quality`
+const BOOK_QUALITY_DEPTH_LEVELS: usize = 5;
+
+/// Aggregate book quality score in 0..1 (1 = clean, deep, tight, fresh book).
+///
+/// The score is the product of four components so any single bad condition pulls it down:
+/// - depth: levels on the thinner side relative to `BOOK_QUALITY_DEPTH_LEVELS`
+/// - spread: 1 at zero spread, falling linearly to 0 at `max_spread_bps` (crossed books score 0)
+/// - freshness: 1 when the book is as new as the latest trade, 0 when it lags by `max_age_ms`
+/// - spoofing: 1 while no single level holds more than half its side's notional, 0 when one level holds it all
+pub fn book_quality(
+    order_book: &OrderBookSnapshot,
+    trades: &[&TradeData],
+    average_spread_bps: Option<f64>,
+    max_spread_bps: f64,
+    max_age_ms: u64,
+) -> f64 {
+    let (best_bid, best_ask) = match (order_book.bids.first(), order_book.asks.first()) {
+        (Some(bid), Some(ask)) => (bid.price, ask.price),
+        _ => return 0.0,
+    };
+
+    let depth = order_book.bids.len().min(order_book.asks.len()) as f64;
+    let depth_score = (depth / BOOK_QUALITY_DEPTH_LEVELS as f64).min(1.0);
+
+    let mid = (best_bid + best_ask) / 2.0;
+    let spread_score = if best_ask <= best_bid || mid <= 0.0 {
+        0.0
+    } else if max_spread_bps <= 0.0 {
+        1.0
+    } else {
+        let spread_bps = average_spread_bps.unwrap_or((best_ask - best_bid) / mid * 10_000.0);
+        (1.0 - spread_bps / max_spread_bps).clamp(0.0, 1.0)
+    };
+
+    let latest_trade = trades.iter().map(|trade| trade.timestamp).max().unwrap_or(0);
+    let freshness_score = if max_age_ms == 0 {
+        1.0
+    } else {
+        let age_ms = latest_trade.saturating_sub(order_book.timestamp) as f64;
+        (1.0 - age_ms / max_age_ms as f64).clamp(0.0, 1.0)
+    };
+
+    let concentration = |levels: &[OrderBookLevel]| -> f64 {
+        let notionals: Vec<f64> = levels.iter().map(|level| level.price * level.quantity).collect();
+        let total: f64 = notionals.iter().sum();
+        if total <= 0.0 || notionals.len() < 2 {
+            return 0.0;
+        }
+        notionals.iter().cloned().fold(0.0, f64::max) / total
+    };
+    let max_concentration = concentration(&order_book.bids).max(concentration(&order_book.asks));
+    let spoofing_score = (1.0 - (max_concentration - 0.5).max(0.0) * 2.0).clamp(0.0, 1.0);
+
+    depth_score * spread_score * freshness_score * spoofing_score
+}
+
+/// Fraction (0..1) of `buckets` equal sub-intervals of the lookback window whose delta has the
+/// same sign as the net delta over the whole window. Sustained one-way flow scores 1, choppy
+/// flow scores low; no trades or a zero net delta score 0. Returns 1.0 when `buckets` is 0.
+pub fn flow_consistency(trades: &[&TradeData], window_start: u64, lookback_period_ms: u64, buckets: usize) -> f64 {
+    if buckets == 0 || lookback_period_ms == 0 {
+        return 1.0;
+    }
+    let net_delta = calculate_delta(trades);
+    if net_delta == 0.0 {
+        return 0.0;
+    }
+
+    let mut bucket_deltas = vec![0.0; buckets];
+    for trade in trades {
+        let offset = trade.timestamp.saturating_sub(window_start);
+        let index = ((offset as u128 * buckets as u128) / lookback_period_ms as u128) as usize;
+        bucket_deltas[index.min(buckets - 1)] += calculate_delta(&[*trade]);
+    }
+
+    let consistent = bucket_deltas
+        .iter()
+        .filter(|delta| **delta != 0.0 && delta.signum() == net_delta.signum())
+        .count();
+    consistent as f64 / buckets as f64
+}
+
+/// Count (buy, sell) trades whose notional is at least `min_notional`; (0, 0) when disabled (0)
+pub fn count_large_prints(trades: &[&TradeData], min_notional: f64) -> (usize, usize) {
+    if min_notional <= 0.0 {
+        return (0, 0);
+    }
+    let large = |side: &str| {
+        trades.iter().filter(|trade| trade.side == side && trade.price * trade.quantity >= min_notional).count()
+    };
+    (large("buy"), large("sell"))
+}
+
+/// Count (buy, sell) trades
+pub fn count_trades(trades: &[&TradeData]) -> (usize, usize) {
+    trades.iter().fold((0, 0), |(buys, sells), trade| match trade.side.as_str() {
+        "buy" => (buys + 1, sells),
+        "sell" => (buys, sells + 1),
+        _ => (buys, sells),
+    })
+}
+
 /// Calculate order flow delta (buy volume - sell volume)
 fn calculate_delta(trades: &[&TradeData]) -> f64 {
     let mut buy_volume = 0.0;
@@ -66,6 +471,31 @@ fn calculate_delta(trades: &[&TradeData]) -> f64 {
     buy_volume - sell_volume
 }
 
+/// Delta of the lookback trades; with a half-life, each trade's notional is scaled by
+/// 0.5^(age / half_life) relative to `now`, so recent flow outweighs older flow of the same size
+fn lookback_delta(trades: &[&TradeData], now: u64, half_life_ms: u64) -> f64 {
+    if half_life_ms == 0 {
+        return calculate_delta(trades);
+    }
+    trades
+        .iter()
+        .map(|trade| {
+            let weight = 0.5f64.powf(now.saturating_sub(trade.timestamp) as f64 / half_life_ms as f64);
+            calculate_delta(&[*trade]) * weight
+        })
+        .sum()
+}
+
+/// Calculate order flow delta using only trades strictly after `since_timestamp`
+pub fn calculate_delta_since(trades: &[&TradeData], since_timestamp: u64) -> f64 {
+    let subsequent_trades: Vec<&TradeData> = trades
+        .iter()
+        .filter(|trade| trade.timestamp > since_timestamp)
+        .copied()
+        .collect();
+    calculate_delta(&subsequent_trades)
+}
+
 /// Calculate cumulative order flow delta
 fn calculate_cumulative_delta(trades: &[&TradeData]) -> f64 {
     let mut cumulative_delta = 0.0;
@@ -84,18 +514,125 @@ fn calculate_cumulative_delta(trades: &[&TradeData]) -> f64 {
 
 /// Calculate buy/sell imbalances from order book
 fn calculate_imbalances(order_book: &OrderBookSnapshot) -> (f64, f64) {
+    calculate_imbalances_top(order_book, usize::MAX)
+}
+
+/// Calculate buy/sell imbalances using only the top `levels` of each side
+fn calculate_imbalances_top(order_book: &OrderBookSnapshot, levels: usize) -> (f64, f64) {
+    calculate_imbalances_capped(order_book, levels, f64::INFINITY)
+}
+
+/// Calculate buy/sell imbalances over the first `depth` of notional on each side, from the best
+/// level outwards, the last one partially, so the window covers the same notional on every symbol
+/// whatever its tick size and level sizes. A side holding less than `depth` is used in full.
+pub fn calculate_imbalances_notional(order_book: &OrderBookSnapshot, depth: f64) -> (f64, f64) {
+    if order_book.bids.is_empty() || order_book.asks.is_empty() {
+        return (0.0, 0.0);
+    }
+    let total_buy_size = side_notional_within(&order_book.bids, depth);
+    let total_sell_size = side_notional_within(&order_book.asks, depth);
+
+    let buy_imbalance = if total_sell_size > 0.0 { total_buy_size / total_sell_size } else { 0.0 };
+    let sell_imbalance = if total_buy_size > 0.0 { total_sell_size / total_buy_size } else { 0.0 };
+    (buy_imbalance, sell_imbalance)
+}
+
+/// Notional of one side's levels (best first) up to `depth`, the last level taken partially
+fn side_notional_within(levels: &[OrderBookLevel], depth: f64) -> f64 {
+    let mut taken = 0.0;
+    for level in levels {
+        if taken >= depth {
+            break;
+        }
+        taken += (level.price * level.quantity).min(depth - taken);
+    }
+    taken
+}
+
+/// Maximum notional a single level may contribute to imbalance: `cap_multiple` times the
+/// median level notional across both sides. Infinite (no cap) when `cap_multiple` is 0.
+pub fn level_notional_cap(order_book: &OrderBookSnapshot, cap_multiple: f64) -> f64 {
+    if cap_multiple <= 0.0 {
+        return f64::INFINITY;
+    }
+    let mut notionals: Vec<f64> = order_book
+        .bids
+        .iter()
+        .chain(order_book.asks.iter())
+        .map(|level| level.price * level.quantity)
+        .collect();
+    if notionals.is_empty() {
+        return f64::INFINITY;
+    }
+    notionals.sort_by(|a, b| a.total_cmp(b));
+    let mid = notionals.len() / 2;
+    let median = if notionals.len() % 2 == 1 {
+        notionals[mid]
+    } else {
+        (notionals[mid - 1] + notionals[mid]) / 2.0
+    };
+    median * cap_multiple
+}
+
+/// Realized volatility of the trades in the `window_ms` up to `now`: the square root of the sum of
+/// squared log returns between consecutive trades (by timestamp), for the window as a whole, not
+/// annualized. None with fewer than two returns; trades without a positive price are skipped.
+pub fn realized_volatility(trades: &[&TradeData], now: u64, window_ms: u64) -> Option<f64> {
+    let cutoff = now.saturating_sub(window_ms);
+    let mut window: Vec<&TradeData> = trades
+        .iter()
+        .filter(|trade| trade.timestamp >= cutoff && trade.timestamp <= now && trade.price > 0.0)
+        .copied()
+        .collect();
+    if window.len() < 3 {
+        return None;
+    }
+    window.sort_by_key(|trade| trade.timestamp);
+    let sum_squared: f64 = window.windows(2).map(|pair| (pair[1].price / pair[0].price).ln().powi(2)).sum();
+    Some(sum_squared.sqrt())
+}
+
+/// Size-weighted average price of the top `levels` levels of both sides (fewer when the book is
+/// thinner), a steadier reference than the best bid/ask mid when the top levels are thin.
+/// None without a bid and an ask, or when the considered levels hold no size.
+pub fn weighted_mid(order_book: &OrderBookSnapshot, levels: usize) -> Option<f64> {
+    if order_book.bids.is_empty() || order_book.asks.is_empty() {
+        return None;
+    }
+    let (notional, size) = order_book
+        .bids
+        .iter()
+        .take(levels)
+        .chain(order_book.asks.iter().take(levels))
+        .fold((0.0, 0.0), |(notional, size), level| (notional + level.price * level.quantity, size + level.quantity));
+    (size > 0.0).then(|| notional / size)
+}
+
+/// Whether either side of the book (or both) carries no notional, which leaves the resting
+/// imbalance ratios undefined
+pub fn is_one_sided(order_book: &OrderBookSnapshot) -> bool {
+    let side_notional = |levels: &[OrderBookLevel]| levels.iter().map(|level| level.price * level.quantity).sum::<f64>();
+    side_notional(&order_book.bids) <= 0.0 || side_notional(&order_book.asks) <= 0.0
+}
+
+/// Calculate buy/sell imbalances over the top `levels` of each side, with each level's
+/// notional capped at `notional_cap` so iceberg-like outliers can't dominate.
+/// A ratio whose denominator side is empty is 0.0; check `is_one_sided` before reading it as balance.
+fn calculate_imbalances_capped(order_book: &OrderBookSnapshot, levels: usize, notional_cap: f64) -> (f64, f64) {
     // Calculate total buy side size (bids)
     let total_buy_size: f64 = order_book
         .bids
         .iter()
-        .map(|level| level.price * level.quantity)
+        .take(levels)
+        .map(|level| (level.price * level.quantity).min(notional_cap))
         .sum();
     
     // Calculate total sell side size (asks)
     let total_sell_size: f64 = order_book
         .asks
         .iter()
-        .map(|level| level.price * level.quantity)
+        .take(levels)
+        .map(|level| (level.price * level.quantity).min(notional_cap))
         .sum();
     
     // Calculate imbalances as ratios
@@ -114,85 +651,150 @@ fn calculate_imbalances(order_book: &OrderBookSnapshot) -> (f64, f64) {
     (buy_imbalance, sell_imbalance)
 }
 
-/// Detect stacked imbalances in order book
-pub fn detect_stacked_imbalances(order_book: &OrderBookSnapshot, threshold: f64) -> (bool, bool) {
-    let buy_stacked = detect_stacked_buy_imbalance(order_book, threshold);
-    let sell_stacked = detect_stacked_sell_imbalance(order_book, threshold);
-    (buy_stacked, sell_stacked)
+/// Imbalanced level counts behind a stacked imbalance decision. A side that could not be
+/// evaluated (too shallow, empty opposite side) has `required` 0 and never stacks.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StackedLevels {
+    pub buy_levels: usize,
+    pub buy_required: usize,
+    pub sell_levels: usize,
+    pub sell_required: usize,
 }
 
-/// Detect stacked buy imbalances (large bids at top of book) - Improved version
-/// Analyzes multiple levels for consistent pressure
-fn detect_stacked_buy_imbalance(order_book: &OrderBookSnapshot, threshold: f64) -> bool {
-    detect_stacked_buy_imbalance_advanced(order_book, threshold, 5, 3)
+impl StackedLevels {
+    pub fn buy_stacked(&self) -> bool {
+        self.buy_required > 0 && self.buy_levels >= self.buy_required
+    }
+
+    pub fn sell_stacked(&self) -> bool {
+        self.sell_required > 0 && self.sell_levels >= self.sell_required
+    }
+}
+
+/// Detect stacked imbalances in order book.
+/// Level notionals are capped at `level_cap_multiple` times the median level notional (0 = no cap).
+/// With `adapt_thin_books`, sides shallower than the checked depth are evaluated over the
+/// available levels with a proportional number of required levels instead of never stacking.
+pub fn detect_stacked_imbalances(order_book: &OrderBookSnapshot, threshold: f64, level_cap_multiple: f64, adapt_thin_books: bool, min_depth: usize) -> (bool, bool) {
+    let levels = stacked_imbalance_levels(order_book, threshold, level_cap_multiple, adapt_thin_books, min_depth);
+    (levels.buy_stacked(), levels.sell_stacked())
 }
 
-/// Detect stacked sell imbalances (large asks at top of book) - Improved version
-/// Analyzes multiple levels for consistent pressure
-fn detect_stacked_sell_imbalance(order_book: &OrderBookSnapshot, threshold: f64) -> bool {
-    detect_stacked_sell_imbalance_advanced(order_book, threshold, 5, 3)
+/// Count the imbalanced levels on each side, as evaluated by `detect_stacked_imbalances`
+pub fn stacked_imbalance_levels(order_book: &OrderBookSnapshot, threshold: f64, level_cap_multiple: f64, adapt_thin_books: bool, min_depth: usize) -> StackedLevels {
+    // Both sides must be deep enough, not just the signaling side: the opposite top level is the
+    // denominator of every ratio and a one-level side says little about resting liquidity
+    if order_book.bids.len() < min_depth || order_book.asks.len() < min_depth {
+        return StackedLevels::default();
+    }
+    let notional_cap = level_notional_cap(order_book, level_cap_multiple);
+    let (buy_levels, buy_required) = count_stacked_buy_imbalance(order_book, threshold, notional_cap, adapt_thin_books);
+    let (sell_levels, sell_required) = count_stacked_sell_imbalance(order_book, threshold, notional_cap, adapt_thin_books);
+    StackedLevels { buy_levels, buy_required, sell_levels, sell_required }
+}
+
+/// Levels to check and levels required for a side with `available` levels. Without adaptation a
+/// side shallower than `levels_to_check` can't stack (None); with it both are scaled down to the
+/// available depth, rounding the required levels up.
+fn stacked_levels(available: usize, levels_to_check: usize, required_levels: usize, adapt_thin_books: bool) -> Option<(usize, usize)> {
+    if available >= levels_to_check {
+        return Some((levels_to_check, required_levels));
+    }
+    if !adapt_thin_books || available == 0 {
+        return None;
+    }
+    let required = (required_levels * available).div_ceil(levels_to_check).max(1);
+    Some((available, required))
+}
+
+/// Count stacked buy imbalance levels (large bids at top of book) - Improved version
+/// Analyzes multiple levels for consistent pressure; returns (imbalanced, required) levels
+fn count_stacked_buy_imbalance(order_book: &OrderBookSnapshot, threshold: f64, notional_cap: f64, adapt_thin_books: bool) -> (usize, usize) {
+    match stacked_levels(order_book.bids.len(), 5, 3, adapt_thin_books) {
+        Some((levels_to_check, required_levels)) => {
+            match count_stacked_buy_imbalance_advanced(order_book, threshold, levels_to_check, notional_cap) {
+                Some(imbalanced_levels) => (imbalanced_levels, required_levels),
+                None => (0, 0),
+            }
+        }
+        None => (0, 0),
+    }
+}
+
+/// Count stacked sell imbalance levels (large asks at top of book) - Improved version
+/// Analyzes multiple levels for consistent pressure; returns (imbalanced, required) levels
+fn count_stacked_sell_imbalance(order_book: &OrderBookSnapshot, threshold: f64, notional_cap: f64, adapt_thin_books: bool) -> (usize, usize) {
+    match stacked_levels(order_book.asks.len(), 5, 3, adapt_thin_books) {
+        Some((levels_to_check, required_levels)) => {
+            match count_stacked_sell_imbalance_advanced(order_book, threshold, levels_to_check, notional_cap) {
+                Some(imbalanced_levels) => (imbalanced_levels, required_levels),
+                None => (0, 0),
+            }
+        }
+        None => (0, 0),
+    }
 }
 
 /// Advanced stacked buy imbalance detection
-/// Checks multiple levels to find consistent pressure
-fn detect_stacked_buy_imbalance_advanced(
+/// Checks multiple levels to find consistent pressure; None if the book can't be evaluated
+fn count_stacked_buy_imbalance_advanced(
     order_book: &OrderBookSnapshot, 
     threshold: f64, 
     levels_to_check: usize, 
-    required_levels: usize
-) -> bool {
+    notional_cap: f64,
+) -> Option<usize> {
     if order_book.bids.len() < levels_to_check || order_book.asks.is_empty() {
-        return false;
+        return None;
     }
 
-    let top_ask_size = order_book.asks[0].price * order_book.asks[0].quantity;
+    let top_ask_size = (order_book.asks[0].price * order_book.asks[0].quantity).min(notional_cap);
     if top_ask_size == 0.0 { 
-        return false; 
+        return None; 
     }
 
     let mut imbalanced_levels = 0;
     // Check top 5 bid levels
     for i in 0..std::cmp::min(levels_to_check, order_book.bids.len()) {
-        let bid_size = order_book.bids[i].price * order_book.bids[i].quantity;
+        let bid_size = (order_book.bids[i].price * order_book.bids[i].quantity).min(notional_cap);
         // Check if bid at this level is significantly larger than top ask
         if (bid_size / top_ask_size) >= threshold {
             imbalanced_levels += 1;
         }
     }
 
-    // Signal valid if at least 3 of 5 levels have imbalance
-    imbalanced_levels >= required_levels
+    // Signal valid if at least 3 of 5 levels have imbalance (checked by the caller)
+    Some(imbalanced_levels)
 }
 
 /// Advanced stacked sell imbalance detection
-/// Checks multiple levels to find consistent pressure
-fn detect_stacked_sell_imbalance_advanced(
+/// Checks multiple levels to find consistent pressure; None if the book can't be evaluated
+fn count_stacked_sell_imbalance_advanced(
     order_book: &OrderBookSnapshot, 
     threshold: f64, 
     levels_to_check: usize, 
-    required_levels: usize
-) -> bool {
+    notional_cap: f64,
+) -> Option<usize> {
     if order_book.asks.len() < levels_to_check || order_book.bids.is_empty() {
-        return false;
+        return None;
     }
 
-    let top_bid_size = order_book.bids[0].price * order_book.bids[0].quantity;
+    let top_bid_size = (order_book.bids[0].price * order_book.bids[0].quantity).min(notional_cap);
     if top_bid_size == 0.0 { 
-        return false; 
+        return None; 
     }
 
     let mut imbalanced_levels = 0;
     // Check top 5 ask levels
     for i in 0..std::cmp::min(levels_to_check, order_book.asks.len()) {
-        let ask_size = order_book.asks[i].price * order_book.asks[i].quantity;
+        let ask_size = (order_book.asks[i].price * order_book.asks[i].quantity).min(notional_cap);
         // Check if ask at this level is significantly larger than top bid
         if (ask_size / top_bid_size) >= threshold {
             imbalanced_levels += 1;
         }
     }
 
-    // Signal valid if at least 3 of 5 levels have imbalance
-    imbalanced_levels >= required_levels
+    // Signal valid if at least 3 of 5 levels have imbalance (checked by the caller)
+    Some(imbalanced_levels)
 }
 
 /// Detect absorption (large trades eating through order book levels)
@@ -243,4 +845,404 @@ pub fn detect_absorption(
     }
     
     (false, String::new(), crate::signals::SignalType::NoSignal)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TimestampUnit;
+
+    fn level(price: f64, quantity: f64) -> OrderBookLevel {
+        OrderBookLevel { price, quantity }
+    }
+
+    fn book(bids: &[(f64, f64)], asks: &[(f64, f64)], timestamp: u64) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            symbol: "BTCUSDT".to_string(),
+            bids: bids.iter().map(|&(price, quantity)| level(price, quantity)).collect(),
+            asks: asks.iter().map(|&(price, quantity)| level(price, quantity)).collect(),
+            timestamp,
+        }
+    }
+
+    /// A five-level book per side around 100 with a one-tick spread and even sizes
+    fn deep_book(timestamp: u64) -> OrderBookSnapshot {
+        let bids: Vec<(f64, f64)> = (0..5).map(|i| (99.99 - i as f64 * 0.01, 10.0)).collect();
+        let asks: Vec<(f64, f64)> = (0..5).map(|i| (100.0 + i as f64 * 0.01, 10.0)).collect();
+        book(&bids, &asks, timestamp)
+    }
+
+    fn trade(timestamp: u64, side: &str, quantity: f64, price: f64) -> TradeData {
+        TradeData { symbol: "BTCUSDT".to_string(), price, quantity, side: side.to_string(), timestamp }
+    }
+
+    fn quality(order_book: &OrderBookSnapshot, trades: &[TradeData]) -> f64 {
+        let trades: Vec<&TradeData> = trades.iter().collect();
+        book_quality(order_book, &trades, None, 20.0, 1_000)
+    }
+
+    #[test]
+    fn clean_deep_book_scores_near_one() {
+        let score = quality(&deep_book(1_000), &[trade(1_000, "buy", 1.0, 100.0)]);
+        assert!(score > 0.9, "score {}", score);
+    }
+
+    #[test]
+    fn book_quality_drops_for_each_defect() {
+        let trades = [trade(1_000, "buy", 1.0, 100.0)];
+        let clean = quality(&deep_book(1_000), &trades);
+
+        let wide = book(&[(99.0, 10.0); 5], &[(100.5, 10.0); 5], 1_000);
+        assert!(quality(&wide, &trades) < clean);
+        let thin = book(&[(99.99, 10.0)], &[(100.0, 10.0)], 1_000);
+        assert!((quality(&thin, &trades) / clean - 0.2).abs() < 0.01, "one of five levels gets a fifth of the depth credit");
+        assert!((quality(&deep_book(500), &trades) / clean - 0.5).abs() < 0.01, "a book half of max_age_ms behind the tape");
+        let mut spoofed = deep_book(1_000);
+        spoofed.bids[2].quantity = 5_000.0;
+        assert!(quality(&spoofed, &trades) < 0.1);
+        let crossed = book(&[(100.1, 10.0); 5], &[(100.0, 10.0); 5], 1_000);
+        assert_eq!(quality(&crossed, &trades), 0.0);
+        assert_eq!(quality(&book(&[], &[(100.0, 10.0)], 1_000), &trades), 0.0);
+    }
+
+    #[test]
+    fn normalized_timestamps_fall_inside_the_lookback() {
+        let config = OFIConfig::for_tests();
+        let now = 1_700_000_000_000;
+        let order_book = deep_book(now);
+        let count_in_lookback = |raw_timestamps: &[(u64, TimestampUnit)]| {
+            let trades: Vec<TradeData> = raw_timestamps
+                .iter()
+                .map(|&(raw, unit)| trade(unit.to_millis(raw), "buy", 1.0, 100.0))
+                .collect();
+            let trades: Vec<&TradeData> = trades.iter().collect();
+            calculate_ofi_metrics(&order_book, &trades, &[], 5_000, &config).buy_trades
+        };
+
+        let one_second_ago = [
+            (1_699_999_999, TimestampUnit::Seconds),
+            (1_699_999_999_000, TimestampUnit::Millis),
+            (1_699_999_999_000_000, TimestampUnit::Micros),
+            (1_699_999_999_000_000, TimestampUnit::Auto),
+        ];
+        assert_eq!(count_in_lookback(&one_second_ago), 4);
+        assert_eq!(count_in_lookback(&[(1_699_999_999, TimestampUnit::Millis)]), 0, "seconds read as millis fall outside");
+        assert_eq!(count_in_lookback(&[(now - 6_000, TimestampUnit::Millis)]), 0, "older than the lookback");
+    }
+
+    #[test]
+    fn capped_imbalance_discounts_one_outlier_level() {
+        let mut outlier = deep_book(1_000);
+        outlier.bids[2].quantity = 1_000.0;
+        let metrics = |cap_multiple: f64| {
+            let config = OFIConfig { imbalance_level_cap_multiple: cap_multiple, ..OFIConfig::for_tests() };
+            calculate_ofi_metrics(&outlier, &[], &[], 5_000, &config)
+        };
+
+        let uncapped = metrics(0.0);
+        assert!(uncapped.buy_imbalance > 20.0, "the outlier dominates: {}", uncapped.buy_imbalance);
+        assert_eq!(uncapped.capped_buy_imbalance, uncapped.buy_imbalance, "0 disables the cap");
+
+        // Median level notional is ~1,000, so the outlier counts for at most ~3,000
+        let capped = metrics(3.0);
+        assert_eq!(capped.buy_imbalance, uncapped.buy_imbalance, "the raw imbalance is kept alongside");
+        assert!((capped.capped_buy_imbalance - 1.4).abs() < 0.01, "capped {}", capped.capped_buy_imbalance);
+        assert!((capped.capped_sell_imbalance - 1.0 / capped.capped_buy_imbalance).abs() < 1e-9);
+    }
+
+    #[test]
+    fn consistent_flow_scores_above_choppy_flow() {
+        // A 5_000ms lookback starting at 5_000, in five one-second buckets
+        let score = |sides: &[&str]| {
+            let trades: Vec<TradeData> = sides.iter().enumerate().map(|(i, side)| trade(5_100 + i as u64 * 1_000, side, 1.0, 100.0)).collect();
+            let refs: Vec<&TradeData> = trades.iter().collect();
+            flow_consistency(&refs, 5_000, 5_000, 5)
+        };
+
+        assert_eq!(score(&["buy"; 5]), 1.0);
+        assert_eq!(score(&["sell"; 5]), 1.0, "consistency is about direction, not side");
+        assert_eq!(score(&["buy", "sell", "buy", "sell", "buy"]), 0.6);
+        assert_eq!(score(&["buy", "sell", "buy", "sell"]), 0.0, "no net delta");
+        assert_eq!(score(&["buy"]), 0.2, "one burst in an otherwise quiet window");
+    }
+
+    #[test]
+    fn flow_consistency_is_exposed_on_the_metrics() {
+        let trades: Vec<TradeData> = (0..5).map(|i| trade(5_100 + i * 1_000, "buy", 1.0, 100.0)).collect();
+        let refs: Vec<&TradeData> = trades.iter().collect();
+        let metrics = calculate_ofi_metrics(&deep_book(10_000), &refs, &[], 5_000, &OFIConfig::for_tests());
+        assert_eq!(metrics.flow_consistency, 1.0);
+
+        let unbucketed = OFIConfig { flow_consistency_buckets: 0, ..OFIConfig::for_tests() };
+        assert_eq!(calculate_ofi_metrics(&deep_book(10_000), &refs[..1], &[], 5_000, &unbucketed).flow_consistency, 1.0);
+    }
+
+    fn touch(bid: (f64, f64), ask: (f64, f64), timestamp: u64) -> OrderBookSnapshot {
+        book(&[bid], &[ask], timestamp)
+    }
+
+    fn flow(prev: &OrderBookSnapshot, next: &OrderBookSnapshot) -> f64 {
+        book_flow_event(prev, next).expect("two-sided books").ofi
+    }
+
+    #[test]
+    fn book_flow_event_diffs_the_touch_sizes() {
+        let prev = touch((99.9, 10.0), (100.0, 10.0), 1_000);
+        assert_eq!(flow(&prev, &touch((99.9, 14.0), (100.0, 7.0), 1_100)), 7.0, "bid +4, ask -3");
+        assert_eq!(flow(&prev, &touch((99.95, 5.0), (100.0, 10.0), 1_100)), 5.0, "a higher bid counts its full size");
+        assert_eq!(flow(&prev, &touch((99.8, 30.0), (100.0, 10.0), 1_100)), -10.0, "a lower bid removes the previous size");
+        assert_eq!(flow(&prev, &touch((99.9, 10.0), (99.95, 6.0), 1_100)), -6.0, "a lower ask adds selling pressure");
+        assert_eq!(flow(&prev, &touch((99.9, 10.0), (100.1, 2.0), 1_100)), 10.0, "a higher ask removes the previous size");
+        assert!(book_flow_event(&prev, &book(&[], &[(100.0, 1.0)], 1_100)).is_none());
+    }
+
+    #[test]
+    fn true_ofi_accumulates_over_the_lookback_and_drives_the_flow_basis() {
+        let books = [
+            touch((99.9, 10.0), (100.0, 10.0), 1_000),
+            touch((99.9, 20.0), (100.0, 10.0), 4_000),
+            touch((99.9, 26.0), (100.0, 10.0), 7_000),
+            touch((99.9, 26.0), (100.0, 12.0), 8_000),
+        ];
+        let events: Vec<BookFlowEvent> = books.windows(2).filter_map(|pair| book_flow_event(&pair[0], &pair[1])).collect();
+        let latest = &books[3];
+
+        // The lookback from 3_000 keeps +10, +6 and -2
+        let resting = calculate_ofi_metrics(latest, &[], &events, 5_000, &OFIConfig::for_tests());
+        assert_eq!(resting.true_ofi, 14.0);
+        assert_eq!(resting.buy_imbalance, 26.0 * 99.9 / (12.0 * 100.0), "the resting basis ignores flow");
+
+        let flow_basis = OFIConfig { imbalance_basis: ImbalanceBasis::Flow, ..OFIConfig::for_tests() };
+        let flowing = calculate_ofi_metrics(latest, &[], &events, 5_000, &flow_basis);
+        assert_eq!((flowing.buy_imbalance, flowing.sell_imbalance), (8.0, 0.125));
+        assert_eq!(calculate_ofi_metrics(latest, &[], &events, 500, &flow_basis).true_ofi, -2.0);
+    }
+
+    #[test]
+    fn thin_books_stack_proportionally_when_adapted() {
+        // Three levels per side, two of the three bids far larger than the top ask
+        let thin = book(&[(99.9, 50.0), (99.8, 50.0), (99.7, 1.0)], &[(100.0, 1.0), (100.1, 1.0), (100.2, 1.0)], 1_000);
+
+        // Without adaptation a book shallower than the five checked levels never stacks
+        assert_eq!(detect_stacked_imbalances(&thin, 3.0, 0.0, false, 1), (false, false));
+
+        // Adapted: three levels checked and 3 * 3 / 5 rounded up = 2 required
+        let levels = stacked_imbalance_levels(&thin, 3.0, 0.0, true, 1);
+        assert_eq!((levels.buy_levels, levels.buy_required), (2, 2));
+        assert_eq!(detect_stacked_imbalances(&thin, 3.0, 0.0, true, 1), (true, false));
+
+        // One imbalanced level of three is not proportionally enough
+        let one_level = book(&[(99.9, 50.0), (99.8, 1.0), (99.7, 1.0)], &[(100.0, 1.0), (100.1, 1.0), (100.2, 1.0)], 1_000);
+        assert_eq!(detect_stacked_imbalances(&one_level, 3.0, 0.0, true, 1), (false, false));
+    }
+
+    #[test]
+    fn unified_clock_anchors_the_lookback_at_newer_trades() {
+        // The book lags four seconds behind the latest trade
+        let order_book = deep_book(10_000);
+        let trades = [trade(6_000, "sell", 1.0, 100.0), trade(14_000, "buy", 1.0, 100.0)];
+        let trades: Vec<&TradeData> = trades.iter().collect();
+        assert_eq!(event_clock(&order_book, &trades, false), 10_000);
+        assert_eq!(event_clock(&order_book, &trades, true), 14_000);
+
+        // Anchored at the book the older sell is in the window; anchored at the latest trade it is not
+        let book_anchored = calculate_ofi_metrics(&order_book, &trades, &[], 5_000, &OFIConfig::for_tests());
+        let unified = calculate_ofi_metrics(&order_book, &trades, &[], 5_000, &OFIConfig { unified_event_clock: true, ..OFIConfig::for_tests() });
+        assert_eq!(book_anchored.sell_trades, 1);
+        assert_eq!((unified.buy_trades, unified.sell_trades), (1, 0));
+    }
+
+    #[test]
+    fn large_prints_are_counted_per_side_on_the_metrics() {
+        let trades = [trade(9_000, "buy", 500.0, 100.0), trade(9_100, "buy", 1.0, 100.0), trade(9_200, "sell", 200.0, 100.0)];
+        let trades: Vec<&TradeData> = trades.iter().collect();
+        assert_eq!(count_large_prints(&trades, 10_000.0), (1, 1));
+        assert_eq!(count_large_prints(&trades, 0.0), (0, 0), "disabled");
+
+        let config = OFIConfig { large_print_notional: 30_000.0, ..OFIConfig::for_tests() };
+        let metrics = calculate_ofi_metrics(&deep_book(10_000), &trades, &[], 5_000, &config);
+        assert_eq!((metrics.large_buy_prints, metrics.large_sell_prints), (1, 0));
+    }
+
+    #[test]
+    fn weighted_mid_leans_to_size_and_ignores_a_flickering_thin_top() {
+        let simple_mid = |book: &OrderBookSnapshot| (book.bids[0].price + book.asks[0].price) / 2.0;
+
+        // A heavy bid against a thin ask: the weighted mid leans towards the bid
+        let skewed = book(&[(99.9, 100.0)], &[(100.0, 1.0)], 1_000);
+        let leaning = weighted_mid(&skewed, 5).expect("fewer than five levels is fine");
+        assert!(leaning < simple_mid(&skewed) && leaning > 99.9, "{}", leaning);
+
+        // A tiny bid appearing inside the spread moves the simple mid but barely the weighted one
+        let bids = [(99.8, 50.0), (99.7, 50.0)];
+        let asks = [(100.0, 50.0), (100.1, 50.0)];
+        let before = book(&bids, &asks, 1_000);
+        let after = book(&[(99.95, 0.1), bids[0], bids[1]], &asks, 1_100);
+        let simple_move = (simple_mid(&after) - simple_mid(&before)).abs();
+        let weighted_move = (weighted_mid(&after, 5).unwrap() - weighted_mid(&before, 5).unwrap()).abs();
+        assert!(simple_move > 0.07 && weighted_move < 0.001, "{} vs {}", simple_move, weighted_move);
+
+        // Exposed on the metrics per the configured depth
+        let config = OFIConfig { weighted_mid_levels: 5, ..OFIConfig::for_tests() };
+        assert_eq!(calculate_ofi_metrics(&skewed, &[], &[], 5_000, &config).weighted_mid, leaning);
+        assert_eq!(weighted_mid(&book(&[(99.9, 1.0)], &[], 1_000), 5), None);
+    }
+
+    #[test]
+    fn realized_volatility_is_the_root_sum_of_squared_log_returns() {
+        // Stored out of order; the 500ms print falls outside a 1s window ending at 2_000
+        let series = [trade(1_400, "buy", 1.0, 99.0), trade(1_000, "buy", 1.0, 100.0), trade(1_200, "sell", 1.0, 110.0), trade(2_000, "buy", 1.0, 99.0), trade(500, "buy", 1.0, 50.0)];
+        let trades: Vec<&TradeData> = series.iter().collect();
+        let expected = ((110.0f64 / 100.0).ln().powi(2) + (99.0f64 / 110.0).ln().powi(2)).sqrt();
+        let volatility = realized_volatility(&trades, 2_000, 1_000).expect("four prints in the window");
+        assert!((volatility - expected).abs() < 1e-12, "{} vs {}", volatility, expected);
+
+        // A flat series has none, and fewer than three prices give no estimate
+        let flat: Vec<TradeData> = (0..5).map(|i| trade(1_000 + i * 100, "buy", 1.0, 100.0)).collect();
+        assert_eq!(realized_volatility(&flat.iter().collect::<Vec<_>>(), 1_400, 1_000), Some(0.0));
+        assert_eq!(realized_volatility(&trades, 2_000, 700), None);
+    }
+
+    #[test]
+    fn notional_depth_compares_books_of_different_granularity_alike() {
+        // Two coarse bid levels hold 140k, two ask levels 25k
+        let coarse = book(&[(100.0, 600.0), (80.0, 1000.0)], &[(125.0, 100.0), (250.0, 50.0)], 1_000);
+        // Twenty fine bid levels of ~10k each, five ask levels of 5k each
+        let bids: Vec<(f64, f64)> = (0..20).map(|i| (1.0 - i as f64 * 0.001, 10_000.0)).collect();
+        let asks: Vec<(f64, f64)> = (0..5).map(|i| 1.001 + i as f64 * 0.001).map(|price| (price, 5_000.0 / price)).collect();
+        let fine = book(&bids, &asks, 1_000);
+
+        // The first 100k of each side: 100k of bids against the whole 25k of asks on both
+        let (coarse_buy, coarse_sell) = calculate_imbalances_notional(&coarse, 100_000.0);
+        let (fine_buy, fine_sell) = calculate_imbalances_notional(&fine, 100_000.0);
+        assert!((coarse_buy - 4.0).abs() < 1e-9 && (fine_buy - 4.0).abs() < 1e-9, "{} vs {}", coarse_buy, fine_buy);
+        assert!((coarse_sell - 0.25).abs() < 1e-9 && (fine_sell - 0.25).abs() < 1e-9);
+
+        // Five levels cover very different notional on the two books
+        let (coarse_top, _) = calculate_imbalances_top(&coarse, 5);
+        let (fine_top, _) = calculate_imbalances_top(&fine, 5);
+        assert!((coarse_top - 5.6).abs() < 1e-9 && (fine_top - 2.0).abs() < 0.01, "{} vs {}", coarse_top, fine_top);
+
+        // The configured depth drives the metrics' resting imbalance
+        let config = OFIConfig { imbalance_notional_depth: 100_000.0, ..OFIConfig::for_tests() };
+        let metrics = calculate_ofi_metrics(&fine, &[], &[], 5_000, &config);
+        assert!((metrics.buy_imbalance - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn twa_spread_weights_each_spread_by_how_long_it_stood() {
+        let event = |timestamp: u64, spread_bps: f64| BookFlowEvent {
+            timestamp,
+            ofi: 0.0,
+            imbalance: 0.0,
+            spread_bps,
+            touch_imbalance: 1.0,
+            bid_price: 99.99,
+            bid_size: 1.0,
+            ask_price: 100.01,
+            ask_size: 1.0,
+        };
+        let history = [event(1_000, 2.0), event(1_100, 10.0), event(1_500, 4.0)];
+
+        // 2bps for 100ms, 10bps for 400ms, 4bps for the last 500ms up to now
+        let twa = time_weighted_spread(&history, 0, 2_000).unwrap();
+        assert!((twa - 6.2).abs() < 1e-12, "{}", twa);
+        // Updates before the cutoff are left out
+        let recent = time_weighted_spread(&history, 1_050, 2_000).unwrap();
+        assert!((recent - 6_000.0 / 900.0).abs() < 1e-12, "{}", recent);
+
+        // Sparse history: a single update at now is its own spread, none at all is None
+        assert_eq!(time_weighted_spread(&[event(2_000, 3.0)], 0, 2_000), Some(3.0));
+        assert_eq!(time_weighted_spread(&history, 1_600, 2_000), None);
+
+        // Without book history the metrics fall back to the current spread
+        let order_book = book(&[(99.99, 1.0)], &[(100.01, 1.0)], 2_000);
+        let metrics = calculate_ofi_metrics(&order_book, &[], &[], 5_000, &OFIConfig::for_tests());
+        assert!((metrics.twa_spread - 2.0).abs() < 1e-9);
+        let metrics = calculate_ofi_metrics(&order_book, &[], &history, 5_000, &OFIConfig::for_tests());
+        assert!((metrics.twa_spread - 6.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn decayed_delta_lets_a_recent_burst_outweigh_an_equal_older_one() {
+        // 1k of selling a full half-life ago against 1k of buying just now
+        let older_sells: Vec<TradeData> = (0..5).map(|i| trade(1_000 + i * 10, "sell", 2.0, 100.0)).collect();
+        let recent_buys: Vec<TradeData> = (0..5).map(|i| trade(2_000 - i * 10, "buy", 2.0, 100.0)).collect();
+        let trades: Vec<&TradeData> = older_sells.iter().chain(&recent_buys).collect();
+
+        // Unweighted, the bursts cancel out
+        assert!(lookback_delta(&trades, 2_000, 0).abs() < 1e-9);
+        let weighted = lookback_delta(&trades, 2_000, 1_000);
+        assert!(weighted > 0.0, "recent buying dominates: {}", weighted);
+        // The newest trades count almost fully, the oldest about half
+        assert!(weighted > 400.0 && weighted < 500.0, "{}", weighted);
+
+        // The mirrored tape leans the other way by the same amount
+        let older_buys: Vec<TradeData> = older_sells.iter().map(|t| trade(t.timestamp, "buy", 2.0, 100.0)).collect();
+        let recent_sells: Vec<TradeData> = recent_buys.iter().map(|t| trade(t.timestamp, "sell", 2.0, 100.0)).collect();
+        let mirrored: Vec<&TradeData> = older_buys.iter().chain(&recent_sells).collect();
+        assert!((lookback_delta(&mirrored, 2_000, 1_000) + weighted).abs() < 1e-9);
+
+        // The metrics use the decayed delta only when a half-life is configured
+        let order_book = book(&[(99.99, 1.0)], &[(100.01, 1.0)], 2_000);
+        let metrics = calculate_ofi_metrics(&order_book, &trades, &[], 5_000, &OFIConfig::for_tests());
+        assert!(metrics.delta.abs() < 1e-9);
+        let config = OFIConfig { delta_decay_half_life_ms: 1_000, ..OFIConfig::for_tests() };
+        let metrics = calculate_ofi_metrics(&order_book, &trades, &[], 5_000, &config);
+        assert!((metrics.delta - weighted).abs() < 1e-9);
+    }
+
+    #[test]
+    fn touch_imbalance_is_the_best_bid_size_over_the_best_ask_size() {
+        let bid_heavy = book(&[(99.9, 12.0), (99.8, 1.0)], &[(100.0, 3.0), (100.1, 100.0)], 1_000);
+        assert_eq!(touch_imbalance(&bid_heavy), 4.0, "deeper levels don't count");
+        assert_eq!(touch_imbalance(&book(&[(99.9, 1.0)], &[(100.0, 4.0)], 1_000)), 0.25);
+        assert_eq!(touch_imbalance(&book(&[(99.9, 1.0)], &[], 1_000)), 0.0);
+        assert_eq!(touch_imbalance(&book(&[(99.9, 1.0)], &[(100.0, 0.0)], 1_000)), 0.0);
+
+        let metrics = calculate_ofi_metrics(&bid_heavy, &[], &[], 5_000, &OFIConfig::for_tests());
+        assert_eq!(metrics.touch_imbalance, 4.0);
+        let event = book_flow_event(&book(&[(99.9, 1.0)], &[(100.0, 1.0)], 900), &bid_heavy).unwrap();
+        assert_eq!(event.touch_imbalance, 4.0);
+
+        // Sustained pressure needs every one of the last updates past the threshold
+        let events: Vec<BookFlowEvent> = [0.2, 3.5, 4.0, 3.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &touch_imbalance)| BookFlowEvent { timestamp: 1_000 + i as u64 * 100, touch_imbalance, ..event })
+            .collect();
+        assert_eq!(sustained_touch_pressure(&events, 3.0, 3), 1);
+        assert_eq!(sustained_touch_pressure(&events, 3.0, 4), 0, "the oldest update leaned the other way");
+        assert_eq!(sustained_touch_pressure(&events, 3.5, 3), 0, "the latest update fell back to 3.0");
+        assert_eq!(sustained_touch_pressure(&events[..1], 3.0, 1), -1, "0.2 is below 1 / 3");
+        assert_eq!(sustained_touch_pressure(&events, 3.0, 5), 0, "fewer updates than required");
+        assert_eq!(sustained_touch_pressure(&events, 3.0, 0), 0);
+    }
+
+    #[test]
+    fn replenishment_tells_a_fast_refill_from_a_slow_one() {
+        // Consecutive books with the given best bid (timestamp, price, size) over a fixed ask
+        let history = |touches: &[(u64, f64, f64)]| -> Vec<BookFlowEvent> {
+            let books: Vec<OrderBookSnapshot> = touches.iter().map(|&(timestamp, price, size)| book(&[(price, size)], &[(100.0, 5.0)], timestamp)).collect();
+            books.windows(2).filter_map(|pair| book_flow_event(&pair[0], &pair[1])).collect()
+        };
+        // 8 of 10 swept off the bid, all back within 100ms
+        let fast = history(&[(8_900, 99.9, 10.0), (9_000, 99.9, 10.0), (9_100, 99.9, 2.0), (9_200, 99.9, 10.0)]);
+        // The same sweep, a quarter back after 900ms
+        let slow = history(&[(8_900, 99.9, 10.0), (9_000, 99.9, 10.0), (9_100, 99.9, 2.0), (9_500, 99.9, 3.0), (10_000, 99.9, 4.0)]);
+        // The bid gives way to a lower price instead of refilling
+        let moved = history(&[(8_900, 99.9, 10.0), (9_000, 99.9, 10.0), (9_100, 99.9, 2.0), (9_200, 99.8, 10.0)]);
+
+        let (speed, ratio) = replenishment(&fast, 0, true);
+        assert!((speed - 40.0).abs() < 1e-9 && ratio == 1.0, "{} {}", speed, ratio);
+        let (speed, ratio) = replenishment(&slow, 0, true);
+        assert!((speed - 2.0).abs() < 1e-9 && (ratio - 0.25).abs() < 1e-9, "{} {}", speed, ratio);
+        assert_eq!(replenishment(&moved, 0, true), (0.0, 0.0));
+        assert_eq!(replenishment(&fast, 0, false), (0.0, 1.0), "the untouched ask was never depleted");
+        // Only the window counts: after the cutoff the bid only grows
+        assert_eq!(replenishment(&slow, 9_100, true).1, 1.0);
+
+        let metrics = calculate_ofi_metrics(&book(&[(99.9, 4.0)], &[(100.0, 5.0)], 10_000), &[], &slow, 5_000, &OFIConfig::for_tests());
+        assert!((metrics.bid_replenishment - 2.0).abs() < 1e-9 && (metrics.bid_refill_ratio - 0.25).abs() < 1e-9);
+        assert_eq!((metrics.ask_replenishment, metrics.ask_refill_ratio), (0.0, 1.0));
+    }
+}
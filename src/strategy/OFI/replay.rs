@@ -0,0 +1,221 @@
+//! Replay of recorded Bitget WebSocket frames through an engine, for backtesting and debugging
+
+use crate::engine::OFIEngine;
+use crate::signals::TradingSignal;
+use crate::websocket::{parse_message, ParsedMessage};
+use anyhow::{anyhow, Result};
+use std::time::Duration;
+
+/// How a replay is paced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Apply events back to back, ignoring their timestamps
+    AsFastAsPossible,
+    /// Wait out the gap between consecutive event timestamps
+    RealTime,
+    /// Apply one event per `ReplayRunner::step` call; `run` is not available
+    Step,
+}
+
+/// A recorded book or trade update
+enum ReplayEvent {
+    OrderBook(crate::data::OrderBookSnapshot),
+    Trades(Vec<crate::data::TradeData>),
+}
+
+impl ReplayEvent {
+    fn symbol(&self) -> &str {
+        match self {
+            ReplayEvent::OrderBook(book) => &book.symbol,
+            ReplayEvent::Trades(trades) => trades.first().map_or("", |trade| trade.symbol.as_str()),
+        }
+    }
+
+    fn timestamp(&self) -> u64 {
+        match self {
+            ReplayEvent::OrderBook(book) => book.timestamp,
+            ReplayEvent::Trades(trades) => trades.first().map_or(0, |trade| trade.timestamp),
+        }
+    }
+}
+
+/// Outcome of applying one recorded event
+#[derive(Debug, Clone)]
+pub struct ReplayStep {
+    pub index: usize,  // Position of the event among the replayable events
+    pub symbol: String,
+    pub timestamp: u64,  // Exchange timestamp of the event in milliseconds
+    pub signal: TradingSignal,  // Analysis of the symbol after the event was applied
+}
+
+/// Hook fired after every applied event
+pub type ReplayHook = Box<dyn FnMut(&ReplayStep) + Send>;
+
+/// Feeds recorded frames into an engine one event at a time, analyzing after each
+pub struct ReplayRunner {
+    engine: OFIEngine,
+    events: Vec<ReplayEvent>,
+    mode: ReplayMode,
+    position: usize,
+    hook: Option<ReplayHook>,
+}
+
+impl ReplayRunner {
+    /// Build a replay from raw text frames as received from the WebSocket, in the format of the
+    /// configured `exchange`. Frames that carry no book or trade data (acks, pongs, other
+    /// channels, malformed payloads) are skipped.
+    pub fn from_frames(engine: OFIEngine, frames: &[String], mode: ReplayMode) -> Self {
+        let timestamp_unit = engine.config().timestamp_unit;
+        let exchange = engine.config().exchange;
+        let events = frames
+            .iter()
+            .filter_map(|frame| match parse_message(exchange, frame, timestamp_unit) {
+                ParsedMessage::OrderBook { book, .. } => Some(ReplayEvent::OrderBook(book)),
+                ParsedMessage::Trades(trades) if !trades.is_empty() => Some(ReplayEvent::Trades(trades)),
+                _ => None,
+            })
+            .collect();
+        Self { engine, events, mode, position: 0, hook: None }
+    }
+
+    /// Call `hook` after every applied event, e.g. to inspect intermediate state
+    pub fn with_hook(mut self, hook: ReplayHook) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    /// The engine being replayed into
+    pub fn engine(&self) -> &OFIEngine {
+        &self.engine
+    }
+
+    /// Number of replayable events
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Number of events applied so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Apply the next event and analyze its symbol, without pacing. None once all events are applied.
+    pub async fn step(&mut self) -> Option<ReplayStep> {
+        let event = self.events.get(self.position)?;
+        let (symbol, timestamp) = (event.symbol().to_string(), event.timestamp());
+        match event {
+            ReplayEvent::OrderBook(book) => {
+                self.engine.update_order_book(book.clone()).await;
+            }
+            ReplayEvent::Trades(trades) => {
+                for trade in trades {
+                    self.engine.add_trade(trade.clone()).await;
+                }
+            }
+        }
+        let step = ReplayStep {
+            index: self.position,
+            signal: self.engine.analyze_symbol(&symbol).await,
+            symbol,
+            timestamp,
+        };
+        self.position += 1;
+        if let Some(hook) = self.hook.as_mut() {
+            hook(&step);
+        }
+        Some(step)
+    }
+
+    /// Replay the remaining events with the configured pacing and return the actionable signals
+    pub async fn run(&mut self) -> Result<Vec<TradingSignal>> {
+        if self.mode == ReplayMode::Step {
+            return Err(anyhow!("Step mode replays are driven one event at a time with step()"));
+        }
+        let mut signals = Vec::new();
+        let mut previous_timestamp: Option<u64> = None;
+        while let Some(event) = self.events.get(self.position) {
+            let timestamp = event.timestamp();
+            if self.mode == ReplayMode::RealTime {
+                if let Some(previous) = previous_timestamp {
+                    tokio::time::sleep(Duration::from_millis(timestamp.saturating_sub(previous))).await;
+                }
+            }
+            previous_timestamp = Some(timestamp);
+            if let Some(step) = self.step().await {
+                if !matches!(step.signal.signal_type, crate::signals::SignalType::NoSignal) {
+                    signals.push(step.signal);
+                }
+            }
+        }
+        Ok(signals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OFIConfig;
+    use std::sync::{Arc, Mutex};
+
+    fn books_frame(timestamp: u64) -> String {
+        format!(
+            r#"{{"action":"snapshot","arg":{{"instType":"USDT-FUTURES","channel":"books","instId":"BTCUSDT"}},"data":[{{"asks":[["100.0","1.0"]],"bids":[["99.9","1.0"]],"ts":"{}"}}],"ts":{}}}"#,
+            timestamp, timestamp
+        )
+    }
+
+    fn trade_frame(timestamp: u64) -> String {
+        format!(
+            r#"{{"action":"update","arg":{{"instType":"USDT-FUTURES","channel":"trade","instId":"BTCUSDT"}},"data":[{{"ts":"{}","price":"100.0","size":"0.5","side":"buy","tradeId":"1"}}],"ts":{}}}"#,
+            timestamp, timestamp
+        )
+    }
+
+    /// A book, an ack to skip, and a trade printed an hour after the book
+    fn frames() -> Vec<String> {
+        vec![
+            books_frame(1_727_083_571_321),
+            r#"{"event":"subscribe","arg":{"instType":"USDT-FUTURES","channel":"books","instId":"BTCUSDT"}}"#.to_string(),
+            trade_frame(1_727_087_171_321),
+        ]
+    }
+
+    fn runner(mode: ReplayMode) -> ReplayRunner {
+        let engine = crate::engine::analysis_engine(3.0, 1000.0, 5000, OFIConfig::for_tests());
+        ReplayRunner::from_frames(engine, &frames(), mode)
+    }
+
+    #[tokio::test]
+    async fn step_mode_applies_exactly_one_event_per_call() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = Arc::clone(&seen);
+        let mut replay = runner(ReplayMode::Step).with_hook(Box::new(move |step| hook_seen.lock().unwrap().push(step.index)));
+        assert_eq!(replay.len(), 2, "the ack carries no data");
+        assert!(replay.run().await.is_err(), "step replays are driven by step()");
+
+        let first = replay.step().await.expect("book event");
+        assert_eq!((first.index, first.timestamp, replay.position()), (0, 1_727_083_571_321, 1));
+        assert!(replay.engine().current_metrics("BTCUSDT").await.is_some(), "the book is applied");
+        assert_eq!(*seen.lock().unwrap(), vec![0]);
+
+        let second = replay.step().await.expect("trade event");
+        assert_eq!((second.index, replay.position()), (1, 2));
+        assert!(replay.step().await.is_none());
+        assert_eq!(*seen.lock().unwrap(), vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn fast_replay_ignores_event_timestamps() {
+        let mut replay = runner(ReplayMode::AsFastAsPossible);
+        // The events are an hour apart; a paced replay would wait that long
+        tokio::time::timeout(Duration::from_secs(5), replay.run())
+            .await
+            .expect("fast replays don't wait between events")
+            .unwrap();
+        assert_eq!(replay.position(), replay.len());
+    }
+}
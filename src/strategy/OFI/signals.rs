@@ -2,13 +2,15 @@
 
 #![allow(dead_code)]
 
-use crate::data::{OrderBookSnapshot, TradeData};
-use crate::ofi::{calculate_ofi_metrics, detect_absorption, detect_stacked_imbalances};
+use crate::config::{AbsorptionInterpretation, ImbalanceBasis, OFIConfig, SignalPriceSource};
+use crate::data::{BookFlowEvent, OrderBookLevel, OrderBookSnapshot, TradeData};
+use crate::ofi::{calculate_fast_ofi_metrics, calculate_ofi_metrics, detect_absorption, stacked_imbalance_levels, sustained_touch_pressure, weighted_mid, OFIMetrics, StackedLevels};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Represents a trading signal
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum SignalType {
     StrongBuy,
     StrongSell,
@@ -29,15 +31,211 @@ impl fmt::Display for SignalType {
     }
 }
 
+impl SignalType {
+    /// Position direction the signal calls for: 1 long, -1 short, 0 none
+    pub fn direction(self) -> i8 {
+        match self {
+            SignalType::StrongBuy | SignalType::Buy => 1,
+            SignalType::StrongSell | SignalType::Sell => -1,
+            SignalType::NoSignal => 0,
+        }
+    }
+}
+
+/// Which strategy rule produced a signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ReasonCode {
+    StackedImbalance,
+    Absorption,
+    AbsorptionConfirmed,
+    Exhaustion,
+    TopOfBookImbalance,
+    TouchImbalance,
+    GroupFlow,
+    NoSignal,
+}
+
 /// Trading signal with details
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TradingSignal {
     pub symbol: String,
     pub signal_type: SignalType,
     pub price: f64,
     pub confidence: f64, // 0.0 to 1.0
     pub reason: String,
+    pub reason_code: ReasonCode,
     pub timestamp: u64,
+    pub tier: Option<String>, // Confidence tier label, set from the configured [[tier]] mapping
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<SignalExplanation>, // Rule inputs behind the decision, set in explain mode
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub corroborated_by: Vec<ReasonCode>, // Other rules that fired in the same direction in the same analysis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_params: Option<EffectiveParams>, // Parameters in force at emission, set with record_effective_params
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_advisable_size: Option<f64>, // Notional executable within sizing_slippage_bps of the touch, set when that is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chosen_lookback_ms: Option<u64>, // Window the signal was selected from, set with multi_lookback
+}
+
+/// The fully resolved parameters a signal was detected with, after per-symbol overrides and
+/// market-condition adaptation, so a recorded signal can be reproduced with `detect_signals`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EffectiveParams {
+    pub imbalance_threshold: f64,
+    pub absorption_threshold: f64,
+    pub delta_threshold: f64,
+    pub lookback_period_ms: u64,
+    pub market_condition_multiplier: f64,
+    pub adjusted_imbalance_threshold: f64,   // imbalance_threshold * market_condition_multiplier
+    pub adjusted_absorption_threshold: f64,  // absorption_threshold * market_condition_multiplier
+    pub adjusted_delta_threshold: f64,       // delta_threshold * market_condition_multiplier
+    pub fast_mode: bool,
+    pub fast_mode_levels: usize,
+    pub fast_mode_signal_confidence: f64,
+    pub touch_imbalance_threshold: f64,
+    pub touch_imbalance_updates: usize,
+    pub touch_imbalance_confidence: f64,
+    pub replenishment_confidence_weight: f64,
+    pub imbalance_basis: ImbalanceBasis,
+    pub imbalance_notional_depth: f64,
+    pub imbalance_level_cap_multiple: f64,
+    pub adapt_thin_books: bool,
+    pub stacked_min_depth: usize,
+    pub strong_signal_confidence: f64,
+    pub reversal_signal_confidence: f64,
+    pub exhaustion_signal_confidence: f64,
+    pub absorption_interpretation: AbsorptionInterpretation,
+    pub large_print_notional: f64,
+    pub large_print_min_count: usize,
+    pub large_print_confidence_boost: f64,
+    pub min_buy_trades: usize,
+    pub min_sell_trades: usize,
+    pub min_book_quality: f64,
+    pub book_quality_confidence_scaling: bool,
+    pub flow_consistency_confidence_scaling: bool,
+    pub imbalance_momentum_snapshots: usize,
+    pub delta_decay_half_life_ms: u64,
+    pub suppress_decaying_imbalance: bool,
+    pub signal_price_source: SignalPriceSource,
+    pub weighted_mid_levels: usize,
+    pub unified_event_clock: bool,
+}
+
+impl EffectiveParams {
+    /// Snapshot of `params` and the detection settings of `config`
+    pub fn resolve(params: &StrategyParams, config: &OFIConfig) -> Self {
+        Self {
+            imbalance_threshold: params.imbalance_threshold,
+            absorption_threshold: params.absorption_threshold,
+            delta_threshold: params.delta_threshold,
+            lookback_period_ms: params.lookback_period_ms,
+            market_condition_multiplier: params.market_condition_multiplier,
+            adjusted_imbalance_threshold: params.imbalance_threshold * params.market_condition_multiplier,
+            adjusted_absorption_threshold: params.absorption_threshold * params.market_condition_multiplier,
+            adjusted_delta_threshold: params.delta_threshold * params.market_condition_multiplier,
+            fast_mode: config.fast_mode,
+            fast_mode_levels: config.fast_mode_levels,
+            fast_mode_signal_confidence: config.fast_mode_signal_confidence,
+            touch_imbalance_threshold: config.touch_imbalance_threshold,
+            touch_imbalance_updates: config.touch_imbalance_updates,
+            touch_imbalance_confidence: config.touch_imbalance_confidence,
+            replenishment_confidence_weight: config.replenishment_confidence_weight,
+            imbalance_basis: config.imbalance_basis,
+            imbalance_notional_depth: config.imbalance_notional_depth,
+            imbalance_level_cap_multiple: config.imbalance_level_cap_multiple,
+            adapt_thin_books: config.adapt_thin_books,
+            stacked_min_depth: config.stacked_min_depth,
+            strong_signal_confidence: config.strong_signal_confidence,
+            reversal_signal_confidence: config.reversal_signal_confidence,
+            exhaustion_signal_confidence: config.exhaustion_signal_confidence,
+            absorption_interpretation: config.absorption_interpretation,
+            large_print_notional: config.large_print_notional,
+            large_print_min_count: config.large_print_min_count,
+            large_print_confidence_boost: config.large_print_confidence_boost,
+            min_buy_trades: config.min_buy_trades,
+            min_sell_trades: config.min_sell_trades,
+            min_book_quality: config.min_book_quality,
+            book_quality_confidence_scaling: config.book_quality_confidence_scaling,
+            flow_consistency_confidence_scaling: config.flow_consistency_confidence_scaling,
+            imbalance_momentum_snapshots: config.imbalance_momentum_snapshots,
+            delta_decay_half_life_ms: config.delta_decay_half_life_ms,
+            suppress_decaying_imbalance: config.suppress_decaying_imbalance,
+            signal_price_source: config.signal_price_source,
+            weighted_mid_levels: config.weighted_mid_levels,
+            unified_event_clock: config.unified_event_clock,
+        }
+    }
+
+    /// The strategy params to pass to `detect_signals` to reproduce the signal
+    pub fn strategy_params(&self) -> StrategyParams {
+        StrategyParams {
+            imbalance_threshold: self.imbalance_threshold,
+            absorption_threshold: self.absorption_threshold,
+            delta_threshold: self.delta_threshold,
+            lookback_period_ms: self.lookback_period_ms,
+            market_condition_multiplier: self.market_condition_multiplier,
+        }
+    }
+
+    /// Restore the recorded detection settings into `config`; other settings are left as they are
+    pub fn apply_to(&self, config: &mut OFIConfig) {
+        config.fast_mode = self.fast_mode;
+        config.fast_mode_levels = self.fast_mode_levels;
+        config.fast_mode_signal_confidence = self.fast_mode_signal_confidence;
+        config.touch_imbalance_threshold = self.touch_imbalance_threshold;
+        config.touch_imbalance_updates = self.touch_imbalance_updates;
+        config.touch_imbalance_confidence = self.touch_imbalance_confidence;
+        config.replenishment_confidence_weight = self.replenishment_confidence_weight;
+        config.imbalance_basis = self.imbalance_basis;
+        config.imbalance_notional_depth = self.imbalance_notional_depth;
+        config.imbalance_level_cap_multiple = self.imbalance_level_cap_multiple;
+        config.adapt_thin_books = self.adapt_thin_books;
+        config.stacked_min_depth = self.stacked_min_depth;
+        config.strong_signal_confidence = self.strong_signal_confidence;
+        config.reversal_signal_confidence = self.reversal_signal_confidence;
+        config.exhaustion_signal_confidence = self.exhaustion_signal_confidence;
+        config.absorption_interpretation = self.absorption_interpretation;
+        config.large_print_notional = self.large_print_notional;
+        config.large_print_min_count = self.large_print_min_count;
+        config.large_print_confidence_boost = self.large_print_confidence_boost;
+        config.min_buy_trades = self.min_buy_trades;
+        config.min_sell_trades = self.min_sell_trades;
+        config.min_book_quality = self.min_book_quality;
+        config.book_quality_confidence_scaling = self.book_quality_confidence_scaling;
+        config.flow_consistency_confidence_scaling = self.flow_consistency_confidence_scaling;
+        config.imbalance_momentum_snapshots = self.imbalance_momentum_snapshots;
+        config.delta_decay_half_life_ms = self.delta_decay_half_life_ms;
+        config.suppress_decaying_imbalance = self.suppress_decaying_imbalance;
+        config.signal_price_source = self.signal_price_source;
+        config.weighted_mid_levels = self.weighted_mid_levels;
+        config.unified_event_clock = self.unified_event_clock;
+    }
+}
+
+/// The values the strategy rules evaluated for one analysis, recorded in explain mode
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SignalExplanation {
+    pub delta: f64,
+    pub cumulative_delta: f64,
+    pub buy_imbalance: f64,
+    pub sell_imbalance: f64,
+    pub adjusted_imbalance_threshold: f64,
+    pub adjusted_delta_threshold: f64,
+    pub buy_stacked_levels: usize,    // Imbalanced bid levels, 0 on the flow basis
+    pub sell_stacked_levels: usize,   // Imbalanced ask levels, 0 on the flow basis
+    pub required_stacked_levels: (usize, usize),  // (bid, ask) levels needed to stack, 0 = side not evaluable
+    pub buy_stacked: bool,
+    pub sell_stacked: bool,
+    pub absorption: Option<String>,   // Absorption check result, None if no absorption was detected
+    pub large_buy_prints: usize,
+    pub large_sell_prints: usize,
+    pub exhaustion: bool,
+    pub book_strategies: bool,        // False when a trade-only analysis skipped the book rules
 }
 
 impl TradingSignal {
@@ -49,7 +247,14 @@ impl TradingSignal {
             price: 0.0,
             confidence: 0.0,
             reason: "No significant signal detected".to_string(),
+            reason_code: ReasonCode::NoSignal,
             timestamp: 0,
+            tier: None,
+            explanation: None,
+            corroborated_by: Vec::new(),
+            effective_params: None,
+            max_advisable_size: None,
+            chosen_lookback_ms: None,
         }
     }
     
@@ -61,13 +266,31 @@ impl TradingSignal {
             price: 0.0,
             confidence: 0.0,
             reason: reason.to_string(),
+            reason_code: ReasonCode::NoSignal,
             timestamp: 0,
+            tier: None,
+            explanation: None,
+            corroborated_by: Vec::new(),
+            effective_params: None,
+            max_advisable_size: None,
+            chosen_lookback_ms: None,
+        }
+    }
+    
+    /// Helper to create a NoSignal carrying the analysed price and timestamp
+    pub fn no_signal_at(symbol: &str, price: f64, timestamp: u64, reason: String) -> Self {
+        Self {
+            price,
+            timestamp,
+            reason,
+            ..Self::no_signal(symbol)
         }
     }
 }
 
 /// Strategy parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StrategyParams {
     pub imbalance_threshold: f64,     // Threshold for detecting imbalances
     pub absorption_threshold: f64,    // Threshold for detecting absorption
@@ -76,33 +299,397 @@ pub struct StrategyParams {
     pub market_condition_multiplier: f64, // Multiplier based on market conditions
 }
 
-/// Detect trading signals based on OFI analysis
+/// Detect trading signals based on OFI analysis.
+/// `flow_events` are the symbol's book flow events, used for `true_ofi` and the flow imbalance basis.
 pub fn detect_signals(
     order_book: &OrderBookSnapshot,
     trades: &[&TradeData],
+    flow_events: &[BookFlowEvent],
+    params: &StrategyParams,
+    config: &OFIConfig,
+) -> TradingSignal {
+    detect_signals_scoped(order_book, trades, flow_events, params, config, true)
+}
+
+/// Like `detect_signals`, but with `book_strategies` false the book-driven strategies
+/// (stacked imbalance, fast mode top-of-book imbalance) are skipped and only the trade-driven
+/// ones (absorption, exhaustion) are evaluated
+pub fn detect_signals_scoped(
+    order_book: &OrderBookSnapshot,
+    trades: &[&TradeData],
+    flow_events: &[BookFlowEvent],
+    params: &StrategyParams,
+    config: &OFIConfig,
+    book_strategies: bool,
+) -> TradingSignal {
+    let mut signal = evaluate_signals(order_book, trades, flow_events, params, config, book_strategies);
+    if config.record_effective_params && signal.signal_type != SignalType::NoSignal {
+        signal.effective_params = Some(EffectiveParams::resolve(params, config));
+    }
+    signal.max_advisable_size = max_advisable_size(order_book, signal.signal_type, config);
+    signal
+}
+
+/// Like `detect_signals_scoped`, but over every `multi_lookback` window in place of
+/// `params.lookback_period_ms` (just that with no windows configured), emitting the window
+/// selected by `select_lookback_signal`
+pub fn detect_signals_multi_lookback(
+    order_book: &OrderBookSnapshot,
+    trades: &[&TradeData],
+    flow_events: &[BookFlowEvent],
+    params: &StrategyParams,
+    config: &OFIConfig,
+    book_strategies: bool,
+) -> TradingSignal {
+    if config.multi_lookback.is_empty() {
+        return detect_signals_scoped(order_book, trades, flow_events, params, config, book_strategies);
+    }
+    let candidates = config
+        .multi_lookback
+        .iter()
+        .map(|&lookback_period_ms| {
+            let params = StrategyParams { lookback_period_ms, ..params.clone() };
+            (lookback_period_ms, detect_signals_scoped(order_book, trades, flow_events, &params, config, book_strategies))
+        })
+        .collect();
+    select_lookback_signal(candidates)
+}
+
+/// Pick the signal to emit from analyses of the same data over different lookbacks: the most
+/// consistent direction (the one most windows signal) first, then the highest confidence within
+/// it, then the earlier window. The winner's window is recorded in `chosen_lookback_ms`. When no
+/// window signals, the first window's NoSignal is returned. `candidates` must not be empty.
+pub fn select_lookback_signal(candidates: Vec<(u64, TradingSignal)>) -> TradingSignal {
+    let agreeing = |direction: i8| candidates.iter().filter(|(_, signal)| signal.signal_type.direction() == direction).count();
+    let best = candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, signal))| signal.signal_type != SignalType::NoSignal)
+        .max_by(|(i, (_, a)), (j, (_, b))| {
+            agreeing(a.signal_type.direction())
+                .cmp(&agreeing(b.signal_type.direction()))
+                .then(a.confidence.total_cmp(&b.confidence))
+                .then(j.cmp(i))
+        })
+        .map_or(0, |(index, _)| index);
+    let (lookback_period_ms, mut signal) = candidates.into_iter().nth(best).expect("candidates must not be empty");
+    if signal.signal_type != SignalType::NoSignal {
+        signal.chosen_lookback_ms = Some(lookback_period_ms);
+    }
+    signal
+}
+
+/// Sizing hint for a signal: the notional executable in its direction within `sizing_slippage_bps`,
+/// None for NoSignal or without a slippage budget
+pub fn max_advisable_size(order_book: &OrderBookSnapshot, signal_type: SignalType, config: &OFIConfig) -> Option<f64> {
+    (config.sizing_slippage_bps > 0.0 && signal_type != SignalType::NoSignal)
+        .then(|| executable_notional(order_book, signal_type.direction(), config.sizing_slippage_bps))
+}
+
+/// Notional that can be taken from the book in `direction` (asks for buys, bids for sells)
+/// without filling more than `slippage_bps` away from the touch. Levels beyond the limit count
+/// nothing, so a thin book yields a small size however large the signal.
+pub fn executable_notional(order_book: &OrderBookSnapshot, direction: i8, slippage_bps: f64) -> f64 {
+    let (levels, limit) = match (direction, order_book.asks.first(), order_book.bids.first()) {
+        (1, Some(best_ask), _) => (&order_book.asks, best_ask.price * (1.0 + slippage_bps / 10_000.0)),
+        (-1, _, Some(best_bid)) => (&order_book.bids, best_bid.price * (1.0 - slippage_bps / 10_000.0)),
+        _ => return 0.0,
+    };
+    levels
+        .iter()
+        .take_while(|level| if direction > 0 { level.price <= limit } else { level.price >= limit })
+        .map(|level| level.price * level.quantity)
+        .sum()
+}
+
+/// Book sanity checks, the configured strategy and the confidence scaling behind `detect_signals_scoped`
+fn evaluate_signals(
+    order_book: &OrderBookSnapshot,
+    trades: &[&TradeData],
+    flow_events: &[BookFlowEvent],
     params: &StrategyParams,
-    strong_signal_confidence: f64,
-    reversal_signal_confidence: f64,
-    exhaustion_signal_confidence: f64,
+    config: &OFIConfig,
+    book_strategies: bool,
 ) -> TradingSignal {
+    // A crossed or locked book comes from a bad update; its mid and imbalance are meaningless
+    if config.reject_crossed_books {
+        if let (Some(bid), Some(ask)) = (order_book.bids.first(), order_book.asks.first()) {
+            if bid.price >= ask.price {
+                let state = if bid.price > ask.price { "Crossed" } else { "Locked" };
+                return TradingSignal::no_signal_at(
+                    &order_book.symbol,
+                    (bid.price + ask.price) / 2.0,
+                    order_book.timestamp,
+                    format!("{} order book: best bid {} >= best ask {}", state, bid.price, ask.price),
+                );
+            }
+        }
+    }
+    
+    if config.fast_mode {
+        if !book_strategies {
+            return TradingSignal::no_signal_with_reason(&order_book.symbol, "Book unchanged since last analysis (fast mode)");
+        }
+        trace_span!("fast_mode");
+        return detect_signals_fast(order_book, trades, flow_events, params, config);
+    }
+    
     // Calculate OFI metrics
-    let ofi_metrics = calculate_ofi_metrics(order_book, trades, params.lookback_period_ms);
+    let ofi_metrics = {
+        trace_span!("ofi_metrics");
+        calculate_ofi_metrics(order_book, trades, flow_events, params.lookback_period_ms, config)
+    };
+    
+    // Get current price from the configured source
+    let current_price = signal_price(order_book, trades, config.signal_price_source, config.weighted_mid_levels);
+    
+    // A zero imbalance from an empty side is a broken book, not a balanced one
+    if ofi_metrics.one_sided {
+        return one_sided_no_signal(order_book, current_price, ofi_metrics.timestamp);
+    }
+    
+    // Gate on overall book quality before evaluating any strategy rule
+    if config.min_book_quality > 0.0 && ofi_metrics.book_quality < config.min_book_quality {
+        return TradingSignal::no_signal_at(
+            &order_book.symbol,
+            current_price,
+            ofi_metrics.timestamp,
+            format!("Book quality {:.2} below minimum {:.2}", ofi_metrics.book_quality, config.min_book_quality),
+        );
+    }
+    
+    let mut signal = evaluate_strategy_rules(order_book, trades, &ofi_metrics, params, config, current_price, book_strategies);
+    if signal.signal_type == SignalType::NoSignal && book_strategies {
+        if let Some(touch) = touch_imbalance_signal(order_book, flow_events, current_price, &ofi_metrics, config) {
+            signal = touch;
+        }
+    }
+    // Reversals lean on the touch holding; one that doesn't refill after being hit is weaker support
+    if config.replenishment_confidence_weight > 0.0 && matches!(signal.reason_code, ReasonCode::Absorption | ReasonCode::Exhaustion) {
+        let refill_ratio = if signal.signal_type.direction() > 0 { ofi_metrics.bid_refill_ratio } else { ofi_metrics.ask_refill_ratio };
+        signal.confidence *= 1.0 - config.replenishment_confidence_weight * (1.0 - refill_ratio);
+    }
+    signal = gate_on_imbalance_momentum(signal, ofi_metrics.imbalance_momentum, config);
+    
+    // Scale confidence by book quality so degraded books produce weaker signals
+    if config.book_quality_confidence_scaling && signal.signal_type != SignalType::NoSignal {
+        signal.confidence *= ofi_metrics.book_quality;
+    }
+    
+    // Scale confidence by how consistently flow kept its direction across the lookback
+    if config.flow_consistency_confidence_scaling && signal.signal_type != SignalType::NoSignal {
+        signal.confidence *= ofi_metrics.flow_consistency;
+    }
+    
+    signal
+}
+
+/// Standalone top-of-book pressure signal: Buy/Sell when the touch imbalance has held beyond
+/// `touch_imbalance_threshold` for `touch_imbalance_updates` books. None when off or not sustained.
+fn touch_imbalance_signal(
+    order_book: &OrderBookSnapshot,
+    flow_events: &[BookFlowEvent],
+    current_price: f64,
+    ofi_metrics: &OFIMetrics,
+    config: &OFIConfig,
+) -> Option<TradingSignal> {
+    if config.touch_imbalance_threshold <= 0.0 {
+        return None;
+    }
+    let signal_type = match sustained_touch_pressure(flow_events, config.touch_imbalance_threshold, config.touch_imbalance_updates) {
+        1 => SignalType::Buy,
+        -1 => SignalType::Sell,
+        _ => return None,
+    };
+    Some(TradingSignal {
+        signal_type,
+        confidence: config.touch_imbalance_confidence,
+        reason: format!(
+            "Touch imbalance {:.2} held for {} book updates (threshold {:.2})",
+            ofi_metrics.touch_imbalance, config.touch_imbalance_updates, config.touch_imbalance_threshold
+        ),
+        reason_code: ReasonCode::TouchImbalance,
+        ..TradingSignal::no_signal_at(&order_book.symbol, current_price, ofi_metrics.timestamp, String::new())
+    })
+}
+
+/// Whether the lookback holds at least `min_buy_trades` buys and `min_sell_trades` sells, as
+/// continuation signals require
+fn has_participation(ofi_metrics: &OFIMetrics, config: &OFIConfig) -> bool {
+    ofi_metrics.buy_trades >= config.min_buy_trades && ofi_metrics.sell_trades >= config.min_sell_trades
+}
+
+/// With `suppress_decaying_imbalance`, withhold directional signals whose book imbalance is moving
+/// against them, keeping those where pressure is building in the signal's direction
+fn gate_on_imbalance_momentum(signal: TradingSignal, momentum: f64, config: &OFIConfig) -> TradingSignal {
+    if !config.suppress_decaying_imbalance || config.imbalance_momentum_snapshots == 0 || (signal.signal_type.direction() as f64) * momentum >= 0.0 {
+        return signal;
+    }
+    let reason = format!("{} suppressed: book imbalance decaying against it (momentum {:.3}/s)", signal.signal_type, momentum);
+    TradingSignal::no_signal_at(&signal.symbol, signal.price, signal.timestamp, reason)
+}
+
+/// Downgrade StrongBuy/StrongSell to Buy/Sell when the book updates slower than `min_rate`
+/// (updates per second); other signals pass through unchanged
+pub fn gate_strong_on_update_rate(mut signal: TradingSignal, rate: f64, min_rate: f64) -> TradingSignal {
+    let downgraded = match signal.signal_type {
+        SignalType::StrongBuy => SignalType::Buy,
+        SignalType::StrongSell => SignalType::Sell,
+        _ => return signal,
+    };
+    if rate >= min_rate {
+        return signal;
+    }
+    signal.reason = format!(
+        "{} (downgraded from {}: book updating at {:.2}/s, below minimum {:.2}/s)",
+        signal.reason, signal.signal_type, rate, min_rate
+    );
+    signal.signal_type = downgraded;
+    signal
+}
+
+/// NoSignal for a book with an empty (zero notional) side
+fn one_sided_no_signal(order_book: &OrderBookSnapshot, current_price: f64, timestamp: u64) -> TradingSignal {
+    let has_notional = |levels: &[OrderBookLevel]| levels.iter().any(|level| level.price * level.quantity > 0.0);
+    let empty_side = match (has_notional(&order_book.bids), has_notional(&order_book.asks)) {
+        (false, false) => "both sides",
+        (false, true) => "bid side",
+        _ => "ask side",
+    };
+    TradingSignal::no_signal_at(&order_book.symbol, current_price, timestamp, format!("One-sided order book: {} empty", empty_side))
+}
+
+/// Price for a signal from the configured source, falling back to mid (or the only side
+/// present) when the source is unavailable
+pub fn signal_price(order_book: &OrderBookSnapshot, trades: &[&TradeData], source: SignalPriceSource, weighted_mid_levels: usize) -> f64 {
+    let best_bid = order_book.bids.first();
+    let best_ask = order_book.asks.first();
+    let mid = match (best_bid, best_ask) {
+        (Some(bid), Some(ask)) if bid.price > 0.0 && ask.price > 0.0 => (bid.price + ask.price) / 2.0,
+        _ => best_bid.map(|b| b.price).unwrap_or(0.0).max(best_ask.map(|a| a.price).unwrap_or(0.0)),
+    };
+
+    match source {
+        SignalPriceSource::Mid => mid,
+        SignalPriceSource::Microprice => match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) if bid.quantity + ask.quantity > 0.0 => {
+                (bid.price * ask.quantity + ask.price * bid.quantity) / (bid.quantity + ask.quantity)
+            }
+            _ => mid,
+        },
+        SignalPriceSource::LastTrade => trades
+            .iter()
+            .max_by_key(|trade| trade.timestamp)
+            .map(|trade| trade.price)
+            .filter(|price| *price > 0.0)
+            .unwrap_or(mid),
+        SignalPriceSource::WeightedMid => weighted_mid(order_book, weighted_mid_levels).unwrap_or(mid),
+    }
+}
+
+/// Fast mode: top-of-book imbalance confirmed by trade delta.
+///
+/// Only emits Buy/Sell. Stacked imbalance (StrongBuy/StrongSell), absorption, exhaustion and the
+/// book quality gate/scaling are not available in fast mode.
+fn detect_signals_fast(
+    order_book: &OrderBookSnapshot,
+    trades: &[&TradeData],
+    flow_events: &[BookFlowEvent],
+    params: &StrategyParams,
+    config: &OFIConfig,
+) -> TradingSignal {
+    let ofi_metrics = calculate_fast_ofi_metrics(order_book, trades, flow_events, params.lookback_period_ms, config);
+    
+    let current_price = signal_price(order_book, trades, config.signal_price_source, config.weighted_mid_levels);
+    
+    if ofi_metrics.one_sided {
+        return one_sided_no_signal(order_book, current_price, ofi_metrics.timestamp);
+    }
     
-    // Get current price (mid price)
-    let best_bid = order_book.bids.first().map(|b| b.price).unwrap_or(0.0);
-    let best_ask = order_book.asks.first().map(|a| a.price).unwrap_or(0.0);
-    let current_price = if best_bid > 0.0 && best_ask > 0.0 {
-        (best_bid + best_ask) / 2.0
+    let adjusted_imbalance_threshold = params.imbalance_threshold * params.market_condition_multiplier;
+    let adjusted_delta_threshold = params.delta_threshold * params.market_condition_multiplier;
+    
+    let participation = has_participation(&ofi_metrics, config);
+    let signal_type = if participation && ofi_metrics.buy_imbalance >= adjusted_imbalance_threshold && ofi_metrics.delta > adjusted_delta_threshold {
+        SignalType::Buy
+    } else if participation && ofi_metrics.sell_imbalance >= adjusted_imbalance_threshold && ofi_metrics.delta < -adjusted_delta_threshold {
+        SignalType::Sell
+    } else if let Some(touch) = touch_imbalance_signal(order_book, flow_events, current_price, &ofi_metrics, config) {
+        return gate_on_imbalance_momentum(touch, ofi_metrics.imbalance_momentum, config);
     } else {
-        best_bid.max(best_ask)
+        return TradingSignal::no_signal_at(
+            &order_book.symbol,
+            current_price,
+            ofi_metrics.timestamp,
+            format!(
+                "No significant signal detected (delta {:.0}, buy imbalance {:.2}, sell imbalance {:.2}, fast mode)",
+                ofi_metrics.delta, ofi_metrics.buy_imbalance, ofi_metrics.sell_imbalance
+            ),
+        );
     };
     
+    let imbalance = match signal_type {
+        SignalType::Buy => ofi_metrics.buy_imbalance,
+        _ => ofi_metrics.sell_imbalance,
+    };
+    let signal = TradingSignal {
+        symbol: order_book.symbol.clone(),
+        signal_type,
+        price: current_price,
+        confidence: config.fast_mode_signal_confidence,
+        reason: format!(
+            "Top-{} level imbalance {:.2} with delta {:.0} (fast mode)",
+            config.fast_mode_levels, imbalance, ofi_metrics.delta
+        ),
+        reason_code: ReasonCode::TopOfBookImbalance,
+        timestamp: ofi_metrics.timestamp,
+        tier: None,
+        explanation: None,
+        corroborated_by: Vec::new(),
+        effective_params: None,
+        max_advisable_size: None,
+        chosen_lookback_ms: None,
+    };
+    gate_on_imbalance_momentum(signal, ofi_metrics.imbalance_momentum, config)
+}
+
+/// Evaluate every strategy rule (continuation, reversal, exhaustion) and resolve their candidates
+/// with `resolve_candidates`. Continuation is book-driven and gets no stacked imbalances when
+/// `book_strategies` is false.
+fn evaluate_strategy_rules(
+    order_book: &OrderBookSnapshot,
+    trades: &[&TradeData],
+    ofi_metrics: &OFIMetrics,
+    params: &StrategyParams,
+    config: &OFIConfig,
+    current_price: f64,
+    book_strategies: bool,
+) -> TradingSignal {
     // Adjust parameters based on market condition multiplier
     let adjusted_imbalance_threshold = params.imbalance_threshold * params.market_condition_multiplier;
     let adjusted_delta_threshold = params.delta_threshold * params.market_condition_multiplier;
     
-    // Detect stacked imbalances with adjusted threshold
-    let (buy_stacked, sell_stacked) = detect_stacked_imbalances(order_book, adjusted_imbalance_threshold);
+    // Detect stacked imbalances with adjusted threshold; on the flow basis the flow imbalance
+    // ratio takes the place of stacked resting levels
+    let mut stacked_levels = StackedLevels::default();
+    let (buy_stacked, sell_stacked) = match config.imbalance_basis {
+        _ if !book_strategies => (false, false),
+        ImbalanceBasis::Resting => {
+            trace_span!("stacked_imbalance");
+            stacked_levels = stacked_imbalance_levels(
+                order_book,
+                adjusted_imbalance_threshold,
+                config.imbalance_level_cap_multiple,
+                config.adapt_thin_books,
+                config.stacked_min_depth,
+            );
+            (stacked_levels.buy_stacked(), stacked_levels.sell_stacked())
+        }
+        ImbalanceBasis::Flow => (
+            ofi_metrics.buy_imbalance >= adjusted_imbalance_threshold,
+            ofi_metrics.sell_imbalance >= adjusted_imbalance_threshold,
+        ),
+    };
     
     // Create a copy of params with adjusted values
     let adjusted_params = crate::signals::StrategyParams {
@@ -114,69 +701,640 @@ pub fn detect_signals(
     };
     
     // Detect absorption - using improved logic from ofi.rs with adjusted params
-    let absorption_detected = detect_absorption(order_book, trades, &ofi_metrics, &adjusted_params);
-    
-    // Determine signal based on strategy rules using adjusted parameters
+    let absorption_detected = {
+        trace_span!("absorption");
+        detect_absorption(order_book, trades, ofi_metrics, &adjusted_params)
+    };
+    let exhaustion = ofi_metrics.delta < -adjusted_delta_threshold && ofi_metrics.cumulative_delta > adjusted_delta_threshold * 2.0;
+
+    // Explain mode: record every input the rules below decide on
+    let explanation = config.explain_signals.then(|| SignalExplanation {
+        delta: ofi_metrics.delta,
+        cumulative_delta: ofi_metrics.cumulative_delta,
+        buy_imbalance: ofi_metrics.buy_imbalance,
+        sell_imbalance: ofi_metrics.sell_imbalance,
+        adjusted_imbalance_threshold,
+        adjusted_delta_threshold,
+        buy_stacked_levels: stacked_levels.buy_levels,
+        sell_stacked_levels: stacked_levels.sell_levels,
+        required_stacked_levels: (stacked_levels.buy_required, stacked_levels.sell_required),
+        buy_stacked,
+        sell_stacked,
+        absorption: absorption_detected.0.then(|| absorption_detected.1.clone()),
+        large_buy_prints: ofi_metrics.large_buy_prints,
+        large_sell_prints: ofi_metrics.large_sell_prints,
+        exhaustion,
+        book_strategies,
+    });
     
-    // 1. Continuation signals
-    if buy_stacked && ofi_metrics.delta > adjusted_delta_threshold {
-        // Strong buy signal - stacked buy imbalances with positive delta
-        return TradingSignal {
+    let inputs = RuleInputs {
+        ofi_metrics,
+        config,
+        adjusted_delta_threshold,
+        buy_stacked,
+        sell_stacked,
+        absorption: absorption_detected
+            .0
+            .then(|| interpret_absorption(absorption_detected.2, absorption_detected.1.clone(), config.absorption_interpretation)),
+        exhaustion,
+    };
+    let candidates = STRATEGIES.iter().filter_map(|strategy| strategy.evaluate(&inputs)).collect();
+
+    match resolve_candidates(candidates, config.consensus_confidence_boost) {
+        Some((candidate, corroborated_by)) => TradingSignal {
             symbol: order_book.symbol.clone(),
-            signal_type: SignalType::StrongBuy,
+            signal_type: candidate.signal_type,
             price: current_price,
-            confidence: strong_signal_confidence,
-            reason: format!("Stacked buy imbalances with strong positive delta (adjusted threshold: {:.2})", adjusted_delta_threshold),
+            confidence: candidate.confidence,
+            reason: candidate.reason,
+            reason_code: candidate.reason_code,
             timestamp: ofi_metrics.timestamp,
-        };
-    }
-    
-    if sell_stacked && ofi_metrics.delta < -adjusted_delta_threshold {
-        // Strong sell signal - stacked sell imbalances with negative delta
-        return TradingSignal {
+            tier: None,
+            explanation,
+            corroborated_by,
+            effective_params: None,
+            max_advisable_size: None,
+            chosen_lookback_ms: None,
+        },
+        // No strong signal detected; keep the metrics in the reason for observability
+        None => TradingSignal {
             symbol: order_book.symbol.clone(),
-            signal_type: SignalType::StrongSell,
+            signal_type: SignalType::NoSignal,
             price: current_price,
-            confidence: strong_signal_confidence,
-            reason: format!("Stacked sell imbalances with strong negative delta (adjusted threshold: {:.2})", adjusted_delta_threshold),
+            confidence: 0.0,
+            reason: format!(
+                "No significant signal detected (delta {:.0}, buy imbalance {:.2}, sell imbalance {:.2}, book quality {:.2})",
+                ofi_metrics.delta, ofi_metrics.buy_imbalance, ofi_metrics.sell_imbalance, ofi_metrics.book_quality
+            ),
+            reason_code: ReasonCode::NoSignal,
             timestamp: ofi_metrics.timestamp,
-        };
+            tier: None,
+            explanation,
+            corroborated_by: Vec::new(),
+            effective_params: None,
+            max_advisable_size: None,
+            chosen_lookback_ms: None,
+        },
     }
-    
-    // 2. Reversal signals using improved absorption detection
-    if absorption_detected.0 {
-        // Buy/Sell signal - absorption detected
-        return TradingSignal {
-            symbol: order_book.symbol.clone(),
-            signal_type: absorption_detected.2, // Use the signal type from absorption detection
-            price: current_price,
-            confidence: reversal_signal_confidence,
-            reason: absorption_detected.1, // Use the reason from absorption detection
-            timestamp: ofi_metrics.timestamp,
+}
+
+/// What the strategy rules of one analysis decide on, evaluated once from the book and trades
+pub struct RuleInputs<'a> {
+    pub ofi_metrics: &'a OFIMetrics,
+    pub config: &'a OFIConfig,
+    pub adjusted_delta_threshold: f64,
+    pub buy_stacked: bool,  // Stacked buy imbalances (or the flow ratio) past the adjusted threshold
+    pub sell_stacked: bool,
+    pub absorption: Option<(SignalType, String)>,  // Detected absorption, already interpreted per config
+    pub exhaustion: bool,  // Delta turning negative after a strong positive cumulative delta
+}
+
+/// A signal proposed by one strategy rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleCandidate {
+    pub signal_type: SignalType,
+    pub confidence: f64,
+    pub reason: String,
+    pub reason_code: ReasonCode,
+}
+
+/// A strategy rule: proposes a directional signal from the analysis inputs, or nothing
+pub trait Strategy: Send + Sync {
+    fn evaluate(&self, inputs: &RuleInputs) -> Option<RuleCandidate>;
+}
+
+/// Continuation: stacked imbalances with delta in the same direction, confirmed by large
+/// aggressive prints and enough participation when configured
+pub struct StackedImbalanceStrategy;
+
+/// Absorption of aggressive flow by passive liquidity, traded per `absorption_interpretation`
+pub struct AbsorptionStrategy;
+
+/// Exhaustion: delta turning negative after a strong positive cumulative delta
+pub struct ExhaustionStrategy;
+
+/// The strategy rules evaluated on every analysis, in priority order (ties go to the earlier)
+static STRATEGIES: [&dyn Strategy; 3] = [&StackedImbalanceStrategy, &AbsorptionStrategy, &ExhaustionStrategy];
+
+impl Strategy for StackedImbalanceStrategy {
+    fn evaluate(&self, inputs: &RuleInputs) -> Option<RuleCandidate> {
+        let (metrics, config, threshold) = (inputs.ofi_metrics, inputs.config, inputs.adjusted_delta_threshold);
+        let large_prints_enabled = config.large_print_notional > 0.0;
+        let confidence = if large_prints_enabled {
+            (config.strong_signal_confidence + config.large_print_confidence_boost).min(1.0)
+        } else {
+            config.strong_signal_confidence
+        };
+        let buy_prints_confirmed = !large_prints_enabled || metrics.large_buy_prints >= config.large_print_min_count;
+        let sell_prints_confirmed = !large_prints_enabled || metrics.large_sell_prints >= config.large_print_min_count;
+        // Delta from a handful of one-sided prints is not participation enough for a continuation
+        if !has_participation(metrics, config) {
+            return None;
+        }
+        let (signal_type, reason) = if inputs.buy_stacked && metrics.delta > threshold && buy_prints_confirmed {
+            (SignalType::StrongBuy, format!("Stacked buy imbalances with strong positive delta (adjusted threshold: {:.2})", threshold))
+        } else if inputs.sell_stacked && metrics.delta < -threshold && sell_prints_confirmed {
+            (SignalType::StrongSell, format!("Stacked sell imbalances with strong negative delta (adjusted threshold: {:.2})", threshold))
+        } else {
+            return None;
         };
+        Some(RuleCandidate { signal_type, confidence, reason, reason_code: ReasonCode::StackedImbalance })
     }
-    
-    // 3. Check for exhaustion (delta turning negative after strong positive)
-    if ofi_metrics.delta < -adjusted_delta_threshold && ofi_metrics.cumulative_delta > adjusted_delta_threshold * 2.0 {
-        // Sell signal - exhaustion
-        return TradingSignal {
-            symbol: order_book.symbol.clone(),
+}
+
+impl Strategy for AbsorptionStrategy {
+    fn evaluate(&self, inputs: &RuleInputs) -> Option<RuleCandidate> {
+        let (signal_type, reason) = inputs.absorption.clone()?;
+        Some(RuleCandidate { signal_type, confidence: inputs.config.reversal_signal_confidence, reason, reason_code: ReasonCode::Absorption })
+    }
+}
+
+impl Strategy for ExhaustionStrategy {
+    fn evaluate(&self, inputs: &RuleInputs) -> Option<RuleCandidate> {
+        inputs.exhaustion.then(|| RuleCandidate {
             signal_type: SignalType::Sell,
-            price: current_price,
-            confidence: exhaustion_signal_confidence,
-            reason: format!("Potential exhaustion detected (adjusted threshold: {:.2})", adjusted_delta_threshold),
-            timestamp: ofi_metrics.timestamp,
-        };
+            confidence: inputs.config.exhaustion_signal_confidence,
+            reason: format!("Potential exhaustion detected (adjusted threshold: {:.2})", inputs.adjusted_delta_threshold),
+            reason_code: ReasonCode::Exhaustion,
+        })
     }
-    
-    // No strong signal detected
-    TradingSignal {
-        symbol: order_book.symbol.clone(),
-        signal_type: SignalType::NoSignal,
-        price: current_price,
-        confidence: 0.0,
-        reason: "No significant signal detected".to_string(),
-        timestamp: ofi_metrics.timestamp,
+}
+
+/// Resolve the candidates of one analysis, in priority order, into the signal to emit and the
+/// rules corroborating it. When every candidate agrees on the direction, the strongest is
+/// emitted with the others in `corroborated_by` and `consensus_boost` added per corroborating
+/// rule; when they conflict, the single strongest is emitted as is. Ties go to the earlier
+/// candidate; None without candidates.
+pub fn resolve_candidates(candidates: Vec<RuleCandidate>, consensus_boost: f64) -> Option<(RuleCandidate, Vec<ReasonCode>)> {
+    if candidates.is_empty() {
+        return None;
     }
+    let mut strongest = 0;
+    for (index, candidate) in candidates.iter().enumerate().skip(1) {
+        if candidate.confidence > candidates[strongest].confidence {
+            strongest = index;
+        }
+    }
+    let direction = candidates[strongest].signal_type.direction();
+    let agreed = candidates.iter().all(|candidate| candidate.signal_type.direction() == direction);
+    let corroborated_by: Vec<ReasonCode> = if agreed {
+        candidates
+            .iter()
+            .enumerate()
+            .filter(|(index, candidate)| *index != strongest && candidate.reason_code != candidates[strongest].reason_code)
+            .map(|(_, candidate)| candidate.reason_code)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut signal = candidates.into_iter().nth(strongest)?;
+    signal.confidence = (signal.confidence + consensus_boost * corroborated_by.len() as f64).min(1.0);
+    Some((signal, corroborated_by))
 }
 
+/// Map the reversal direction reported by `detect_absorption` to the configured interpretation.
+/// Continuation trades with the absorbed aggressors, i.e. the opposite direction.
+pub fn interpret_absorption(reversal: SignalType, reason: String, interpretation: AbsorptionInterpretation) -> (SignalType, String) {
+    match (interpretation, reversal) {
+        (AbsorptionInterpretation::Reversal, _) => (reversal, reason),
+        (AbsorptionInterpretation::Continuation, SignalType::Buy) => (SignalType::Sell, format!("{} Traded as continuation.", reason)),
+        (AbsorptionInterpretation::Continuation, SignalType::Sell) => (SignalType::Buy, format!("{} Traded as continuation.", reason)),
+        (AbsorptionInterpretation::Continuation, other) => (other, reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(bids: &[(f64, f64)], asks: &[(f64, f64)], timestamp: u64) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            symbol: "BTCUSDT".to_string(),
+            bids: bids.iter().map(|&(price, quantity)| OrderBookLevel { price, quantity }).collect(),
+            asks: asks.iter().map(|&(price, quantity)| OrderBookLevel { price, quantity }).collect(),
+            timestamp,
+        }
+    }
+
+    fn trade(timestamp: u64, side: &str, quantity: f64, price: f64) -> TradeData {
+        TradeData { symbol: "BTCUSDT".to_string(), price, quantity, side: side.to_string(), timestamp }
+    }
+
+    fn params() -> StrategyParams {
+        StrategyParams {
+            imbalance_threshold: 3.0,
+            absorption_threshold: 1000.0,
+            delta_threshold: 1000.0,
+            lookback_period_ms: 5000,
+            market_condition_multiplier: 1.0,
+        }
+    }
+
+    fn detect(order_book: &OrderBookSnapshot, trades: &[TradeData], config: &OFIConfig) -> TradingSignal {
+        let trades: Vec<&TradeData> = trades.iter().collect();
+        detect_signals(order_book, &trades, &[], &params(), config)
+    }
+
+    /// Five bid levels each far larger than the top ask: stacked buy imbalance
+    fn stacked_bid_book(timestamp: u64) -> OrderBookSnapshot {
+        book(
+            &[(99.9, 50.0), (99.8, 50.0), (99.7, 50.0), (99.6, 50.0), (99.5, 50.0)],
+            &[(100.0, 1.0), (100.1, 1.0), (100.2, 1.0), (100.3, 1.0), (100.4, 1.0)],
+            timestamp,
+        )
+    }
+
+    fn fast_config() -> OFIConfig {
+        OFIConfig { fast_mode: true, fast_mode_levels: 1, fast_mode_signal_confidence: 0.6, ..OFIConfig::for_tests() }
+    }
+
+    #[test]
+    fn fast_mode_emits_buy_and_sell_from_the_touch() {
+        let config = fast_config();
+        // Deeper levels lean the other way; fast mode only looks at the touch
+        let bid_heavy = book(&[(99.9, 50.0), (99.8, 1.0)], &[(100.0, 10.0), (100.1, 500.0)], 10_000);
+        let buy = detect(&bid_heavy, &[trade(9_500, "buy", 20.0, 100.0)], &config);
+        assert_eq!((buy.signal_type, buy.confidence, buy.reason_code), (SignalType::Buy, 0.6, ReasonCode::TopOfBookImbalance));
+
+        let ask_heavy = book(&[(99.9, 10.0)], &[(100.0, 50.0)], 10_000);
+        let sell = detect(&ask_heavy, &[trade(9_500, "sell", 20.0, 100.0)], &config);
+        assert_eq!(sell.signal_type, SignalType::Sell);
+
+        let against_delta = detect(&bid_heavy, &[trade(9_500, "sell", 20.0, 100.0)], &config);
+        assert_eq!(against_delta.signal_type, SignalType::NoSignal);
+    }
+
+    #[test]
+    fn fast_mode_never_emits_strong_signals() {
+        let stacked = stacked_bid_book(10_000);
+        let trades = [trade(9_500, "buy", 500.0, 100.0)];
+        let full = detect(&stacked, &trades, &OFIConfig::for_tests());
+        assert_eq!(full.signal_type, SignalType::StrongBuy);
+        assert_eq!(detect(&stacked, &trades, &fast_config()).signal_type, SignalType::Buy);
+    }
+
+    #[test]
+    fn fast_metrics_skip_the_full_depth_work() {
+        let deep = book(&[(99.9, 50.0), (99.8, 5_000.0)], &[(100.0, 10.0), (100.1, 10.0)], 10_000);
+        let trades = [trade(9_500, "buy", 100.0, 100.0)];
+        let trade_refs: Vec<&TradeData> = trades.iter().collect();
+        let fast = calculate_fast_ofi_metrics(&deep, &trade_refs, &[], 5000, &fast_config());
+        assert_eq!(fast.buy_imbalance, 5.0 * 99.9 / 100.0, "only the touch is compared");
+        assert_eq!((fast.large_buy_prints, fast.book_quality, fast.realized_volatility), (0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn each_price_source_prices_the_same_book_and_trades() {
+        let order_book = book(&[(99.0, 3.0)], &[(101.0, 1.0)], 10_000);
+        let trades = [trade(9_800, "buy", 1.0, 100.25), trade(9_500, "sell", 1.0, 99.5)];
+        let trade_refs: Vec<&TradeData> = trades.iter().collect();
+        let price = |source| signal_price(&order_book, &trade_refs, source, 5);
+
+        assert_eq!(price(SignalPriceSource::Mid), 100.0);
+        assert_eq!(price(SignalPriceSource::Microprice), 100.5, "leans towards the thinner ask");
+        assert_eq!(price(SignalPriceSource::LastTrade), 100.25, "latest by timestamp, not arrival");
+
+        let config = OFIConfig { signal_price_source: SignalPriceSource::LastTrade, ..OFIConfig::for_tests() };
+        assert_eq!(detect(&order_book, &trades, &config).price, 100.25);
+    }
+
+    #[test]
+    fn unavailable_price_sources_fall_back_to_the_mid() {
+        let order_book = book(&[(99.0, 0.0)], &[(101.0, 0.0)], 10_000);
+        assert_eq!(signal_price(&order_book, &[], SignalPriceSource::LastTrade, 5), 100.0, "no recent trade");
+        assert_eq!(signal_price(&order_book, &[], SignalPriceSource::Microprice, 5), 100.0, "no resting size");
+
+        let bids_only = book(&[(99.0, 3.0)], &[], 10_000);
+        assert_eq!(signal_price(&bids_only, &[], SignalPriceSource::Microprice, 5), 99.0);
+    }
+
+    #[test]
+    fn flow_consistency_scales_confidence_when_enabled() {
+        let stacked = stacked_bid_book(10_000);
+        let steady: Vec<TradeData> = (0..5).map(|i| trade(5_100 + i * 1_000, "buy", 200.0, 100.0)).collect();
+        let choppy: Vec<TradeData> = (0..5)
+            .map(|i| if i % 2 == 0 { trade(5_100 + i * 1_000, "buy", 200.0, 100.0) } else { trade(5_100 + i * 1_000, "sell", 100.0, 100.0) })
+            .collect();
+        let scaling = OFIConfig { flow_consistency_confidence_scaling: true, ..OFIConfig::for_tests() };
+
+        let unscaled = detect(&stacked, &choppy, &OFIConfig::for_tests());
+        assert_ne!(unscaled.signal_type, SignalType::NoSignal);
+        let scaled = detect(&stacked, &choppy, &scaling);
+        assert_eq!(scaled.signal_type, unscaled.signal_type);
+        assert!((scaled.confidence - unscaled.confidence * 0.6).abs() < 1e-9, "choppy flow: {} vs {}", scaled.confidence, unscaled.confidence);
+
+        assert_eq!(detect(&stacked, &steady, &scaling).confidence, detect(&stacked, &steady, &OFIConfig::for_tests()).confidence);
+    }
+
+    #[test]
+    fn absorption_is_traded_per_the_configured_interpretation() {
+        // Heavy selling into a book whose bid holds: buy absorption
+        let order_book = book(&[(99.9, 10.0), (99.8, 10.0)], &[(100.0, 10.0), (100.1, 10.0)], 10_000);
+        let trades = [trade(9_500, "sell", 30.0, 99.9)];
+
+        let reversal = detect(&order_book, &trades, &OFIConfig { absorption_interpretation: AbsorptionInterpretation::Reversal, ..OFIConfig::for_tests() });
+        let continuation = detect(&order_book, &trades, &OFIConfig { absorption_interpretation: AbsorptionInterpretation::Continuation, ..OFIConfig::for_tests() });
+        assert_eq!((reversal.signal_type, reversal.reason_code), (SignalType::Buy, ReasonCode::Absorption));
+        assert_eq!((continuation.signal_type, continuation.reason_code), (SignalType::Sell, ReasonCode::Absorption));
+        assert!(continuation.reason.ends_with("Traded as continuation."));
+    }
+
+    #[test]
+    fn stacked_imbalance_waits_for_the_minimum_depth_on_both_sides() {
+        let config = OFIConfig { stacked_min_depth: 3, ..OFIConfig::for_tests() };
+        let trades = [trade(9_500, "buy", 500.0, 100.0)];
+        let bids = [(99.9, 50.0), (99.8, 50.0), (99.7, 50.0), (99.6, 50.0), (99.5, 50.0)];
+
+        // A deep bid side over a one-level ask side is not a stacked imbalance
+        let one_level_asks = book(&bids, &[(100.0, 1.0)], 10_000);
+        assert_eq!(detect(&one_level_asks, &trades, &OFIConfig::for_tests()).signal_type, SignalType::StrongBuy, "stacks at the default depth");
+        assert_ne!(detect(&one_level_asks, &trades, &config).signal_type, SignalType::StrongBuy);
+
+        // Once the ask side meets the minimum depth the stacked bids count
+        let deep_enough = book(&bids, &[(100.0, 1.0), (100.1, 1.0), (100.2, 1.0)], 10_000);
+        assert_eq!(detect(&deep_enough, &trades, &config).signal_type, SignalType::StrongBuy);
+    }
+
+    #[test]
+    fn explanation_records_the_inputs_behind_a_strong_buy() {
+        let trades = [trade(9_500, "buy", 500.0, 100.0)];
+        let plain = detect(&stacked_bid_book(10_000), &trades, &OFIConfig::for_tests());
+        assert!(plain.explanation.is_none(), "explain mode is off by default");
+
+        let signal = detect(&stacked_bid_book(10_000), &trades, &OFIConfig { explain_signals: true, ..OFIConfig::for_tests() });
+        assert_eq!(signal.signal_type, SignalType::StrongBuy);
+        let explanation = signal.explanation.expect("explained");
+        assert_eq!(explanation.delta, 50_000.0);
+        assert_eq!((explanation.adjusted_delta_threshold, explanation.adjusted_imbalance_threshold), (1000.0, 3.0));
+        assert_eq!((explanation.buy_stacked_levels, explanation.required_stacked_levels.0), (5, 3));
+        assert!(explanation.buy_stacked && !explanation.sell_stacked);
+    }
+
+    #[test]
+    fn large_print_confirmation_tells_a_whale_from_dust() {
+        let config = OFIConfig { large_print_notional: 10_000.0, large_print_min_count: 1, large_print_confidence_boost: 0.1, ..OFIConfig::for_tests() };
+        // The same 50000 delta: one whale print vs five hundred dust prints
+        let whale = [trade(9_500, "buy", 500.0, 100.0)];
+        let dust: Vec<TradeData> = (0..500).map(|i| trade(9_000 + i, "buy", 1.0, 100.0)).collect();
+
+        let confirmed = detect(&stacked_bid_book(10_000), &whale, &config);
+        assert_eq!(confirmed.signal_type, SignalType::StrongBuy);
+        assert!((confirmed.confidence - (config.strong_signal_confidence + 0.1).min(1.0)).abs() < 1e-9, "boosted by the confirmation");
+        assert_ne!(detect(&stacked_bid_book(10_000), &dust, &config).signal_type, SignalType::StrongBuy);
+
+        // Without the confirmation both qualify
+        assert_eq!(detect(&stacked_bid_book(10_000), &dust, &OFIConfig::for_tests()).signal_type, SignalType::StrongBuy);
+    }
+
+    #[test]
+    fn one_sided_books_suppress_signals_with_a_reason() {
+        // Heavy buying that would otherwise stack against an empty or missing ask
+        let trades = [trade(9_500, "buy", 500.0, 100.0)];
+        let bids = [(99.9, 50.0), (99.8, 50.0), (99.7, 50.0)];
+        let asks = [(100.0, 50.0), (100.1, 50.0), (100.2, 50.0)];
+        for (bids, asks, empty) in [(&bids[..], &[][..], "ask side"), (&[][..], &asks[..], "bid side"), (&[][..], &[][..], "both sides")] {
+            let order_book = book(bids, asks, 10_000);
+            let trade_refs: Vec<&TradeData> = trades.iter().collect();
+            let metrics = calculate_ofi_metrics(&order_book, &trade_refs, &[], 5000, &OFIConfig::for_tests());
+            assert!(metrics.one_sided, "{} empty", empty);
+
+            let signal = detect(&order_book, &trades, &OFIConfig::for_tests());
+            assert_eq!(signal.signal_type, SignalType::NoSignal);
+            assert_eq!(signal.reason, format!("One-sided order book: {} empty", empty));
+        }
+
+        // A zero-quantity side carries no notional either
+        let zero_asks = book(&bids, &[(100.0, 0.0)], 10_000);
+        assert_eq!(detect(&zero_asks, &trades, &OFIConfig::for_tests()).reason, "One-sided order book: ask side empty");
+    }
+
+    #[test]
+    fn agreeing_rules_corroborate_and_conflicting_ones_yield_the_strongest() {
+        let candidate = |signal_type: SignalType, confidence: f64, reason_code: ReasonCode| RuleCandidate { signal_type, confidence, reason: format!("{:?}", reason_code), reason_code };
+
+        // All agree: the strongest is emitted, boosted once per corroborating rule
+        let (signal, corroborated_by) = resolve_candidates(
+            vec![
+                candidate(SignalType::StrongBuy, 0.8, ReasonCode::StackedImbalance),
+                candidate(SignalType::Buy, 0.7, ReasonCode::Absorption),
+                candidate(SignalType::Buy, 0.6, ReasonCode::Exhaustion),
+            ],
+            0.05,
+        )
+        .unwrap();
+        assert_eq!((signal.signal_type, signal.reason_code), (SignalType::StrongBuy, ReasonCode::StackedImbalance));
+        assert!((signal.confidence - 0.9).abs() < 1e-9);
+        assert_eq!(corroborated_by, vec![ReasonCode::Absorption, ReasonCode::Exhaustion]);
+
+        // Conflict: the single strongest as is, with no corroboration
+        let (signal, corroborated_by) = resolve_candidates(
+            vec![candidate(SignalType::StrongBuy, 0.7, ReasonCode::StackedImbalance), candidate(SignalType::Sell, 0.75, ReasonCode::Absorption)],
+            0.05,
+        )
+        .unwrap();
+        assert_eq!((signal.signal_type, signal.confidence), (SignalType::Sell, 0.75));
+        assert!(corroborated_by.is_empty());
+
+        // A single trigger is emitted unchanged; ties go to the earlier candidate
+        let (signal, corroborated_by) = resolve_candidates(vec![candidate(SignalType::Buy, 0.7, ReasonCode::Absorption)], 0.05).unwrap();
+        assert_eq!((signal.confidence, corroborated_by.len()), (0.7, 0));
+        let (signal, _) = resolve_candidates(
+            vec![candidate(SignalType::Sell, 0.7, ReasonCode::Absorption), candidate(SignalType::Buy, 0.7, ReasonCode::Exhaustion)],
+            0.0,
+        )
+        .unwrap();
+        assert_eq!(signal.reason_code, ReasonCode::Absorption);
+        assert!(resolve_candidates(Vec::new(), 0.05).is_none());
+    }
+
+    #[test]
+    fn crossed_and_locked_books_never_signal() {
+        let trades = [trade(9_500, "buy", 500.0, 100.0)];
+        let bids: Vec<(f64, f64)> = (0..5).map(|i| (99.9 - i as f64 * 0.1, 50.0)).collect();
+        let asks_from = |best: f64| (0..5).map(|i| (best + i as f64 * 0.1, 1.0)).collect::<Vec<_>>();
+
+        let crossed = detect(&book(&bids, &asks_from(99.8), 10_000), &trades, &OFIConfig::for_tests());
+        assert_eq!(crossed.signal_type, SignalType::NoSignal);
+        assert!(crossed.reason.starts_with("Crossed order book"), "{}", crossed.reason);
+
+        let locked = detect(&book(&bids, &asks_from(99.9), 10_000), &trades, &OFIConfig::for_tests());
+        assert_eq!(locked.signal_type, SignalType::NoSignal);
+        assert!(locked.reason.starts_with("Locked order book"), "{}", locked.reason);
+
+        // The same flow on a normal book signals
+        assert_eq!(detect(&book(&bids, &asks_from(100.0), 10_000), &trades, &OFIConfig::for_tests()).signal_type, SignalType::StrongBuy);
+    }
+
+    #[test]
+    fn imbalance_momentum_keeps_building_signals_and_drops_decaying_ones() {
+        // Book history whose bid size at the touch grows (or shrinks) every 100ms
+        let history = |bid_sizes: &[f64]| -> Vec<BookFlowEvent> {
+            let books: Vec<OrderBookSnapshot> = bid_sizes
+                .iter()
+                .enumerate()
+                .map(|(i, &size)| book(&[(99.9, size)], &[(100.0, 10.0)], 1_000 + i as u64 * 100))
+                .collect();
+            books.windows(2).filter_map(|pair| crate::ofi::book_flow_event(&pair[0], &pair[1])).collect()
+        };
+        let rising = history(&[10.0, 20.0, 30.0, 40.0, 50.0]);
+        let falling = history(&[50.0, 40.0, 30.0, 20.0, 10.0]);
+        assert!(crate::ofi::imbalance_momentum(&rising, 4) > 0.0);
+        assert!(crate::ofi::imbalance_momentum(&falling, 4) < 0.0);
+
+        let config = OFIConfig { imbalance_momentum_snapshots: 4, suppress_decaying_imbalance: true, ..OFIConfig::for_tests() };
+        let order_book = stacked_bid_book(1_400);
+        let buys = [trade(1_300, "buy", 600.0, 100.0)];
+        let trades: Vec<&TradeData> = buys.iter().collect();
+
+        let building = detect_signals(&order_book, &trades, &rising, &params(), &config);
+        assert_eq!(building.signal_type.direction(), 1, "{}", building.reason);
+        let decaying = detect_signals(&order_book, &trades, &falling, &params(), &config);
+        assert_eq!(decaying.signal_type, SignalType::NoSignal);
+        assert!(decaying.reason.contains("decaying against it"), "{}", decaying.reason);
+
+        // Momentum is only reported without the gate
+        let ungated = OFIConfig { suppress_decaying_imbalance: false, ..config };
+        assert_eq!(detect_signals(&order_book, &trades, &falling, &params(), &ungated).signal_type, building.signal_type);
+    }
+
+    #[test]
+    fn continuation_needs_the_minimum_trades_on_each_side() {
+        let order_book = stacked_bid_book(10_000);
+        let one_print = [trade(9_500, "buy", 600.0, 100.0)];
+        let mut many_prints: Vec<TradeData> = (0..10).map(|i| trade(9_000 + i * 50, "buy", 60.0, 100.0)).collect();
+        many_prints.extend((0..3).map(|i| trade(9_100 + i * 100, "sell", 1.0, 100.0)));
+
+        // Ungated, a single whale print drives the continuation
+        let ungated = detect(&order_book, &one_print, &OFIConfig::for_tests());
+        assert_eq!(ungated.reason_code, ReasonCode::StackedImbalance);
+
+        let gated = OFIConfig { min_buy_trades: 5, min_sell_trades: 2, ..OFIConfig::for_tests() };
+        let thin = detect(&order_book, &one_print, &gated);
+        assert_ne!(thin.reason_code, ReasonCode::StackedImbalance);
+        assert_ne!(thin.signal_type.direction(), 1, "{}", thin.reason);
+
+        let populated = detect(&order_book, &many_prints, &gated);
+        assert_eq!(populated.reason_code, ReasonCode::StackedImbalance);
+        assert_eq!(populated.signal_type.direction(), 1);
+
+        // Plenty of buys without the required sells is still one-sided participation
+        let buys_only = &many_prints[..10];
+        assert_ne!(detect(&order_book, buys_only, &gated).reason_code, ReasonCode::StackedImbalance);
+    }
+
+    #[test]
+    fn advisable_size_scales_with_the_liquidity_inside_the_slippage_budget() {
+        let deep = book(&[(99.95, 10.0), (99.92, 10.0), (99.7, 500.0)], &[(100.0, 10.0), (100.05, 10.0), (100.08, 10.0), (100.3, 500.0)], 10_000);
+        let thin = book(&[(99.95, 0.1), (99.92, 0.1), (99.7, 500.0)], &[(100.0, 0.1), (100.05, 0.1), (100.08, 0.1), (100.3, 500.0)], 10_000);
+
+        // 10bps from the touch: three ask levels for a buy, two bid levels for a sell; the big
+        // levels beyond the limit count nothing
+        let deep_buy = executable_notional(&deep, 1, 10.0);
+        assert!((deep_buy - 3_001.3).abs() < 1e-9, "{}", deep_buy);
+        assert!((executable_notional(&deep, -1, 10.0) - 1_998.7).abs() < 1e-9);
+        assert!((executable_notional(&thin, 1, 10.0) - deep_buy / 100.0).abs() < 1e-9, "a hundredth of the size on a hundredth of the depth");
+        // A wider budget reaches further into the book
+        assert!(executable_notional(&deep, 1, 50.0) > 50_000.0);
+        assert_eq!(executable_notional(&book(&[(99.95, 10.0)], &[], 10_000), 1, 10.0), 0.0);
+
+        // Attached to directional signals only, and only with a budget configured
+        let config = OFIConfig { sizing_slippage_bps: 10.0, ..OFIConfig::for_tests() };
+        assert_eq!(max_advisable_size(&deep, SignalType::StrongBuy, &config), Some(deep_buy));
+        assert_eq!(max_advisable_size(&deep, SignalType::NoSignal, &config), None);
+        assert_eq!(max_advisable_size(&deep, SignalType::Buy, &OFIConfig::for_tests()), None);
+
+        let config = OFIConfig { sizing_slippage_bps: 5.0, ..fast_config() };
+        let bid_heavy = book(&[(99.9, 50.0), (99.8, 1.0)], &[(100.0, 10.0), (100.1, 500.0)], 10_000);
+        let buy = detect(&bid_heavy, &[trade(9_500, "buy", 20.0, 100.0)], &config);
+        assert_eq!(buy.signal_type, SignalType::Buy);
+        assert_eq!(buy.max_advisable_size, Some(1_000.0));
+    }
+
+    #[test]
+    fn touch_imbalance_signals_only_when_held_past_the_threshold() {
+        let event = |timestamp: u64, touch_imbalance: f64| BookFlowEvent {
+            timestamp,
+            ofi: 0.0,
+            imbalance: 0.0,
+            spread_bps: 10.0,
+            touch_imbalance,
+            bid_price: 99.9,
+            bid_size: 1.0,
+            ask_price: 100.0,
+            ask_size: 1.0,
+        };
+        let detect_with = |order_book: &OrderBookSnapshot, events: &[BookFlowEvent], config: &OFIConfig| detect_signals(order_book, &[], events, &params(), config);
+        let config = OFIConfig { touch_imbalance_threshold: 3.0, touch_imbalance_updates: 3, touch_imbalance_confidence: 0.55, ..fast_config() };
+        let bid_heavy = book(&[(99.9, 40.0)], &[(100.0, 10.0)], 10_000);
+        let held = [event(9_800, 3.5), event(9_900, 4.0), event(10_000, 4.0)];
+
+        // No trades, so fast mode's own rule stays quiet and the touch pressure speaks
+        let buy = detect_with(&bid_heavy, &held, &config);
+        assert_eq!((buy.signal_type, buy.reason_code, buy.confidence), (SignalType::Buy, ReasonCode::TouchImbalance, 0.55));
+        assert!(buy.reason.starts_with("Touch imbalance 4.00 held for 3 book updates"), "{}", buy.reason);
+        assert_eq!(detect_with(&bid_heavy, &held, &fast_config()).signal_type, SignalType::NoSignal, "off without a threshold");
+
+        // One update below the threshold, or too few updates, is not sustained
+        let dipped = [event(9_800, 3.5), event(9_900, 2.9), event(10_000, 4.0)];
+        assert_eq!(detect_with(&bid_heavy, &dipped, &config).signal_type, SignalType::NoSignal);
+        assert_eq!(detect_with(&bid_heavy, &held[1..], &config).signal_type, SignalType::NoSignal);
+
+        let ask_heavy = book(&[(99.9, 10.0)], &[(100.0, 40.0)], 10_000);
+        let held_on_the_ask = [event(9_800, 0.3), event(9_900, 0.25), event(10_000, 0.25)];
+        assert_eq!(detect_with(&ask_heavy, &held_on_the_ask, &config).signal_type, SignalType::Sell);
+
+        // Outside fast mode it fills in when no deeper rule fires
+        let full = OFIConfig { fast_mode: false, ..config };
+        let signal = detect_with(&bid_heavy, &held, &full);
+        assert_eq!((signal.signal_type, signal.reason_code), (SignalType::Buy, ReasonCode::TouchImbalance));
+    }
+
+    #[test]
+    fn slow_replenishment_weakens_an_absorption() {
+        let history = |touches: &[(u64, f64)]| -> Vec<BookFlowEvent> {
+            let books: Vec<OrderBookSnapshot> = touches.iter().map(|&(timestamp, size)| book(&[(99.9, size), (99.8, 10.0)], &[(100.0, 10.0)], timestamp)).collect();
+            books.windows(2).filter_map(|pair| crate::ofi::book_flow_event(&pair[0], &pair[1])).collect()
+        };
+        let fast = history(&[(8_900, 10.0), (9_000, 10.0), (9_100, 2.0), (9_200, 10.0)]);
+        let slow = history(&[(8_900, 10.0), (9_000, 10.0), (9_100, 2.0), (9_500, 3.0), (10_000, 4.0)]);
+        // Heavy selling into a bid that holds: buy absorption at 0.8
+        let order_book = book(&[(99.9, 10.0), (99.8, 10.0)], &[(100.0, 10.0), (100.1, 10.0)], 10_000);
+        let trades = [trade(9_500, "sell", 30.0, 99.9)];
+        let absorption = |events: &[BookFlowEvent], config: &OFIConfig| detect_signals(&order_book, &trades.iter().collect::<Vec<_>>(), events, &params(), config);
+
+        let weighted = OFIConfig { replenishment_confidence_weight: 0.5, ..OFIConfig::for_tests() };
+        let refilled = absorption(&fast, &weighted);
+        assert_eq!((refilled.signal_type, refilled.reason_code), (SignalType::Buy, ReasonCode::Absorption));
+        assert!((refilled.confidence - 0.8).abs() < 1e-9, "a full refill keeps the confidence");
+        // A quarter refilled: 0.8 * (1 - 0.5 * 0.75)
+        let thin = absorption(&slow, &weighted);
+        assert!((thin.confidence - 0.5).abs() < 1e-9, "{}", thin.confidence);
+        assert!((absorption(&slow, &OFIConfig::for_tests()).confidence - 0.8).abs() < 1e-9, "unweighted by default");
+    }
+
+    #[test]
+    fn the_most_consistent_strongest_window_is_emitted_and_recorded() {
+        // Buying in the last 500ms after heavier selling earlier in the 5s window
+        let trades = [trade(6_000, "sell", 1_000.0, 100.0), trade(9_800, "buy", 500.0, 100.0)];
+        let trades: Vec<&TradeData> = trades.iter().collect();
+        let order_book = stacked_bid_book(10_000);
+        let config = OFIConfig { multi_lookback: vec![5_000, 500], ..OFIConfig::for_tests() };
+        let over = |lookback_period_ms: u64| detect_signals(&order_book, &trades, &[], &StrategyParams { lookback_period_ms, ..params() }, &config);
+
+        // The short window sees a continuation, the long one an absorption of the selling
+        assert_eq!((over(500).signal_type, over(500).confidence), (SignalType::StrongBuy, 0.9));
+        assert_eq!((over(5_000).signal_type, over(5_000).reason_code), (SignalType::Buy, ReasonCode::Absorption));
+        let signal = detect_signals_multi_lookback(&order_book, &trades, &[], &params(), &config, true);
+        assert_eq!((signal.signal_type, signal.chosen_lookback_ms), (SignalType::StrongBuy, Some(500)));
+        assert_eq!(detect_signals_multi_lookback(&order_book, &trades, &[], &params(), &OFIConfig::for_tests(), true).chosen_lookback_ms, None);
+
+        // Agreement across windows outranks one stronger outlier; ties go to the earlier window
+        let candidate = |signal_type: SignalType, confidence: f64| TradingSignal { signal_type, confidence, ..TradingSignal::no_signal("BTCUSDT") };
+        let chosen = select_lookback_signal(vec![(100, candidate(SignalType::Sell, 0.95)), (500, candidate(SignalType::Buy, 0.7)), (1_000, candidate(SignalType::Buy, 0.8))]);
+        assert_eq!((chosen.signal_type, chosen.chosen_lookback_ms), (SignalType::Buy, Some(1_000)));
+        let tied = select_lookback_signal(vec![(100, candidate(SignalType::Buy, 0.8)), (500, candidate(SignalType::Buy, 0.8))]);
+        assert_eq!(tied.chosen_lookback_ms, Some(100));
+        let quiet = select_lookback_signal(vec![(100, TradingSignal::no_signal("BTCUSDT")), (500, TradingSignal::no_signal("ETHUSDT"))]);
+        assert_eq!((quiet.symbol.as_str(), quiet.chosen_lookback_ms), ("BTCUSDT", None));
+    }
+}
@@ -0,0 +1,227 @@
+//! Synchronous, single-threaded engine for embedding where no Tokio runtime can be hosted
+//! (some Python setups, WASM experiments)
+
+use crate::config::OFIConfig;
+use crate::data::{OrderBookSnapshot, OrderBookStorage, TradeData, TradeStorage};
+use crate::ofi::{book_flow_event, event_clock, realized_volatility};
+use crate::signals::{detect_signals_multi_lookback, gate_strong_on_update_rate, SignalType, StrategyParams, TradingSignal};
+use std::cell::RefCell;
+
+/// Thin synchronous wrapper over the core strategy functions: the caller feeds books and trades
+/// and analyzes on its own thread, with no async runtime or locks. Not `Sync`; use one per thread.
+///
+/// Covers storage, market-condition adaptation, signal detection (including `multi_lookback`),
+/// per-symbol strategy selection, the book update-rate gate and tier labels. The stateful stages of `OFIEngine::analyze_symbol`
+/// (absorption and candidate confirmation, jump suppression, direction stickiness, calibration),
+/// the funding gate, `book_dedupe`, synthetics, memory budgets and readiness are not applied.
+pub struct SyncEngine {
+    order_book_storage: RefCell<OrderBookStorage>,
+    trade_storage: RefCell<TradeStorage>,
+    strategy_params: StrategyParams,
+    config: OFIConfig,
+}
+
+impl SyncEngine {
+    pub fn new(params: StrategyParams, config: OFIConfig) -> Self {
+        Self {
+            order_book_storage: RefCell::new(OrderBookStorage::new()),
+            trade_storage: RefCell::new(TradeStorage::new()),
+            strategy_params: params,
+            config,
+        }
+    }
+
+    /// Get reference to the configuration
+    pub fn config(&self) -> &OFIConfig {
+        &self.config
+    }
+
+    /// Store a book, recording its order flow against the previous one
+    pub fn update_order_book(&self, book: OrderBookSnapshot) {
+        let mut storage = self.order_book_storage.borrow_mut();
+        let event = storage.get_order_book(&book.symbol).and_then(|prev| book_flow_event(prev, &book));
+        let cutoff = book.timestamp.saturating_sub(self.config.flow_retention_ms(self.strategy_params.lookback_period_ms));
+        if let Some(event) = event {
+            storage.add_flow_event(&book.symbol, event, cutoff);
+        }
+        storage.record_update_time(&book.symbol, book.timestamp, cutoff);
+        storage.update_order_book(book);
+    }
+
+    /// Store a trade (reordered and deduplicated like in `OFIEngine`)
+    pub fn add_trade(&self, trade: TradeData) {
+        self.trade_storage.borrow_mut().add_trade(trade, &self.config);
+    }
+
+    /// Analyze a symbol from the data fed so far
+    pub fn analyze_symbol(&self, symbol: &str) -> TradingSignal {
+        if !self.config.analysis_enabled_for(symbol) {
+            return TradingSignal::no_signal_with_reason(symbol, "No strategy enabled for symbol (data collection only)");
+        }
+
+        let order_book_storage = self.order_book_storage.borrow();
+        let trade_storage = self.trade_storage.borrow();
+        let order_book = match order_book_storage.get_order_book(symbol) {
+            Some(book) => book,
+            None => return TradingSignal::no_signal_with_reason(symbol, "No order book data"),
+        };
+        if order_book.bids.is_empty() || order_book.asks.is_empty() {
+            return TradingSignal::no_signal_with_reason(symbol, "Order book is empty");
+        }
+
+        let recent_trades = trade_storage.get_recent_trades(symbol, self.config.analysis_trade_limit);
+
+        let mut params = self.strategy_params.clone();
+        if self.config.market_condition_adaptation && self.config.reference_volatility > 0.0 {
+            let now = event_clock(order_book, &recent_trades, self.config.unified_event_clock);
+            if let Some(volatility) = realized_volatility(&recent_trades, now, self.config.volatility_window_ms) {
+                let max = self.config.max_volatility_multiplier;
+                params.market_condition_multiplier *= (volatility / self.config.reference_volatility).clamp(1.0 / max, max);
+            }
+        }
+
+        let mut signal = detect_signals_multi_lookback(order_book, &recent_trades, order_book_storage.get_flow_events(symbol), &params, &self.config, true);
+        if signal.signal_type != SignalType::NoSignal && !self.config.strategy_enabled_for(symbol, signal.reason_code) {
+            let reason = format!("{:?} {} not enabled for {}", signal.reason_code, signal.signal_type, symbol);
+            signal = TradingSignal::no_signal_at(symbol, signal.price, signal.timestamp, reason);
+        }
+
+        if self.config.min_book_update_rate > 0.0 {
+            let rate = order_book_storage.update_rate(symbol, signal.timestamp, self.strategy_params.lookback_period_ms);
+            signal = gate_strong_on_update_rate(signal, rate, self.config.min_book_update_rate);
+        }
+
+        if signal.signal_type != SignalType::NoSignal {
+            signal.tier = self.config.tier_for_confidence(signal.confidence).map(str::to_string);
+        }
+
+        signal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfidenceTier, SymbolOverride};
+    use crate::data::OrderBookLevel;
+    use crate::signals::ReasonCode;
+
+    const DEEP: f64 = 50.0;
+    const THIN: f64 = 1.0;
+
+    fn book(symbol: &str, bids: &[(f64, f64)], asks: &[(f64, f64)], timestamp: u64) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            symbol: symbol.to_string(),
+            bids: bids.iter().map(|&(price, quantity)| OrderBookLevel { price, quantity }).collect(),
+            asks: asks.iter().map(|&(price, quantity)| OrderBookLevel { price, quantity }).collect(),
+            timestamp,
+        }
+    }
+
+    /// Five levels a side, every bid level `bid_size` and every ask level `ask_size`
+    fn stacked_book(symbol: &str, bid_size: f64, ask_size: f64, timestamp: u64) -> OrderBookSnapshot {
+        let bids: Vec<(f64, f64)> = (0..5).map(|i| (99.9 - i as f64 * 0.1, bid_size)).collect();
+        let asks: Vec<(f64, f64)> = (0..5).map(|i| (100.0 + i as f64 * 0.1, ask_size)).collect();
+        book(symbol, &bids, &asks, timestamp)
+    }
+
+    fn trade(symbol: &str, timestamp: u64, side: &str, quantity: f64, price: f64) -> TradeData {
+        TradeData { symbol: symbol.to_string(), price, quantity, side: side.to_string(), timestamp }
+    }
+
+    fn params() -> StrategyParams {
+        StrategyParams {
+            imbalance_threshold: 3.0,
+            absorption_threshold: 1000.0,
+            delta_threshold: 1000.0,
+            lookback_period_ms: 5000,
+            market_condition_multiplier: 1.0,
+        }
+    }
+
+    #[test]
+    fn every_signal_branch_is_reached_without_a_runtime() {
+        let engine = SyncEngine::new(params(), OFIConfig::for_tests());
+        assert_eq!(engine.analyze_symbol("BTCUSDT").reason, "No order book data");
+
+        engine.update_order_book(book("ONESIDED", &[(99.9, 1.0)], &[], 10_000));
+        assert_eq!(engine.analyze_symbol("ONESIDED").reason, "Order book is empty");
+
+        // Stacked bids with buying: continuation up
+        engine.update_order_book(stacked_book("BTCUSDT", DEEP, THIN, 10_000));
+        engine.add_trade(trade("BTCUSDT", 9_500, "buy", 500.0, 100.0));
+        let strong_buy = engine.analyze_symbol("BTCUSDT");
+        assert_eq!((strong_buy.signal_type, strong_buy.reason_code), (SignalType::StrongBuy, ReasonCode::StackedImbalance));
+
+        // Stacked asks with selling: continuation down
+        engine.update_order_book(stacked_book("ETHUSDT", THIN, DEEP, 10_000));
+        engine.add_trade(trade("ETHUSDT", 9_500, "sell", 500.0, 100.0));
+        let strong_sell = engine.analyze_symbol("ETHUSDT");
+        assert_eq!((strong_sell.signal_type, strong_sell.reason_code), (SignalType::StrongSell, ReasonCode::StackedImbalance));
+
+        // Heavy selling into a bid that holds: buy absorption
+        engine.update_order_book(book("SOLUSDT", &[(99.9, 10.0), (99.8, 10.0)], &[(100.0, 10.0), (100.1, 10.0)], 10_000));
+        engine.add_trade(trade("SOLUSDT", 9_500, "sell", 30.0, 99.9));
+        let buy = engine.analyze_symbol("SOLUSDT");
+        assert_eq!((buy.signal_type, buy.reason_code), (SignalType::Buy, ReasonCode::Absorption));
+
+        // Heavy buying without the bid rising: sell absorption
+        engine.update_order_book(book("XRPUSDT", &[(99.9, 10.0)], &[(100.0, 10.0)], 10_000));
+        engine.add_trade(trade("XRPUSDT", 9_500, "buy", 30.0, 100.0));
+        let sell = engine.analyze_symbol("XRPUSDT");
+        assert_eq!((sell.signal_type, sell.reason_code), (SignalType::Sell, ReasonCode::Absorption));
+
+        // Balanced book and a small print
+        engine.update_order_book(book("DOGEUSDT", &[(99.9, 10.0), (99.8, 10.0)], &[(100.0, 10.0), (100.1, 10.0)], 10_000));
+        engine.add_trade(trade("DOGEUSDT", 9_500, "buy", 1.0, 100.0));
+        let quiet = engine.analyze_symbol("DOGEUSDT");
+        assert_eq!(quiet.signal_type, SignalType::NoSignal);
+        assert!(quiet.reason.starts_with("No significant signal detected"), "{}", quiet.reason);
+
+        // Old buying decayed away under fresh selling: exhaustion, here outranking the absorption
+        let config = OFIConfig { delta_decay_half_life_ms: 100, exhaustion_signal_confidence: 0.85, ..OFIConfig::for_tests() };
+        let engine = SyncEngine::new(params(), config);
+        engine.update_order_book(book("BTCUSDT", &[(99.9, 10.0), (99.8, 10.0)], &[(100.0, 10.0), (100.1, 10.0)], 10_000));
+        engine.add_trade(trade("BTCUSDT", 9_000, "buy", 50.0, 100.0));
+        engine.add_trade(trade("BTCUSDT", 10_000, "sell", 20.0, 100.0));
+        let exhaustion = engine.analyze_symbol("BTCUSDT");
+        assert_eq!((exhaustion.signal_type, exhaustion.reason_code), (SignalType::Sell, ReasonCode::Exhaustion));
+    }
+
+    #[test]
+    fn symbol_strategies_update_rate_gate_and_tiers_apply() {
+        let mut config = OFIConfig {
+            min_book_update_rate: 2.0,
+            confidence_tiers: vec![
+                ConfidenceTier { min_confidence: 0.5, label: "watch".to_string() },
+                ConfidenceTier { min_confidence: 0.9, label: "full".to_string() },
+            ],
+            ..OFIConfig::for_tests()
+        };
+        config.symbol_overrides.insert("ETHUSDT".to_string(), SymbolOverride { strategies: Some(Vec::new()), ..SymbolOverride::default() });
+        config.symbol_overrides.insert("SOLUSDT".to_string(), SymbolOverride { strategies: Some(vec![ReasonCode::Absorption]), ..SymbolOverride::default() });
+        let engine = SyncEngine::new(params(), config);
+        for symbol in ["BTCUSDT", "ETHUSDT", "SOLUSDT"] {
+            engine.update_order_book(stacked_book(symbol, DEEP, THIN, 10_000));
+            engine.add_trade(trade(symbol, 9_500, "buy", 500.0, 100.0));
+        }
+
+        assert!(engine.analyze_symbol("ETHUSDT").reason.contains("data collection only"));
+        let filtered = engine.analyze_symbol("SOLUSDT");
+        assert_eq!(filtered.signal_type, SignalType::NoSignal);
+        assert!(filtered.reason.contains("not enabled for SOLUSDT"), "{}", filtered.reason);
+
+        // A single book is no update rate: the strong signal is downgraded, then kept once the
+        // book updates at 4/s
+        let downgraded = engine.analyze_symbol("BTCUSDT");
+        assert_eq!((downgraded.signal_type, downgraded.tier.as_deref()), (SignalType::Buy, Some("full")));
+        for timestamp in [10_250, 10_500] {
+            engine.update_order_book(stacked_book("BTCUSDT", DEEP, THIN, timestamp));
+        }
+        let strong = engine.analyze_symbol("BTCUSDT");
+        assert_eq!((strong.signal_type, strong.tier.as_deref()), (SignalType::StrongBuy, Some("full")));
+
+        // Same inputs, same signal: nothing depends on wall time or a runtime
+        assert_eq!(engine.analyze_symbol("BTCUSDT").reason, strong.reason);
+    }
+}
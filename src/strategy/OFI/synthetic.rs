@@ -0,0 +1,107 @@
+//! Synthetic spread instruments derived from two legs: `long_leg - ratio * short_leg`
+//!
+//! Buying the spread means buying the long leg and selling `ratio` units of the short leg, so the
+//! synthetic bid pairs the long leg's bid with the short leg's ask and vice versa. Levels are
+//! paired by depth index, an approximation that is exact at the top of the book. Synthetic
+//! prices that are not positive are dropped, since notional-based imbalance needs positive prices.
+
+use crate::config::SyntheticInstrument;
+use crate::data::{OrderBookLevel, OrderBookSnapshot, TradeData};
+
+/// Mid price of a book, None without both a bid and an ask
+fn mid_price(book: &OrderBookSnapshot) -> Option<f64> {
+    Some((book.bids.first()?.price + book.asks.first()?.price) / 2.0)
+}
+
+/// Pair `long` levels with `short` levels into synthetic levels with size in spread units
+fn combine_levels(long: &[OrderBookLevel], short: &[OrderBookLevel], ratio: f64) -> Vec<OrderBookLevel> {
+    long.iter()
+        .zip(short)
+        .map(|(long, short)| OrderBookLevel {
+            price: long.price - ratio * short.price,
+            quantity: long.quantity.min(short.quantity / ratio),
+        })
+        .filter(|level| level.price > 0.0)
+        .collect()
+}
+
+/// Synthetic book from the two legs' books, stamped with the newer leg timestamp
+pub fn combine_books(synthetic: &SyntheticInstrument, long: &OrderBookSnapshot, short: &OrderBookSnapshot) -> OrderBookSnapshot {
+    OrderBookSnapshot {
+        symbol: synthetic.name.clone(),
+        bids: combine_levels(&long.bids, &short.asks, synthetic.ratio),
+        asks: combine_levels(&long.asks, &short.bids, synthetic.ratio),
+        timestamp: long.timestamp.max(short.timestamp),
+    }
+}
+
+/// Synthetic trade for a trade on one leg, priced against the other leg's current mid.
+///
+/// A long leg trade keeps its side and size; a short leg trade flips its side (buying the short
+/// leg sells the spread) and is sized in spread units. None if the trade is on neither leg, the
+/// other leg has no mid yet, or the synthetic price is not positive.
+pub fn synthetic_trade(synthetic: &SyntheticInstrument, trade: &TradeData, other_leg: &OrderBookSnapshot) -> Option<TradeData> {
+    let other_mid = mid_price(other_leg)?;
+    let (price, quantity, side) = if trade.symbol == synthetic.long_leg {
+        (trade.price - synthetic.ratio * other_mid, trade.quantity, trade.side.clone())
+    } else if trade.symbol == synthetic.short_leg {
+        let side = match trade.side.as_str() {
+            "buy" => "sell".to_string(),
+            "sell" => "buy".to_string(),
+            other => other.to_string(),
+        };
+        (other_mid - synthetic.ratio * trade.price, trade.quantity / synthetic.ratio, side)
+    } else {
+        return None;
+    };
+    if price <= 0.0 {
+        return None;
+    }
+    Some(TradeData { symbol: synthetic.name.clone(), price, quantity, side, timestamp: trade.timestamp })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg_book(symbol: &str, bid: f64, ask: f64, timestamp: u64) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            symbol: symbol.to_string(),
+            bids: vec![OrderBookLevel { price: bid, quantity: 2.0 }],
+            asks: vec![OrderBookLevel { price: ask, quantity: 2.0 }],
+            timestamp,
+        }
+    }
+
+    fn spread() -> SyntheticInstrument {
+        SyntheticInstrument { name: "BTC-ETH".to_string(), long_leg: "BTCUSDT".to_string(), short_leg: "ETHUSDT".to_string(), ratio: 20.0 }
+    }
+
+    #[test]
+    fn legs_combine_per_the_ratio() {
+        let book = combine_books(&spread(), &leg_book("BTCUSDT", 60_000.0, 60_010.0, 1_000), &leg_book("ETHUSDT", 2_990.0, 2_991.0, 1_200));
+        assert_eq!(book.symbol, "BTC-ETH");
+        // Bid: long bid against the short ask; ask: long ask against the short bid
+        assert_eq!((book.bids[0].price, book.asks[0].price), (180.0, 210.0));
+        // Size in spread units: 2 of the long leg, 2 / 20 of the short leg
+        assert_eq!(book.bids[0].quantity, 0.1);
+        assert_eq!(book.timestamp, 1_200);
+    }
+
+    #[test]
+    fn leg_trades_become_spread_trades() {
+        let trade = |symbol: &str, side: &str, quantity: f64, price: f64| TradeData { symbol: symbol.to_string(), price, quantity, side: side.to_string(), timestamp: 1_000 };
+        let eth = leg_book("ETHUSDT", 2_990.0, 2_991.0, 1_000);
+        let btc = leg_book("BTCUSDT", 60_000.0, 60_010.0, 1_000);
+
+        let long = synthetic_trade(&spread(), &trade("BTCUSDT", "buy", 1.0, 60_010.0), &eth).unwrap();
+        assert_eq!((long.price, long.quantity, long.side.as_str()), (200.0, 1.0, "buy"));
+        // Buying the short leg sells the spread
+        let short = synthetic_trade(&spread(), &trade("ETHUSDT", "buy", 20.0, 2_991.0), &btc).unwrap();
+        assert_eq!((short.price, short.quantity, short.side.as_str()), (185.0, 1.0, "sell"));
+
+        assert!(synthetic_trade(&spread(), &trade("SOLUSDT", "buy", 1.0, 150.0), &eth).is_none());
+        let no_mid = OrderBookSnapshot { bids: Vec::new(), ..eth };
+        assert!(synthetic_trade(&spread(), &trade("BTCUSDT", "buy", 1.0, 60_010.0), &no_mid).is_none());
+    }
+}
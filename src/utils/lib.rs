@@ -1,5 +1,14 @@
 // src/utils/lib.rs
 
+/// Enter a `tracing` span named `$name` for the rest of the enclosing block with the `trace-spans`
+/// feature, recording the given variables as fields; expands to nothing without it
+macro_rules! trace_span {
+    ($name:literal $(, $field:ident)*) => {
+        #[cfg(feature = "trace-spans")]
+        let _span = tracing::info_span!($name $(, $field = %$field)*).entered();
+    };
+}
+
 #[path = "../config/mod.rs"]
 pub mod config;
 
@@ -15,10 +24,54 @@ mod ofi;
 #[path = "../strategy/OFI/signals.rs"]
 pub mod signals;
 
+#[path = "../strategy/OFI/intent.rs"]
+pub mod intent;
+
+#[path = "../strategy/OFI/replay.rs"]
+pub mod replay;
+
+#[path = "../strategy/OFI/synthetic.rs"]
+pub mod synthetic;
+
+#[path = "../strategy/OFI/group.rs"]
+pub mod group;
+
+#[path = "../strategy/OFI/sync_engine.rs"]
+pub mod sync_engine;
+
+#[path = "../strategy/OFI/funding.rs"]
+pub mod funding;
+
+#[cfg(feature = "schema")]
+#[path = "../config/schema.rs"]
+pub mod schema;
+
 #[path = "../connectors/websocket.rs"]
 pub mod websocket;
 
-use crate::config::OFIConfig;
+#[path = "../connectors/status_http.rs"]
+pub mod status_http;
+
+#[cfg(feature = "signal-server")]
+#[path = "../connectors/signal_server.rs"]
+pub mod signal_server;
+
+#[cfg(feature = "mqtt")]
+#[path = "../connectors/mqtt.rs"]
+pub mod mqtt;
+
+#[cfg(feature = "tui")]
+#[path = "../connectors/dashboard.rs"]
+pub mod dashboard;
+
+#[path = "../execution_service/position_store.rs"]
+pub mod position_store;
+
+#[cfg(feature = "sqlite")]
+#[path = "../execution_service/signal_store.rs"]
+pub mod signal_store;
+
+use crate::config::{ExchangeCredentials, OFIConfig};
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use rustls::crypto::ring;
@@ -30,13 +83,32 @@ use chrono;
 // Initialize the crypto provider once
 static INIT: Once = Once::new();
 
-fn initialize_crypto_provider() {
+/// Install the Ring crypto provider as the process default. Idempotent and never panics: later
+/// calls, and a provider installed elsewhere first (binary and Python module in one process), are no-ops.
+pub fn initialize_crypto_provider() {
     INIT.call_once(|| {
-        // Install the Ring crypto provider as the default
-        ring::default_provider().install_default().expect("Failed to install crypto provider");
+        if rustls::crypto::CryptoProvider::get_default().is_some() {
+            return;
+        }
+        // Losing a race to another installer leaves a usable default in place
+        let _ = ring::default_provider().install_default();
     });
 }
 
+// Runtimes built on this thread, so tests can check that calls reuse their engine's runtime.
+// Per thread, so engines built by tests running in parallel don't count.
+#[cfg(test)]
+thread_local! {
+    static RUNTIMES_BUILT: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+// Current-thread runtime that drives an engine's async calls
+fn build_runtime() -> std::io::Result<tokio::runtime::Runtime> {
+    #[cfg(test)]
+    RUNTIMES_BUILT.with(|built| built.set(built.get() + 1));
+    tokio::runtime::Builder::new_current_thread().enable_all().build()
+}
+
 // Re-export the internal TradingSignal for Python
 #[pyclass]
 pub struct TradingSignal {
@@ -52,12 +124,27 @@ pub struct TradingSignal {
     pub timestamp: String,
     #[pyo3(get, set)]
     pub reason: String,
+    #[pyo3(get, set)]
+    pub tier: Option<String>,
+    #[pyo3(get, set)]
+    pub max_advisable_size: Option<f64>,
 }
 
 #[pymethods]
 impl TradingSignal {
     #[new]
-    fn new(symbol: String, signal_type: String, price: f64, confidence: f64, timestamp: String, reason: String) -> Self {
+    #[pyo3(signature = (symbol, signal_type, price, confidence, timestamp, reason, tier=None, max_advisable_size=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        symbol: String,
+        signal_type: String,
+        price: f64,
+        confidence: f64,
+        timestamp: String,
+        reason: String,
+        tier: Option<String>,
+        max_advisable_size: Option<f64>,
+    ) -> Self {
         TradingSignal {
             symbol,
             signal_type,
@@ -65,13 +152,15 @@ impl TradingSignal {
             confidence,
             timestamp,
             reason,
+            tier,
+            max_advisable_size,
         }
     }
     
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!(
-            "TradingSignal(symbol='{}', signal_type='{}', price={}, confidence={}, timestamp='{}', reason='{}')",
-            self.symbol, self.signal_type, self.price, self.confidence, self.timestamp, self.reason
+            "TradingSignal(symbol='{}', signal_type='{}', price={}, confidence={}, timestamp='{}', reason='{}', tier={:?}, max_advisable_size={:?})",
+            self.symbol, self.signal_type, self.price, self.confidence, self.timestamp, self.reason, self.tier, self.max_advisable_size
         ))
     }
     
@@ -83,6 +172,8 @@ impl TradingSignal {
         dict.set_item("confidence", self.confidence)?;
         dict.set_item("timestamp", &self.timestamp)?;
         dict.set_item("reason", &self.reason)?;
+        dict.set_item("tier", &self.tier)?;
+        dict.set_item("max_advisable_size", self.max_advisable_size)?;
         Ok(dict.into())
     }
 }
@@ -97,6 +188,8 @@ impl From<signals::TradingSignal> for TradingSignal {
             confidence: signal.confidence,
             timestamp: signal.timestamp.to_string(),
             reason: signal.reason,
+            tier: signal.tier,
+            max_advisable_size: signal.max_advisable_size,
         }
     }
 }
@@ -105,25 +198,28 @@ impl From<signals::TradingSignal> for TradingSignal {
 #[pyclass]
 pub struct OFIEngine {
     config: OFIConfig,
+    engines: std::sync::Mutex<HashMap<String, crate::engine::OFIEngine>>,  // Engine of the most recent analysis per symbol, for diagnostics
+    runtime: tokio::runtime::Runtime,  // Shared by all calls; shut down when the engine is dropped
 }
 
 #[pymethods]
 impl OFIEngine {
     #[new]
     fn new(api_key: String, secret_key: String, passphrase: String) -> PyResult<Self> {
-        let config = OFIConfig {
-            api_key,
-            secret_key,
-            passphrase,
-            ..Default::default()
-        };
+        let mut config = OFIConfig::default();
+        *config.credentials_mut("bitget") = ExchangeCredentials { api_key, secret_key, passphrase };
         
         // Validate configuration
         if let Err(e) = config.validate() {
             return Err(pyo3::exceptions::PyValueError::new_err(format!("Invalid configuration: {}", e)));
         }
         
-        Ok(OFIEngine { config })
+        // Built once so repeated calls don't pay runtime setup and teardown. The runtime is Send + Sync,
+        // so the engine can be used from any Python thread; calls take turns driving its scheduler.
+        let runtime = build_runtime()
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to create runtime: {}", e)))?;
+
+        Ok(OFIEngine { config, engines: std::sync::Mutex::new(HashMap::new()), runtime })
     }
     
     /// Analyze a symbol for trading signals using OFI methodology.
@@ -159,23 +255,21 @@ impl OFIEngine {
             return Err(pyo3::exceptions::PyValueError::new_err("Lookback period must be between 1ms and 5 minutes"));
         }
 
-        // Create a Tokio runtime to run our async code from a sync context
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to create runtime: {}", e)))?;
-            
-        // Block on the async analysis function
-        let result = rt.block_on(async {
+        // Block on the async analysis function on the engine's shared runtime
+        let result = self.runtime.block_on(async {
             // Use the engine's configuration instead of loading from environment each time
-            crate::engine::run_analysis_with_config(
-                symbol, 
-                imbalance_ratio, 
-                analysis_duration_ms, 
-                delta_threshold, 
-                lookback_period_ms, 
-                self.config.clone()
-            ).await
+            crate::engine::validate_analysis_request(
+                &symbol,
+                imbalance_ratio,
+                analysis_duration_ms,
+                delta_threshold,
+                lookback_period_ms,
+                &self.config,
+            )?;
+            let engine = crate::engine::analysis_engine(imbalance_ratio, delta_threshold, lookback_period_ms, self.config.clone());
+            // Keep the engine so its data can be inspected after the analysis
+            self.engines.lock().unwrap().insert(symbol.clone(), engine.clone());
+            crate::engine::run_analysis_on_engine(symbol, analysis_duration_ms, engine).await
         });
         
         match result {
@@ -188,6 +282,70 @@ impl OFIEngine {
         }
     }
     
+    /// Symbols with order book or trade data across the most recent analysis of every symbol, sorted
+    #[pyo3(name = "active_symbols")]
+    fn active_symbols(&self) -> PyResult<Vec<String>> {
+        let engines: Vec<_> = self.engines.lock().unwrap().values().cloned().collect();
+        let mut symbols: Vec<String> = self.runtime.block_on(async {
+            let mut symbols = Vec::new();
+            for engine in &engines {
+                symbols.extend(engine.active_symbols().await);
+            }
+            symbols
+        });
+        symbols.sort();
+        symbols.dedup();
+        Ok(symbols)
+    }
+
+    /// Whether the symbol met the warm-up criteria in its most recent analysis
+    #[pyo3(name = "is_ready")]
+    fn is_ready(&self, symbol: &str) -> PyResult<bool> {
+        let engine = match self.engines.lock().unwrap().get(symbol).cloned() {
+            Some(engine) => engine,
+            None => return Ok(false),
+        };
+        Ok(self.runtime.block_on(engine.is_ready(symbol)))
+    }
+
+    /// Realized volatility of the symbol's trades over `window_ms` in its most recent analysis,
+    /// None with too few trades
+    #[pyo3(name = "realized_volatility")]
+    fn realized_volatility(&self, symbol: &str, window_ms: u64) -> PyResult<Option<f64>> {
+        let engine = match self.engines.lock().unwrap().get(symbol).cloned() {
+            Some(engine) => engine,
+            None => return Ok(None),
+        };
+        Ok(self.runtime.block_on(engine.realized_volatility(symbol, window_ms)))
+    }
+
+    /// Per-symbol trade count and book freshness from the most recent analysis of every symbol,
+    /// as a list of dicts sorted by symbol
+    #[pyo3(name = "symbol_data_status")]
+    fn symbol_data_status(&self, py: Python) -> PyResult<Vec<PyObject>> {
+        let engines: Vec<_> = self.engines.lock().unwrap().values().cloned().collect();
+        let mut statuses = self.runtime.block_on(async {
+            let mut statuses = Vec::new();
+            for engine in &engines {
+                statuses.extend(engine.symbol_data_status().await);
+            }
+            statuses
+        });
+        statuses.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        statuses
+            .into_iter()
+            .map(|status| {
+                let dict = PyDict::new_bound(py);
+                dict.set_item("symbol", status.symbol)?;
+                dict.set_item("trade_count", status.trade_count)?;
+                dict.set_item("book_levels", status.book_levels)?;
+                dict.set_item("book_age_ms", status.book_age_ms)?;
+                dict.set_item("ready", status.ready)?;
+                Ok(dict.into())
+            })
+            .collect()
+    }
+    
     /// Get current order book for a symbol
     fn get_order_book(&self, _symbol: &str) -> PyResult<HashMap<String, f64>> {
         // Placeholder implementation
@@ -206,23 +364,24 @@ impl OFIEngine {
     /// Update configuration parameters
     #[pyo3(name = "update_config", signature = (api_key=None, secret_key=None, passphrase=None))]
     fn update_config(&mut self, api_key: Option<String>, secret_key: Option<String>, passphrase: Option<String>) {
+        let credentials = self.config.credentials_mut("bitget");
         if let Some(key) = api_key {
-            self.config.api_key = key;
+            credentials.api_key = key;
         }
         if let Some(key) = secret_key {
-            self.config.secret_key = key;
+            credentials.secret_key = key;
         }
         if let Some(pass) = passphrase {
-            self.config.passphrase = pass;
+            credentials.passphrase = pass;
         }
     }
     
     /// Get current configuration status
     #[pyo3(name = "get_config_status")]
     fn get_config_status(&self) -> String {
-        let has_api_key = !self.config.api_key.is_empty();
-        let has_secret_key = !self.config.secret_key.is_empty();
-        let has_passphrase = !self.config.passphrase.is_empty();
+        let has_api_key = !self.config.api_key().is_empty();
+        let has_secret_key = !self.config.secret_key().is_empty();
+        let has_passphrase = !self.config.passphrase().is_empty();
         
         format!(
             "API Key: {}, Secret Key: {}, Passphrase: {}", 
@@ -232,6 +391,12 @@ impl OFIEngine {
         )
     }
     
+    /// Get the effective configuration as pretty JSON (credentials redacted)
+    #[pyo3(name = "get_effective_config")]
+    fn get_effective_config(&self) -> String {
+        self.config.effective_dump()
+    }
+    
     /// Initialize logging system with modern colors
     #[pyo3(name = "init_logging", signature = (level=None))]
     fn init_logging(&self, level: Option<String>) -> PyResult<()> {
@@ -290,3 +455,50 @@ fn ofi_engine_rust(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<OFIEngine>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crypto_provider_initialization_is_idempotent() {
+        initialize_crypto_provider();
+        initialize_crypto_provider();
+        assert!(rustls::crypto::CryptoProvider::get_default().is_some());
+
+        // A provider installed elsewhere first is left in place
+        assert!(ring::default_provider().install_default().is_err());
+        initialize_crypto_provider();
+        assert!(rustls::crypto::CryptoProvider::get_default().is_some());
+    }
+
+    #[test]
+    fn repeated_and_concurrent_calls_run_on_one_engine_runtime() {
+        let built = || RUNTIMES_BUILT.with(|built| built.get());
+        let engine = OFIEngine {
+            config: OFIConfig::for_tests(),
+            engines: std::sync::Mutex::new(HashMap::new()),
+            runtime: build_runtime().expect("runtime builds"),
+        };
+
+        let inner = crate::engine::analysis_engine(3.0, 1000.0, 5000, engine.config.clone());
+        engine.engines.lock().unwrap().insert("BTCUSDT".to_string(), inner);
+        let before = built();
+        for _ in 0..100 {
+            assert_eq!(engine.active_symbols().unwrap(), Vec::<String>::new());
+            assert!(!engine.is_ready("BTCUSDT").unwrap());
+            assert_eq!(engine.realized_volatility("BTCUSDT", 1000).unwrap(), None);
+        }
+        assert_eq!(built(), before, "calls build no runtime of their own");
+
+        // Calls from other threads take turns on the same runtime
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    assert!(!engine.is_ready("BTCUSDT").unwrap());
+                    assert_eq!(built(), 0, "no runtime built on the calling thread");
+                });
+            }
+        });
+    }
+}